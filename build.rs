@@ -0,0 +1,11 @@
+// generates the grpc service from proto/hl.proto, with a bundled protoc so no system install is needed
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // the postgres journal embeds these with sqlx::migrate!
+    println!("cargo:rerun-if-changed=migrations");
+    if std::env::var_os("PROTOC").is_none() {
+        // SAFETY: build scripts are single threaded
+        unsafe { std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?) };
+    }
+    tonic_build::configure().build_client(false).compile_protos(&["proto/hl.proto"], &["proto"])?;
+    Ok(())
+}
@@ -0,0 +1,53 @@
+-- same tables as the sqlite journal, times in ms since the epoch
+CREATE TABLE IF NOT EXISTS fills (
+    id BIGSERIAL PRIMARY KEY,
+    time BIGINT NOT NULL,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    size DOUBLE PRECISION NOT NULL,
+    direction TEXT NOT NULL,
+    closed_pnl DOUBLE PRECISION NOT NULL,
+    fee DOUBLE PRECISION NOT NULL,
+    order_id BIGINT NOT NULL,
+    trade_id BIGINT NOT NULL,
+    hash TEXT NOT NULL,
+    UNIQUE (hash, trade_id, order_id, time)
+);
+CREATE TABLE IF NOT EXISTS orders (
+    order_id BIGINT PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    size DOUBLE PRECISION NOT NULL,
+    filled DOUBLE PRECISION NOT NULL,
+    placed_at BIGINT NOT NULL,
+    last_seen BIGINT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS funding (
+    id BIGSERIAL PRIMARY KEY,
+    time BIGINT NOT NULL,
+    symbol TEXT NOT NULL,
+    usdc DOUBLE PRECISION NOT NULL,
+    position_size DOUBLE PRECISION NOT NULL,
+    funding_rate DOUBLE PRECISION NOT NULL,
+    UNIQUE (time, symbol)
+);
+CREATE TABLE IF NOT EXISTS equity (
+    time BIGINT PRIMARY KEY,
+    account_value DOUBLE PRECISION NOT NULL,
+    margin_used DOUBLE PRECISION NOT NULL,
+    unrealized_pnl DOUBLE PRECISION NOT NULL,
+    open_positions BIGINT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS market_contexts (
+    time BIGINT NOT NULL,
+    symbol TEXT NOT NULL,
+    mark_price DOUBLE PRECISION NOT NULL,
+    open_interest DOUBLE PRECISION NOT NULL,
+    volume_24h DOUBLE PRECISION NOT NULL,
+    funding_rate DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (time, symbol)
+);
+CREATE INDEX IF NOT EXISTS idx_fills_time ON fills (time);
+CREATE INDEX IF NOT EXISTS idx_funding_time ON funding (time);
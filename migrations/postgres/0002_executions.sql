@@ -0,0 +1,17 @@
+-- post-trade execution quality reports, times in ms since the epoch
+CREATE TABLE IF NOT EXISTS executions (
+    id BIGSERIAL PRIMARY KEY,
+    started BIGINT NOT NULL,
+    finished BIGINT NOT NULL,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL,
+    requested_qty DOUBLE PRECISION NOT NULL,
+    filled_qty DOUBLE PRECISION NOT NULL,
+    fills BIGINT NOT NULL,
+    avg_price DOUBLE PRECISION NOT NULL,
+    arrival_mid DOUBLE PRECISION NOT NULL,
+    interval_vwap DOUBLE PRECISION,
+    fees DOUBLE PRECISION NOT NULL,
+    shortfall_bps DOUBLE PRECISION NOT NULL,
+    vs_vwap_bps DOUBLE PRECISION
+);
@@ -1,9 +1,30 @@
 use clap::{Parser, Subcommand};
-use anyhow::Result;
+use crate::services::journal::{self, Journal};
+use crate::services::{accounts, book_recorder, breakeven, config_check::{self, CheckLevel, ConfigReport}, contacts, health, daily_loss, deadman, download, execution_report, fees, guard, influx, kafka, kill_switch, liquidation_watch, market_watch, mqtt, redis_bridge, risk_reload};
+use crate::services::ws_hub::WsHub;
+use crate::services::order_groups::{self, OrderGroups};
+use crate::services::paper::{self, PaperEngine};
+use crate::services::peg::{self, PegParams, PegReference};
+use crate::services::smart_order::{SmartChoice, SmartPlan};
+use crate::services::{indicators::{self, Indicator}, price, protect, scanner, snapshot, tape, volatility, watch, withdrawals};
+use crate::services::rate_budget::RateBudgetSnapshot;
+use crate::services::spread_monitor::{self, SpreadTracker};
+use crate::services::strategy::{
+    self,
+    market_maker::{MarketMaker, MarketMakerParams},
+    momentum::{Momentum, MomentumParams},
+    script::ScriptStrategy,
+};
+use anyhow::{Context, Result};
 use crate::{
-    services::{ExchangeService, TradingService}, 
-    types::{Config, OrderRequest}
+    config::loader,
+    error::HlError,
+    services::{round_to_tick, trading::check_slippage, ExchangeService, Notifier, TradingService}, 
+    types::{streaming::{SpreadAlert, TradeFilter, TradeSide}, builder_fee_percent, cloid_hex, BuilderFee, parse_cloid, Config, CostMethod, DailyPnlSummary, ExecutionReport, FeeSummary, GroupCancelReport, GroupedOrder, HedgePlan, ImpactEstimate, MarginMode, MarketType, NotificationEvent, OrderRef, OrderRequest, OrderResponse, OrderResult, PaperAccount, PortfolioResponse, ReferralStatus, RiskSizing, StakingSummary, ValidatorInfo, VaultInfo, Withdrawal, WithdrawalStatus}
 };
+use std::fmt::Write as _;
+use std::time::Duration;
+use uuid::Uuid;
 
 #[derive(Parser)]
 #[command(name = "hl")]
@@ -11,103 +32,994 @@ use crate::{
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    #[arg(long, global = true, help = "Validate and price orders without sending them")]
+    pub dry_run: bool,
+    #[arg(long, global = true, help = "Simulate fills against live prices with a local paper account")]
+    pub paper: bool,
+    #[arg(short = 'y', long, global = true, help = "Skip confirmation prompts")]
+    pub yes: bool,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
-    Status, 
-    Balances,
+    Status {
+        #[arg(long, num_args = 0..=1, default_missing_value = "5", value_name = "SECS", help = "Redraw every SECS seconds (default 5), highlighting changes")]
+        watch: Option<u64>,
+        #[arg(short, long, help = "Also show the API rate-limit weight left this minute")]
+        verbose: bool,
+    },
+    Balances {
+        #[arg(long, num_args = 0..=1, default_missing_value = "5", value_name = "SECS", help = "Redraw every SECS seconds (default 5), highlighting changes")]
+        watch: Option<u64>,
+        #[arg(long, value_delimiter = ',', help = "Comma-separated wallets to show side by side and summed, instead of the configured ones")]
+        addresses: Vec<String>,
+    },
+    #[command(about = "Show open positions with value and unrealized PnL")]
+    Positions {
+        #[arg(long, num_args = 0..=1, default_missing_value = "5", value_name = "SECS", help = "Redraw every SECS seconds (default 5), highlighting changes")]
+        watch: Option<u64>,
+    },
     Spot,
+    #[command(about = "Show what a symbol resolves to: market type, size decimals and tick size")]
+    Symbol {
+        #[arg(help = "Perp or spot symbol, e.g. btc, UBTC/USDC or @142")]
+        input: String,
+        #[arg(long, help = "Print JSON instead of text")]
+        json: bool,
+    },
+    #[command(about = "Spot token balances, held and available, valued in USDC")]
+    SpotBalances {
+        #[arg(long, help = "Print JSON instead of a table")]
+        json: bool,
+    },
     Stream {
         symbol: String,
         #[arg(short, long, default_value = "30", help = "Duration in seconds")]
         duration: u64,
+        #[arg(long, num_args = 0..=1, default_missing_value = "10", value_name = "SECS", help = "Print VWAP, buy/sell volume, largest trade and trades per minute every SECS seconds (default 10)")]
+        stats: Option<u64>,
+        #[arg(long, help = "Only show trades of at least this size")]
+        min_size: Option<f64>,
+        #[arg(long, value_enum, help = "Only show buys or sells")]
+        side: Option<TradeSide>,
+        #[arg(long, help = "Only show trades worth at least this many USD")]
+        min_notional: Option<f64>,
+    },
+    #[command(about = "One merged, time-ordered trade tape across the most traded perps")]
+    Tape {
+        #[arg(long, default_value = "10", help = "Number of perps, by 24h volume")]
+        top: usize,
+        #[arg(long, help = "Only show trades worth at least this many USD")]
+        min_notional: Option<f64>,
     },
     Buy {
         symbol: String,
-        qty: f64,
+        #[arg(required_unless_present_any = ["notional", "size_pct"], conflicts_with_all = ["notional", "size_pct"])]
+        qty: Option<f64>,
+        #[arg(long, conflicts_with = "size_pct", help = "Order size in USD, converted at the limit or mid price")]
+        notional: Option<f64>,
+        #[arg(long, help = "Order margin as a percentage of account equity, times --leverage")]
+        size_pct: Option<f64>,
         #[arg(long, help = "Limit price (if not specified, places market order)")]
         limit: Option<f64>,
+        #[arg(long, conflicts_with_all = ["limit", "peg"], help = "After a market or smart order, print its implementation shortfall and record it in the journal")]
+        report: bool,
+        #[arg(long, conflicts_with_all = ["limit", "peg", "tif"], help = "Cross with a capped IOC or rest at the touch, whichever the live spread and depth favour")]
+        smart: bool,
+        #[arg(long, value_enum, conflicts_with = "limit", help = "Peg the limit price to the market and re-price the order as it moves")]
+        peg: Option<PegReference>,
+        #[arg(long, default_value = "0", requires = "peg", help = "Pegged distance from mid in basis points, below it for a buy and above it for a sell")]
+        offset_bps: f64,
+        #[arg(long, default_value = "5", requires = "peg", help = "Re-price once mid has moved this many basis points from where the order was pegged")]
+        reprice_bps: f64,
+        #[arg(long, default_value = "10", requires = "peg", help = "Most re-prices per minute, further drift waits for the budget to refill")]
+        max_reprices_per_min: u32,
         #[arg(long, help = "Leverage multiplier")]
         leverage: Option<u32>,
+        #[arg(long, value_enum, help = "Margin mode, defaults to the symbol's configured mode")]
+        margin: Option<MarginMode>,
         #[arg(long, help = "Reduce only order")]
         reduce_only: bool,
-        #[arg(long, default_value = "Gtc", help = "Time in force (Gtc, Ioc, Alo)")]
-        tif: String,
+        #[arg(long, help = "Time in force (Gtc, Ioc, Alo), defaults to Gtc")]
+        tif: Option<String>,
         #[arg(long, help = "Slippage tolerance for market orders (e.g., 0.01 = 1%)")]
         slippage: Option<f64>,
         #[arg(long, help = "Custom tick size for price rounding (e.g., 0.01, 0.1, 1.0)")]
         tick_size: Option<f64>,
+        #[arg(long, value_parser = parse_cloid_arg, help = "Client order ID (0x + 32 hex digits, or a UUID)")]
+        cloid: Option<Uuid>,
+        #[arg(long, help = "Add the order to a named group, cancelled together with `hl cancel-group`")]
+        group: Option<String>,
+        #[arg(long, requires = "builder_fee_bps", help = "Builder address to route the order through")]
+        builder: Option<String>,
+        #[arg(long, requires = "builder", help = "Builder fee in basis points (multiples of 0.1, max 10)")]
+        builder_fee_bps: Option<f64>,
+        #[arg(long, help = "Named preset from the config file, flags given here override it")]
+        preset: Option<String>,
     },
     Sell {
         symbol: String,
-        qty: f64,
+        #[arg(required_unless_present_any = ["notional", "size_pct"], conflicts_with_all = ["notional", "size_pct"])]
+        qty: Option<f64>,
+        #[arg(long, conflicts_with = "size_pct", help = "Order size in USD, converted at the limit or mid price")]
+        notional: Option<f64>,
+        #[arg(long, help = "Order margin as a percentage of account equity, times --leverage")]
+        size_pct: Option<f64>,
         #[arg(long, help = "Limit price (if not specified, places market order)")]
         limit: Option<f64>,
+        #[arg(long, conflicts_with_all = ["limit", "peg"], help = "After a market or smart order, print its implementation shortfall and record it in the journal")]
+        report: bool,
+        #[arg(long, conflicts_with_all = ["limit", "peg", "tif"], help = "Cross with a capped IOC or rest at the touch, whichever the live spread and depth favour")]
+        smart: bool,
+        #[arg(long, value_enum, conflicts_with = "limit", help = "Peg the limit price to the market and re-price the order as it moves")]
+        peg: Option<PegReference>,
+        #[arg(long, default_value = "0", requires = "peg", help = "Pegged distance from mid in basis points, below it for a buy and above it for a sell")]
+        offset_bps: f64,
+        #[arg(long, default_value = "5", requires = "peg", help = "Re-price once mid has moved this many basis points from where the order was pegged")]
+        reprice_bps: f64,
+        #[arg(long, default_value = "10", requires = "peg", help = "Most re-prices per minute, further drift waits for the budget to refill")]
+        max_reprices_per_min: u32,
         #[arg(long, help = "Leverage multiplier")]
         leverage: Option<u32>,
+        #[arg(long, value_enum, help = "Margin mode, defaults to the symbol's configured mode")]
+        margin: Option<MarginMode>,
         #[arg(long, help = "Reduce only order")]
         reduce_only: bool,
-        #[arg(long, default_value = "Gtc", help = "Time in force (Gtc, Ioc, Alo)")]
-        tif: String,
+        #[arg(long, help = "Time in force (Gtc, Ioc, Alo), defaults to Gtc")]
+        tif: Option<String>,
         #[arg(long, help = "Slippage tolerance for market orders (e.g., 0.01 = 1%)")]
         slippage: Option<f64>,
         #[arg(long, help = "Custom tick size for price rounding (e.g., 0.01, 0.1, 1.0)")]
         tick_size: Option<f64>,
+        #[arg(long, value_parser = parse_cloid_arg, help = "Client order ID (0x + 32 hex digits, or a UUID)")]
+        cloid: Option<Uuid>,
+        #[arg(long, help = "Add the order to a named group, cancelled together with `hl cancel-group`")]
+        group: Option<String>,
+        #[arg(long, requires = "builder_fee_bps", help = "Builder address to route the order through")]
+        builder: Option<String>,
+        #[arg(long, requires = "builder", help = "Builder fee in basis points (multiples of 0.1, max 10)")]
+        builder_fee_bps: Option<f64>,
+        #[arg(long, help = "Named preset from the config file, flags given here override it")]
+        preset: Option<String>,
+    },
+    #[command(about = "Offset the current perp position with a market order (all of it, or --ratio)")]
+    Hedge {
+        symbol: String,
+        #[arg(long, default_value = "1.0", help = "Share of the position to offset, 1.0 leaves it flat (up to 2.0)")]
+        ratio: f64,
+        #[arg(long, help = "Slippage tolerance for the market order (e.g., 0.01 = 1%)")]
+        slippage: Option<f64>,
+    },
+    #[command(about = "Move the position's stop to its average entry, creating one if there is none")]
+    Breakeven {
+        symbol: String,
+        #[arg(long, default_value = "5", help = "Basis points past entry, on the profit side, to cover fees")]
+        offset_bps: f64,
+    },
+    #[command(about = "Attach reduce-only stop loss / take profit triggers to every position opened or increased")]
+    Protect {
+        #[arg(long, help = "Stop loss distance from the average entry (e.g. 2% or 0.02)")]
+        sl: Option<String>,
+        #[arg(long, help = "Take profit distance from the average entry (e.g. 4% or 0.04)")]
+        tp: Option<String>,
+    },
+    #[command(about = "Reverse a position with one market order of twice its size")]
+    Flip {
+        symbol: String,
+        #[arg(long, help = "Slippage tolerance for the market order (e.g., 0.01 = 1%)")]
+        slippage: Option<f64>,
+        #[arg(long, help = "If the risk limits block the flip, just close the position instead")]
+        or_close: bool,
     },
     Cancel {
         symbol: String,
         order_id: u64,
-    }
+    },
+    #[command(about = "Cancel every resting order placed with --group <id>")]
+    CancelGroup {
+        group: String,
+    },
+    #[command(about = "Cancel an order by its client order ID")]
+    CancelCloid {
+        symbol: String,
+        #[arg(value_parser = parse_cloid_arg)]
+        cloid: Uuid,
+    },
+    #[command(about = "Add or remove margin on an isolated position")]
+    Margin {
+        symbol: String,
+        #[arg(long, required_unless_present = "remove", conflicts_with = "remove", help = "USDC to add")]
+        add: Option<f64>,
+        #[arg(long, help = "USDC to remove")]
+        remove: Option<f64>,
+    },
+    #[command(about = "Show mid, mark and oracle price, 24h change and premium")]
+    Price {
+        symbol: String,
+        #[arg(long, help = "Refresh every second from the live mids stream until Ctrl-C")]
+        watch: bool,
+    },
+    #[command(about = "Estimate the fill price and slippage of a market order from the current book")]
+    Impact {
+        symbol: String,
+        qty: f64,
+        #[arg(long, help = "Estimate a sell instead of a buy")]
+        sell: bool,
+        #[arg(long, help = "Slippage tolerance to compare against (e.g., 0.01 = 1%)")]
+        slippage: Option<f64>,
+    },
+    #[command(about = "Size a position so hitting the stop loses a fixed fraction of equity")]
+    Size {
+        symbol: String,
+        #[arg(long, help = "Equity to lose at the stop (e.g. 1% or 0.01)")]
+        risk: String,
+        #[arg(long, help = "Stop loss price, below entry for a long and above for a short")]
+        stop: f64,
+        #[arg(long, help = "Entry price, placed as a limit with --execute (mid price if not specified)")]
+        entry: Option<f64>,
+        #[arg(long, help = "Optional take profit price, placed with --execute")]
+        take_profit: Option<f64>,
+        #[arg(long, help = "Leverage multiplier for the entry order")]
+        leverage: Option<u32>,
+        #[arg(long, help = "Place the entry with a reduce-only stop (and take profit) bracket")]
+        execute: bool,
+    },
+    #[command(about = "Run the risk checks for an order without placing it")]
+    Check {
+        symbol: String,
+        qty: f64,
+        #[arg(long, help = "Check a sell instead of a buy")]
+        sell: bool,
+        #[arg(long, help = "Limit price (mid price if not specified)")]
+        limit: Option<f64>,
+        #[arg(long, help = "Leverage multiplier")]
+        leverage: Option<u32>,
+        #[arg(long, help = "Reduce only order")]
+        reduce_only: bool,
+    },
+    #[command(about = "Send a daily PnL summary to the configured notification sinks")]
+    PnlSummary,
+    #[command(about = "Cancel every open order and market-close every position")]
+    Panic,
+    #[command(about = "Market-close every open position one by one, reporting realized PnL")]
+    CloseAll {
+        #[arg(long, value_delimiter = ',', help = "Symbols to leave open (comma separated)")]
+        except: Vec<String>,
+    },
+    #[command(about = "Flatten and lock trading when equity falls too far from its session high")]
+    Guard {
+        #[arg(long, help = "Drawdown from the session high that trips the breaker (e.g. 10% or 0.1)")]
+        max_drawdown: String,
+        #[arg(long, default_value = "10", help = "Seconds between account value polls")]
+        interval: u64,
+        #[arg(long, value_parser = parse_duration_arg, help = "Start from the highest account value the journal recorded in this window (e.g. 24h)")]
+        lookback: Option<Duration>,
+    },
+    #[command(about = "Arm the exchange-side cancel of all open orders if this client stops heartbeating")]
+    Deadman {
+        #[arg(long, value_parser = parse_duration_arg, required_unless_present = "off", help = "Cancel all orders this long from now (e.g. 60s, 5m)")]
+        timeout: Option<Duration>,
+        #[arg(long, requires = "timeout", help = "Keep pushing the deadline back until Ctrl-C")]
+        keepalive: bool,
+        #[arg(long, conflicts_with_all = ["timeout", "keepalive"], help = "Disarm the switch")]
+        off: bool,
+    },
+    #[command(about = "Inspect or override risk state")]
+    Risk {
+        #[command(subcommand)]
+        command: RiskCommands,
+    },
+    #[command(about = "Show whether an order is open, filled, canceled or rejected")]
+    Order {
+        symbol: String,
+        #[arg(value_parser = parse_order_ref, help = "Order ID, or a 0x client order ID")]
+        order_id: OrderRef,
+    },
+    #[command(about = "List open orders")]
+    Orders {
+        #[arg(long, help = "Only show orders for this symbol")]
+        symbol: Option<String>,
+    },
+    #[command(about = "List recent fills")]
+    Fills {
+        #[arg(long, help = "Only show fills for this symbol")]
+        symbol: Option<String>,
+        #[arg(long, default_value = "20", help = "Number of fills to show")]
+        limit: usize,
+    },
+    #[command(about = "Balances, positions, open orders, recent fills and funding in one view")]
+    Portfolio {
+        #[arg(long, default_value = "10", help = "Number of recent fills to show")]
+        fills: usize,
+        #[arg(long, default_value = "24h", value_parser = parse_duration_arg, help = "Funding history window (e.g. 24h, 7d)")]
+        funding: Duration,
+        #[arg(long, help = "Print the portfolio as JSON")]
+        json: bool,
+    },
+    #[command(about = "Show maker/taker rates, 14 day volume, fee tier progress and fees paid")]
+    Fees {
+        #[arg(long, default_value = "7d", value_parser = parse_duration_arg, help = "Window for fees paid (e.g. 24h, 7d, 30d)")]
+        window: Duration,
+        #[arg(long, help = "Print the summary as JSON")]
+        json: bool,
+    },
+    #[command(about = "Set or show the referral code this account trades under")]
+    Referral {
+        #[command(subcommand)]
+        command: ReferralCommands,
+    },
+    #[command(about = "Approve or check builder fees for orders routed through a builder")]
+    Builder {
+        #[command(subcommand)]
+        command: BuilderCommands,
+    },
+    #[command(about = "Delegate staked HYPE to validators and show staking rewards")]
+    Stake {
+        #[command(subcommand)]
+        command: StakeCommands,
+    },
+    #[command(about = "Deposit into, withdraw from, or inspect a vault")]
+    Vault {
+        #[command(subcommand)]
+        command: VaultCommands,
+    },
+    #[command(about = "Send USDC from the perp balance to a contact or address")]
+    Transfer {
+        usd: f64,
+        #[arg(help = "Contact label or 0x address")]
+        recipient: String,
+    },
+    #[command(about = "Recent withdrawals from the ledger and whether they should have left the bridge")]
+    Withdrawals {
+        #[arg(long, default_value = "30d", value_parser = parse_duration_arg, help = "How far back to look (e.g. 24h, 7d, 30d)")]
+        window: Duration,
+        #[arg(long, conflicts_with = "json", help = "Keep checking until every pending withdrawal is past the bridge window")]
+        watch: bool,
+        #[arg(long, help = "Print the withdrawals as JSON")]
+        json: bool,
+    },
+    #[command(about = "Save, remove or list labelled transfer recipients")]
+    Contacts {
+        #[command(subcommand)]
+        command: ContactsCommands,
+    },
+    #[command(about = "Check DNS, TLS, REST, WebSocket, wallet, clock and collateral end to end")]
+    Doctor {
+        #[arg(long, help = "Print the checks as JSON")]
+        json: bool,
+    },
+    #[command(about = "Check the config file, private key, endpoints and risk limits")]
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    #[command(about = "Show or switch the network profile (mainnet, testnet, or [profiles.<name>])")]
+    Env {
+        #[command(subcommand)]
+        command: EnvCommands,
+    },
+    #[command(about = "Sync fills, orders, funding and account value into the journal")]
+    Sync,
+    #[command(about = "Drop the cached asset metadata and fetch it again")]
+    RefreshMeta,
+    #[command(about = "Realized volatility, ATR and typical hourly range from 1h candles")]
+    Vol {
+        symbol: String,
+        #[arg(long, default_value = "24h", value_parser = parse_duration_arg, help = "Window to measure over (e.g. 24h, 7d)")]
+        window: Duration,
+        #[arg(long, help = "Print the report as JSON")]
+        json: bool,
+    },
+    #[command(about = "Technical indicators and simple signals for a symbol")]
+    Ta {
+        symbol: String,
+        #[arg(long, default_value = "1h", help = "Candle interval (1m, 5m, 15m, 1h, 4h, 1d, ...)")]
+        interval: String,
+        #[arg(long, value_delimiter = ',', default_value = "rsi,ema20,ema50,atr", help = "sma<n>, ema<n>, rsi[n], atr[n], macd, bb[n]")]
+        indicators: Vec<String>,
+        #[arg(long, help = "Print the readings as JSON")]
+        json: bool,
+    },
+    #[command(about = "Scan the perp universe for opportunities")]
+    Scan {
+        #[command(subcommand)]
+        command: ScanCommands,
+    },
+    #[command(about = "Watch market conditions")]
+    Monitor {
+        #[command(subcommand)]
+        command: MonitorCommands,
+    },
+    #[command(about = "Forward live market and account streams to other systems")]
+    Bridge {
+        #[command(subcommand)]
+        command: BridgeCommands,
+    },
+    #[command(about = "Inspect the trade journal")]
+    Journal {
+        #[command(subcommand)]
+        command: JournalCommands,
+    },
+    #[command(about = "Print balances, positions, open orders and risk state as one JSON document")]
+    Snapshot,
+    #[command(about = "Show what changed between two `hl snapshot` files")]
+    Diff {
+        before: String,
+        after: String,
+        #[arg(long, help = "Print the diff as JSON")]
+        json: bool,
+    },
+    #[command(about = "Performance statistics from the journal")]
+    Stats {
+        #[arg(long, help = "Print stats as JSON")]
+        json: bool,
+    },
+    #[command(about = "Run trading strategies")]
+    Strategy {
+        #[command(subcommand)]
+        command: StrategyCommands,
+    },
+    #[command(about = "Inspect or drive the local paper-trading account")]
+    Paper {
+        #[command(subcommand)]
+        command: PaperCommands,
+    },
+    #[command(about = "Export journal data")]
+    Export {
+        #[command(subcommand)]
+        command: ExportCommands,
+    },
+    #[command(about = "Record live market data to disk")]
+    Record {
+        #[command(subcommand)]
+        command: RecordCommands,
+    },
+    #[command(about = "Download trades or candles to CSV for backtesting, resuming an existing file")]
+    Download {
+        #[command(subcommand)]
+        command: DownloadCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RecordCommands {
+    #[command(about = "Snapshot account value, margin used and unrealized PnL into the journal")]
+    Equity {
+        #[arg(long, default_value = "60s", value_parser = parse_duration_arg, help = "Time between snapshots (e.g. 30s, 5m)")]
+        every: Duration,
+        #[arg(long, value_parser = parse_duration_arg, help = "Stop after this long (e.g. 1d), otherwise run until Ctrl-C")]
+        duration: Option<Duration>,
+    },
+    #[command(about = "Sample the L2 book at a fixed interval into JSON lines")]
+    Book {
+        symbol: String,
+        #[arg(long, default_value = "1s", value_parser = parse_duration_arg, help = "Time between snapshots (e.g. 1s, 10s, 1m)")]
+        every: Duration,
+        #[arg(long, help = "JSONL file, appended to if it already exists")]
+        out: String,
+        #[arg(long, default_value = "0", help = "Levels per side to keep, 0 for all the stream sends (20)")]
+        depth: usize,
+        #[arg(long, value_parser = parse_duration_arg, help = "Stop after this long (e.g. 1h), otherwise run until Ctrl-C")]
+        duration: Option<Duration>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DownloadCommands {
+    #[command(about = "This account's fills, then optionally live public trades until --to")]
+    Trades {
+        symbol: String,
+        #[arg(long, value_parser = parse_time_arg, help = "Start (YYYY-MM-DD, RFC 3339 or unix ms)")]
+        from: u64,
+        #[arg(long, value_parser = parse_time_arg, default_value = "now", help = "End, may be in the future with --record")]
+        to: u64,
+        #[arg(long, help = "CSV file, appended to if it already exists")]
+        out: String,
+        #[arg(long, help = "Keep recording live trades until --to is reached")]
+        record: bool,
+    },
+    #[command(about = "Closed OHLCV candles (the exchange keeps the latest 5000 per interval)")]
+    Candles {
+        symbol: String,
+        #[arg(long, default_value = "1m", help = "Candle interval (1m, 5m, 15m, 1h, 4h, 1d, ...)")]
+        interval: String,
+        #[arg(long, value_parser = parse_time_arg, help = "Start (YYYY-MM-DD, RFC 3339 or unix ms)")]
+        from: u64,
+        #[arg(long, value_parser = parse_time_arg, default_value = "now", help = "End")]
+        to: u64,
+        #[arg(long, help = "CSV file, appended to if it already exists")]
+        out: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExportCommands {
+    #[command(about = "CSV of closed trades with cost basis and realized gains")]
+    Tax {
+        #[arg(long, help = "Tax year (UTC)")]
+        year: i32,
+        #[arg(long, value_enum, default_value = "fifo", help = "Cost basis method")]
+        method: CostMethod,
+        #[arg(long, help = "Write CSV to this file instead of stdout")]
+        out: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RiskCommands {
+    #[command(about = "Show today's realized PnL against the daily loss limit")]
+    Status,
+    #[command(about = "Lift the daily loss limit for the rest of today (UTC)")]
+    Override {
+        #[arg(long, help = "Remove an override instead")]
+        clear: bool,
+    },
+    #[command(about = "Remove the trading lock left by `hl guard`")]
+    Unlock,
+}
+
+#[derive(Subcommand)]
+pub enum StrategyCommands {
+    #[command(about = "Run a .rhai strategy script against live market data")]
+    Run {
+        script: std::path::PathBuf,
+        #[arg(long)]
+        symbol: String,
+        #[arg(short, long, default_value = "3600", help = "Duration in seconds")]
+        duration: u64,
+        #[arg(long, value_parser = parse_duration_arg, help = "Keep a dead man's switch armed with this timeout (e.g. 60s)")]
+        deadman: Option<Duration>,
+    },
+    #[command(about = "Quote a symmetric post-only bid/ask around mid")]
+    Mm {
+        symbol: String,
+        #[arg(long, default_value = "10", help = "Total quoted spread in basis points")]
+        spread_bps: f64,
+        #[arg(long, help = "Size of each quote")]
+        qty: f64,
+        #[arg(long, default_value = "5", help = "Re-quote when mid moves this many basis points")]
+        requote_bps: f64,
+        #[arg(long, help = "Inventory cap (defaults to 10x qty)")]
+        max_position: Option<f64>,
+        #[arg(long, default_value = "5", help = "Quote center shift at full inventory, in basis points")]
+        skew_bps: f64,
+        #[arg(short, long, default_value = "3600", help = "Duration in seconds")]
+        duration: u64,
+        #[arg(long, value_parser = parse_duration_arg, help = "Keep a dead man's switch armed with this timeout (e.g. 60s)")]
+        deadman: Option<Duration>,
+    },
+    #[command(about = "Trade moving-average crossovers on live candles")]
+    Momentum {
+        symbol: String,
+        #[arg(long, help = "Position size to hold while in a trend")]
+        qty: f64,
+        #[arg(long, default_value = "5m", help = "Candle interval (1m, 5m, 15m, 1h, ...)")]
+        interval: String,
+        #[arg(long, default_value = "9", help = "Fast moving average length")]
+        fast: usize,
+        #[arg(long, default_value = "21", help = "Slow moving average length")]
+        slow: usize,
+        #[arg(long, help = "Go flat on a bearish cross instead of short")]
+        long_only: bool,
+        #[arg(short, long, default_value = "3600", help = "Duration in seconds")]
+        duration: u64,
+        #[arg(long, value_parser = parse_duration_arg, help = "Keep a dead man's switch armed with this timeout (e.g. 60s)")]
+        deadman: Option<Duration>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PaperCommands {
+    #[command(about = "Show paper balance, positions and resting orders")]
+    Status,
+    #[command(about = "Start over with a fresh paper account")]
+    Reset {
+        #[arg(long, help = "Starting balance (defaults to paper.starting_balance)")]
+        balance: Option<f64>,
+    },
+    #[command(about = "Watch live trades and fill resting paper limit orders")]
+    Run {
+        #[arg(short, long, default_value = "300", help = "Duration in seconds")]
+        duration: u64,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ReferralCommands {
+    #[command(about = "Use a referral code; the exchange only allows this once per account")]
+    Set {
+        code: String,
+    },
+    #[command(about = "Show who referred this account, its own code and rewards")]
+    Status {
+        #[arg(long, help = "Print the status as JSON")]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BuilderCommands {
+    #[command(about = "Let a builder charge up to --max-fee-bps on this account's orders")]
+    Approve {
+        address: String,
+        #[arg(long, help = "Maximum builder fee in basis points (multiples of 0.1, max 10)")]
+        max_fee_bps: f64,
+    },
+    #[command(about = "Show the maximum fee a builder is approved for")]
+    Status {
+        address: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum StakeCommands {
+    #[command(about = "Delegate HYPE from the staking balance to a validator")]
+    Delegate {
+        #[arg(help = "Validator address or name")]
+        validator: String,
+        amount: f64,
+    },
+    #[command(about = "Move HYPE from a validator back to the staking balance")]
+    Undelegate {
+        #[arg(help = "Validator address or name")]
+        validator: String,
+        amount: f64,
+    },
+    #[command(about = "Show staking balances, delegations and rewards")]
+    Summary {
+        #[arg(long, help = "Print the summary as JSON")]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum VaultCommands {
+    #[command(about = "Deposit USDC from the perp balance into a vault")]
+    Deposit {
+        vault: String,
+        usd: f64,
+    },
+    #[command(about = "Withdraw USDC from a vault back to the perp balance")]
+    Withdraw {
+        vault: String,
+        usd: f64,
+    },
+    #[command(about = "Show a vault's equity, APR and this account's share")]
+    Info {
+        vault: String,
+        #[arg(long, help = "Print the vault as JSON")]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    #[command(about = "Load the config and report every problem found, exiting nonzero on errors")]
+    Validate {
+        #[arg(long, help = "Print the report as JSON")]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum EnvCommands {
+    #[command(about = "List the profiles, marking the selected one")]
+    List,
+    #[command(about = "Select a profile in the config file")]
+    Use {
+        profile: String,
+    },
+    #[command(about = "Show the network, endpoints and wallet actually in effect")]
+    Show,
+}
+
+#[derive(Subcommand)]
+pub enum ContactsCommands {
+    #[command(about = "Save an address under a label, checking its checksum")]
+    Add {
+        label: String,
+        address: String,
+    },
+    #[command(about = "Forget a saved contact")]
+    Remove {
+        label: String,
+    },
+    #[command(about = "Show saved contacts")]
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum ScanCommands {
+    #[command(about = "Rank perps by annualized funding for delta-neutral funding capture")]
+    Funding {
+        #[arg(long, default_value = "1000000", help = "Minimum open interest in USD")]
+        min_oi: f64,
+        #[arg(long, default_value = "1000000", help = "Minimum 24h volume in USD")]
+        min_volume: f64,
+        #[arg(long, default_value = "10", help = "Annualized funding (percent) that marks a candidate")]
+        min_apr: f64,
+        #[arg(long, default_value = "20", help = "Number of markets to show")]
+        top: usize,
+        #[arg(long, help = "Print results as JSON")]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum MonitorCommands {
+    #[command(about = "Flag wide spreads and collapsing top-of-book size")]
+    Spread {
+        #[arg(required = true)]
+        symbols: Vec<String>,
+        #[arg(long, default_value = "5", help = "Flag spreads wider than this many basis points")]
+        threshold_bps: f64,
+        #[arg(long, default_value = "80", help = "Flag when top-of-book size falls this many percent below its average")]
+        collapse_pct: f64,
+        #[arg(short, long, default_value = "3600", help = "Duration in seconds")]
+        duration: u64,
+    },
+    #[command(about = "Track each position's distance to liquidation and notify as it shrinks")]
+    Positions {
+        #[arg(long, default_value = "15%", help = "Distance that warns; info from twice this, critical from half")]
+        warn_at: String,
+        #[arg(long, value_parser = parse_duration_arg, default_value = "5s", help = "How often to sample positions and marks")]
+        interval: Duration,
+    },
+    #[command(about = "Record open interest and volume to the journal and flag sharp moves")]
+    Markets {
+        #[arg(long, default_value = "25", help = "Percent change within the window that raises an alert")]
+        threshold_pct: f64,
+        #[arg(long, value_parser = parse_duration_arg, default_value = "1h", help = "Window to compare against (e.g. 15m, 1h)")]
+        window: Duration,
+        #[arg(long, value_parser = parse_duration_arg, default_value = "60s", help = "How often to sample")]
+        interval: Duration,
+        #[arg(long, default_value = "1000000", help = "Ignore markets with less open interest in USD")]
+        min_oi: f64,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BridgeCommands {
+    #[command(about = "Publish trades, BBO, fills and positions to the mqtt_broker until Ctrl-C")]
+    Mqtt {
+        #[arg(long, value_delimiter = ',', help = "Symbols for trades and BBO, instead of mqtt.symbols")]
+        symbols: Vec<String>,
+    },
+    #[command(about = "Forward WebSocket streams to Redis pub/sub on redis_url until Ctrl-C")]
+    Redis {
+        #[arg(long, value_delimiter = ',', help = "e.g. trades:BTC,bbo:ETH,candle:SOL:1m,fills, instead of redis.channels")]
+        channels: Vec<String>,
+        #[arg(long, help = "Also append every message to a Redis stream of the same name")]
+        streams: bool,
+    },
+    #[command(about = "Write mark prices, funding, account value and position PnL to influx_url until Ctrl-C")]
+    Influx {
+        #[arg(long, help = "Seconds between samples, instead of influx.interval_secs")]
+        interval: Option<u64>,
+    },
+    #[command(about = "Produce trades, candles and fills to kafka_brokers until Ctrl-C (needs --features kafka)")]
+    Kafka {
+        #[arg(long, value_delimiter = ',', help = "Symbols for trades and candles, instead of kafka.symbols")]
+        symbols: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum JournalCommands {
+    #[command(about = "Run a read-only SQL query against the journal")]
+    Query {
+        sql: String,
+        #[arg(long, help = "Print rows as JSON")]
+        json: bool,
+    },
 }
 
 pub async fn run_cli(cli: Cli) -> Result<()> {
-    let config = Config::load()?;
-    
+    // runs before the config loads so a broken one is reported rather than just failing
+    if let Commands::Config { command: ConfigCommands::Validate { json } } = cli.command {
+        let report = config_check::validate().await;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            print_config_report(&report);
+        }
+        let errors = report.count(CheckLevel::Error);
+        if errors > 0 {
+            anyhow::bail!("Config has {} error(s)", errors);
+        }
+        return Ok(());
+    }
+
+    let mut config = Config::load()?;
+    if cli.dry_run {
+        config.dry_run = true;
+    }
+    if cli.paper {
+        config.paper.enabled = true;
+    }
+    if cli.yes {
+        config.confirm = false;
+    }
+    let confirm = Confirm::new(&config);
+    if config.dry_run {
+        println!("DRY RUN: orders are validated and priced but never sent");
+    } else if config.paper.enabled {
+        println!("PAPER: orders are filled by the local simulator ({})", config.paper.path);
+    }
+
     match cli.command {
-        Commands::Status => {
+        Commands::Status { watch: Some(secs), verbose } => {
+            let exchange = ExchangeService::new(config)?;
+            watch::run(watch_interval(secs)?, || async {
+                let mut frame = render_status(&exchange.get_status().await?)?;
+                if verbose {
+                    frame.push_str(&render_rate_budget(&exchange.pool().budget().snapshot()));
+                }
+                Ok(frame)
+            })
+            .await?;
+        },
+        Commands::Status { watch: None, verbose } => {
             let exchange = ExchangeService::new(config)?;
             println!("Fetching exchange status...");
             let status = exchange.get_status().await?;
             print_status(&status);
+            if verbose {
+                print!("{}", render_rate_budget(&exchange.pool().budget().snapshot()));
+            }
+        },
+        Commands::Balances { watch, addresses } if !addresses.is_empty() || !config.addresses.is_empty() => {
+            let addresses = if addresses.is_empty() {
+                config.addresses.clone()
+            } else {
+                accounts::parse_addresses(&addresses)?
+            };
+            let exchange = ExchangeService::new(config)?;
+            match watch {
+                Some(secs) => {
+                    watch::run(watch_interval(secs)?, || async {
+                        Ok(render_multi_balances(&exchange.get_balances_many(&addresses).await?)?)
+                    })
+                    .await?;
+                }
+                None => {
+                    println!("Fetching balances for {} addresses...", addresses.len());
+                    let balances = exchange.get_balances_many(&addresses).await?;
+                    print!("{}", render_multi_balances(&balances)?);
+                }
+            }
+        },
+        Commands::Balances { watch: Some(secs), .. } => {
+            let exchange = ExchangeService::new(config)?;
+            watch::run(watch_interval(secs)?, || async {
+                Ok(render_balances(&exchange.get_balances().await?)?)
+            })
+            .await?;
         },
-        Commands::Balances => {
+        Commands::Balances { watch: None, .. } => {
             let exchange = ExchangeService::new(config)?;
             println!("Fetching account balances...");
             let balances = exchange.get_balances().await?;
             print_balances(&balances);
         },
+        Commands::Positions { watch: Some(secs) } => {
+            let exchange = ExchangeService::new(config)?;
+            watch::run(watch_interval(secs)?, || async {
+                Ok(render_positions(&exchange.get_balances().await?)?)
+            })
+            .await?;
+        },
+        Commands::Positions { watch: None } => {
+            let exchange = ExchangeService::new(config)?;
+            print!("{}", render_positions(&exchange.get_balances().await?)?);
+        },
         Commands::Spot => {
             let exchange = ExchangeService::new(config)?;
             println!("Fetching spot markets...");
             let spot_data = exchange.get_spot_markets().await?;
             print_spot_markets(&spot_data);
         },
-        Commands::Stream { symbol, duration } => {
+        Commands::Symbol { input, json } => {
+            let resolved = ExchangeService::new(config)?.resolve_symbol(&input).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&resolved)?);
+            } else {
+                let market = match resolved.market {
+                    MarketType::Perp => "perp",
+                    MarketType::Spot => "spot",
+                };
+                println!("{} ({} {})", resolved.display, market, resolved.name);
+                println!("  Size decimals   {}", resolved.sz_decimals);
+                println!("  Tick size       {} (prices also keep at most 5 significant figures)", resolved.tick_size());
+                if let Some(max_leverage) = resolved.max_leverage {
+                    println!("  Max leverage    {}x", max_leverage);
+                }
+            }
+        },
+        Commands::SpotBalances { json } => {
+            let exchange = ExchangeService::new(config)?;
+            let balances = exchange.get_spot_balances().await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&balances)?);
+            } else {
+                print_spot_balances(&balances);
+            }
+        },
+        Commands::Stream { symbol, duration, stats, min_size, side, min_notional } => {
             use crate::services::streaming::StreamingService;
+            let symbol = ExchangeService::new(config.clone())?.resolve_symbol(&symbol).await?.name;
             println!("Starting trade stream for {} ({}s)", symbol, duration);
             let streaming = StreamingService::new(config)?;
-            streaming.stream_data(&symbol, "trades", duration).await?;
+            let stats_every = stats.map(|secs| Duration::from_secs(secs.max(1)));
+            let filter = TradeFilter { min_size, side, min_notional };
+            streaming.stream_data(&symbol, "trades", duration, stats_every, &filter).await?;
+        },
+        Commands::Tape { top, min_notional } => {
+            if top == 0 {
+                anyhow::bail!("--top must be at least 1");
+            }
+            let markets = ExchangeService::new(config.clone())?.get_status().await?.markets;
+            let symbols = tape::top_symbols(&markets, top);
+            println!("Tape for {} (Ctrl-C to stop)", symbols.join(", "));
+            let hub = WsHub::start(config.ws_url.clone(), config.http.clone());
+            let filter = TradeFilter { min_notional, ..Default::default() };
+            tokio::select! {
+                result = tape::run(hub, &symbols, &filter) => result?,
+                _ = tokio::signal::ctrl_c() => println!("Tape stopped"),
+            }
         },
-        Commands::Buy { symbol, qty, limit, leverage, reduce_only, tif, slippage, tick_size } => {
+        Commands::Buy { symbol, qty, notional, size_pct, limit, report, smart, peg, offset_bps, reprice_bps, max_reprices_per_min, leverage, margin, reduce_only, tif, slippage, tick_size, cloid, group, builder, builder_fee_bps, preset } => {
+            // the preset only fills in what wasn't given as a flag
+            let preset = preset.as_deref().map(|name| config.preset(name)).transpose()?.cloned().unwrap_or_default();
+            let tif = tif.or(preset.tif).unwrap_or_else(|| "Gtc".to_string());
+            let slippage = slippage.or(preset.slippage);
+            let leverage = leverage.or(preset.leverage);
+            let margin = margin.or(preset.margin);
+            let reduce_only = reduce_only || preset.reduce_only.unwrap_or(false);
+            let tick_size = tick_size.or(preset.tick_size);
+            let groups_path = config.order_groups_path.clone();
+            let peg_exchange = peg.map(|_| ExchangeService::new(config.clone())).transpose()?;
+            let report_config = report.then(|| config.clone());
             let trading = TradingService::new(config).await?;
+            let symbol = trading.resolve_symbol(&symbol).await?.name;
+            let peg_anchor = match &peg_exchange {
+                Some(exchange) => Some(pegged_mid(&trading, exchange, &symbol).await?),
+                None => None,
+            };
+            let limit = limit.or(peg_anchor.map(|mid| peg::peg_price(mid, true, offset_bps)));
             
-            if limit.is_none() && slippage.is_some() {
-                let slippage_pct = slippage.unwrap();
-                if slippage_pct < 0.0 || slippage_pct > 0.1 {
-                    eprintln!("Error: Slippage must be between 0% and 10% (0.0 to 0.1)");
-                    std::process::exit(1);
-                }
+            if limit.is_none() && let Some(slippage) = slippage {
+                check_slippage(slippage)?;
             }
             
-            if let Some(ts) = tick_size {
-                if ts <= 0.0 {
-                    eprintln!("Error: Tick size must be greater than 0");
-                    std::process::exit(1);
+            let limit = match tick_size {
+                Some(ts) if ts <= 0.0 => {
+                    return Err(HlError::validation("tick_size", "Tick size must be greater than 0").into());
                 }
-                println!("Using custom tick size: {}", ts);
-            }
+                Some(ts) => {
+                    println!("Using custom tick size: {}", ts);
+                    limit.map(|price| round_to_tick(price, ts))
+                }
+                None => limit,
+            };
             
+            let builder = resolve_builder(&trading, builder.as_deref(), builder_fee_bps).await?;
+            let qty = resolve_qty(&trading, &symbol, qty, notional, size_pct, limit, leverage).await?;
+            let (limit, tif, mut arrival_mid) = if smart {
+                let plan = trading.smart_plan(&symbol, qty, true, slippage).await?;
+                print_smart_plan(&plan);
+                if plan.choice == SmartChoice::Cross {
+                    confirm_market_order(&confirm, &trading, &plan.estimate, leverage).await?;
+                }
+                (Some(plan.price), plan.tif.to_string(), Some(plan.estimate.mid))
+            } else {
+                (limit, tif, None)
+            };
+            if limit.is_none() {
+                let estimate = check_impact(&trading, &symbol, qty, true, slippage).await?;
+                confirm_market_order(&confirm, &trading, &estimate, leverage).await?;
+                arrival_mid = Some(estimate.mid);
+            }
             let order_type = if limit.is_some() { "LIMIT BUY" } else { "MARKET BUY" };
             println!("Placing {} order for {} {}", order_type, qty, symbol);
             
@@ -119,37 +1031,83 @@ pub async fn run_cli(cli: Cli) -> Result<()> {
                 leverage,
                 reduce_only,
                 tif,
+                margin_mode: margin,
+                // grouped orders are cancelled by cloid, so they always get one
+                cloid: cloid.or_else(|| group.as_ref().map(|_| Uuid::new_v4())),
+                slippage,
+                builder,
             };
             
-            match trading.place_order(order_request).await {
+            let started = chrono::Utc::now().timestamp_millis() as u64;
+            match trading.place_order(order_request.clone()).await {
                 Ok(response) => {
-                    print_order_response(&response, "BUY", &symbol, qty, limit.is_none());
+                    let slippage_bound = limit.is_none().then(|| trading.market_slippage(slippage));
+                    print_order_response(&response, "BUY", &symbol, qty, slippage_bound);
+                    if let Some(group) = &group {
+                        remember_in_group(&groups_path, group, &symbol, &response)?;
+                    }
+                    if let (Some(config), Some(arrival_mid)) = (&report_config, arrival_mid) {
+                        report_execution(config, &response, &symbol, true, qty, arrival_mid, started).await?;
+                    }
+                    if let (Some(exchange), Some(anchor)) = (&peg_exchange, peg_anchor) {
+                        let params = PegParams { offset_bps, reprice_bps, max_reprices_per_minute: max_reprices_per_min };
+                        follow_peg(&trading, exchange, order_request, &response, anchor, params).await?;
+                    }
                 },
-                Err(e) => {
-                    eprintln!("Failed to place BUY order: {}", e);
-                    std::process::exit(1);
-                }
+                Err(e) => return Err(e.context("Failed to place BUY order")),
             }
         },
-        Commands::Sell { symbol, qty, limit, leverage, reduce_only, tif, slippage, tick_size } => {
+        Commands::Sell { symbol, qty, notional, size_pct, limit, report, smart, peg, offset_bps, reprice_bps, max_reprices_per_min, leverage, margin, reduce_only, tif, slippage, tick_size, cloid, group, builder, builder_fee_bps, preset } => {
+            let preset = preset.as_deref().map(|name| config.preset(name)).transpose()?.cloned().unwrap_or_default();
+            let tif = tif.or(preset.tif).unwrap_or_else(|| "Gtc".to_string());
+            let slippage = slippage.or(preset.slippage);
+            let leverage = leverage.or(preset.leverage);
+            let margin = margin.or(preset.margin);
+            let reduce_only = reduce_only || preset.reduce_only.unwrap_or(false);
+            let tick_size = tick_size.or(preset.tick_size);
+            let groups_path = config.order_groups_path.clone();
+            let peg_exchange = peg.map(|_| ExchangeService::new(config.clone())).transpose()?;
+            let report_config = report.then(|| config.clone());
             let trading = TradingService::new(config).await?;
+            let symbol = trading.resolve_symbol(&symbol).await?.name;
+            let peg_anchor = match &peg_exchange {
+                Some(exchange) => Some(pegged_mid(&trading, exchange, &symbol).await?),
+                None => None,
+            };
+            let limit = limit.or(peg_anchor.map(|mid| peg::peg_price(mid, false, offset_bps)));
             
-            if limit.is_none() && slippage.is_some() {
-                let slippage_pct = slippage.unwrap();
-                if slippage_pct < 0.0 || slippage_pct > 0.1 {
-                    eprintln!("Error: Slippage must be between 0% and 10% (0.0 to 0.1)");
-                    std::process::exit(1);
-                }
+            if limit.is_none() && let Some(slippage) = slippage {
+                check_slippage(slippage)?;
             }
             
-            if let Some(ts) = tick_size {
-                if ts <= 0.0 {
-                    eprintln!("Error: Tick size must be greater than 0");
-                    std::process::exit(1);
+            let limit = match tick_size {
+                Some(ts) if ts <= 0.0 => {
+                    return Err(HlError::validation("tick_size", "Tick size must be greater than 0").into());
                 }
-                println!("Using custom tick size: {}", ts);
-            }
+                Some(ts) => {
+                    println!("Using custom tick size: {}", ts);
+                    limit.map(|price| round_to_tick(price, ts))
+                }
+                None => limit,
+            };
             
+            let builder = resolve_builder(&trading, builder.as_deref(), builder_fee_bps).await?;
+            let qty = resolve_qty(&trading, &symbol, qty, notional, size_pct, limit, leverage).await?;
+            let (limit, tif, mut arrival_mid) = if smart {
+                let plan = trading.smart_plan(&symbol, qty, false, slippage).await?;
+                print_smart_plan(&plan);
+                if plan.choice == SmartChoice::Cross {
+                    confirm_market_order(&confirm, &trading, &plan.estimate, leverage).await?;
+                }
+                (Some(plan.price), plan.tif.to_string(), Some(plan.estimate.mid))
+            } else {
+                (limit, tif, None)
+            };
+            if limit.is_none() {
+                let estimate = check_impact(&trading, &symbol, qty, false, slippage).await?;
+                confirm_market_order(&confirm, &trading, &estimate, leverage).await?;
+                arrival_mid = Some(estimate.mid);
+            }
             let order_type = if limit.is_some() { "LIMIT SELL" } else { "MARKET SELL" };
             println!("Placing {} order for {} {}", order_type, qty, symbol);
             
@@ -161,200 +1119,2527 @@ pub async fn run_cli(cli: Cli) -> Result<()> {
                 leverage,
                 reduce_only,
                 tif,
+                margin_mode: margin,
+                // grouped orders are cancelled by cloid, so they always get one
+                cloid: cloid.or_else(|| group.as_ref().map(|_| Uuid::new_v4())),
+                slippage,
+                builder,
             };
             
-            match trading.place_order(order_request).await {
+            let started = chrono::Utc::now().timestamp_millis() as u64;
+            match trading.place_order(order_request.clone()).await {
                 Ok(response) => {
-                    print_order_response(&response, "SELL", &symbol, qty, limit.is_none());
+                    let slippage_bound = limit.is_none().then(|| trading.market_slippage(slippage));
+                    print_order_response(&response, "SELL", &symbol, qty, slippage_bound);
+                    if let Some(group) = &group {
+                        remember_in_group(&groups_path, group, &symbol, &response)?;
+                    }
+                    if let (Some(config), Some(arrival_mid)) = (&report_config, arrival_mid) {
+                        report_execution(config, &response, &symbol, false, qty, arrival_mid, started).await?;
+                    }
+                    if let (Some(exchange), Some(anchor)) = (&peg_exchange, peg_anchor) {
+                        let params = PegParams { offset_bps, reprice_bps, max_reprices_per_minute: max_reprices_per_min };
+                        follow_peg(&trading, exchange, order_request, &response, anchor, params).await?;
+                    }
                 },
-                Err(e) => {
-                    eprintln!("Failed to place SELL order: {}", e);
-                    std::process::exit(1);
-                }
+                Err(e) => return Err(e.context("Failed to place SELL order")),
             }
         },
         Commands::Cancel { symbol, order_id } => {
             let trading = TradingService::new(config).await?;
+            let symbol = trading.resolve_symbol(&symbol).await?.name;
             println!("Cancelling order {} for {}", order_id, symbol);
             
             match trading.cancel_order(&symbol, order_id).await {
+                Ok(_) if trading.is_dry_run() => {
+                    println!("DRY RUN: would cancel order {} for {}", order_id, symbol);
+                },
                 Ok(_) => {
                     println!("Order {} cancelled successfully", order_id);
                 },
-                Err(e) => {
-                    eprintln!("Failed to cancel order: {}", e);
-                    std::process::exit(1);
-                }
+                Err(e) => return Err(e.context("Failed to cancel order")),
             }
         },
-    }
-    
-    Ok(())
-}
+        Commands::CancelCloid { symbol, cloid } => {
+            let trading = TradingService::new(config).await?;
+            let symbol = trading.resolve_symbol(&symbol).await?.name;
+            let cloid_str = cloid_hex(&cloid);
+            println!("Cancelling order {} for {}", cloid_str, symbol);
 
-fn print_order_response(response: &crate::types::OrderResponse, side: &str, symbol: &str, qty: f64, is_market: bool) {
-    let order_type = if is_market { "MARKET" } else { "LIMIT" };
-    
-    println!("\n╔═══════════════════════════════════════╗");
-    println!("║           ORDER CONFIRMATION         ║");
-    println!("╠═══════════════════════════════════════╣");
-    println!("║ Type: {} {:<25} ║", order_type, side);
-    println!("║ Symbol: {:<29} ║", symbol);
-    println!("║ Quantity: {:<27.4} ║", qty);
-    println!("║ Status: {:<29} ║", response.status);
-    
-    match &response.result {
-        crate::types::OrderResult::Success { order_id, filled_qty, avg_price } => {
-            println!("║ Order ID: {:<27} ║", order_id);
-            if *filled_qty > 0.0 {
-                println!("║ Filled: {:.4} @ ${:<15.4} ║", filled_qty, avg_price.unwrap_or(0.0));
-                if is_market {
-                    println!("║ Market order executed!            ║");
+            match trading.cancel_by_cloid(&symbol, cloid).await {
+                Ok(_) if trading.is_dry_run() => {
+                    println!("DRY RUN: would cancel order {} for {}", cloid_str, symbol);
+                },
+                Ok(_) => {
+                    println!("Order {} cancelled successfully", cloid_str);
+                },
+                Err(e) => return Err(e.context("Failed to cancel order")),
+            }
+        },
+        Commands::CancelGroup { group } => {
+            let mut groups = OrderGroups::load(&config.order_groups_path)?;
+            let count = groups.get(&group).map_or(0, |orders| orders.len());
+            confirm.ask(&format!("Cancel order group {}", group), &[("Orders", count.to_string())])?;
+            let trading = TradingService::new(config).await?;
+            println!("Cancelling order group {}...", group);
+            let report = order_groups::cancel_group(&trading, &mut groups, &group).await?;
+            print_group_cancel_report(&group, &report);
+            if !report.errors.is_empty() {
+                anyhow::bail!("{} order(s) in group {} were not cancelled", report.errors.len(), group);
+            }
+        },
+        Commands::Deadman { timeout, keepalive, off } => {
+            let trading = TradingService::new(config).await?;
+            if trading.is_paper() {
+                anyhow::bail!("The dead man's switch acts on live orders; paper orders are never on the exchange");
+            }
+            match timeout {
+                Some(timeout) if !off => {
+                    deadman::check_timeout(timeout)?;
+                    let cancel_at = deadman::arm(&trading, timeout).await?;
+                    let when = chrono::DateTime::from_timestamp_millis(cancel_at as i64)
+                        .map(|dt| dt.format("%H:%M:%S UTC").to_string())
+                        .unwrap_or_default();
+                    if trading.is_dry_run() {
+                        println!("DRY RUN: would cancel all open orders at {}", when);
+                    } else if keepalive {
+                        println!("Dead man's switch armed ({}s); heartbeating until Ctrl-C...", timeout.as_secs());
+                        deadman::keepalive(&trading, timeout).await?;
+                        println!("Heartbeat stopped; open orders will be cancelled within {}s", timeout.as_secs());
+                    } else {
+                        println!("All open orders will be cancelled at {} unless re-armed", when);
+                    }
                 }
+                _ => {
+                    deadman::disarm(&trading).await?;
+                    println!("Dead man's switch disarmed");
+                }
+            }
+        },
+        Commands::Price { symbol, watch } => {
+            let exchange = ExchangeService::new(config.clone())?;
+            let symbol = exchange.resolve_symbol(&symbol).await?.name;
+            if watch {
+                println!("{:<10} {:>12} {:>12} {:>12} {:>9} {:>9}", "TIME", "MID", "MARK", "ORACLE", "24H", "PREMIUM");
+                price::watch(&exchange, &config.ws_url, &config.http, &symbol, |p| {
+                    println!(
+                        "{:<10} {:>12} {:>12.4} {:>12.4} {:>8.2}% {:>8.4}%",
+                        chrono::Utc::now().format("%H:%M:%S"),
+                        p.mid.map(|m| format!("{:.4}", m)).unwrap_or_else(|| "-".to_string()),
+                        p.mark,
+                        p.oracle,
+                        p.change_24h * 100.0,
+                        p.premium * 100.0,
+                    );
+                })
+                .await?;
             } else {
-                if is_market {
-                    println!("║ Market order awaiting fill         ║");
+                print_price(&exchange.get_price(&symbol).await?);
+            }
+        },
+        Commands::Impact { symbol, qty, sell, slippage } => {
+            let trading = TradingService::new(config).await?;
+            let symbol = trading.resolve_symbol(&symbol).await?.name;
+            let estimate = trading.estimate_impact(&symbol, qty, !sell).await?;
+            print_impact(&estimate, trading.market_slippage(slippage));
+        },
+        Commands::Margin { symbol, add, remove } => {
+            let amount = match (add, remove) {
+                (Some(add), _) => add,
+                (None, Some(remove)) => -remove,
+                (None, None) => anyhow::bail!("Specify --add or --remove"),
+            };
+            if amount == 0.0 || !amount.is_finite() {
+                anyhow::bail!("Margin amount must be non-zero");
+            }
+            let trading = TradingService::new(config).await?;
+            let symbol = trading.resolve_symbol(&symbol).await?.name;
+            trading.update_isolated_margin(&symbol, amount).await?;
+            if trading.is_dry_run() {
+                println!("DRY RUN: would move ${:.2} {} {} isolated margin", amount.abs(), if amount > 0.0 { "into" } else { "out of" }, symbol);
+            } else {
+                println!("{} ${:.2} {} {} isolated margin", if amount > 0.0 { "Added" } else { "Removed" }, amount.abs(), if amount > 0.0 { "to" } else { "from" }, symbol);
+            }
+        },
+        Commands::Hedge { symbol, ratio, slippage } => {
+            let trading = TradingService::new(config).await?;
+            let symbol = trading.resolve_symbol(&symbol).await?.name;
+            let plan = trading.hedge_plan(&symbol, ratio).await?;
+            print_hedge_plan(&plan);
+            if plan.order_size == 0.0 {
+                println!("Nothing to hedge");
+                return Ok(());
+            }
+            let (is_buy, qty) = (plan.order_size > 0.0, plan.order_size.abs());
+            let estimate = check_impact(&trading, &symbol, qty, is_buy, slippage).await?;
+            confirm_market_order(&confirm, &trading, &estimate, None).await?;
+            let order_request = OrderRequest {
+                symbol: symbol.clone(),
+                is_buy,
+                qty,
+                // offsetting at most the whole position can only ever reduce it
+                reduce_only: ratio <= 1.0,
+                tif: "Ioc".to_string(),
+                slippage,
+                ..Default::default()
+            };
+            let response = trading.place_order(order_request).await?;
+            let side = if is_buy { "BUY" } else { "SELL" };
+            print_order_response(&response, side, &symbol, qty, Some(trading.market_slippage(slippage)));
+        },
+        Commands::Breakeven { symbol, offset_bps } => {
+            let exchange = ExchangeService::new(config.clone())?;
+            let trading = TradingService::with_exchange(config, exchange.clone()).await?;
+            let symbol = trading.resolve_symbol(&symbol).await?.name;
+            if trading.is_paper() {
+                anyhow::bail!("The paper account doesn't simulate trigger orders");
+            }
+            let (balances, triggers, price) =
+                tokio::try_join!(exchange.get_balances(), exchange.get_trigger_orders(Some(&symbol)), exchange.get_price(&symbol))?;
+            let position = balances
+                .positions
+                .iter()
+                .find(|p| p.symbol.eq_ignore_ascii_case(&symbol) && p.size != 0.0)
+                .ok_or_else(|| anyhow::anyhow!("No open {} position", symbol))?;
+            let stop_px = breakeven::breakeven_price(position.entry_price, position.size, offset_bps);
+            breakeven::validate(stop_px, price.mark, position.size)?;
+            let existing = breakeven::find_stop(&triggers, &symbol, position.size);
+            // an existing stop keeps its size, a new one covers the whole position
+            let size = existing.map_or(position.size.abs(), |stop| stop.size);
+            let move_label = match existing {
+                Some(stop) => format!("${:.4} -> ${:.4}", stop.trigger_px, stop_px),
+                None => format!("new at ${:.4}", stop_px),
+            };
+            confirm.ask(
+                &format!("Move {} stop to breakeven", symbol),
+                &[
+                    ("Position", format!("{:+} @ ${:.4}", position.size, position.entry_price)),
+                    ("Stop", move_label),
+                    ("Size", size.to_string()),
+                ],
+            )?;
+
+            // the new stop goes in before the old one is cancelled, so the position is never unprotected
+            let response = trading
+                .place_trigger(&symbol, position.size < 0.0, size, stop_px, "sl")
+                .await
+                .context("New stop rejected, the existing one was left in place")?;
+            print_trigger_response(&response, "Breakeven stop", stop_px);
+            if let Some(stop) = existing {
+                trading
+                    .cancel_order(&symbol, stop.order_id)
+                    .await
+                    .with_context(|| format!("Breakeven stop placed but the old stop {} is still open", stop.order_id))?;
+                if trading.is_dry_run() {
+                    println!("DRY RUN: would cancel the old stop {} at ${:.4}", stop.order_id, stop.trigger_px);
                 } else {
-                    println!("║ Limit order resting on book       ║");
+                    println!("Cancelled the old stop {} at ${:.4}", stop.order_id, stop.trigger_px);
+                }
+            }
+        },
+        Commands::Protect { sl, tp } => {
+            let protection = protect::Protection {
+                stop_loss: sl.as_deref().map(guard::parse_fraction).transpose()?,
+                take_profit: tp.as_deref().map(guard::parse_fraction).transpose()?,
+            };
+            if protection.stop_loss.is_none() && protection.take_profit.is_none() {
+                anyhow::bail!("Pass --sl, --tp or both");
+            }
+            let exchange = ExchangeService::new(config.clone())?;
+            let trading = TradingService::with_exchange(config.clone(), exchange.clone()).await?;
+            if trading.is_paper() {
+                anyhow::bail!("The paper account doesn't simulate trigger orders");
+            }
+            let hub = WsHub::start(config.ws_url.clone(), config.http.clone());
+            println!("Protecting new and increased positions (Ctrl-C to stop)");
+            tokio::select! {
+                result = protect::run(&trading, &exchange, hub, protection) => result?,
+                _ = tokio::signal::ctrl_c() => println!("Protection stopped, existing triggers stay in place"),
+            }
+        },
+        Commands::Flip { symbol, slippage, or_close } => {
+            let trading = TradingService::new(config).await?;
+            let symbol = trading.resolve_symbol(&symbol).await?.name;
+            // twice the position, already on the size grid since the position is
+            let plan = trading.hedge_plan(&symbol, 2.0).await?;
+            if plan.order_size == 0.0 {
+                println!("No open {} position to flip", symbol);
+                return Ok(());
+            }
+            let is_buy = plan.order_size > 0.0;
+            let side = if is_buy { "BUY" } else { "SELL" };
+            println!("Flip {}: {:+} -> {:+} ({} {} at market)", symbol, plan.position, plan.resulting_position, side, plan.order_size.abs());
+            let mut order_request = OrderRequest {
+                symbol: symbol.clone(),
+                is_buy,
+                qty: plan.order_size.abs(),
+                tif: "Ioc".to_string(),
+                slippage,
+                ..Default::default()
+            };
+            // the closing half always passes, so a block comes from the new opposite position
+            let check = trading.check_order(&order_request).await?;
+            if !check.allowed {
+                print_risk_check(&check, side, &symbol, order_request.qty);
+                let rule = check.rule.as_deref().unwrap_or("risk");
+                if !or_close {
+                    anyhow::bail!("Flip blocked by the {} rule, nothing was sent; pass --or-close to just close the position", rule);
+                }
+                println!("Flip blocked by the {} rule, closing the position only", rule);
+                order_request.qty = plan.position.abs();
+                order_request.reduce_only = true;
+            }
+            let estimate = check_impact(&trading, &symbol, order_request.qty, is_buy, slippage).await?;
+            confirm_market_order(&confirm, &trading, &estimate, None).await?;
+            let qty = order_request.qty;
+            let response = trading.place_order(order_request).await?;
+            print_order_response(&response, side, &symbol, qty, Some(trading.market_slippage(slippage)));
+        },
+        Commands::Size { symbol, risk, stop, entry, take_profit, leverage, execute } => {
+            let risk = guard::parse_fraction(&risk)?;
+            let exchange = ExchangeService::new(config.clone())?;
+            let trading = TradingService::new(config).await?;
+            let symbol = trading.resolve_symbol(&symbol).await?.name;
+            let sizing = trading.size_for_risk(&symbol, risk, entry, stop).await?;
+            print_risk_sizing(&sizing, risk);
+            // only a hint, sizing works without it
+            match volatility::fetch(&exchange, &symbol, Duration::from_secs(24 * 3600)).await {
+                Ok(report) => print_stop_hints(&sizing, &report),
+                Err(e) => tracing::debug!(error = %e, "no volatility for stop hints"),
+            }
+            if !execute {
+                return Ok(());
+            }
+            if trading.is_paper() {
+                anyhow::bail!("--execute needs trigger orders, which the paper account doesn't simulate");
+            }
+            if let Some(tp) = take_profit {
+                let wrong_side = if sizing.is_buy { tp <= sizing.entry } else { tp >= sizing.entry };
+                if wrong_side {
+                    anyhow::bail!("Take profit {} is on the wrong side of entry {}", tp, sizing.entry);
+                }
+            }
+
+            let side = if sizing.is_buy { "BUY" } else { "SELL" };
+            confirm.ask(
+                &format!("{} {} {} with a stop at {}", if entry.is_some() { "Limit" } else { "Market" }, side, symbol, stop),
+                &[
+                    ("Size", sizing.size.to_string()),
+                    ("Notional", format!("~${:.2}", sizing.notional)),
+                    ("Leverage", leverage.map_or_else(|| "current".to_string(), |l| format!("{}x", l))),
+                    ("Risk", format!("${:.2}", sizing.risk_amount)),
+                ],
+            )?;
+            let order_request = OrderRequest {
+                symbol: symbol.clone(),
+                is_buy: sizing.is_buy,
+                qty: sizing.size,
+                limit_price: entry,
+                leverage,
+                ..Default::default()
+            };
+            let response = trading.place_order(order_request).await.context("Entry rejected, no stop placed")?;
+            let slippage_bound = entry.is_none().then(|| trading.market_slippage(None));
+            print_order_response(&response, side, &symbol, sizing.size, slippage_bound);
+
+            let stop_response = trading.place_trigger(&symbol, !sizing.is_buy, sizing.size, stop, "sl").await?;
+            print_trigger_response(&stop_response, "Stop loss", stop);
+            if let Some(tp) = take_profit {
+                let tp_response = trading.place_trigger(&symbol, !sizing.is_buy, sizing.size, tp, "tp").await?;
+                print_trigger_response(&tp_response, "Take profit", tp);
+            }
+        },
+        Commands::Check { symbol, qty, sell, limit, leverage, reduce_only } => {
+            let trading = TradingService::new(config).await?;
+            let symbol = trading.resolve_symbol(&symbol).await?.name;
+            let order_request = OrderRequest {
+                symbol: symbol.clone(),
+                is_buy: !sell,
+                qty,
+                limit_price: limit,
+                leverage,
+                reduce_only,
+                tif: "Gtc".to_string(),
+                ..Default::default()
+            };
+            let result = trading.check_order(&order_request).await?;
+            print_risk_check(&result, if sell { "SELL" } else { "BUY" }, &symbol, qty);
+            if !result.allowed {
+                let reason = result.reason.as_deref().unwrap_or("blocked by the risk rules");
+                return Err(HlError::validation("risk_check", format!("Order would be rejected: {}", reason)).into());
+            }
+        },
+        Commands::Panic => {
+            let exchange = ExchangeService::new(config.clone())?;
+            let trading = TradingService::with_exchange(config, exchange.clone()).await?;
+            if confirm.enabled {
+                let (orders, balances) = tokio::try_join!(exchange.get_open_orders(None), exchange.get_balances())?;
+                let notional: f64 = balances.positions.iter().map(|p| p.position_value.abs()).sum();
+                confirm.ask(
+                    "PANIC: cancel every order and market-close every position",
+                    &[
+                        ("Orders", orders.total_orders.to_string()),
+                        ("Positions", format!("{} (${:.2} notional)", balances.positions.len(), notional)),
+                    ],
+                )?;
+            }
+            println!("PANIC: cancelling all orders and closing all positions...");
+            let report = kill_switch::panic(&trading, &exchange).await?;
+            print_panic_report(&report);
+            if !report.clean && !report.dry_run {
+                anyhow::bail!("Panic left orders or positions behind, check the account");
+            }
+        },
+        Commands::CloseAll { except } => {
+            let exchange = ExchangeService::new(config.clone())?;
+            let trading = TradingService::with_exchange(config, exchange.clone()).await?;
+            if trading.is_paper() {
+                anyhow::bail!("close-all acts on the live account; use `hl paper reset` for the paper account");
+            }
+            let balances = exchange.get_balances().await?;
+            let positions = kill_switch::closable(&balances.positions, &except);
+            if positions.is_empty() {
+                println!("No open positions to close");
+                return Ok(());
+            }
+
+            let (mut closed, mut failed, mut total) = (0, 0, 0.0);
+            for position in positions {
+                let side = if position.size > 0.0 { "LONG" } else { "SHORT" };
+                // answering no skips this position only
+                if let Err(e) = confirm.ask(
+                    &format!("Market-close {} {}", side, position.symbol),
+                    &[
+                        ("Size", position.size.abs().to_string()),
+                        ("Notional", format!("${:.2}", position.position_value.abs())),
+                        ("uPnL", format!("${:.2}", position.unrealized_pnl)),
+                    ],
+                ) {
+                    println!("{:<10} skipped: {}", position.symbol, e);
+                    continue;
+                }
+                let started = chrono::Utc::now().timestamp_millis() as u64;
+                if let Err(e) = trading.close_position(&position.symbol, position.size).await {
+                    eprintln!("{:<10} close failed: {}", position.symbol, e);
+                    failed += 1;
+                    continue;
+                }
+                closed += 1;
+                if trading.is_dry_run() {
+                    println!("{:<10} {} {} (dry run, not sent)", position.symbol, side, position.size.abs());
+                    continue;
+                }
+                // fills take a moment to show up in userFillsByTime
+                tokio::time::sleep(kill_switch::SETTLE_DELAY).await;
+                let now = chrono::Utc::now().timestamp_millis() as u64;
+                match exchange.get_fills_between(started, now).await {
+                    Ok(fills) => {
+                        let pnl = kill_switch::realized_pnl(&fills, &position.symbol, started);
+                        total += pnl;
+                        println!("{:<10} {} {} closed, realized ${:.2}", position.symbol, side, position.size.abs(), pnl);
+                    }
+                    Err(e) => println!("{:<10} {} {} closed, realized PnL unavailable: {}", position.symbol, side, position.size.abs(), e),
+                }
+            }
+            println!("Closed {} position(s), realized ${:.2} net of fees", closed, total);
+            if failed > 0 {
+                anyhow::bail!("{} close(s) failed, check the account", failed);
+            }
+        },
+        Commands::Guard { max_drawdown, interval, lookback } => {
+            let max_drawdown = guard::parse_fraction(&max_drawdown)?;
+            let starting_high = match lookback {
+                Some(lookback) => {
+                    let journal = Journal::from_config(&config).await?;
+                    let since = (chrono::Utc::now().timestamp_millis() as u64).saturating_sub(lookback.as_millis() as u64);
+                    let high = guard::equity_high(&journal.load_equity().await?, since);
+                    match high {
+                        Some(high) => println!("Starting from the recorded high of ${:.2} in {}", high, journal.location()),
+                        None => println!("No equity recorded in {} for that window, starting from the first poll", journal.location()),
+                    }
+                    high
+                }
+                None => None,
+            };
+            let exchange = ExchangeService::new(config.clone())?;
+            let trading = TradingService::with_exchange(config.clone(), exchange.clone()).await?;
+            println!("Guarding account: flatten and lock at {:.2}% drawdown from the session high (Ctrl+C to stop)", max_drawdown * 100.0);
+            let report = guard::run(&config, &trading, &exchange, max_drawdown, std::time::Duration::from_secs(interval.max(1)), starting_high).await?;
+            println!("CIRCUIT BREAKER TRIPPED");
+            print_panic_report(&report);
+            if !report.dry_run {
+                println!("Trading locked ({}); run `hl risk unlock` to resume", config.lock_path);
+            }
+            if !report.clean && !report.dry_run {
+                anyhow::bail!("Flattening left orders or positions behind, check the account");
+            }
+        },
+        Commands::Risk { command } => {
+            let limit = config.risk_limits.max_daily_loss;
+            let lock_path = config.lock_path.clone();
+            let trading = TradingService::new(config).await?;
+            let (today, midnight) = daily_loss::utc_today();
+            match command {
+                RiskCommands::Status => {
+                    let realized = trading.realized_pnl_since(midnight).await?;
+                    let state = trading.daily_loss_guard().state();
+                    println!("Realized PnL today ({}): ${:.2}", today, realized);
+                    match limit {
+                        Some(limit) => {
+                            let breached = realized <= -limit;
+                            println!("Daily loss limit: ${:.2} ({})", limit, if breached { "BREACHED" } else { "ok" });
+                        },
+                        None => println!("Daily loss limit: not configured"),
+                    }
+                    if state.override_day.as_deref() == Some(today.as_str()) {
+                        println!("Override active for today");
+                    }
+                    if let Some(lock) = guard::read_lock(&lock_path)? {
+                        println!("Trading LOCKED since {}: {}", lock.locked_at, lock.reason);
+                    }
+                },
+                RiskCommands::Unlock => {
+                    if guard::clear_lock(&lock_path)? {
+                        println!("Trading lock removed");
+                    } else {
+                        println!("Trading was not locked");
+                    }
+                },
+                RiskCommands::Override { clear } => {
+                    if clear {
+                        trading.daily_loss_guard().set_override(None)?;
+                        println!("Daily loss override cleared");
+                    } else {
+                        trading.daily_loss_guard().set_override(Some(&today))?;
+                        println!("Daily loss limit lifted until 00:00 UTC");
+                    }
+                },
+            }
+        },
+        Commands::PnlSummary => {
+            let notifier = Notifier::new(config.notifications.clone(), &config.http);
+            let exchange = ExchangeService::new(config)?;
+            println!("Building daily PnL summary...");
+            let balances = exchange.get_balances().await?;
+            let summary = DailyPnlSummary::from_balances(&balances);
+            println!("Account value ${:.2}, unrealized PnL ${:.2} across {} positions",
+                summary.account_value, summary.unrealized_pnl, summary.open_positions);
+            notifier.notify(NotificationEvent::DailyPnl(summary)).await;
+            println!("PnL summary sent!");
+        },
+        Commands::Order { symbol, order_id } => {
+            let exchange = ExchangeService::new(config)?;
+            let symbol = exchange.resolve_symbol(&symbol).await?.display;
+            println!("Fetching status of order {}...", order_id);
+            match exchange.get_order_status(&order_id).await? {
+                Some(status) => {
+                    if status.order.symbol != symbol {
+                        eprintln!("Warning: order {} is on {}, not {}", order_id, status.order.symbol, symbol);
+                    }
+                    print_order_status(&status);
+                },
+                None => anyhow::bail!("Order {} not found for this account", order_id),
+            }
+        },
+        Commands::Orders { symbol } => {
+            let exchange = ExchangeService::new(config)?;
+            let symbol = match symbol {
+                Some(symbol) => Some(exchange.resolve_symbol(&symbol).await?.name),
+                None => None,
+            };
+            println!("Fetching open orders...");
+            let orders = exchange.get_open_orders(symbol.as_deref()).await?;
+            print_orders(&orders);
+        },
+        Commands::Fills { symbol, limit } => {
+            let exchange = ExchangeService::new(config)?;
+            let symbol = match symbol {
+                Some(symbol) => Some(exchange.resolve_symbol(&symbol).await?.name),
+                None => None,
+            };
+            println!("Fetching recent fills...");
+            let fills = exchange.get_fills(symbol.as_deref(), Some(limit)).await?;
+            print_fills(&fills);
+        },
+        Commands::Portfolio { fills, funding, json } => {
+            let exchange = ExchangeService::new(config)?;
+            let funding_since = (chrono::Utc::now() - funding).timestamp_millis() as u64;
+            let portfolio = exchange.get_portfolio(fills, funding_since).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&portfolio)?);
+            } else {
+                print_portfolio(&portfolio);
+            }
+        },
+        Commands::Fees { window, json } => {
+            let exchange = ExchangeService::new(config)?;
+            let window_start = (chrono::Utc::now() - window).timestamp_millis() as u64;
+            let (schedule, fills) = tokio::try_join!(exchange.get_fee_schedule(), exchange.get_fills_since(window_start))?;
+            let summary = fees::summarize(schedule, &fills, window_start);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&summary)?);
+            } else {
+                print_fees(&summary, window);
+            }
+        },
+        Commands::Referral { command: ReferralCommands::Set { code } } => {
+            confirm.ask("Set referral code (only allowed once per account)", &[("Code", code.clone())])?;
+            let trading = TradingService::new(config).await?;
+            trading.set_referrer(&code).await?;
+            if trading.is_dry_run() {
+                println!("DRY RUN: would set referral code {}", code);
+            } else {
+                println!("Referral code set to {}", code);
+            }
+        },
+        Commands::Referral { command: ReferralCommands::Status { json } } => {
+            let exchange = ExchangeService::new(config)?;
+            let status = exchange.get_referral_status().await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&status)?);
+            } else {
+                print_referral_status(&status);
+            }
+        },
+        Commands::Builder { command: BuilderCommands::Approve { address, max_fee_bps } } => {
+            let builder = BuilderFee::new(&address, max_fee_bps)?;
+            confirm.ask("Approve builder fee", &[("Builder", builder.address.clone()), ("Max Fee", format!("{} bps", builder.fee_bps))])?;
+            let trading = TradingService::new(config).await?;
+            trading.approve_builder_fee(&builder).await?;
+            if trading.is_dry_run() {
+                println!("DRY RUN: would approve {} for up to {} bps", builder.address, builder.fee_bps);
+            } else {
+                println!("Approved {} for up to {} bps ({})", builder.address, builder.fee_bps, builder_fee_percent(builder.fee_bps));
+            }
+        },
+        Commands::Builder { command: BuilderCommands::Status { address } } => {
+            let exchange = ExchangeService::new(config)?;
+            let approved = exchange.get_max_builder_fee(&address).await?;
+            if approved == 0 {
+                println!("{} is not approved to charge builder fees", address);
+            } else {
+                println!("{} is approved for up to {} bps", address, approved as f64 / 10.0);
+            }
+        },
+        Commands::Vault { command: VaultCommands::Info { vault, json } } => {
+            let exchange = ExchangeService::new(config)?;
+            let info = exchange.get_vault(&vault).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&info)?);
+            } else {
+                print_vault(&info);
+            }
+        },
+        Commands::Vault { command } => {
+            let (vault, usd, is_deposit) = match command {
+                VaultCommands::Deposit { vault, usd } => (vault, usd, true),
+                VaultCommands::Withdraw { vault, usd } => (vault, usd, false),
+                VaultCommands::Info { .. } => unreachable!("handled above"),
+            };
+            let exchange = ExchangeService::new(config.clone())?;
+            let info = exchange.get_vault(&vault).await?;
+            if is_deposit {
+                if info.closed || !info.allow_deposits {
+                    anyhow::bail!("{} is not accepting deposits", info.name);
+                }
+            } else {
+                let position = info
+                    .my_position
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("No deposit in {}", info.name))?;
+                if let Some(lockup) = position.lockup_until
+                    && lockup > chrono::Utc::now().timestamp_millis() as u64
+                {
+                    anyhow::bail!("Deposit in {} is locked until {}", info.name, format_millis(lockup));
+                }
+                if usd > position.equity {
+                    anyhow::bail!("Withdrawal ${:.2} exceeds your ${:.2} in {}", usd, position.equity, info.name);
+                }
+            }
+
+            confirm.ask(
+                &format!("Vault {}", if is_deposit { "deposit" } else { "withdrawal" }),
+                &[("Vault", format!("{} ({})", info.name, info.address)), ("Amount", format!("${:.2}", usd))],
+            )?;
+            let trading = TradingService::with_exchange(config, exchange).await?;
+            trading.vault_transfer(&info.address, usd, is_deposit).await?;
+            let (verb, preposition) = if is_deposit { ("deposit", "into") } else { ("withdraw", "from") };
+            if trading.is_dry_run() {
+                println!("DRY RUN: would {} ${:.2} {} {}", verb, usd, preposition, info.name);
+            } else {
+                println!("{} ${:.2} {} {} ({})", if is_deposit { "Deposited" } else { "Withdrew" }, usd, preposition, info.name, info.address);
+            }
+        },
+        Commands::Transfer { usd, recipient } => {
+            let recipient = contacts::resolve(&config.contacts, &recipient)?;
+            confirm.ask(
+                "USDC transfer",
+                &[
+                    ("Recipient", recipient.label.clone().unwrap_or_else(|| "(not a saved contact)".to_string())),
+                    ("Address", recipient.address.clone()),
+                    ("Amount", format!("${:.2}", usd)),
+                ],
+            )?;
+            let trading = TradingService::new(config).await?;
+            trading.usd_send(&recipient.address, usd).await?;
+            if trading.is_dry_run() {
+                println!("DRY RUN: would send ${:.2} to {}", usd, recipient.describe());
+            } else {
+                println!("Sent ${:.2} to {}", usd, recipient.describe());
+            }
+        },
+        Commands::Withdrawals { window, watch, json } => {
+            let exchange = ExchangeService::new(config)?;
+            let since = (chrono::Utc::now().timestamp_millis() as u64).saturating_sub(window.as_millis() as u64);
+            let mut seen = exchange.get_withdrawals(since).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&seen)?);
+                return Ok(());
+            }
+            print_withdrawals(&seen);
+            while watch && seen.iter().any(|w| w.status == WithdrawalStatus::Pending) {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => break,
+                    _ = tokio::time::sleep(withdrawals::POLL_EVERY) => {}
+                }
+                let latest = exchange.get_withdrawals(since).await?;
+                for withdrawal in &latest {
+                    match seen.iter().find(|w| w.hash == withdrawal.hash).map(|w| w.status) {
+                        None => println!("New withdrawal of ${:.2} at {} ({})", withdrawal.usdc, format_millis(withdrawal.time), withdrawal.hash),
+                        Some(WithdrawalStatus::Pending) if withdrawal.status == WithdrawalStatus::ExpectedFinal => println!(
+                            "Withdrawal of ${:.2} is past the bridge window and expected final, check Arbitrum to confirm ({})",
+                            withdrawal.usdc, withdrawal.hash
+                        ),
+                        _ => {}
+                    }
+                }
+                seen = latest;
+            }
+        },
+        Commands::Config { .. } => unreachable!("handled before the config loads"),
+        Commands::Doctor { json } => {
+            let exchange = ExchangeService::new(config)?;
+            let checks = health::doctor(&exchange).await;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&checks)?);
+            } else {
+                println!("{} on {}", exchange.config().network().to_uppercase(), exchange.config().api_url);
+                for check in &checks {
+                    let latency = check.latency_ms.map(|ms| format!(" ({} ms)", ms)).unwrap_or_default();
+                    println!(
+                        "  {:<4} {:<11} {}{}",
+                        if check.status == "ok" { "PASS" } else { "FAIL" },
+                        check.name,
+                        check.detail.as_deref().unwrap_or_default(),
+                        latency
+                    );
+                }
+            }
+            let failures = checks.iter().filter(|check| check.status != "ok").count();
+            if failures > 0 {
+                anyhow::bail!("{} of {} checks failed", failures, checks.len());
+            }
+        },
+        Commands::Env { command: EnvCommands::List } => {
+            for (name, endpoints) in &config.profiles {
+                let marker = if *name == config.profile { "*" } else { " " };
+                println!("{} {:<12} {}", marker, name, endpoints.api_url);
+            }
+        },
+        Commands::Env { command: EnvCommands::Use { profile } } => {
+            if !config.profiles.contains_key(&profile) {
+                anyhow::bail!(
+                    "Unknown profile '{}', expected one of: {}",
+                    profile,
+                    config.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+                );
+            }
+            let path = loader::config_file_path();
+            loader::set_profile(&path, &profile)?;
+            println!("Selected {} in {}", profile, path.display());
+            let config = Config::load()?;
+            if config.profile != profile {
+                println!("HL_PROFILE={} is set and still takes precedence in this shell", config.profile);
+            }
+            print_env(&config)?;
+        },
+        Commands::Env { command: EnvCommands::Show } => print_env(&config)?,
+        Commands::Contacts { command: ContactsCommands::Add { label, address } } => {
+            let path = loader::config_file_path();
+            let address = contacts::add(&path, &label, &address)?;
+            println!("Saved {} as {} in {}", address, label.trim().to_lowercase(), path.display());
+        },
+        Commands::Contacts { command: ContactsCommands::Remove { label } } => {
+            let path = loader::config_file_path();
+            contacts::remove(&path, &label)?;
+            println!("Removed {} from {}", label.trim().to_lowercase(), path.display());
+        },
+        Commands::Contacts { command: ContactsCommands::List } => {
+            if config.contacts.is_empty() {
+                println!("No contacts saved, add one with `hl contacts add <label> <address>`");
+            }
+            for (label, address) in &config.contacts {
+                println!("{:<20} {}", label, address);
+            }
+        },
+        Commands::Stake { command: StakeCommands::Summary { json } } => {
+            let exchange = ExchangeService::new(config)?;
+            let summary = exchange.get_staking_summary().await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&summary)?);
+            } else {
+                print_staking_summary(&summary);
+            }
+        },
+        Commands::Stake { command } => {
+            let (validator, amount, undelegate) = match command {
+                StakeCommands::Delegate { validator, amount } => (validator, amount, false),
+                StakeCommands::Undelegate { validator, amount } => (validator, amount, true),
+                StakeCommands::Summary { .. } => unreachable!("handled above"),
+            };
+            let exchange = ExchangeService::new(config.clone())?;
+            let validators = exchange.get_validators().await?;
+            let validator = find_validator(&validators, &validator)
+                .ok_or_else(|| anyhow::anyhow!("Unknown validator {}", validator))?;
+            if undelegate {
+                let summary = exchange.get_staking_summary().await?;
+                let delegation = summary
+                    .delegations
+                    .iter()
+                    .find(|d| d.validator.eq_ignore_ascii_case(&validator.address))
+                    .ok_or_else(|| anyhow::anyhow!("Nothing delegated to {}", validator.name))?;
+                if delegation.locked_until > chrono::Utc::now().timestamp_millis() as u64 {
+                    anyhow::bail!("Delegation to {} is locked until {}", validator.name, format_millis(delegation.locked_until));
+                }
+            } else if validator.jailed {
+                eprintln!("Warning: {} is jailed and not earning rewards", validator.name);
+            }
+
+            confirm.ask(
+                if undelegate { "Undelegate HYPE" } else { "Delegate HYPE" },
+                &[("Validator", format!("{} ({})", validator.name, validator.address)), ("Amount", format!("{} HYPE", amount))],
+            )?;
+            let trading = TradingService::with_exchange(config, exchange).await?;
+            trading.delegate(&validator.address, amount, undelegate).await?;
+            let verb = if undelegate { "undelegate" } else { "delegate" };
+            if trading.is_dry_run() {
+                println!("DRY RUN: would {} {} HYPE {} {}", verb, amount, if undelegate { "from" } else { "to" }, validator.name);
+            } else {
+                println!("{}d {} HYPE {} {} ({})", capitalize(verb), amount, if undelegate { "from" } else { "to" }, validator.name, validator.address);
+            }
+        },
+        Commands::RefreshMeta => {
+            let exchange = ExchangeService::new(config)?;
+            let (perps, spot) = exchange.refresh_meta().await?;
+            println!("Refreshed metadata: {} perps, {} spot pairs", perps, spot);
+            if let Some(path) = exchange.pool().meta().path() {
+                println!("Cache file: {}", path.display());
+            }
+        },
+        Commands::Sync => {
+            let journal = Journal::from_config(&config).await?;
+            let exchange = ExchangeService::new(config)?;
+            println!("Syncing account history into {}...", journal.location());
+            let summary = journal::sync(&journal, &exchange).await?;
+            println!("New fills: {}", summary.new_fills);
+            println!("Open orders: {}", summary.open_orders);
+            println!("New funding payments: {}", summary.new_funding_payments);
+            println!("Account value: ${:.2}", summary.account_value);
+            println!("Journal synced successfully!");
+        },
+        Commands::Vol { symbol, window, json } => {
+            let exchange = ExchangeService::new(config)?;
+            let symbol = exchange.resolve_symbol(&symbol).await?.name;
+            let report = volatility::fetch(&exchange, &symbol, window).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print_volatility(&report);
+            }
+        },
+        Commands::Ta { symbol, interval, indicators, json } => {
+            let indicators = indicators.iter().map(|spec| Indicator::parse(spec)).collect::<Result<Vec<_>>>()?;
+            let interval_ms = strategy::interval_millis(&interval)
+                .ok_or_else(|| anyhow::anyhow!("Unsupported candle interval: {}", interval))?;
+            let exchange = ExchangeService::new(config)?;
+            let symbol = exchange.resolve_symbol(&symbol).await?.name;
+            // wilder smoothing and emas settle with a few times their period of history, 200 at least
+            let lookback = indicators.iter().map(Indicator::lookback).max().unwrap_or(0);
+            let count = (lookback as u64 * 3).max(200);
+            let now = chrono::Utc::now().timestamp_millis() as u64;
+            let candles = exchange.get_candles(&symbol, &interval, now.saturating_sub(interval_ms * count), now).await?;
+            let Some(last) = candles.last() else {
+                anyhow::bail!("No {} candles for {}", interval, symbol);
+            };
+            let readings: Vec<_> = indicators.iter().map(|indicator| (indicator, indicator.evaluate(&candles))).collect();
+            if json {
+                let readings: Vec<_> = readings.into_iter().filter_map(|(_, reading)| reading).collect();
+                let output = serde_json::json!({ "symbol": symbol, "interval": interval, "price": last.close, "readings": readings });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                print_ta(&symbol, &interval, last.close, candles.len(), &readings);
+            }
+        },
+        Commands::Scan { command: ScanCommands::Funding { min_oi, min_volume, min_apr, top, json } } => {
+            let exchange = ExchangeService::new(config)?;
+            let markets = exchange.get_status().await?.markets;
+            let mut ranked = scanner::rank_funding(&markets, min_oi, min_volume, min_apr / 100.0);
+            ranked.truncate(top);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&ranked)?);
+            } else {
+                print_funding_scan(&ranked, min_apr);
+            }
+        },
+        Commands::Monitor { command: MonitorCommands::Positions { warn_at, interval } } => {
+            let warn_at = guard::parse_fraction(&warn_at)?;
+            if interval.is_zero() {
+                anyhow::bail!("--interval must be at least 1s");
+            }
+            let notifier = Notifier::new(config.notifications.clone(), &config.http);
+            let exchange = ExchangeService::new(config)?;
+            println!(
+                "Watching liquidation distance every {}s: info under {:.1}%, warn under {:.1}%, critical under {:.1}% (Ctrl-C to stop)...",
+                interval.as_secs(),
+                warn_at * 200.0,
+                warn_at * 100.0,
+                warn_at * 50.0
+            );
+            liquidation_watch::run(&exchange, &notifier, warn_at, interval, |risks| {
+                let time = chrono::Utc::now().format("%H:%M:%S");
+                for risk in risks {
+                    println!(
+                        "{} {:<8} {:<8} {:>+12} mark {:>12.4} liq {:>12.4} {:>7.2}%",
+                        time,
+                        risk.level.to_string(),
+                        risk.symbol,
+                        risk.size,
+                        risk.mark,
+                        risk.liquidation_price,
+                        risk.distance * 100.0
+                    );
+                }
+            })
+            .await?;
+            println!("Monitor stopped");
+        },
+        Commands::Monitor { command: MonitorCommands::Markets { threshold_pct, window, interval, min_oi } } => {
+            if threshold_pct <= 0.0 || interval.is_zero() || window < interval {
+                anyhow::bail!("--threshold-pct must be positive and --window at least one --interval");
+            }
+            let journal = Journal::from_config(&config).await?;
+            let notifier = Notifier::new(config.notifications.clone(), &config.http);
+            let exchange = ExchangeService::new(config)?;
+            let params = market_watch::WatchParams {
+                threshold: threshold_pct / 100.0,
+                window,
+                interval,
+                min_open_interest: min_oi,
+            };
+            println!("Sampling market contexts every {}s; alerting on {}% moves within {}s (Ctrl-C to stop)...", interval.as_secs(), threshold_pct, window.as_secs());
+            market_watch::run(&exchange, &journal, &notifier, &params, |anomaly| {
+                println!(
+                    "{} {:<8} {:<13} {:+.1}% ({:.0} -> {:.0})",
+                    chrono::Utc::now().format("%H:%M:%S"),
+                    anomaly.symbol,
+                    anomaly.metric,
+                    anomaly.change * 100.0,
+                    anomaly.from,
+                    anomaly.to,
+                );
+            })
+            .await?;
+            println!("Monitor stopped");
+        },
+        Commands::Monitor { command: MonitorCommands::Spread { symbols, threshold_bps, collapse_pct, duration } } => {
+            if threshold_bps <= 0.0 || !(collapse_pct > 0.0 && collapse_pct < 100.0) {
+                anyhow::bail!("--threshold-bps must be positive and --collapse-pct between 0 and 100");
+            }
+            let exchange = ExchangeService::new(config.clone())?;
+            let mut resolved = Vec::new();
+            for symbol in &symbols {
+                resolved.push(exchange.resolve_symbol(symbol).await?.name);
+            }
+            let symbols = resolved;
+            let mut tracker = SpreadTracker::new(threshold_bps, 1.0 - collapse_pct / 100.0);
+            println!("Monitoring {} spreads (>{}bps, top size -{}%) for {}s...", symbols.join(", "), threshold_bps, collapse_pct, duration);
+            spread_monitor::run(&config.ws_url, &config.http, &symbols, &mut tracker, duration, |_, alerts| {
+                let time = chrono::Utc::now().format("%H:%M:%S");
+                for alert in alerts {
+                    match alert {
+                        SpreadAlert::Wide { symbol, spread_bps } => {
+                            println!("{} WIDE      {:<8} spread {:.2}bps", time, symbol, spread_bps);
+                        }
+                        SpreadAlert::Thin { symbol, top_notional, average } => {
+                            println!("{} THIN      {:<8} top ${:.0} vs avg ${:.0}", time, symbol, top_notional, average);
+                        }
+                        SpreadAlert::Recovered { symbol, spread_bps, top_notional } => {
+                            println!("{} RECOVERED {:<8} spread {:.2}bps, top ${:.0}", time, symbol, spread_bps, top_notional);
+                        }
+                    }
                 }
+            })
+            .await?;
+            println!("Monitor stopped");
+        },
+        Commands::Bridge { command: BridgeCommands::Mqtt { symbols } } => {
+            let broker = config.mqtt_broker.clone().context("Set mqtt_broker in hl.toml (or HL_MQTT_BROKER)")?;
+            let mut mqtt_config = config.mqtt.clone();
+            if !symbols.is_empty() {
+                mqtt_config.symbols = symbols;
+            }
+            let hub = WsHub::start(config.ws_url.clone(), config.http.clone());
+            let exchange = ExchangeService::new(config)?;
+            println!("Publishing to {} under {}/ (Ctrl-C to stop)", broker, mqtt_config.topic_prefix);
+            tokio::select! {
+                result = mqtt::run(&broker, &mqtt_config, exchange, hub) => result?,
+                _ = tokio::signal::ctrl_c() => println!("Bridge stopped"),
+            }
+        },
+        Commands::Bridge { command: BridgeCommands::Redis { channels, streams } } => {
+            let url = config.redis_url.clone().context("Set redis_url in hl.toml (or HL_REDIS_URL)")?;
+            let mut redis_config = config.redis.clone();
+            if !channels.is_empty() {
+                redis_config.channels = channels;
             }
+            redis_config.streams |= streams;
+            let hub = WsHub::start(config.ws_url.clone(), config.http.clone());
+            let exchange = ExchangeService::new(config)?;
+            println!(
+                "Forwarding {} to {} (Ctrl-C to stop)",
+                redis_config.channels.join(", "),
+                url
+            );
+            tokio::select! {
+                result = redis_bridge::run(&url, &redis_config, exchange, hub) => result?,
+                _ = tokio::signal::ctrl_c() => println!("Bridge stopped"),
+            }
+        },
+        Commands::Bridge { command: BridgeCommands::Influx { interval } } => {
+            let url = config.influx_url.clone().context("Set influx_url in hl.toml (or HL_INFLUX_URL)")?;
+            let mut influx_config = config.influx.clone();
+            influx_config.interval_secs = interval.unwrap_or(influx_config.interval_secs);
+            let http = config.http.clone();
+            let exchange = ExchangeService::new(config)?;
+            println!("Writing to {} every {}s (Ctrl-C to stop)", url, influx_config.interval_secs.max(1));
+            tokio::select! {
+                result = influx::run(&url, &influx_config, &http, exchange) => result?,
+                _ = tokio::signal::ctrl_c() => println!("Bridge stopped"),
+            }
+        },
+        Commands::Bridge { command: BridgeCommands::Kafka { symbols } } => {
+            let brokers = config.kafka_brokers.clone().context("Set kafka_brokers in hl.toml (or HL_KAFKA_BROKERS)")?;
+            let mut kafka_config = config.kafka.clone();
+            if !symbols.is_empty() {
+                kafka_config.symbols = symbols;
+            }
+            let hub = WsHub::start(config.ws_url.clone(), config.http.clone());
+            let exchange = ExchangeService::new(config)?;
+            println!(
+                "Producing to {} ({}, {}, {}) (Ctrl-C to stop)",
+                brokers, kafka_config.trades_topic, kafka_config.candles_topic, kafka_config.user_events_topic
+            );
+            tokio::select! {
+                result = kafka::run(&brokers, &kafka_config, exchange, hub) => result?,
+                _ = tokio::signal::ctrl_c() => println!("Bridge stopped"),
+            }
+        },
+        Commands::Journal { command: JournalCommands::Query { sql, json } } => {
+            let (columns, rows) = Journal::from_config(&config).await?.query(&sql).await?;
+            if json {
+                let objects: Vec<serde_json::Map<String, serde_json::Value>> = rows
+                    .iter()
+                    .map(|row| {
+                        columns
+                            .iter()
+                            .cloned()
+                            .zip(row.iter().map(|v| serde_json::Value::String(v.clone())))
+                            .collect()
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&objects)?);
+            } else {
+                print_query_result(&columns, &rows);
+            }
+        },
+        Commands::Snapshot => {
+            let lock_path = config.lock_path.clone();
+            let exchange = ExchangeService::new(config.clone())?;
+            let trading = TradingService::with_exchange(config, exchange.clone()).await?;
+            let snapshot = snapshot::capture(&trading, &exchange, &lock_path).await?;
+            println!("{}", serde_json::to_string_pretty(&snapshot)?);
+        },
+        Commands::Diff { before, after, json } => {
+            let diff = snapshot::diff(&snapshot::load(std::path::Path::new(&before))?, &snapshot::load(std::path::Path::new(&after))?);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            } else {
+                print_snapshot_diff(&diff);
+            }
+        },
+        Commands::Stats { json } => {
+            let journal = Journal::from_config(&config).await?;
+            let equity = journal.load_equity().await?;
+            let trades = crate::services::tax::closed_trades(&journal.load_fills(None).await?, CostMethod::Fifo);
+            let stats = crate::services::stats::compute(&equity, &trades);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+            } else {
+                print_stats(&stats);
+            }
+        },
+        Commands::Strategy { command: StrategyCommands::Run { script, symbol, duration, deadman } } => {
+            let mut strategy = ScriptStrategy::from_file(&script)?;
+            let ws_url = config.ws_url.clone();
+            let http = config.http.clone();
+            let exchange = ExchangeService::new(config.clone())?;
+            let trading = TradingService::with_exchange(config, exchange.clone()).await?;
+            let _watcher = risk_reload::watch(trading.shared_risk_limits())?;
+            let symbol = exchange.resolve_symbol(&symbol).await?.name;
+            if let Some(timeout) = deadman {
+                deadman::check_timeout(timeout)?;
+            }
+            println!("Running {} on {} for {}s...", script.display(), symbol, duration);
+            strategy::run(&mut strategy, &trading, &exchange, &ws_url, &http, &symbol, duration, deadman).await?;
+            println!("Strategy finished");
+        },
+        Commands::Strategy { command: StrategyCommands::Mm { symbol, spread_bps, qty, requote_bps, max_position, skew_bps, duration, deadman } } => {
+            if spread_bps <= 0.0 || qty <= 0.0 {
+                anyhow::bail!("--spread-bps and --qty must be greater than 0");
+            }
+            let mut strategy = MarketMaker::new(MarketMakerParams {
+                spread_bps,
+                qty,
+                requote_bps,
+                max_position: max_position.unwrap_or(qty * 10.0),
+                skew_bps,
+            });
+            let ws_url = config.ws_url.clone();
+            let http = config.http.clone();
+            let exchange = ExchangeService::new(config.clone())?;
+            let trading = TradingService::with_exchange(config, exchange.clone()).await?;
+            let _watcher = risk_reload::watch(trading.shared_risk_limits())?;
+            let symbol = exchange.resolve_symbol(&symbol).await?.name;
+            if let Some(timeout) = deadman {
+                deadman::check_timeout(timeout)?;
+            }
+            println!("Market making {} at {}bps, {} per side, for {}s...", symbol, spread_bps, qty, duration);
+            strategy::run(&mut strategy, &trading, &exchange, &ws_url, &http, &symbol, duration, deadman).await?;
+            println!("Strategy finished");
+        },
+        Commands::Strategy { command: StrategyCommands::Momentum { symbol, qty, interval, fast, slow, long_only, duration, deadman } } => {
+            if fast == 0 || fast >= slow {
+                anyhow::bail!("--fast must be at least 1 and shorter than --slow");
+            }
+            if strategy::interval_millis(&interval).is_none() {
+                anyhow::bail!("Unsupported candle interval: {}", interval);
+            }
+            let mut strategy = Momentum::new(MomentumParams { interval, fast, slow, qty, long_only });
+            let ws_url = config.ws_url.clone();
+            let http = config.http.clone();
+            let exchange = ExchangeService::new(config.clone())?;
+            let trading = TradingService::with_exchange(config, exchange.clone()).await?;
+            let _watcher = risk_reload::watch(trading.shared_risk_limits())?;
+            let symbol = exchange.resolve_symbol(&symbol).await?.name;
+            if let Some(timeout) = deadman {
+                deadman::check_timeout(timeout)?;
+            }
+            println!("Trading {}/{} crossovers on {} for {}s...", fast, slow, symbol, duration);
+            strategy::run(&mut strategy, &trading, &exchange, &ws_url, &http, &symbol, duration, deadman).await?;
+            println!("Strategy finished");
+        },
+        Commands::Paper { command } => match command {
+            PaperCommands::Status => {
+                let engine = PaperEngine::load(&config.paper)?;
+                let exchange = ExchangeService::new(config)?;
+                println!("Fetching paper account...");
+                let mids = exchange.get_mids().await?;
+                print_paper_account(engine.account(), &mids);
+            },
+            PaperCommands::Reset { balance } => {
+                let balance = balance.unwrap_or(config.paper.starting_balance);
+                PaperEngine::with_account(&config.paper, PaperAccount::new(balance)).save()?;
+                println!("Paper account reset to ${:.2} ({})", balance, config.paper.path);
+            },
+            PaperCommands::Run { duration } => {
+                let mut engine = PaperEngine::load(&config.paper)?;
+                println!("Watching {} resting paper orders for {}s...", engine.account().open_orders.len(), duration);
+                let fills = paper::run(&mut engine, &config.ws_url, &config.http, duration).await?;
+                for fill in &fills {
+                    println!("Filled #{} {} {} {:.4} @ ${:.4} (pnl ${:.2}, fee ${:.4})",
+                        fill.order_id,
+                        if fill.is_buy { "BUY" } else { "SELL" },
+                        fill.symbol,
+                        fill.size,
+                        fill.price,
+                        fill.closed_pnl,
+                        fill.fee
+                    );
+                }
+                println!("{} paper fills, {} orders still resting", fills.len(), engine.account().open_orders.len());
+            },
+        },
+        Commands::Export { command: ExportCommands::Tax { year, method, out } } => {
+            use chrono::TimeZone;
+
+            let year_start = chrono::Utc
+                .with_ymd_and_hms(year, 1, 1, 0, 0, 0)
+                .single()
+                .ok_or_else(|| anyhow::anyhow!("Invalid year: {}", year))?
+                .timestamp_millis() as u64;
+            let year_end = chrono::Utc
+                .with_ymd_and_hms(year + 1, 1, 1, 0, 0, 0)
+                .single()
+                .ok_or_else(|| anyhow::anyhow!("Invalid year: {}", year))?
+                .timestamp_millis() as u64;
+
+            // lots opened in earlier years still need to be known
+            let journal = Journal::from_config(&config).await?;
+            let fills = journal.load_fills(Some(year_end)).await?;
+            let trades: Vec<_> = crate::services::tax::closed_trades(&fills, method)
+                .into_iter()
+                .filter(|trade| trade.closed_at >= year_start)
+                .collect();
+            let csv = crate::services::tax::to_csv(&trades);
+
+            match out {
+                Some(path) => {
+                    std::fs::write(&path, csv)?;
+                    let total: f64 = trades.iter().map(|t| t.gain).sum();
+                    println!("Wrote {} closed trades to {} (realized gain ${:.2})", trades.len(), path, total);
+                },
+                None => print!("{}", csv),
+            }
+        },
+        Commands::Record { command: RecordCommands::Equity { every, duration } } => {
+            if every.is_zero() {
+                anyhow::bail!("--every must be positive");
+            }
+            let journal = Journal::from_config(&config).await?;
+            let exchange = ExchangeService::new(config)?;
+            println!("Recording equity to {} every {}s (Ctrl-C to stop)", journal.location(), every.as_secs());
+            let mut samples = 0;
+            let recording = journal::record_equity(&journal, &exchange, every, |balances| {
+                samples += 1;
+                let unrealized: f64 = balances.positions.iter().map(|p| p.unrealized_pnl).sum();
+                println!(
+                    "{} equity ${:.2}, margin used ${:.2}, unrealized ${:+.2}",
+                    chrono::Utc::now().format("%H:%M:%S"),
+                    balances.account_value,
+                    balances.cross_margin_used,
+                    unrealized
+                );
+            });
+            tokio::select! {
+                result = recording => result?,
+                _ = async {
+                    match duration {
+                        Some(duration) => tokio::time::sleep(duration).await,
+                        None => std::future::pending().await,
+                    }
+                } => {}
+                _ = tokio::signal::ctrl_c() => {}
+            }
+            println!("Recorded {} snapshots", samples);
+        },
+        Commands::Record { command: RecordCommands::Book { symbol, every, out, depth, duration } } => {
+            if every.is_zero() {
+                anyhow::bail!("--every must be positive");
+            }
+            let symbol = ExchangeService::new(config.clone())?.resolve_symbol(&symbol).await?.name;
+            let hub = WsHub::start(config.ws_url.clone(), config.http.clone());
+            println!("Recording {} book every {:?} into {} (Ctrl-C to stop)", symbol, every, out);
+            tokio::select! {
+                written = book_recorder::run(hub, &symbol, every, depth, duration, std::path::Path::new(&out)) => {
+                    println!("Wrote {} snapshots to {}", written?, out);
+                }
+                _ = tokio::signal::ctrl_c() => println!("Recording stopped"),
+            }
+        },
+        Commands::Download { command: DownloadCommands::Trades { symbol, from, to, out, record } } => {
+            if from >= to {
+                anyhow::bail!("--from must be before --to");
+            }
+            let exchange = ExchangeService::new(config.clone())?;
+            let symbol = exchange.resolve_symbol(&symbol).await?.name;
+            let hub = record.then(|| WsHub::start(config.ws_url.clone(), config.http.clone()));
+            if record {
+                println!("Recording live {} trades into {} until {} (Ctrl-C to stop, rerun to resume)", symbol, out, format_millis(to));
+            }
+            tokio::select! {
+                summary = download::trades(&exchange, hub, &symbol, from, to, std::path::Path::new(&out)) => print_download(&summary?, &out),
+                _ = tokio::signal::ctrl_c() => println!("Stopped, rerun the same command to resume"),
+            }
+        },
+        Commands::Download { command: DownloadCommands::Candles { symbol, interval, from, to, out } } => {
+            if from >= to {
+                anyhow::bail!("--from must be before --to");
+            }
+            let exchange = ExchangeService::new(config)?;
+            let symbol = exchange.resolve_symbol(&symbol).await?.name;
+            let summary = download::candles(&exchange, &symbol, &interval, from, to, std::path::Path::new(&out)).await?;
+            print_download(&summary, &out);
+        },
+    }
+    
+    Ok(())
+}
+
+// slippage_bound is set for market orders
+fn print_order_response(response: &crate::types::OrderResponse, side: &str, symbol: &str, qty: f64, slippage_bound: Option<f64>) {
+    let is_market = slippage_bound.is_some();
+    let order_type = if is_market { "MARKET" } else { "LIMIT" };
+    if let Some(cloid) = &response.cloid {
+        println!("Client order ID: {}", cloid_hex(cloid));
+    }
+    
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║           ORDER CONFIRMATION         ║");
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Type: {} {:<25} ║", order_type, side);
+    println!("║ Symbol: {:<29} ║", symbol);
+    println!("║ Quantity: {:<27.4} ║", qty);
+    if let Some(bound) = slippage_bound {
+        println!("║ Slippage Bound: {:<21} ║", format!("{:.2}%", bound * 100.0));
+    }
+    println!("║ Status: {:<29} ║", response.status);
+    
+    match &response.result {
+        crate::types::OrderResult::Success { order_id, filled_qty, avg_price } => {
+            println!("║ Order ID: {:<27} ║", order_id);
+            if *filled_qty > 0.0 {
+                println!("║ Filled: {:.4} @ ${:<15.4} ║", filled_qty, avg_price.unwrap_or(0.0));
+                if is_market {
+                    println!("║ Market order executed!            ║");
+                }
+            } else {
+                if is_market {
+                    println!("║ Market order awaiting fill         ║");
+                } else {
+                    println!("║ Limit order resting on book       ║");
+                }
+            }
+        },
+        crate::types::OrderResult::Error { message } => {
+            println!("║ Error: {:<26} ║", message);
+        },
+        crate::types::OrderResult::Resting { order_id } => {
+            println!("║ Order resting - ID: {:<15} ║", order_id);
+            if is_market {
+                println!("║ Market order resting (low liq)    ║");
+            }
+        },
+        crate::types::OrderResult::DryRun { price, size, notional, estimated_fee } => {
+            println!("║ Would send: {:.4} @ ${:<16.4} ║", size, price);
+            println!("║ Est. Notional: ${:<22.2} ║", notional);
+            println!("║ Est. Fee: ${:<27.4} ║", estimated_fee);
+            println!("║ DRY RUN - nothing was submitted       ║");
+            println!("╚═══════════════════════════════════════╝");
+            return;
+        }
+    }
+    println!("║ Timestamp: {:<23} ║", response.timestamp);
+    println!("╚═══════════════════════════════════════╝");
+    println!("Order submitted successfully!");
+}
+
+fn print_status(status: &crate::types::StatusResponse) {
+    print!("{}", render_status(status).unwrap_or_default());
+    println!("Status retrieved successfully!");
+}
+
+// this process's share of the per-ip budget, other processes on the same ip spend it too
+fn render_rate_budget(budget: &RateBudgetSnapshot) -> String {
+    format!(
+        "Rate budget: {}/{} weight left this minute ({} used, {:.1}s spent waiting)\n",
+        budget.remaining,
+        budget.capacity,
+        budget.used_total,
+        budget.throttled_ms_total as f64 / 1000.0
+    )
+}
+
+fn render_status(status: &crate::types::StatusResponse) -> Result<String, std::fmt::Error> {
+    let mut out = String::new();
+    writeln!(out, "\n╔═══════════════════════════════════════════════════════════════╗")?;
+    writeln!(out, "║                  HYPERLIQUID TESTNET STATUS                   ║")?;
+    writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+    writeln!(out, "║ Available Markets: {:<39} ║", status.total_markets)?;
+    writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+    writeln!(out, "║{:<12} {:<12} {:<12} {:<12} {:<8} {:<12}║", 
+        "SYMBOL", "MARK PRICE", "24H VOLUME", "FUNDING", "MAX LEV", "OPEN INT")?;
+    writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+    
+    for market in status.markets.iter().take(10) {
+        writeln!(out, "║{:<12} ${:<11.4} ${:<11.0} {:<11.6} {:<8}x ${:<11.0}║", 
+            market.symbol,
+            market.mark_price,
+            market.volume_24h,
+            market.funding_rate * 100.0,
+            market.max_leverage,
+            market.open_interest
+        )?;
+    }
+    
+    writeln!(out, "╚═══════════════════════════════════════════════════════════════╝")?;
+    if status.markets.len() > 10 {
+        writeln!(out, "... and {} more markets", status.markets.len() - 10)?;
+    }
+    Ok(out)
+}
+
+fn print_balances(balances: &crate::types::BalanceResponse) {
+    print!("{}", render_balances(balances).unwrap_or_default());
+    println!("Balances retrieved successfully!");
+}
+
+fn render_balances(balances: &crate::types::BalanceResponse) -> Result<String, std::fmt::Error> {
+    let mut out = String::new();
+    writeln!(out, "\n╔═══════════════════════════════════════════════════════════════╗")?;
+    writeln!(out, "║                        ACCOUNT SUMMARY                        ║")?;
+    writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+    writeln!(out, "║ Account Value: ${:<43.2} ║", balances.account_value)?;
+    writeln!(out, "║ Withdrawable: ${:<44.2} ║", balances.withdrawable)?;
+    writeln!(out, "║ Cross Margin Used: ${:<38.2} ║", balances.cross_margin_used)?;
+    writeln!(out, "║ Maintenance Margin: ${:<38.2} ║", balances.maintenance_margin)?;
+    writeln!(out, "║ Margin Ratio: {:<45} ║", format!("{:.2}% (liquidation at 100%)", balances.margin_ratio * 100.0))?;
+    writeln!(out, "║ Free Collateral: ${:<41.2} ║", balances.free_collateral)?;
+    
+    if !balances.positions.is_empty() {
+        writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+        writeln!(out, "║                           POSITIONS                           ║")?;
+        writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+        writeln!(out, "║{:<8} {:<12} {:<12} {:<8} {:<12} {:<12}║", 
+            "ASSET", "SIZE", "ENTRY PRICE", "LEVERAGE", "UNREALIZED", "VALUE")?;
+        writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+        
+        for pos in &balances.positions {
+            let size_colored = if pos.size > 0.0 {
+                format!("LONG {:.4}", pos.size)
+            } else {
+                format!("SHORT {:.4}", pos.size)
+            };
+            
+            let pnl_colored = if pos.unrealized_pnl > 0.0 {
+                format!("PROFIT ${:.2}", pos.unrealized_pnl)
+            } else {
+                format!("LOSS ${:.2}", pos.unrealized_pnl)
+            };
+            
+            let leverage = format!("{}x {}", pos.leverage, if pos.margin_mode == "isolated" { "iso" } else { "cross" });
+            writeln!(out, "║{:<8} {:<12} ${:<11.4} {:<9} {:<12} ${:<11.2}║", 
+                pos.symbol,
+                size_colored,
+                pos.entry_price,
+                leverage,
+                pnl_colored,
+                pos.position_value
+            )?;
+        }
+    } else {
+        writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+        writeln!(out, "║                     No open positions                         ║")?;
+    }
+    
+    writeln!(out, "╚═══════════════════════════════════════════════════════════════╝")?;
+    Ok(out)
+}
+
+fn render_multi_balances(multi: &crate::types::MultiBalanceResponse) -> Result<String, std::fmt::Error> {
+    let mut out = String::new();
+    writeln!(out, "\n╔═══════════════════════════════════════════════════════════════╗")?;
+    writeln!(out, "║                           ACCOUNTS                            ║")?;
+    writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+    writeln!(out, "║ {:<12} {:>12} {:>12} {:>11} {:>5}       ║", "ADDRESS", "VALUE", "WITHDRAW", "PNL", "POS")?;
+    writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+    for account in &multi.accounts {
+        let balances = &account.balances;
+        let unrealized: f64 = balances.positions.iter().map(|p| p.unrealized_pnl).sum();
+        writeln!(out, "║ {:<12} {:>12.2} {:>12.2} {:>+11.2} {:>5}       ║",
+            short_address(&account.address), balances.account_value, balances.withdrawable, unrealized, balances.positions.len())?;
+    }
+    writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+    writeln!(out, "║ Total Account Value: {:<40} ║", format!("${:.2}", multi.total_account_value))?;
+    writeln!(out, "║ Total Withdrawable: {:<41} ║", format!("${:.2}", multi.total_withdrawable))?;
+    writeln!(out, "║ Total Margin Used: {:<42} ║", format!("${:.2}", multi.total_margin_used))?;
+    writeln!(out, "║ Total Unrealized PnL: {:<39} ║", format!("${:+.2}", multi.total_unrealized_pnl))?;
+    writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+    writeln!(out, "║                       COMBINED EXPOSURE                       ║")?;
+    writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+    if multi.exposure.is_empty() {
+        writeln!(out, "║                     No open positions                         ║")?;
+    } else {
+        writeln!(out, "║ {:<8} {:>11} {:>11} {:>11} {:>11} {:>4} ║", "ASSET", "NET SIZE", "NET VALUE", "GROSS", "PNL", "ACCT")?;
+        for exposure in &multi.exposure {
+            writeln!(out, "║ {:<8} {:>+11.4} {:>+11.2} {:>11.2} {:>+11.2} {:>4} ║",
+                exposure.symbol,
+                exposure.net_size,
+                exposure.net_value(),
+                exposure.long_value + exposure.short_value,
+                exposure.unrealized_pnl,
+                exposure.accounts
+            )?;
+        }
+    }
+    writeln!(out, "╚═══════════════════════════════════════════════════════════════╝")?;
+    Ok(out)
+}
+
+// 0x1234…abcd
+fn short_address(address: &str) -> String {
+    if address.len() <= 12 {
+        return address.to_string();
+    }
+    format!("{}…{}", &address[..6], &address[address.len() - 4..])
+}
+
+fn render_positions(balances: &crate::types::BalanceResponse) -> Result<String, std::fmt::Error> {
+    let mut out = String::new();
+    writeln!(out, "\n╔═══════════════════════════════════════════════════════════════╗")?;
+    writeln!(out, "║                           POSITIONS                           ║")?;
+    writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+    if balances.positions.is_empty() {
+        writeln!(out, "║                     No open positions                         ║")?;
+    } else {
+        writeln!(out, "║{:<8} {:<6} {:>10} {:>11} {:>11} {:>11}  ║", "ASSET", "SIDE", "SIZE", "ENTRY", "VALUE", "PNL")?;
+        writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+        for pos in &balances.positions {
+            let side = if pos.size > 0.0 { "LONG" } else { "SHORT" };
+            writeln!(out, "║{:<8} {:<6} {:>10.4} {:>11.4} {:>11.2} {:>11.2}  ║",
+                pos.symbol, side, pos.size.abs(), pos.entry_price, pos.position_value, pos.unrealized_pnl)?;
+        }
+        let unrealized: f64 = balances.positions.iter().map(|p| p.unrealized_pnl).sum();
+        writeln!(out, "╠═══════════════════════════════════════════════════════════════╣")?;
+        writeln!(out, "║ Unrealized PnL: {:<45} ║", format!("${:+.2}", unrealized))?;
+    }
+    writeln!(out, "║ Account Value: {:<46} ║", format!("${:.2}", balances.account_value))?;
+    writeln!(out, "╚═══════════════════════════════════════════════════════════════╝")?;
+    Ok(out)
+}
+
+// --watch takes whole seconds, at least one
+fn watch_interval(secs: u64) -> Result<Duration> {
+    if secs == 0 {
+        anyhow::bail!("--watch interval must be at least 1 second");
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+fn print_portfolio(portfolio: &PortfolioResponse) {
+    let balances = &portfolio.balances;
+    let time = |ms: u64| {
+        chrono::DateTime::from_timestamp_millis(ms as i64)
+            .map(|dt| dt.format("%m-%d %H:%M").to_string())
+            .unwrap_or_default()
+    };
+
+    println!("\n╔═══════════════════════════════════════════════════════════════╗");
+    println!("║                           PORTFOLIO                           ║");
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║ Account Value: {:<46} ║", format!("${:.2}", balances.account_value));
+    println!("║ Withdrawable: {:<47} ║", format!("${:.2}", balances.withdrawable));
+    println!("║ Cross Margin Used: {:<42} ║", format!("${:.2}", balances.cross_margin_used));
+    println!("║ Margin Ratio: {:<47} ║", format!("{:.2}%", balances.margin_ratio * 100.0));
+    println!("║ Unrealized PnL: {:<45} ║", format!("${:+.2}", portfolio.unrealized_pnl));
+    println!("║ Funding Since {}: {:<width$} ║", time(portfolio.funding_since), format!("${:+.4}", portfolio.funding_total), width = 45 - time(portfolio.funding_since).len());
+
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║ {:<61} ║", format!("POSITIONS ({})", balances.positions.len()));
+    for pos in &balances.positions {
+        let side = if pos.size > 0.0 { "LONG" } else { "SHORT" };
+        println!("║ {:<61} ║", format!(
+            "{:<8} {:<5} {:>10.4} @ ${:<10.4} {:>3}x  PnL ${:+.2}",
+            pos.symbol, side, pos.size.abs(), pos.entry_price, pos.leverage, pos.unrealized_pnl
+        ));
+    }
+
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║ {:<61} ║", format!("OPEN ORDERS ({})", portfolio.orders.total_orders));
+    for order in &portfolio.orders.orders {
+        println!("║ {:<61} ║", format!(
+            "{:<8} {:<5} {:>10.4} @ ${:<10.4} id {}",
+            order.symbol, order.side, order.remaining_qty, order.price, order.order_id
+        ));
+    }
+
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║ {:<61} ║", format!("RECENT FILLS ({})", portfolio.fills.total_fills));
+    for fill in &portfolio.fills.fills {
+        println!("║ {:<61} ║", format!(
+            "{} {:<8} {:<5} {:>9.4} @ ${:<10.4} fee ${:.3}",
+            time(fill.timestamp), fill.symbol, fill.side, fill.size, fill.price, fill.fee
+        ));
+    }
+
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║ {:<61} ║", format!("FUNDING ({} payments)", portfolio.funding.len()));
+    for payment in portfolio.funding.iter().take(10) {
+        println!("║ {:<61} ║", format!(
+            "{} {:<8} ${:+.4} rate {:+.4}%",
+            time(payment.timestamp), payment.symbol, payment.usdc, payment.funding_rate * 100.0
+        ));
+    }
+    println!("╚═══════════════════════════════════════════════════════════════╝");
+}
+
+fn print_spot_markets(spot_data: &crate::types::SpotResponse) {
+    println!("\n╔═══════════════════════════════════════════════════════════════╗");
+    println!("║                         SPOT MARKETS                          ║");
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║ Available Tokens: {:<43} ║", spot_data.tokens.len());
+    println!("║ Trading Pairs: {:<46} ║", spot_data.pairs.len());
+    
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║                            TOKENS                             ║");
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║{:<12} {:<8} {:<20}║", "NAME", "DECIMALS", "TOKEN ID");
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    
+    for token in spot_data.tokens.iter().take(5) {
+        let short_id = if token.token_id.len() > 20 {
+            format!("{}...{}", &token.token_id[..8], &token.token_id[token.token_id.len()-6..])
+        } else {
+            token.token_id.clone()
+        };
+        
+        println!("║{:<12} {:<8} {:<20}║", 
+            token.name,
+            token.decimals,
+            short_id
+        );
+    }
+    
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║                         TRADING PAIRS                         ║");
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║{:<15} {:<12} {:<12} {:<12}║", 
+        "PAIR", "MARK PRICE", "MID PRICE", "24H VOLUME");
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    
+    for pair in spot_data.pairs.iter().take(10) {
+        println!("║{:<15} ${:<11.6} ${:<11.6} ${:<11.0}║", 
+            pair.name,
+            pair.mark_price,
+            pair.mid_price,
+            pair.volume_24h
+        );
+    }
+    
+    println!("╚═══════════════════════════════════════════════════════════════╝");
+    if spot_data.pairs.len() > 10 {
+        println!("... and {} more pairs", spot_data.pairs.len() - 10);
+    }
+    println!("Spot markets retrieved successfully!");
+}
+
+fn print_spot_balances(balances: &crate::types::SpotBalancesResponse) {
+    println!("\n╔═══════════════════════════════════════════════════════════════╗");
+    println!("║                         SPOT BALANCES                         ║");
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║ Total Value: ${:<47.2} ║", balances.total_value);
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║{:<12} {:>16} {:>16} {:>16}║", "TOKEN", "TOTAL", "AVAILABLE", "VALUE (USDC)");
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    for balance in &balances.balances {
+        let value = balance.value.map(|value| format!("${:.2}", value)).unwrap_or_else(|| "-".to_string());
+        println!("║{:<12} {:>16.6} {:>16.6} {:>16}║", balance.token, balance.total, balance.available, value);
+    }
+    if balances.balances.is_empty() {
+        println!("║ {:<62}║", "No spot balances");
+    }
+    println!("╚═══════════════════════════════════════════════════════════════╝");
+}
+
+fn print_orders(orders: &crate::types::OrdersResponse) {
+    println!("\n╔═══════════════════════════════════════════════════════════════╗");
+    println!("║                          OPEN ORDERS                          ║");
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║ Open Orders: {:<48} ║", orders.total_orders);
+
+    if orders.orders.is_empty() {
+        println!("╠═══════════════════════════════════════════════════════════════╣");
+        println!("║                        No open orders                         ║");
+    } else {
+        println!("╠═══════════════════════════════════════════════════════════════╣");
+        println!("║{:<12} {:<8} {:<6} {:<12} {:<10} {:<10}║",
+            "ORDER ID", "SYMBOL", "SIDE", "PRICE", "SIZE", "FILLED");
+        println!("╠═══════════════════════════════════════════════════════════════╣");
+
+        for order in &orders.orders {
+            println!("║{:<12} {:<8} {:<6} ${:<11.4} {:<10.4} {:<10.4}║",
+                order.order_id,
+                order.symbol,
+                order.side,
+                order.price,
+                order.remaining_qty,
+                order.filled_qty
+            );
+        }
+    }
+
+    println!("╚═══════════════════════════════════════════════════════════════╝");
+    println!("Orders retrieved successfully!");
+}
+
+fn print_order_status(status: &crate::types::OrderStatusInfo) {
+    let order = &status.order;
+    let time = |ms: u64| {
+        chrono::DateTime::from_timestamp_millis(ms as i64)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default()
+    };
+
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║             ORDER STATUS              ║");
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Order ID: {:<27} ║", order.order_id);
+    println!("║ Status: {:<29} ║", order.status.to_uppercase());
+    println!("║ Order: {:<30} ║", format!("{} {} {}", order.side, order.qty, order.symbol));
+    println!("║ Limit Price: {:<24} ║", format!("${:.4}", order.price));
+    println!("║ Filled: {:<29} ║", format!("{} / {}", order.filled_qty, order.qty));
+    if let Some(avg) = status.avg_fill_price {
+        println!("║ Avg Fill Price: {:<21} ║", format!("${:.4}", avg));
+    }
+    println!("║ Placed: {:<29} ║", time(order.timestamp));
+    println!("║ Updated: {:<28} ║", time(status.status_timestamp));
+    if !status.fills.is_empty() {
+        println!("╠═══════════════════════════════════════╣");
+        for fill in &status.fills {
+            println!("║ {:<37} ║", format!("{} {:.4} @ ${:.4} fee ${:.4}", time(fill.timestamp).get(11..).unwrap_or(""), fill.size, fill.price, fill.fee));
+        }
+    }
+    println!("╚═══════════════════════════════════════╝");
+    if let Some(cloid) = &status.cloid {
+        println!("Client order ID: {}", cloid);
+    }
+}
+
+fn print_fills(fills: &crate::types::FillsResponse) {
+    println!("\n╔═══════════════════════════════════════════════════════════════╗");
+    println!("║                         RECENT FILLS                          ║");
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+
+    if fills.fills.is_empty() {
+        println!("║                           No fills                            ║");
+    } else {
+        println!("║{:<9} {:<8} {:<5} {:<11} {:<9} {:<9} {:<7}║",
+            "TIME", "SYMBOL", "SIDE", "PRICE", "SIZE", "PNL", "FEE");
+        println!("╠═══════════════════════════════════════════════════════════════╣");
+
+        for fill in &fills.fills {
+            let time_str = chrono::DateTime::from_timestamp_millis(fill.timestamp as i64)
+                .map(|dt| dt.format("%H:%M:%S").to_string())
+                .unwrap_or_default();
+
+            println!("║{:<9} {:<8} {:<5} ${:<10.4} {:<9.4} ${:<8.2} ${:<6.3}║",
+                time_str,
+                fill.symbol,
+                fill.side,
+                fill.price,
+                fill.size,
+                fill.closed_pnl,
+                fill.fee
+            );
+        }
+    }
+
+    println!("╚═══════════════════════════════════════════════════════════════╝");
+    println!("Fills retrieved successfully!");
+}
+
+fn print_paper_account(account: &PaperAccount, mids: &std::collections::HashMap<String, f64>) {
+    let unrealized: f64 = account
+        .positions
+        .iter()
+        .map(|(symbol, p)| mids.get(symbol).map(|mid| (mid - p.entry_price) * p.size).unwrap_or(0.0))
+        .sum();
+
+    println!("\n╔═══════════════════════════════════════════════════════════════╗");
+    println!("║                         PAPER ACCOUNT                         ║");
+    println!("╠═══════════════════════════════════════════════════════════════╣");
+    println!("║ Balance: ${:<52.2} ║", account.balance);
+    println!("║ Unrealized PnL: ${:<45.2} ║", unrealized);
+    println!("║ Equity: ${:<53.2} ║", account.balance + unrealized);
+    println!("║ Fills: {:<54} ║", account.fills.len());
+
+    if !account.positions.is_empty() {
+        println!("╠═══════════════════════════════════════════════════════════════╣");
+        println!("║{:<8} {:<14} {:<14} {:<12} {:<12}║", "ASSET", "SIZE", "ENTRY PRICE", "MID", "UNREALIZED");
+        println!("╠═══════════════════════════════════════════════════════════════╣");
+        for (symbol, position) in &account.positions {
+            let mid = mids.get(symbol).copied().unwrap_or(position.entry_price);
+            println!("║{:<8} {:<14.4} ${:<13.4} ${:<11.4} ${:<11.2}║",
+                symbol,
+                position.size,
+                position.entry_price,
+                mid,
+                (mid - position.entry_price) * position.size
+            );
+        }
+    }
+
+    if !account.open_orders.is_empty() {
+        println!("╠═══════════════════════════════════════════════════════════════╣");
+        println!("║{:<12} {:<8} {:<6} {:<14} {:<19}║", "ORDER ID", "SYMBOL", "SIDE", "PRICE", "SIZE");
+        println!("╠═══════════════════════════════════════════════════════════════╣");
+        for order in &account.open_orders {
+            println!("║{:<12} {:<8} {:<6} ${:<13.4} {:<19.4}║",
+                order.order_id,
+                order.symbol,
+                if order.is_buy { "BUY" } else { "SELL" },
+                order.price,
+                order.size
+            );
+        }
+    }
+
+    println!("╚═══════════════════════════════════════════════════════════════╝");
+}
+
+fn print_panic_report(report: &crate::types::PanicReport) {
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║             PANIC REPORT              ║");
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Orders Cancelled: {:<19} ║", report.cancelled_orders);
+    println!("║ Positions Closed: {:<19} ║", report.closed_positions.len());
+    for symbol in &report.closed_positions {
+        println!("║   - {:<33} ║", symbol);
+    }
+    println!("║ Attempts: {:<27} ║", report.attempts);
+    for error in &report.errors {
+        println!("║ Error: {:<30.30} ║", error);
+    }
+    println!("╚═══════════════════════════════════════╝");
+
+    if report.dry_run {
+        println!("DRY RUN: nothing was sent");
+    } else if report.clean {
+        println!("Account is flat: no open orders or positions");
+    } else {
+        eprintln!("WARNING: orders or positions remain after {} attempts, check the account", report.attempts);
+    }
+}
+
+fn print_download(summary: &download::DownloadSummary, out: &str) {
+    match summary.resumed_from {
+        Some(resumed) => println!("Resumed after {}, appended {} rows to {}", format_millis(resumed), summary.rows, out),
+        None => println!("Wrote {} rows to {}", summary.rows, out),
+    }
+}
+
+fn print_ta(symbol: &str, interval: &str, price: f64, candles: usize, readings: &[(&Indicator, Option<indicators::Reading>)]) {
+    println!("{} {} @ {} ({} candles, last one still forming)", symbol, interval, price, candles);
+    println!("{:<8} {:<36} SIGNAL", "", "VALUE");
+    for (indicator, reading) in readings {
+        let Some(reading) = reading else {
+            println!("{:<8} {:<36} -", indicator.to_string(), "not enough history");
+            continue;
+        };
+        let values = match reading.values.as_slice() {
+            [single] => format!("{:.4}", single.value),
+            values => values.iter().map(|v| format!("{} {:.4}", v.name, v.value)).collect::<Vec<_>>().join("  "),
+        };
+        println!("{:<8} {:<36} {}", reading.indicator, values, reading.signal.as_deref().unwrap_or("-"));
+    }
+}
+
+fn print_funding_scan(ranked: &[crate::types::FundingCandidate], min_apr: f64) {
+    if ranked.is_empty() {
+        println!("No markets pass the open interest and volume filters");
+        return;
+    }
+    println!("{:<3} {:<10} {:>11} {:>10} {:>8} {:>14} {:>14}", "", "SYMBOL", "FUNDING/H", "APR", "COLLECT", "OPEN INT", "24H VOLUME");
+    for c in ranked {
+        println!(
+            "{:<3} {:<10} {:>10.4}% {:>9.2}% {:>8} {:>14} {:>14}",
+            if c.candidate { "*" } else { "" },
+            c.symbol,
+            c.funding_rate * 100.0,
+            c.annualized * 100.0,
+            c.collect_side.to_uppercase(),
+            format!("${:.0}", c.open_interest_usd),
+            format!("${:.0}", c.volume_24h),
+        );
+    }
+    println!("* annualized funding of at least {}%; hold the COLLECT side on the perp and hedge the other leg", min_apr);
+}
+
+fn print_vault(info: &VaultInfo) {
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║ {:<37} ║", format!("VAULT: {}", info.name));
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Equity: {:<29} ║", format!("${:.2}", info.equity));
+    println!("║ APR: {:<32} ║", format!("{:.2}%", info.apr * 100.0));
+    println!("║ Leader Commission: {:<18} ║", format!("{:.2}%", info.leader_commission * 100.0));
+    println!("║ Max Withdrawable: {:<19} ║", format!("${:.2}", info.max_withdrawable));
+    let state = if info.closed { "closed" } else if info.allow_deposits { "open" } else { "deposits disabled" };
+    println!("║ Status: {:<29} ║", state);
+    println!("╠═══════════════════════════════════════╣");
+    match &info.my_position {
+        Some(position) => {
+            println!("║ My Equity: {:<26} ║", format!("${:.2}", position.equity));
+            println!("║ My Share: {:<27} ║", format!("{:.4}%", position.share * 100.0));
+            println!("║ PnL: {:<32} ║", format!("${:.2} (all time ${:.2})", position.pnl, position.all_time_pnl));
+            println!("║ Following: {:<26} ║", format!("{} days", position.days_following));
+            if let Some(lockup) = position.lockup_until {
+                println!("║ Locked Until: {:<23} ║", format_millis(lockup));
+            }
+        },
+        None => println!("║ No deposit in this vault              ║"),
+    }
+    println!("╚═══════════════════════════════════════╝");
+    println!("Address: {}", info.address);
+    println!("Leader: {}", info.leader);
+}
+
+fn print_config_report(report: &ConfigReport) {
+    println!("Config: {}", report.path);
+    for check in &report.checks {
+        let mark = match check.level {
+            CheckLevel::Ok => "ok",
+            CheckLevel::Warning => "WARN",
+            CheckLevel::Error => "ERROR",
+        };
+        println!("  {:<5} {:<12} {}", mark, check.name, check.detail);
+    }
+    println!("{} error(s), {} warning(s)", report.count(CheckLevel::Error), report.count(CheckLevel::Warning));
+}
+
+fn print_env(config: &Config) -> Result<()> {
+    let wallet = ExchangeService::new(config.clone())?.get_wallet_address()?;
+    println!("Network: {}", config.network().to_uppercase());
+    println!("Profile: {}", config.profile);
+    println!("API URL: {}", config.api_url);
+    println!("WS URL:  {}", config.ws_url);
+    println!("Wallet:  {}", wallet);
+    if !config.follows_profile() {
+        println!("Note: HYPERLIQUID_API_URL / HYPERLIQUID_WS_URL or api_url / ws_url in the config file override the profile's endpoints");
+    }
+    Ok(())
+}
+
+fn print_withdrawals(withdrawals: &[Withdrawal]) {
+    if withdrawals.is_empty() {
+        println!("No withdrawals in this window");
+        return;
+    }
+    let now = chrono::Utc::now().timestamp_millis() as u64;
+    println!("{:<17} {:>12} {:>7} {:<37} HASH", "TIME", "AMOUNT", "FEE", "STATUS");
+    for withdrawal in withdrawals {
+        let status = match withdrawal.status {
+            WithdrawalStatus::Pending => format!("pending, ~{}s left", withdrawal.final_at.saturating_sub(now).div_ceil(1000)),
+            // the bridge window has passed, the release on arbitrum isn't checked
+            WithdrawalStatus::ExpectedFinal => format!("expected final after {}", format_millis(withdrawal.final_at)),
+        };
+        println!(
+            "{:<17} {:>12} {:>7} {:<37} {}",
+            format_millis(withdrawal.time),
+            format!("${:.2}", withdrawal.usdc),
+            format!("${:.2}", withdrawal.fee),
+            status,
+            withdrawal.hash
+        );
+    }
+}
+
+fn print_staking_summary(summary: &StakingSummary) {
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║               STAKING                 ║");
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Delegated: {:<26} ║", format!("{:.4} HYPE", summary.delegated));
+    println!("║ Undelegated: {:<24} ║", format!("{:.4} HYPE", summary.undelegated));
+    println!("║ Pending Withdrawal: {:<17} ║", format!("{:.4} ({})", summary.pending_withdrawal, summary.pending_withdrawals));
+    println!("║ Rewards Received: {:<19} ║", format!("{:.4} HYPE", summary.rewards_total));
+    println!("╚═══════════════════════════════════════╝");
+    if summary.delegations.is_empty() {
+        println!("No delegations");
+        return;
+    }
+    println!("{:<20} {:>14} {:<20} ADDRESS", "VALIDATOR", "AMOUNT", "LOCKED UNTIL");
+    for delegation in &summary.delegations {
+        println!(
+            "{:<20} {:>14.4} {:<20} {}",
+            delegation.name.as_deref().unwrap_or("-"),
+            delegation.amount,
+            format_millis(delegation.locked_until),
+            delegation.validator
+        );
+    }
+}
+
+// matches an address exactly or a validator name case-insensitively
+fn find_validator<'a>(validators: &'a [ValidatorInfo], query: &str) -> Option<&'a ValidatorInfo> {
+    validators
+        .iter()
+        .find(|v| v.address.eq_ignore_ascii_case(query))
+        .or_else(|| validators.iter().find(|v| v.name.eq_ignore_ascii_case(query)))
+}
+
+fn format_millis(ms: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms as i64)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+fn print_referral_status(status: &ReferralStatus) {
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║               REFERRAL                ║");
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Referred By: {:<24} ║", status.referred_by.as_deref().unwrap_or("-"));
+    println!("║ Own Code: {:<27} ║", status.own_code.as_deref().unwrap_or(&status.stage));
+    println!("║ Referred Users: {:<21} ║", status.referred_users);
+    println!("║ Volume: {:<29} ║", format!("${:.2}", status.cum_volume));
+    println!("║ Unclaimed Rewards: {:<18} ║", format!("${:.4}", status.unclaimed_rewards));
+    println!("║ Claimed Rewards: {:<20} ║", format!("${:.4}", status.claimed_rewards));
+    if status.builder_rewards > 0.0 {
+        println!("║ Builder Rewards: {:<20} ║", format!("${:.4}", status.builder_rewards));
+    }
+    println!("╚═══════════════════════════════════════╝");
+}
+
+fn print_fees(summary: &FeeSummary, window: Duration) {
+    let schedule = &summary.schedule;
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║              FEE SUMMARY              ║");
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Taker Rate: {:<25} ║", format!("{:.4}%", schedule.taker_rate * 100.0));
+    println!("║ Maker Rate: {:<25} ║", format!("{:.4}%", schedule.maker_rate * 100.0));
+    if schedule.referral_discount > 0.0 {
+        println!("║ Referral Discount: {:<18} ║", format!("{:.1}%", schedule.referral_discount * 100.0));
+    }
+    println!("║ 14d Volume: {:<25} ║", format!("${:.2}", schedule.volume_14d));
+    println!("║ Tier: {:<31} ║", summary.tier.tier);
+    match (&summary.next_tier, summary.volume_to_next) {
+        (Some(next), Some(needed)) => {
+            println!("║ Next Tier: {:<26} ║", format!("{} at ${:.0}", next.tier, next.volume_cutoff));
+            println!("║ Volume Needed: {:<22} ║", format!("${:.2}", needed));
+            println!("║ Next Rates: {:<25} ║", format!("{:.4}% / {:.4}%", next.taker_rate * 100.0, next.maker_rate * 100.0));
         },
-        crate::types::OrderResult::Error { message } => {
-            println!("║ Error: {:<26} ║", message);
+        _ => println!("║ Top tier reached                      ║"),
+    }
+    println!("╠═══════════════════════════════════════╣");
+    let hours = window.as_secs() / 3600;
+    let label = if hours >= 24 && hours.is_multiple_of(24) { format!("{}d", hours / 24) } else { format!("{}h", hours) };
+    println!("║ Fees Paid ({}): {:<width$} ║", label, format!("${:.4}", summary.window_fees), width = 24 - label.len());
+    println!("║ Fills: {:<30} ║", summary.window_fills);
+    println!("║ Traded: {:<29} ║", format!("${:.2}", summary.window_notional));
+    if summary.window_notional > 0.0 {
+        println!("║ Effective Rate: {:<21} ║", format!("{:.4}%", summary.window_fees / summary.window_notional * 100.0));
+    }
+    println!("╚═══════════════════════════════════════╝");
+}
+
+fn print_price(price: &crate::types::PriceInfo) {
+    let mid = price.mid.map(|m| format!("${:.4}", m)).unwrap_or_else(|| "-".to_string());
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║ {:^37} ║", format!("{} PRICE", price.symbol));
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Mid: {:<32} ║", mid);
+    println!("║ Mark: {:<31} ║", format!("${:.4}", price.mark));
+    println!("║ Oracle: {:<29} ║", format!("${:.4}", price.oracle));
+    println!("║ 24h Change: {:<25} ║", format!("{:+.2}% (from ${:.4})", price.change_24h * 100.0, price.prev_day));
+    println!("║ Premium: {:<28} ║", format!("{:+.4}%", price.premium * 100.0));
+    println!("║ Funding (1h): {:<23} ║", format!("{:+.4}%", price.funding * 100.0));
+    println!("╚═══════════════════════════════════════╝");
+}
+
+fn print_risk_sizing(sizing: &RiskSizing, risk: f64) {
+    let side = if sizing.is_buy { "LONG" } else { "SHORT" };
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║           POSITION SIZING             ║");
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Position: {:<27} ║", format!("{} {} {}", side, sizing.size, sizing.symbol));
+    println!("║ Entry: {:<30} ║", format!("${:.4}", sizing.entry));
+    println!("║ Stop: {:<31} ║", format!("${:.4}", sizing.stop));
+    println!("║ Equity: {:<29} ║", format!("${:.2}", sizing.equity));
+    println!("║ Risk: {:<31} ║", format!("${:.2} ({:.2}%)", sizing.risk_amount, risk * 100.0));
+    println!("║ Notional: {:<27} ║", format!("${:.2}", sizing.notional));
+    println!("║ Effective Leverage: {:<17} ║", format!("{:.2}x", sizing.effective_leverage));
+    println!("╚═══════════════════════════════════════╝");
+}
+
+fn print_hedge_plan(plan: &HedgePlan) {
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║ {:<37} ║", format!("HEDGE: {}", plan.symbol));
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Position: {:<27} ║", format!("{:+}", plan.position));
+    println!("║ Ratio: {:<30} ║", format!("{:.2}", plan.ratio));
+    println!("║ Hedge Order: {:<24} ║", format!("{:+}", plan.order_size));
+    println!("║ Resulting Position: {:<17} ║", format!("{:+}", plan.resulting_position));
+    println!("╚═══════════════════════════════════════╝");
+}
+
+fn print_stop_hints(sizing: &RiskSizing, report: &volatility::VolatilityReport) {
+    let Some(atr) = report.atr else {
+        return;
+    };
+    let distance = (sizing.entry - sizing.stop).abs();
+    println!("Stop is {:.2} ATR away (1h ATR ${:.4}, typical hourly range ${:.4})", distance / atr, atr, report.avg_hourly_range);
+    let hints: Vec<String> = [1.0, 1.5, 2.0, 3.0]
+        .iter()
+        .filter_map(|&multiple| report.atr_stop(sizing.entry, sizing.is_buy, multiple).map(|stop| format!("{}x ${:.4}", multiple, stop)))
+        .collect();
+    println!("ATR stops: {}", hints.join("  "));
+    if distance < report.avg_hourly_range {
+        println!("Warning: the stop is inside a typical hour's range and may be hit by noise");
+    }
+}
+
+fn print_volatility(report: &volatility::VolatilityReport) {
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║ {:<37} ║", format!("VOLATILITY: {} ({}h)", report.symbol, report.window_hours));
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Price: {:<30} ║", format!("${:.4}", report.price));
+    println!("║ Realized Vol (ann.): {:<16} ║", format!("{:.2}%", report.realized_vol_annual * 100.0));
+    println!("║ Realized Vol (window): {:<14} ║", format!("{:.2}%", report.realized_vol_window * 100.0));
+    if let (Some(atr), Some(atr_pct)) = (report.atr, report.atr_pct) {
+        println!("║ ATR (14, 1h): {:<23} ║", format!("${:.4} ({:.2}%)", atr, atr_pct * 100.0));
+    }
+    println!("║ Avg Hourly Range: {:<19} ║", format!("${:.4} ({:.2}%)", report.avg_hourly_range, report.avg_hourly_range_pct * 100.0));
+    println!("╚═══════════════════════════════════════╝");
+}
+
+fn print_trigger_response(response: &OrderResponse, label: &str, trigger_px: f64) {
+    match &response.result {
+        OrderResult::Success { order_id, .. } | OrderResult::Resting { order_id } => {
+            println!("{} placed at ${:.4} (order {})", label, trigger_px, order_id);
         },
-        crate::types::OrderResult::Resting { order_id } => {
-            println!("║ Order resting - ID: {:<15} ║", order_id);
-            if is_market {
-                println!("║ Market order resting (low liq)    ║");
-            }
+        OrderResult::DryRun { price, size, .. } => {
+            println!("DRY RUN: would place {} for {} at ${:.4}", label.to_lowercase(), size, price);
+        },
+        OrderResult::Error { message } => {
+            eprintln!("{} at ${:.4} rejected: {}", label, trigger_px, message);
+        },
+    }
+}
+
+fn print_impact(estimate: &ImpactEstimate, tolerance: f64) {
+    let side = if estimate.is_buy { "BUY" } else { "SELL" };
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║            MARKET IMPACT              ║");
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Order: {:<30} ║", format!("{} {} {}", side, estimate.qty, estimate.symbol));
+    println!("║ Mid: {:<32} ║", format!("${:.4}", estimate.mid));
+    println!("║ Avg Fill: {:<27} ║", format!("${:.4}", estimate.avg_price));
+    println!("║ Worst Level: {:<24} ║", format!("${:.4} ({} levels)", estimate.worst_price, estimate.levels_used));
+    println!("║ Slippage: {:<27} ║", format!("{:.3}% (limit {:.2}%)", estimate.slippage * 100.0, tolerance * 100.0));
+    if !estimate.fully_fillable() {
+        println!("║ Book Depth: {:<25} ║", format!("only {:.4} fillable", estimate.fillable_qty));
+    }
+    println!("╚═══════════════════════════════════════╝");
+    if estimate.exceeds(tolerance) {
+        println!("Exceeds slippage tolerance");
+    } else {
+        println!("Within slippage tolerance");
+    }
+}
+
+// prints the expected fill of a market order; an explicit --slippage it would exceed aborts it
+async fn check_impact(trading: &TradingService, symbol: &str, qty: f64, is_buy: bool, slippage: Option<f64>) -> Result<ImpactEstimate> {
+    let estimate = trading.estimate_impact(symbol, qty, is_buy).await?;
+    let tolerance = trading.market_slippage(slippage);
+    println!(
+        "Expected fill ${:.4} vs mid ${:.4} ({:.3}% slippage, {} levels)",
+        estimate.avg_price, estimate.mid, estimate.slippage * 100.0, estimate.levels_used
+    );
+    if !estimate.exceeds(tolerance) {
+        return Ok(estimate);
+    }
+    let reason = if estimate.fully_fillable() {
+        format!("expected slippage {:.3}% exceeds {:.2}%", estimate.slippage * 100.0, tolerance * 100.0)
+    } else {
+        format!("only {:.4} of {} is on the book", estimate.fillable_qty, qty)
+    };
+    if slippage.is_some() {
+        return Err(HlError::validation("slippage", format!("{}; order not placed", reason)).into());
+    }
+    eprintln!("Warning: {}", reason);
+    Ok(estimate)
+}
+
+fn print_smart_plan(plan: &SmartPlan) {
+    let choice = match plan.choice {
+        SmartChoice::Cross => "cross",
+        SmartChoice::Rest => "rest",
+    };
+    println!("Smart order: {}", choice);
+    for reason in &plan.reasons {
+        println!("  - {}", reason);
+    }
+}
+
+// "Proceed? [y/N]" before market orders, mass cancels and fund movements
+// off with --yes or confirm = false, and for dry runs and paper where nothing reaches the exchange
+#[derive(Clone, Copy)]
+struct Confirm {
+    enabled: bool,
+    network: &'static str,
+}
+
+impl Confirm {
+    fn new(config: &Config) -> Self {
+        Self {
+            enabled: config.confirm && !config.dry_run && !config.paper.enabled,
+            network: config.network(),
         }
     }
-    println!("║ Timestamp: {:<23} ║", response.timestamp);
+
+    fn ask(&self, action: &str, details: &[(&str, String)]) -> Result<()> {
+        use std::io::{IsTerminal, Write as _};
+
+        if !self.enabled {
+            return Ok(());
+        }
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!("{} needs confirmation but stdin is not a terminal; pass --yes or set confirm = false in hl.toml", action);
+        }
+        println!("\n{}", action);
+        for (label, value) in details {
+            println!("  {:<10} {}", format!("{}:", label), value);
+        }
+        println!("  {:<10} {}", "Network:", self.network);
+        print!("Proceed? [y/N] ");
+        std::io::stdout().flush()?;
+
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            Ok(())
+        } else {
+            anyhow::bail!("Aborted, nothing was sent")
+        }
+    }
+}
+
+async fn confirm_market_order(confirm: &Confirm, trading: &TradingService, estimate: &ImpactEstimate, leverage: Option<u32>) -> Result<()> {
+    if !confirm.enabled {
+        return Ok(());
+    }
+    let leverage = match leverage {
+        Some(leverage) => format!("{}x", leverage),
+        None => {
+            let (leverage, mode) = trading.active_leverage(&estimate.symbol).await?;
+            format!("{}x {} (current)", leverage, mode.as_str())
+        }
+    };
+    confirm.ask(
+        &format!("Market {} {}", if estimate.is_buy { "BUY" } else { "SELL" }, estimate.symbol),
+        &[
+            ("Size", estimate.qty.to_string()),
+            ("Notional", format!("~${:.2}", estimate.qty * estimate.avg_price)),
+            ("Leverage", leverage),
+        ],
+    )
+}
+
+fn print_group_cancel_report(group: &str, report: &GroupCancelReport) {
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║          GROUP CANCEL REPORT          ║");
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Group: {:<30.30} ║", group);
+    println!("║ Orders Cancelled: {:<19} ║", report.cancelled);
+    println!("║ Already Filled/Cancelled: {:<11} ║", report.not_live);
+    for error in &report.errors {
+        println!("║ Error: {:<30.30} ║", error);
+    }
     println!("╚═══════════════════════════════════════╝");
-    println!("Order submitted successfully!");
+
+    if report.dry_run {
+        println!("DRY RUN: nothing was sent");
+    } else if !report.errors.is_empty() {
+        eprintln!("WARNING: some orders could not be cancelled; the group was kept so you can retry");
+    }
 }
 
-fn print_status(status: &crate::types::StatusResponse) {
-    println!("\n╔═══════════════════════════════════════════════════════════════╗");
-    println!("║                  HYPERLIQUID TESTNET STATUS                   ║");
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    println!("║ Available Markets: {:<39} ║", status.total_markets);
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    println!("║{:<12} {:<12} {:<12} {:<12} {:<8} {:<12}║", 
-        "SYMBOL", "MARK PRICE", "24H VOLUME", "FUNDING", "MAX LEV", "OPEN INT");
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    
-    for market in status.markets.iter().take(10) {
-        println!("║{:<12} ${:<11.4} ${:<11.0} {:<11.6} {:<8}x ${:<11.0}║", 
-            market.symbol,
-            market.mark_price,
-            market.volume_24h,
-            market.funding_rate * 100.0,
-            market.max_leverage,
-            market.open_interest
-        );
+// positional qty, or a USD notional / equity percentage converted to a correctly rounded size
+// implementation shortfall of a filled order, printed and kept in the journal
+async fn report_execution(
+    config: &Config,
+    response: &OrderResponse,
+    symbol: &str,
+    is_buy: bool,
+    qty: f64,
+    arrival_mid: f64,
+    started: u64,
+) -> Result<()> {
+    let OrderResult::Success { order_id, .. } = response.result else {
+        return Ok(());
+    };
+    if config.paper.enabled {
+        println!("Paper fills aren't on the exchange, no execution report");
+        return Ok(());
     }
-    
-    println!("╚═══════════════════════════════════════════════════════════════╝");
-    if status.markets.len() > 10 {
-        println!("... and {} more markets", status.markets.len() - 10);
+    let exchange = ExchangeService::new(config.clone())?;
+    let Some(report) = execution_report::collect(&exchange, symbol, is_buy, qty, arrival_mid, &[order_id], started).await? else {
+        println!("No fills found for order {} yet, no execution report", order_id);
+        return Ok(());
+    };
+    print_execution_report(&report);
+    let journal = Journal::from_config(config).await?;
+    journal.record_execution(&report).await?;
+    println!("Recorded in {}", journal.location());
+    Ok(())
+}
+
+fn print_execution_report(report: &ExecutionReport) {
+    println!("Execution: {} {} of {} {} in {} fill(s)", report.side, report.filled_qty, report.requested_qty, report.symbol, report.fills);
+    println!("  Avg fill       ${:.4}", report.avg_price);
+    println!("  Arrival mid    ${:.4}   shortfall {:+.2} bps", report.arrival_mid, report.shortfall_bps);
+    match (report.interval_vwap, report.vs_vwap_bps) {
+        (Some(vwap), Some(bps)) => println!("  Interval VWAP  ${:.4}   vs vwap   {:+.2} bps", vwap, bps),
+        _ => println!("  Interval VWAP  n/a (no candle volume yet)"),
     }
-    println!("Status retrieved successfully!");
+    println!("  Fees           ${:.4}", report.fees);
 }
 
-fn print_balances(balances: &crate::types::BalanceResponse) {
-    println!("\n╔═══════════════════════════════════════════════════════════════╗");
-    println!("║                        ACCOUNT SUMMARY                        ║");
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    println!("║ Account Value: ${:<43.2} ║", balances.account_value);
-    println!("║ Withdrawable: ${:<44.2} ║", balances.withdrawable);
-    println!("║ Cross Margin Used: ${:<38.2} ║", balances.cross_margin_used);
-    
-    if !balances.positions.is_empty() {
-        println!("╠═══════════════════════════════════════════════════════════════╣");
-        println!("║                           POSITIONS                           ║");
-        println!("╠═══════════════════════════════════════════════════════════════╣");
-        println!("║{:<8} {:<12} {:<12} {:<8} {:<12} {:<12}║", 
-            "ASSET", "SIZE", "ENTRY PRICE", "LEVERAGE", "UNREALIZED", "VALUE");
-        println!("╠═══════════════════════════════════════════════════════════════╣");
-        
-        for pos in &balances.positions {
-            let size_colored = if pos.size > 0.0 {
-                format!("LONG {:.4}", pos.size)
-            } else {
-                format!("SHORT {:.4}", pos.size)
-            };
-            
-            let pnl_colored = if pos.unrealized_pnl > 0.0 {
-                format!("PROFIT ${:.2}", pos.unrealized_pnl)
-            } else {
-                format!("LOSS ${:.2}", pos.unrealized_pnl)
-            };
-            
-            println!("║{:<8} {:<12} ${:<11.4} {:<8}x {:<12} ${:<11.2}║", 
-                pos.symbol,
-                size_colored,
-                pos.entry_price,
-                pos.leverage,
-                pnl_colored,
-                pos.position_value
-            );
+// mid a pegged order starts from; following it needs the live order status, which the paper account doesn't have
+async fn pegged_mid(trading: &TradingService, exchange: &ExchangeService, symbol: &str) -> Result<f64> {
+    if trading.is_paper() {
+        anyhow::bail!("Pegged orders can't be followed on the paper account");
+    }
+    exchange.get_mids().await?.get(symbol).copied().with_context(|| format!("No mid price for {}", symbol))
+}
+
+// re-prices a resting pegged order until it fills, is cancelled elsewhere or ctrl-c leaves it resting
+async fn follow_peg(
+    trading: &TradingService,
+    exchange: &ExchangeService,
+    request: OrderRequest,
+    response: &OrderResponse,
+    anchor: f64,
+    params: PegParams,
+) -> Result<()> {
+    let OrderResult::Resting { order_id } = response.result else {
+        return Ok(());
+    };
+    if trading.is_dry_run() {
+        return Ok(());
+    }
+    println!(
+        "Pegged {:.1} bps {} mid ${:.4}, re-pricing past {:.1} bps of drift (Ctrl-C to stop)",
+        params.offset_bps,
+        if request.is_buy { "below" } else { "above" },
+        anchor,
+        params.reprice_bps
+    );
+    let hub = WsHub::start(exchange.config().ws_url.clone(), exchange.config().http.clone());
+    match peg::run(trading, exchange, hub, request, order_id, anchor, params).await? {
+        peg::PegOutcome::Filled { order_id } => println!("Order {} filled", order_id),
+        peg::PegOutcome::Ended { order_id, status } => println!("Order {} is {}, no longer pegging", order_id, status),
+        peg::PegOutcome::Stopped { order_id, price } => println!("Peg stopped, order {} left resting at ${:.4}", order_id, price),
+    }
+    Ok(())
+}
+
+async fn resolve_qty(
+    trading: &TradingService,
+    symbol: &str,
+    qty: Option<f64>,
+    notional: Option<f64>,
+    size_pct: Option<f64>,
+    limit: Option<f64>,
+    leverage: Option<u32>,
+) -> Result<f64> {
+    let notional = match size_pct {
+        Some(pct) if pct <= 0.0 || pct > 100.0 => anyhow::bail!("--size-pct must be between 0 and 100"),
+        Some(pct) => {
+            let leverage = leverage.unwrap_or(1).max(1);
+            let (notional, equity, free_margin) = trading.notional_for_equity_pct(pct, leverage).await?;
+            println!("{}% of ${:.2} equity (${:.2} free) at {}x = ${:.2} notional",
+                pct, equity, free_margin, leverage, notional);
+            Some(notional)
+        }
+        None => notional,
+    };
+
+    match (qty, notional) {
+        (Some(qty), _) => Ok(qty),
+        (None, Some(notional)) => {
+            if notional <= 0.0 {
+                anyhow::bail!("--notional must be greater than 0");
+            }
+            let (size, price) = trading.size_for_notional(symbol, notional, limit).await?;
+            println!("${:.2} at ${:.4} = {} {}", notional, price, size, symbol);
+            Ok(size)
         }
+        (None, None) => anyhow::bail!("Specify a qty, --notional or --size-pct"),
+    }
+}
+
+fn print_risk_check(result: &crate::types::RiskCheckResult, side: &str, symbol: &str, qty: f64) {
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║            PRE-TRADE CHECK            ║");
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Order: {:<30} ║", format!("{} {} {}", side, qty, symbol));
+    println!("║ Price: {:<30} ║", format!("${:.4}", result.price));
+    println!("║ Notional: {:<27} ║", format!("${:.2}", result.notional));
+    println!("║ Margin: {:<29} ║", format!("${:.2} at {}x", result.margin_required, result.leverage));
+    println!("║ Available: {:<26} ║", format!("${:.2}", result.available_margin));
+    println!("║ Exposure: {:<27} ║", format!("${:.2} -> ${:.2}", result.current_exposure, result.resulting_exposure));
+    println!("║ Positions: {:<26} ║", format!("{} -> {}", result.open_positions, result.resulting_positions));
+    println!("╠═══════════════════════════════════════╣");
+    if result.allowed {
+        println!("║ Verdict: {:<28} ║", "ALLOWED");
     } else {
-        println!("╠═══════════════════════════════════════════════════════════════╣");
-        println!("║                     No open positions                         ║");
+        println!("║ Verdict: {:<28} ║", format!("BLOCKED ({})", result.rule.as_deref().unwrap_or("unknown")));
+    }
+    println!("╚═══════════════════════════════════════╝");
+
+    if let Some(reason) = &result.reason {
+        eprintln!("{}", reason);
     }
-    
-    println!("╚═══════════════════════════════════════════════════════════════╝");
-    println!("Balances retrieved successfully!");
 }
 
-fn print_spot_markets(spot_data: &crate::types::SpotResponse) {
-    println!("\n╔═══════════════════════════════════════════════════════════════╗");
-    println!("║                         SPOT MARKETS                          ║");
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    println!("║ Available Tokens: {:<43} ║", spot_data.tokens.len());
-    println!("║ Trading Pairs: {:<46} ║", spot_data.pairs.len());
-    
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    println!("║                            TOKENS                             ║");
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    println!("║{:<12} {:<8} {:<20}║", "NAME", "DECIMALS", "TOKEN ID");
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    
-    for token in spot_data.tokens.iter().take(5) {
-        let short_id = if token.token_id.len() > 20 {
-            format!("{}...{}", &token.token_id[..8], &token.token_id[token.token_id.len()-6..])
-        } else {
-            token.token_id.clone()
-        };
-        
-        println!("║{:<12} {:<8} {:<20}║", 
-            token.name,
-            token.decimals,
-            short_id
-        );
+fn print_snapshot_diff(diff: &crate::types::SnapshotDiff) {
+    let change = |label: &str, (before, after): (f64, f64)| {
+        println!("{:<20} ${:>14.2} -> ${:>14.2}  ({:+.2})", label, before, after, after - before);
+    };
+    println!("Snapshots {}s apart", diff.elapsed_ms / 1000);
+    change("Account value", diff.account_value);
+    change("Withdrawable", diff.withdrawable);
+    change("Unrealized PnL", diff.unrealized_pnl);
+    if diff.same_day {
+        change("Realized PnL today", diff.realized_pnl_today);
     }
-    
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    println!("║                         TRADING PAIRS                         ║");
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    println!("║{:<15} {:<12} {:<12} {:<12}║", 
-        "PAIR", "MARK PRICE", "MID PRICE", "24H VOLUME");
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    
-    for pair in spot_data.pairs.iter().take(10) {
-        println!("║{:<15} ${:<11.6} ${:<11.6} ${:<11.0}║", 
-            pair.name,
-            pair.mark_price,
-            pair.mid_price,
-            pair.volume_24h
-        );
+    let (ratio_before, ratio_after) = diff.margin_ratio;
+    println!("{:<20} {:>14.2}% -> {:>14.2}%", "Margin ratio", ratio_before * 100.0, ratio_after * 100.0);
+
+    if diff.positions.is_empty() {
+        println!("\nPositions: unchanged");
+    } else {
+        println!("\nPositions:");
+        for position in &diff.positions {
+            println!(
+                "  {:<10} {:<10} {:>+12} -> {:<+12} uPnL ${:+.2} -> ${:+.2}",
+                position.symbol,
+                format!("{:?}", position.kind).to_uppercase(),
+                position.size_before,
+                position.size_after,
+                position.unrealized_pnl_before,
+                position.unrealized_pnl_after
+            );
+        }
     }
-    
-    println!("╚═══════════════════════════════════════════════════════════════╝");
-    if spot_data.pairs.len() > 10 {
-        println!("... and {} more pairs", spot_data.pairs.len() - 10);
+
+    for (title, orders) in [("New orders", &diff.new_orders), ("Orders gone (filled or cancelled)", &diff.gone_orders)] {
+        if orders.is_empty() {
+            continue;
+        }
+        println!("\n{}:", title);
+        for order in orders {
+            println!("  #{:<12} {:<10} {:<4} {} @ ${:.4}", order.order_id, order.symbol, order.side, order.qty, order.price);
+        }
     }
-    println!("Spot markets retrieved successfully!");
-}
\ No newline at end of file
+    if diff.new_orders.is_empty() && diff.gone_orders.is_empty() {
+        println!("\nOpen orders: unchanged");
+    }
+
+    match (&diff.lock_before, &diff.lock_after) {
+        (None, Some(lock)) => println!("\nTrading LOCKED since {}: {}", lock.locked_at, lock.reason),
+        (Some(_), None) => println!("\nTrading lock removed"),
+        _ => {}
+    }
+}
+
+fn print_stats(stats: &crate::types::PortfolioStats) {
+    let ratio = |v: Option<f64>| v.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "n/a".to_string());
+    let dollars = |v: Option<f64>| v.map(|v| format!("${:.2}", v)).unwrap_or_else(|| "n/a".to_string());
+
+    println!("\n╔═══════════════════════════════════════╗");
+    println!("║          PORTFOLIO STATISTICS         ║");
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Days Recorded: {:<22} ║", stats.days);
+    println!("║ Total Return: {:<23} ║", format!("{:.2}%", stats.total_return * 100.0));
+    println!("║ Sharpe: {:<29} ║", ratio(stats.sharpe));
+    println!("║ Sortino: {:<28} ║", ratio(stats.sortino));
+    println!("║ Max Drawdown: {:<23} ║", format!("{:.2}%", stats.max_drawdown * 100.0));
+    println!("╠═══════════════════════════════════════╣");
+    println!("║ Closed Trades: {:<22} ║", stats.closed_trades);
+    println!("║ Win Rate: {:<27} ║", stats.win_rate.map(|w| format!("{:.1}%", w * 100.0)).unwrap_or_else(|| "n/a".to_string()));
+    println!("║ Avg Win: {:<28} ║", dollars(stats.avg_win));
+    println!("║ Avg Loss: {:<27} ║", dollars(stats.avg_loss));
+    println!("║ Profit Factor: {:<22} ║", ratio(stats.profit_factor));
+    println!("║ Realized PnL: {:<23} ║", format!("${:.2}", stats.realized_pnl));
+    println!("╚═══════════════════════════════════════╝");
+}
+
+fn print_query_result(columns: &[String], rows: &[Vec<String>]) {
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            rows.iter()
+                .map(|row| row[i].len())
+                .chain(std::iter::once(name.len()))
+                .max()
+                .unwrap_or(0)
+                .min(40)
+        })
+        .collect();
+
+    let header: Vec<String> = columns
+        .iter()
+        .zip(&widths)
+        .map(|(name, width)| format!("{:<width$}", name, width = width))
+        .collect();
+    println!("{}", header.join(" | "));
+    println!("{}", widths.iter().map(|w| "─".repeat(*w)).collect::<Vec<_>>().join("─┼─"));
+
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:<width$.width$}", value, width = width))
+            .collect();
+        println!("{}", cells.join(" | "));
+    }
+    println!("({} rows)", rows.len());
+}
+
+fn parse_cloid_arg(value: &str) -> Result<Uuid, String> {
+    parse_cloid(value).ok_or_else(|| format!("invalid client order ID '{}': expected 0x + 32 hex digits", value))
+}
+
+fn parse_order_ref(value: &str) -> Result<OrderRef, String> {
+    OrderRef::parse(value).ok_or_else(|| format!("invalid order ID '{}': expected a number or 0x client order ID", value))
+}
+
+// remembers a resting order under its --group so `hl cancel-group` can find it
+// validates --builder/--builder-fee-bps and that the builder is approved for the fee
+async fn resolve_builder(trading: &TradingService, address: Option<&str>, fee_bps: Option<f64>) -> Result<Option<BuilderFee>> {
+    let (Some(address), Some(fee_bps)) = (address, fee_bps) else {
+        return Ok(None);
+    };
+    if trading.is_paper() {
+        anyhow::bail!("Builder fees aren't simulated by the paper account");
+    }
+    let builder = BuilderFee::new(address, fee_bps)?;
+    trading.check_builder_approval(&builder).await?;
+    Ok(Some(builder))
+}
+
+fn remember_in_group(path: &str, group: &str, symbol: &str, response: &OrderResponse) -> Result<()> {
+    let (OrderResult::Resting { order_id }, Some(cloid)) = (&response.result, &response.cloid) else {
+        return Ok(());
+    };
+    let mut groups = OrderGroups::load(path)?;
+    groups.add(group, GroupedOrder {
+        symbol: symbol.to_string(),
+        order_id: *order_id,
+        cloid: cloid_hex(cloid),
+    });
+    groups.save()
+}
+
+fn parse_duration_arg(value: &str) -> Result<Duration, String> {
+    deadman::parse_duration(value).map_err(|e| e.to_string())
+}
+
+fn parse_time_arg(value: &str) -> Result<u64, String> {
+    download::parse_time(value).map_err(|e| e.to_string())
+}
@@ -1,7 +1,183 @@
 // config to load api keys with fallback urls and risk parameters
-use std::{env, collections::HashMap};
-use anyhow::Result;
-use crate::types::{Config, SymbolLimits, RiskLimits};
+use std::{env, collections::{BTreeMap, HashMap}, net::IpAddr, path::{Path, PathBuf}};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use toml_edit::DocumentMut;
+use crate::error::HlError;
+use crate::handlers::cors;
+use crate::services::{accounts, contacts, mqtt, net, redis_bridge, symbols};
+use crate::services::config_check::{check_risk_limits, CheckLevel};
+use crate::types::{ApiKeyConfig, ApiScope, CacheConfig, Config, EndpointProfile, MqttConfig, RedisConfig, Exposure, HttpConfig, InfluxConfig, JournalConfig, KafkaConfig, MarginMode, NotificationConfig, OrderPreset, OrderTransport, PaperConfig, RiskLimits, ServerConfig, SymbolLimits};
+
+const TESTNET_API_URL: &str = "https://api.hyperliquid-testnet.xyz";
+const TESTNET_WS_URL: &str = "wss://api.hyperliquid-testnet.xyz/ws";
+const MAINNET_API_URL: &str = "https://api.hyperliquid.xyz";
+const MAINNET_WS_URL: &str = "wss://api.hyperliquid.xyz/ws";
+const DEFAULT_PROFILE: &str = "testnet";
+const DEFAULT_CONFIG_FILE: &str = "hl.toml";
+const DEFAULT_AUDIT_LOG: &str = "hl-audit.jsonl";
+const DEFAULT_RISK_STATE: &str = "hl-risk-state.json";
+const DEFAULT_LOCK_FILE: &str = "hl-trading.lock";
+const DEFAULT_ORDER_GROUPS: &str = "hl-order-groups.json";
+const DEFAULT_MARKET_SLIPPAGE: f64 = 0.05;
+// same bound the cli puts on --slippage
+pub const MAX_MARKET_SLIPPAGE: f64 = 0.1;
+
+// optional settings file (hl.toml or $HL_CONFIG), env vars take precedence
+#[derive(Debug, Default, Deserialize)]
+struct FileConfig {
+    // picks one of the profiles, api_url / ws_url still override it
+    profile: Option<String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, EndpointProfile>,
+    api_url: Option<String>,
+    ws_url: Option<String>,
+    #[serde(default)]
+    notifications: NotificationConfig,
+    #[serde(default)]
+    server: ServerConfig,
+    audit_log_path: Option<String>,
+    #[serde(default)]
+    journal: JournalConfig,
+    database_url: Option<String>,
+    #[serde(default)]
+    dry_run: bool,
+    #[serde(default)]
+    paper: PaperConfig,
+    // None when the file has no [risk] table
+    risk: Option<RiskFileConfig>,
+    risk_state_path: Option<String>,
+    lock_path: Option<String>,
+    order_groups_path: Option<String>,
+    market_slippage: Option<f64>,
+    confirm: Option<bool>,
+    #[serde(default)]
+    presets: HashMap<String, OrderPreset>,
+    #[serde(default)]
+    addresses: Vec<String>,
+    #[serde(default)]
+    aliases: BTreeMap<String, String>,
+    #[serde(default)]
+    contacts: BTreeMap<String, String>,
+    #[serde(default)]
+    order_transport: OrderTransport,
+    #[serde(default)]
+    http: HttpConfig,
+    #[serde(default)]
+    cache: CacheConfig,
+    mqtt_broker: Option<String>,
+    #[serde(default)]
+    mqtt: MqttConfig,
+    redis_url: Option<String>,
+    #[serde(default)]
+    redis: RedisConfig,
+    kafka_brokers: Option<String>,
+    #[serde(default)]
+    kafka: KafkaConfig,
+    influx_url: Option<String>,
+    #[serde(default)]
+    influx: InfluxConfig,
+}
+
+// [risk] section, unset fields keep the built-in defaults
+#[derive(Debug, Default, Deserialize)]
+struct RiskFileConfig {
+    max_notional_per_order: Option<f64>,
+    max_notional_per_symbol: Option<f64>,
+    #[serde(default)]
+    strict_symbols: bool,
+    default_symbol_limits: Option<SymbolLimitsFile>,
+    // replaces the built-in symbol table when present
+    symbols: Option<HashMap<String, SymbolLimitsFile>>,
+    max_daily_loss: Option<f64>,
+    max_open_positions: Option<usize>,
+    max_total_notional: Option<f64>,
+    max_orders_per_minute: Option<u32>,
+}
+
+// [risk.symbols.<SYMBOL>] / [risk.default_symbol_limits], unset fields fall back to the defaults
+#[derive(Debug, Default, Deserialize)]
+struct SymbolLimitsFile {
+    max_leverage: Option<u32>,
+    max_notional: Option<f64>,
+    enabled: Option<bool>,
+    allow_long: Option<bool>,
+    allow_short: Option<bool>,
+    margin_mode: Option<MarginMode>,
+}
+
+impl SymbolLimitsFile {
+    fn resolve(self, fallback: &SymbolLimits) -> SymbolLimits {
+        SymbolLimits {
+            max_leverage: self.max_leverage.unwrap_or(fallback.max_leverage),
+            max_notional: self.max_notional.unwrap_or(fallback.max_notional),
+            enabled: self.enabled.unwrap_or(fallback.enabled),
+            allow_long: self.allow_long.unwrap_or(fallback.allow_long),
+            allow_short: self.allow_short.unwrap_or(fallback.allow_short),
+            margin_mode: self.margin_mode.or(fallback.margin_mode),
+        }
+    }
+}
+
+impl RiskFileConfig {
+    fn into_limits(self) -> RiskLimits {
+        let mut limits = RiskLimits::default();
+
+        if let Some(max_notional_per_order) = self.max_notional_per_order {
+            limits.max_notional_per_order = max_notional_per_order;
+        }
+        if let Some(max_notional_per_symbol) = self.max_notional_per_symbol {
+            limits.max_notional_per_symbol = max_notional_per_symbol;
+            limits.default_symbol_limits.max_notional = max_notional_per_symbol;
+        }
+        if let Some(defaults) = self.default_symbol_limits {
+            limits.default_symbol_limits = defaults.resolve(&limits.default_symbol_limits);
+        }
+        if let Some(symbols) = self.symbols {
+            limits.symbol_limits = symbols
+                .into_iter()
+                .map(|(symbol, file)| {
+                    (symbol.to_uppercase(), file.resolve(&limits.default_symbol_limits))
+                })
+                .collect();
+        }
+        limits.strict_symbols = self.strict_symbols;
+
+        limits.max_daily_loss = self.max_daily_loss;
+        limits.max_open_positions = self.max_open_positions;
+        limits.max_total_notional = self.max_total_notional;
+        limits.max_orders_per_minute = self.max_orders_per_minute;
+        limits
+    }
+}
+
+// limits for symbols without their own entry
+impl Default for SymbolLimits {
+    fn default() -> Self {
+        Self {
+            max_leverage: 10,
+            max_notional: 25_000.0,
+            enabled: true,
+            allow_long: true,
+            allow_short: true,
+            margin_mode: None,
+        }
+    }
+}
+
+impl SymbolLimits {
+    // whether an order is allowed given the signed position it trades against
+    pub fn allows(&self, is_buy: bool, qty: f64, position: f64) -> bool {
+        let allowed = if is_buy { self.allow_long } else { self.allow_short };
+        // a disabled side still accepts orders that only shrink the opposite position
+        allowed
+            || if is_buy {
+                position < 0.0 && qty <= -position
+            } else {
+                position > 0.0 && qty <= position
+            }
+    }
+}
 
 impl Default for RiskLimits {
     fn default() -> Self {
@@ -11,78 +187,488 @@ impl Default for RiskLimits {
             max_leverage: 10,
             max_notional: 50_000.0,
             enabled: true,
+            ..Default::default()
         });
         
         symbol_limits.insert("ETH".to_string(), SymbolLimits {
             max_leverage: 15,
             max_notional: 30_000.0,
             enabled: true,
+            ..Default::default()
         });
         
         symbol_limits.insert("SOL".to_string(), SymbolLimits {
             max_leverage: 20,
             max_notional: 20_000.0,
             enabled: true,
+            ..Default::default()
         });
 
         symbol_limits.insert("ARB".to_string(), SymbolLimits {
             max_leverage: 25,
             max_notional: 15_000.0, 
             enabled: true,
+            ..Default::default()
         });
 
         symbol_limits.insert("AVAX".to_string(), SymbolLimits {
             max_leverage: 20,
             max_notional: 15_000.0,
             enabled: true,
+            ..Default::default()
         });
         
-        // if you need more tokens please add it
+        // more tokens can be added under [risk.symbols] in hl.toml
         
         // global limits
         Self {
             max_notional_per_order: 10_000.0,   
             max_notional_per_symbol: 25_000.0,
             symbol_limits,
+            default_symbol_limits: SymbolLimits::default(),
+            strict_symbols: false,
+            max_daily_loss: None,
+            max_open_positions: None,
+            max_total_notional: None,
+            max_orders_per_minute: None,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            api_url: TESTNET_API_URL.to_string(),
+            ws_url: TESTNET_WS_URL.to_string(),
+            profile: DEFAULT_PROFILE.to_string(),
+            profiles: builtin_profiles(),
+            private_key: String::new(),
+            risk_limits: RiskLimits::default(),
+            risk_state_path: DEFAULT_RISK_STATE.to_string(),
+            lock_path: DEFAULT_LOCK_FILE.to_string(),
+            order_groups_path: DEFAULT_ORDER_GROUPS.to_string(),
+            market_slippage: DEFAULT_MARKET_SLIPPAGE,
+            notifications: NotificationConfig::default(),
+            server: ServerConfig::default(),
+            // only a loaded config writes the audit log, so tests and library users don't litter the cwd
+            audit_log_path: None,
+            journal: JournalConfig::default(),
+            database_url: None,
+            dry_run: false,
+            paper: PaperConfig::default(),
+            confirm: true,
+            presets: HashMap::new(),
+            addresses: Vec::new(),
+            aliases: BTreeMap::new(),
+            contacts: BTreeMap::new(),
+            order_transport: OrderTransport::default(),
+            http: HttpConfig::default(),
+            cache: CacheConfig::default(),
+            mqtt_broker: None,
+            mqtt: MqttConfig::default(),
+            redis_url: None,
+            redis: RedisConfig::default(),
+            kafka_brokers: None,
+            kafka: KafkaConfig::default(),
+            influx_url: None,
+            influx: InfluxConfig::default(),
         }
     }
 }
 
 impl Config {
+    // every failure comes back as HlError::ConfigError, with the whole chain in its message
     pub fn load() -> Result<Self> {
+        Self::read().map_err(|e| HlError::ConfigError(format!("{:#}", e)).into())
+    }
+
+    fn read() -> Result<Self> {
         dotenvy::dotenv().ok();
+
+        let file = load_file_config()?;
+
+        let mut notifications = file.notifications;
+        if let Ok(url) = env::var("DISCORD_WEBHOOK_URL") {
+            notifications.discord_webhook_url = Some(url);
+        }
+
+        let mut paper = file.paper;
+        if let Ok(value) = env::var("HL_PAPER") {
+            paper.enabled = value == "1" || value.eq_ignore_ascii_case("true");
+        }
+
+        let risk_limits = file.risk.unwrap_or_default().into_limits();
+
+        let market_slippage = file.market_slippage.unwrap_or(DEFAULT_MARKET_SLIPPAGE);
+        if !(market_slippage > 0.0 && market_slippage <= MAX_MARKET_SLIPPAGE) {
+            anyhow::bail!("market_slippage must be between 0 and {}, got {}", MAX_MARKET_SLIPPAGE, market_slippage);
+        }
+
+        for (name, preset) in &file.presets {
+            preset.validate().with_context(|| format!("Invalid preset '{}'", name))?;
+        }
+
+        let addresses = if file.addresses.is_empty() {
+            Vec::new()
+        } else {
+            accounts::parse_addresses(&file.addresses).context("Invalid addresses in config file")?
+        };
+        let aliases = symbols::parse_aliases(&file.aliases).context("Invalid aliases in config file")?;
+
+        let mut profiles = builtin_profiles();
+        for (name, endpoints) in file.profiles {
+            if profiles.contains_key(&name) {
+                anyhow::bail!("Profile '{}' is built in and can't be redefined", name);
+            }
+            reqwest::Url::parse(&endpoints.api_url).with_context(|| format!("Invalid api_url in profile '{}'", name))?;
+            reqwest::Url::parse(&endpoints.ws_url).with_context(|| format!("Invalid ws_url in profile '{}'", name))?;
+            profiles.insert(name, endpoints);
+        }
+        let profile = env::var("HL_PROFILE").ok().or(file.profile).unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        let endpoints = profiles.get(&profile).cloned().with_context(|| {
+            format!("Unknown profile '{}', expected one of: {}", profile, profiles.keys().cloned().collect::<Vec<_>>().join(", "))
+        })?;
+
+        let contacts = contacts::parse_contacts(&file.contacts).context("Invalid contacts in config file")?;
+
+        let mut http = file.http;
+        if http.max_attempts == 0 {
+            anyhow::bail!("http.max_attempts must be at least 1");
+        }
+        if http.weight_per_minute == 0 {
+            anyhow::bail!("http.weight_per_minute must be at least 1");
+        }
+        if http.timeout_secs == 0 || http.connect_timeout_secs == 0 {
+            anyhow::bail!("http.timeout_secs and http.connect_timeout_secs must be at least 1");
+        }
+        if let Ok(proxy) = env::var("HL_PROXY") {
+            http.proxy = Some(proxy);
+        }
+        if let Some(proxy) = &http.proxy {
+            net::validate_proxy(proxy)?;
+        }
+
+        let mqtt_broker = env::var("HL_MQTT_BROKER").ok().or(file.mqtt_broker);
+        if let Some(broker) = &mqtt_broker {
+            mqtt::mqtt_options(broker, "hl-config-check")?;
+        }
+        if file.mqtt.qos > 1 {
+            anyhow::bail!("mqtt.qos must be 0 or 1, got {}", file.mqtt.qos);
+        }
+        let redis_url = env::var("HL_REDIS_URL").ok().or(file.redis_url);
+        if let Some(url) = &redis_url {
+            redis_bridge::client(url)?;
+        }
+        for channel in &file.redis.channels {
+            redis_bridge::parse_channel(channel)?;
+        }
+        let database_url = env::var("HL_DATABASE_URL").ok().or(file.database_url);
+        if let Some(url) = &database_url
+            && !(url.starts_with("postgres://") || url.starts_with("postgresql://"))
+        {
+            anyhow::bail!("database_url must be a postgres:// url");
+        }
+        let kafka_brokers = env::var("HL_KAFKA_BROKERS").ok().or(file.kafka_brokers);
+        if kafka_brokers.as_deref().is_some_and(|brokers| brokers.trim().is_empty()) {
+            anyhow::bail!("kafka_brokers is empty");
+        }
+        let influx_url = env::var("HL_INFLUX_URL").ok().or(file.influx_url);
+        if let Some(url) = &influx_url {
+            reqwest::Url::parse(url).with_context(|| format!("Invalid influx_url '{}'", url))?;
+        }
+        let mut influx = file.influx;
+        if let Ok(token) = env::var("HL_INFLUX_TOKEN") {
+            influx.token = Some(token);
+        }
+
+        let mut server = file.server;
+        if let Ok(keys) = env::var("HL_API_KEYS") {
+            server.api_keys.extend(parse_api_keys(&keys)?);
+        }
+        let _ = cors::cors_layer(&server.cors)?;
+        if let Some(bind) = &server.bind {
+            parse_bind(bind)?;
+        }
+        server.base_path = server.base_path.as_deref().map(parse_base_path).transpose()?;
         
         Ok(Config {
             api_url: env::var("HYPERLIQUID_API_URL")
-                .unwrap_or_else(|_| "https://api.hyperliquid-testnet.xyz".to_string()),
+                .ok()
+                .or(file.api_url)
+                .unwrap_or(endpoints.api_url),
             ws_url: env::var("HYPERLIQUID_WS_URL")
-                .unwrap_or_else(|_| "wss://api.hyperliquid-testnet.xyz/ws".to_string()),
+                .ok()
+                .or(file.ws_url)
+                .unwrap_or(endpoints.ws_url),
+            profile,
+            profiles,
             private_key: env::var("PRIVATE_KEY")
                 .map_err(|_| anyhow::anyhow!("PRIVATE_KEY must be set"))?,
-            risk_limits: RiskLimits::default(),
+            risk_limits,
+            risk_state_path: file
+                .risk_state_path
+                .unwrap_or_else(|| DEFAULT_RISK_STATE.to_string()),
+            lock_path: file
+                .lock_path
+                .unwrap_or_else(|| DEFAULT_LOCK_FILE.to_string()),
+            order_groups_path: file
+                .order_groups_path
+                .unwrap_or_else(|| DEFAULT_ORDER_GROUPS.to_string()),
+            market_slippage,
+            notifications,
+            server,
+            // empty string disables the audit log
+            audit_log_path: env::var("HL_AUDIT_LOG")
+                .ok()
+                .or(file.audit_log_path)
+                .or_else(|| Some(DEFAULT_AUDIT_LOG.to_string())),
+            journal: file.journal,
+            database_url,
+            dry_run: env::var("HL_DRY_RUN")
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(file.dry_run),
+            paper,
+            confirm: file.confirm.unwrap_or(true),
+            presets: file.presets,
+            addresses,
+            aliases,
+            contacts,
+            order_transport: file.order_transport,
+            http,
+            cache: file.cache,
+            mqtt_broker,
+            mqtt: file.mqtt,
+            redis_url,
+            redis: file.redis,
+            kafka_brokers,
+            kafka: file.kafka,
+            influx_url,
+            influx,
         })
     }
     
+    // risk limits from the config file alone, used when it changes under a running process
+    pub fn reload_risk_limits() -> Result<RiskLimits> {
+        let path = config_file_path();
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        parse_reloaded_risk_limits(&contents)
+    }
+
+    // shown in confirmations, inferred from the api url
+    pub fn network(&self) -> &'static str {
+        if self.api_url.contains("testnet") { "testnet" } else { "mainnet" }
+    }
+
+    // false when HYPERLIQUID_API_URL / api_url point somewhere other than the selected profile
+    pub fn follows_profile(&self) -> bool {
+        self.profiles
+            .get(&self.profile)
+            .is_some_and(|endpoints| endpoints.api_url == self.api_url && endpoints.ws_url == self.ws_url)
+    }
+
+    pub fn preset(&self, name: &str) -> Result<&OrderPreset> {
+        self.presets.get(name).with_context(|| {
+            let mut known: Vec<&str> = self.presets.keys().map(String::as_str).collect();
+            known.sort_unstable();
+            if known.is_empty() {
+                format!("Unknown preset '{}', none are defined in the config file", name)
+            } else {
+                format!("Unknown preset '{}', expected one of: {}", name, known.join(", "))
+            }
+        })
+    }
+
     pub fn get_symbol_limits(&self, symbol: &str) -> SymbolLimits {
-        self.risk_limits.symbol_limits
-            .get(symbol)
+        self.risk_limits.get_symbol_limits(symbol)
+    }
+
+    pub fn is_symbol_enabled(&self, symbol: &str) -> bool {
+        self.risk_limits.is_symbol_enabled(symbol)
+    }
+    
+    pub fn get_max_leverage(&self, symbol: &str) -> u32 {
+        self.risk_limits.get_max_leverage(symbol)
+    }
+    
+    pub fn get_max_notional(&self, symbol: &str) -> f64 {
+        self.risk_limits.get_max_notional(symbol)
+    }
+
+    pub fn check_exposure(&self, exposure: &Exposure, symbol: &str, order_notional: f64) -> Result<()> {
+        self.risk_limits.check_exposure(exposure, symbol, order_notional)
+    }
+}
+
+impl RiskLimits {
+    // the symbol's own [risk.symbols] entry, keys are stored uppercased
+    pub fn listed_limits(&self, symbol: &str) -> Option<&SymbolLimits> {
+        self.symbol_limits.get(&symbol.to_uppercase())
+    }
+
+    pub fn get_symbol_limits(&self, symbol: &str) -> SymbolLimits {
+        self.listed_limits(symbol)
             .cloned()
-            .unwrap_or(SymbolLimits {
-                max_leverage: 10,
-                max_notional: self.risk_limits.max_notional_per_symbol,
-                enabled: true,
-            })
+            .unwrap_or_else(|| self.default_symbol_limits.clone())
     }
 
     pub fn is_symbol_enabled(&self, symbol: &str) -> bool {
+        if self.strict_symbols && self.listed_limits(symbol).is_none() {
+            return false;
+        }
         self.get_symbol_limits(symbol).enabled
     }
-    
+
     pub fn get_max_leverage(&self, symbol: &str) -> u32 {
         self.get_symbol_limits(symbol).max_leverage
     }
-    
+
     pub fn get_max_notional(&self, symbol: &str) -> f64 {
         self.get_symbol_limits(symbol).max_notional
     }
+
+    // account-wide limits for a new order on top of the current exposure
+    pub fn check_exposure(&self, exposure: &Exposure, symbol: &str, order_notional: f64) -> Result<()> {
+        if let Some(max_positions) = self.max_open_positions {
+            let opens_new = !exposure.positions.iter().any(|(s, _)| s == symbol);
+            let prospective = exposure.positions.len() + usize::from(opens_new);
+            if opens_new && prospective > max_positions {
+                anyhow::bail!(
+                    "Opening {} would make {} open positions, limit is {}",
+                    symbol,
+                    prospective,
+                    max_positions
+                );
+            }
+        }
+
+        if let Some(max_total) = self.max_total_notional {
+            let current: f64 = exposure.positions.iter().map(|(_, n)| n).sum::<f64>() + exposure.open_order_notional;
+            if current + order_notional > max_total {
+                anyhow::bail!(
+                    "Total exposure ${:.2} (current ${:.2} + order ${:.2}) exceeds limit ${:.2}",
+                    current + order_notional,
+                    current,
+                    order_notional,
+                    max_total
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn config_file_path() -> PathBuf {
+    env::var("HL_CONFIG")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_CONFIG_FILE))
+}
+
+pub fn builtin_profiles() -> BTreeMap<String, EndpointProfile> {
+    BTreeMap::from([
+        ("mainnet".to_string(), EndpointProfile { api_url: MAINNET_API_URL.to_string(), ws_url: MAINNET_WS_URL.to_string() }),
+        ("testnet".to_string(), EndpointProfile { api_url: TESTNET_API_URL.to_string(), ws_url: TESTNET_WS_URL.to_string() }),
+    ])
+}
+
+// rewrites the config file in place, keeping comments and the keys `edit` doesn't touch
+pub fn edit_config_file(path: &Path, edit: impl FnOnce(&mut DocumentMut) -> Result<()>) -> Result<()> {
+    let contents = if path.exists() {
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read config file {}", path.display()))?
+    } else {
+        String::new()
+    };
+    let mut document: DocumentMut = contents
+        .parse()
+        .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+    edit(&mut document)?;
+    std::fs::write(path, document.to_string()).with_context(|| format!("Failed to write config file {}", path.display()))
+}
+
+// the profile later loads start from, unless HL_PROFILE says otherwise
+pub fn set_profile(path: &Path, name: &str) -> Result<()> {
+    edit_config_file(path, |document| {
+        // assigned rather than inserted, so a comment above the key stays with it
+        document["profile"] = toml_edit::value(name);
+        Ok(())
+    })
+}
+
+// risk limits from config file contents, as the loader would read them
+pub fn parse_risk_limits(contents: &str) -> Result<RiskLimits> {
+    let file: FileConfig = toml::from_str(contents).context("Failed to parse config file")?;
+    Ok(file.risk.unwrap_or_default().into_limits())
+}
+
+// like parse_risk_limits, but for swapping limits under a running process:
+// a missing [risk] table or limits that fail `hl config validate` are errors, not defaults
+pub fn parse_reloaded_risk_limits(contents: &str) -> Result<RiskLimits> {
+    let file: FileConfig = toml::from_str(contents).context("Failed to parse config file")?;
+    let limits = file.risk.context("Config file has no [risk] table")?.into_limits();
+    let problems: Vec<String> = check_risk_limits(&limits)
+        .into_iter()
+        .filter(|check| check.level == CheckLevel::Error)
+        .map(|check| check.detail)
+        .collect();
+    if !problems.is_empty() {
+        anyhow::bail!("Invalid risk limits: {}", problems.join("; "));
+    }
+    Ok(limits)
+}
+
+fn load_file_config() -> Result<FileConfig> {
+    let path = config_file_path();
+    if !path.exists() {
+        return Ok(FileConfig::default());
+    }
+
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+
+    toml::from_str(&contents)
+        .with_context(|| format!("Failed to parse config file {}", path.display()))
+}
+
+pub fn parse_bind(value: &str) -> Result<IpAddr> {
+    value
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid bind address '{}', expected an IP like 127.0.0.1 or ::1", value))
+}
+
+// "hl", "/hl/" -> "/hl"; "" and "/" mean the root
+pub fn parse_base_path(value: &str) -> Result<String> {
+    let path = value.trim().trim_matches('/');
+    if path.is_empty() {
+        return Ok(String::new());
+    }
+    if path.split('/').any(|segment| segment.is_empty() || segment == "." || segment == "..")
+        || path.contains(|c: char| c.is_whitespace() || matches!(c, '?' | '#' | '{' | '}' | '*' | ':'))
+    {
+        anyhow::bail!("Invalid base path '{}', expected something like /hl", value);
+    }
+    Ok(format!("/{}", path))
+}
+
+// HL_API_KEYS="key1:read,key2:trade"
+fn parse_api_keys(value: &str) -> Result<Vec<ApiKeyConfig>> {
+    value
+        .split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .enumerate()
+        .map(|(i, entry)| {
+            let (key, scope) = entry
+                .trim()
+                .rsplit_once(':')
+                .ok_or_else(|| anyhow::anyhow!("HL_API_KEYS entries must look like <key>:<read|trade>"))?;
+            let scope = ApiScope::parse(scope)
+                .ok_or_else(|| anyhow::anyhow!("Unknown api key scope '{}', expected read or trade", scope))?;
+            Ok(ApiKeyConfig {
+                name: format!("env-{}", i + 1),
+                key: key.to_string(),
+                scope,
+            })
+        })
+        .collect()
 }
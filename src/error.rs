@@ -0,0 +1,84 @@
+// typed failures for library consumers; they travel inside anyhow::Error (as the error or as a
+// context layer), so callers use HlError::find instead of matching on messages
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum HlError {
+    // the config file, env vars or private key
+    #[error("{0}")]
+    ConfigError(String),
+    // refused locally before anything was sent; `rule` names the check ("notional", "symbol", ...)
+    #[error("{reason}")]
+    ValidationError { rule: &'static str, reason: String },
+    // the exchange received the request and said no
+    #[error("{0}")]
+    ExchangeRejection(String),
+    // couldn't reach the exchange, or it kept failing after retries
+    #[error("{0}")]
+    NetworkError(String),
+    // the exchange answered with something that didn't deserialize
+    #[error("{0}")]
+    ParseError(String),
+}
+
+impl HlError {
+    pub fn validation(rule: &'static str, reason: impl Into<String>) -> Self {
+        Self::ValidationError { rule, reason: reason.into() }
+    }
+
+    // the typed error anywhere in the context chain, None for untyped failures
+    pub fn find(error: &anyhow::Error) -> Option<&HlError> {
+        error.downcast_ref::<HlError>()
+    }
+
+    pub fn rule(&self) -> Option<&'static str> {
+        match self {
+            Self::ValidationError { rule, .. } => Some(rule),
+            _ => None,
+        }
+    }
+
+    // process exit codes for the cli; 1 is anything untyped, 2 is clap's usage error
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::ValidationError { .. } => 3,
+            Self::ConfigError(_) => 4,
+            Self::ExchangeRejection(_) => 5,
+            Self::NetworkError(_) => 6,
+            Self::ParseError(_) => 7,
+        }
+    }
+
+    // machine-readable code in http error bodies
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::ValidationError { .. } => "validation_failed",
+            Self::ConfigError(_) => "config_error",
+            Self::ExchangeRejection(_) => "exchange_rejected",
+            Self::NetworkError(_) => "upstream_unavailable",
+            Self::ParseError(_) => "upstream_invalid_response",
+        }
+    }
+
+    pub fn http_status(&self) -> u16 {
+        match self {
+            Self::ValidationError { .. } => 400,
+            Self::ExchangeRejection(_) => 422,
+            Self::ConfigError(_) => 500,
+            // upstream failures, not ours
+            Self::NetworkError(_) | Self::ParseError(_) => 502,
+        }
+    }
+}
+
+pub fn exit_code(error: &anyhow::Error) -> u8 {
+    HlError::find(error).map_or(1, HlError::exit_code)
+}
+
+pub fn http_status(error: &anyhow::Error) -> u16 {
+    HlError::find(error).map_or(500, HlError::http_status)
+}
+
+pub fn code(error: &anyhow::Error) -> &'static str {
+    HlError::find(error).map_or("internal", HlError::code)
+}
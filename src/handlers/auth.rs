@@ -0,0 +1,143 @@
+use std::{net::IpAddr, sync::Arc};
+use axum::{
+    extract::{Request, State},
+    http::{header, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use crate::{handlers::ApiError, types::{ApiKeyConfig, ApiScope}};
+
+// routes reachable without a token
+const PUBLIC_PATHS: [&str; 6] = ["/health", "/health/deep", "/live", "/ready", "/openapi.json", "/docs"];
+
+// key that authenticated the request, stored in request extensions
+#[derive(Debug, Clone)]
+pub struct AuthenticatedKey {
+    pub name: String,
+    pub scope: ApiScope,
+}
+
+// who may call the http and grpc apis
+#[derive(Debug, Clone, Default)]
+pub struct ServerAuth {
+    pub keys: Vec<ApiKeyConfig>,
+    // --insecure-no-auth: without keys, trading routes are open too instead of refused
+    pub insecure_no_auth: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthFailure {
+    MissingToken,
+    InvalidKey,
+    MissingScope,
+    // no keys are configured, so nothing can prove it may trade
+    TradingDisabled,
+}
+
+impl AuthFailure {
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::MissingToken => "missing bearer token",
+            Self::InvalidKey => "invalid api key",
+            Self::MissingScope => "api key lacks the trade scope",
+            Self::TradingDisabled => "no api keys are configured, trading routes are disabled",
+        }
+    }
+
+    // 401 rather than 403
+    pub fn unauthenticated(self) -> bool {
+        matches!(self, Self::MissingToken | Self::InvalidKey)
+    }
+}
+
+impl From<AuthFailure> for ApiError {
+    fn from(failure: AuthFailure) -> Self {
+        if failure.unauthenticated() {
+            ApiError::unauthorized(failure.message())
+        } else {
+            ApiError::forbidden(failure.message())
+        }
+    }
+}
+
+impl ServerAuth {
+    // the key behind `token`, or None when no key is needed: without keys reads are open and
+    // trading is refused unless insecure_no_auth was asked for
+    pub fn check(&self, token: Option<&str>, required: ApiScope) -> Result<Option<&ApiKeyConfig>, AuthFailure> {
+        if self.keys.is_empty() {
+            return if self.insecure_no_auth || required == ApiScope::Read {
+                Ok(None)
+            } else {
+                Err(AuthFailure::TradingDisabled)
+            };
+        }
+        let token = token.ok_or(AuthFailure::MissingToken)?;
+        let key = find_key(&self.keys, token).ok_or(AuthFailure::InvalidKey)?;
+        if !key.scope.allows(required) {
+            return Err(AuthFailure::MissingScope);
+        }
+        Ok(Some(key))
+    }
+
+    // who a request counts against for rate limits: the key its token matches, else the peer ip.
+    // it doesn't need the request to be authenticated, so it can run before check()
+    pub fn rate_limit_client(&self, token: Option<&str>, peer: Option<IpAddr>) -> String {
+        match token.and_then(|token| find_key(&self.keys, token)) {
+            Some(key) => format!("key:{}", key.name),
+            None => peer.map(|ip| format!("ip:{}", ip)).unwrap_or_else(|| "unknown".to_string()),
+        }
+    }
+}
+
+// the token after "Bearer " in an authorization header value
+pub fn bearer_token(value: Option<&str>) -> Option<&str> {
+    value.and_then(|value| value.strip_prefix("Bearer ")).map(str::trim)
+}
+
+pub async fn require_api_key(
+    State(auth): State<Arc<ServerAuth>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if PUBLIC_PATHS.contains(&request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let token = bearer_token(request.headers().get(header::AUTHORIZATION).and_then(|value| value.to_str().ok()));
+
+    let required = required_scope(request.method(), request.uri().path());
+    let key = match auth.check(token, required) {
+        Ok(key) => key.map(|key| AuthenticatedKey { name: key.name.clone(), scope: key.scope }),
+        Err(failure) => return ApiError::from(failure).into_response(),
+    };
+    if let Some(key) = key {
+        request.extensions_mut().insert(key);
+    }
+
+    next.run(request).await
+}
+
+// reads are safe methods, everything else mutates account state
+pub fn required_scope(method: &Method, path: &str) -> ApiScope {
+    // post a body but never place anything
+    if path == "/risk/check" || path == "/graphql" {
+        return ApiScope::Read;
+    }
+    if method == Method::GET || method == Method::HEAD || method == Method::OPTIONS {
+        ApiScope::Read
+    } else {
+        ApiScope::Trade
+    }
+}
+
+// configured key matching a bearer token
+pub fn find_key<'a>(keys: &'a [ApiKeyConfig], token: &str) -> Option<&'a ApiKeyConfig> {
+    keys.iter().find(|k| constant_time_eq(k.key.as_bytes(), token.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
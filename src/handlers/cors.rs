@@ -0,0 +1,57 @@
+// cors policy from [server.cors]; nothing cross-origin is allowed unless configured
+use std::time::Duration;
+use anyhow::{Context, Result};
+use axum::http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use crate::types::CorsConfig;
+
+// also called at config load, so a bad origin fails there and not when the server starts
+pub fn cors_layer(config: &CorsConfig) -> Result<CorsLayer> {
+    if config.permissive {
+        if config.allow_credentials {
+            anyhow::bail!("server.cors.permissive can't be combined with allow_credentials");
+        }
+        return Ok(CorsLayer::permissive());
+    }
+
+    let wildcard = config.allowed_origins.iter().any(|origin| origin == "*");
+    if wildcard && config.allow_credentials {
+        anyhow::bail!("server.cors.allowed_origins can't be \"*\" with allow_credentials, list the origins");
+    }
+    let origins = if wildcard {
+        AllowOrigin::from(Any)
+    } else {
+        let origins = config
+            .allowed_origins
+            .iter()
+            .map(|origin| {
+                HeaderValue::from_str(origin.trim_end_matches('/'))
+                    .with_context(|| format!("Invalid origin '{}' in server.cors.allowed_origins", origin))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        AllowOrigin::list(origins)
+    };
+    let methods = config
+        .allowed_methods
+        .iter()
+        .map(|method| {
+            Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                .with_context(|| format!("Invalid method '{}' in server.cors.allowed_methods", method))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let headers = config
+        .allowed_headers
+        .iter()
+        .map(|header| {
+            HeaderName::from_bytes(header.as_bytes())
+                .with_context(|| format!("Invalid header '{}' in server.cors.allowed_headers", header))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(config.allow_credentials)
+        .max_age(Duration::from_secs(config.max_age_secs)))
+}
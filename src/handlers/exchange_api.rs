@@ -1,8 +1,15 @@
-use axum::{extract::State, Json};
+use axum::{extract::{rejection::QueryRejection, Query, State}, http::{header, StatusCode}, Json};
 use anyhow::Result;
-use crate::{services::ExchangeService, types::*};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use crate::{handlers::{AppState, ApiError}, services::{health, rate_budget, ExchangeService}, types::*};
 
 // health check 
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses((status = 200, description = "Server is up", body = HealthResponse))
+)]
 pub async fn health() -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "healthy".to_string(),
@@ -14,34 +21,207 @@ pub async fn health() -> Json<HealthResponse> {
     })
 }
 
+// liveness: the process is up and serving, nothing upstream is consulted
+#[utoipa::path(
+    get,
+    path = "/live",
+    responses((status = 200, description = "Process is alive", body = String))
+)]
+pub async fn live() -> &'static str {
+    "ok"
+}
+
+// readiness: 503 until metadata is cached, and whenever the exchange is unreachable
+#[utoipa::path(
+    get,
+    path = "/ready",
+    responses(
+        (status = 200, description = "Ready for traffic", body = ReadinessResponse),
+        (status = 503, description = "Still warming up or upstream unreachable", body = ReadinessResponse)
+    )
+)]
+pub async fn ready(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let report = state.readiness.check(&state.exchange).await;
+    let status = if report.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
+// health with upstream checks, 503 while any of them fails
+#[utoipa::path(
+    get,
+    path = "/health/deep",
+    responses(
+        (status = 200, description = "Every check passed", body = DeepHealthResponse),
+        (status = 503, description = "At least one check failed", body = DeepHealthResponse)
+    )
+)]
+pub async fn health_deep(State(exchange): State<ExchangeService>) -> (StatusCode, Json<DeepHealthResponse>) {
+    let report = health::deep_check(&exchange).await;
+    let status = if report.status == "healthy" { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
+
+// rate-limit budget in prometheus text format
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses((status = 200, description = "Prometheus metrics", body = String, content_type = "text/plain"))
+)]
+pub async fn metrics(State(exchange): State<ExchangeService>) -> ([(header::HeaderName, &'static str); 1], String) {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        rate_budget::render_metrics(&exchange.pool().budget().snapshot()),
+    )
+}
 
 // chain status [markets]
+#[utoipa::path(
+    get,
+    path = "/status",
+    responses(
+        (status = 200, description = "Perp markets with prices and limits", body = StatusResponse),
+        (status = 500, description = "Request failed", body = ErrorResponse),
+        (status = 502, description = "Exchange unreachable or its reply didn't parse", body = ErrorResponse)
+    )
+)]
 pub async fn get_status(
     State(exchange): State<ExchangeService>
-) -> Result<Json<StatusResponse>, String> {
+) -> Result<Json<StatusResponse>, ApiError> {
     match exchange.get_status().await {
         Ok(status) => Ok(Json(status)),
-        Err(e) => Err(format!("Failed to get status: {}", e)),
+        Err(e) => Err(ApiError::new("Failed to get status", e)),
     }
 }
 
 // balances and positions of users
+#[utoipa::path(
+    get,
+    path = "/balances",
+    responses(
+        (status = 200, description = "Account value, margin and open positions", body = BalanceResponse),
+        (status = 500, description = "Request failed", body = ErrorResponse),
+        (status = 502, description = "Exchange unreachable or its reply didn't parse", body = ErrorResponse)
+    )
+)]
 pub async fn get_balances(
     State(exchange): State<ExchangeService>
-) -> Result<Json<BalanceResponse>, String> {
+) -> Result<Json<BalanceResponse>, ApiError> {
     match exchange.get_balances().await {
         Ok(balances) => Ok(Json(balances)),
-        Err(e) => Err(format!("Failed to get balances: {}", e)),
+        Err(e) => Err(ApiError::new("Failed to get balances", e)),
     }
 }
 
 
 // extra get spot markets
+#[utoipa::path(
+    get,
+    path = "/spot",
+    responses(
+        (status = 200, description = "Spot tokens and trading pairs", body = SpotResponse),
+        (status = 500, description = "Request failed", body = ErrorResponse),
+        (status = 502, description = "Exchange unreachable or its reply didn't parse", body = ErrorResponse)
+    )
+)]
 pub async fn get_spot_markets(
     State(exchange): State<ExchangeService>
-) -> Result<Json<SpotResponse>, String> {
+) -> Result<Json<SpotResponse>, ApiError> {
     match exchange.get_spot_markets().await {
         Ok(spot_data) => Ok(Json(spot_data)),
-        Err(e) => Err(format!("Failed to get spot markets: {}", e)),
+        Err(e) => Err(ApiError::new("Failed to get spot markets", e)),
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct OrdersQuery {
+    /// Only return orders for this symbol
+    pub symbol: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct FillsQuery {
+    /// Only return fills for this symbol
+    pub symbol: Option<String>,
+    /// Maximum number of fills to return, newest first
+    pub limit: Option<usize>,
+}
+
+// open orders of the configured wallet
+#[utoipa::path(
+    get,
+    path = "/orders",
+    params(OrdersQuery),
+    responses(
+        (status = 400, description = "Malformed query string", body = ErrorResponse),
+        (status = 200, description = "Open orders", body = OrdersResponse),
+        (status = 500, description = "Request failed", body = ErrorResponse),
+        (status = 502, description = "Exchange unreachable or its reply didn't parse", body = ErrorResponse)
+    )
+)]
+pub async fn get_orders(
+    State(exchange): State<ExchangeService>,
+    query: Result<Query<OrdersQuery>, QueryRejection>,
+) -> Result<Json<OrdersResponse>, ApiError> {
+    let Query(query) = query?;
+    match exchange.get_open_orders(query.symbol.as_deref()).await {
+        Ok(orders) => Ok(Json(orders)),
+        Err(e) => Err(ApiError::new("Failed to get orders", e)),
+    }
+}
+
+// recent fills of the configured wallet
+#[utoipa::path(
+    get,
+    path = "/fills",
+    params(FillsQuery),
+    responses(
+        (status = 400, description = "Malformed query string", body = ErrorResponse),
+        (status = 200, description = "Recent fills, newest first", body = FillsResponse),
+        (status = 500, description = "Request failed", body = ErrorResponse),
+        (status = 502, description = "Exchange unreachable or its reply didn't parse", body = ErrorResponse)
+    )
+)]
+pub async fn get_fills(
+    State(exchange): State<ExchangeService>,
+    query: Result<Query<FillsQuery>, QueryRejection>,
+) -> Result<Json<FillsResponse>, ApiError> {
+    let Query(query) = query?;
+    match exchange.get_fills(query.symbol.as_deref(), query.limit).await {
+        Ok(fills) => Ok(Json(fills)),
+        Err(e) => Err(ApiError::new("Failed to get fills", e)),
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct PortfolioQuery {
+    /// Number of recent fills to include (default 10)
+    pub fills: Option<usize>,
+    /// Hours of funding history to include (default 24)
+    pub funding_hours: Option<u64>,
+}
+
+// balances, positions, open orders, recent fills and funding in one call
+#[utoipa::path(
+    get,
+    path = "/portfolio",
+    params(PortfolioQuery),
+    responses(
+        (status = 400, description = "Malformed query string", body = ErrorResponse),
+        (status = 200, description = "Consolidated account view", body = PortfolioResponse),
+        (status = 500, description = "Request failed", body = ErrorResponse),
+        (status = 502, description = "Exchange unreachable or its reply didn't parse", body = ErrorResponse)
+    )
+)]
+pub async fn get_portfolio(
+    State(exchange): State<ExchangeService>,
+    query: Result<Query<PortfolioQuery>, QueryRejection>,
+) -> Result<Json<PortfolioResponse>, ApiError> {
+    let Query(query) = query?;
+    let funding_hours = query.funding_hours.unwrap_or(24);
+    let funding_since = (chrono::Utc::now() - chrono::Duration::hours(funding_hours as i64)).timestamp_millis() as u64;
+    match exchange.get_portfolio(query.fills.unwrap_or(10), funding_since).await {
+        Ok(portfolio) => Ok(Json(portfolio)),
+        Err(e) => Err(ApiError::new("Failed to get portfolio", e)),
     }
 }
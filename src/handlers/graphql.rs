@@ -0,0 +1,173 @@
+// read-only graphql view of the account and markets, for dashboards that want one round trip
+use std::{collections::VecDeque, sync::Arc};
+use async_graphql::{
+    futures_util::Stream,
+    http::{playground_source, GraphQLPlaygroundConfig, WebSocket as GraphQLWebSocket, WebSocketProtocols, WsMessage},
+    Context, EmptyMutation, Object, Schema, SimpleObject, Subscription,
+};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        OriginalUri, State,
+    },
+    http::{header, HeaderMap},
+    response::{Html, IntoResponse, Response},
+    Json,
+};
+use futures_util::{SinkExt, StreamExt};
+use tokio::sync::broadcast::error::RecvError;
+use crate::{
+    services::{
+        ws_hub::{self, HubSubscription, SubscriptionKey, WsHub},
+        ExchangeService,
+    },
+    types::{BalanceResponse, FillInfo, MarketInfo, OpenOrder, PositionInfo},
+};
+
+pub type HlSchema = Schema<QueryRoot, EmptyMutation, SubscriptionRoot>;
+
+pub fn schema(exchange: ExchangeService, ws_hub: Arc<WsHub>) -> HlSchema {
+    Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
+        .data(exchange)
+        .data(ws_hub)
+        .finish()
+}
+
+pub struct QueryRoot;
+
+// root fields resolve concurrently, so markets + balances + orders cost one upstream round trip
+#[Object]
+impl QueryRoot {
+    async fn markets(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<MarketInfo>> {
+        Ok(exchange(ctx).get_status().await?.markets)
+    }
+
+    async fn balances(&self, ctx: &Context<'_>) -> async_graphql::Result<BalanceResponse> {
+        Ok(exchange(ctx).get_balances().await?)
+    }
+
+    async fn positions(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<PositionInfo>> {
+        Ok(exchange(ctx).get_balances().await?.positions)
+    }
+
+    async fn orders(&self, ctx: &Context<'_>, symbol: Option<String>) -> async_graphql::Result<Vec<OpenOrder>> {
+        Ok(exchange(ctx).get_open_orders(symbol.as_deref()).await?.orders)
+    }
+
+    async fn fills(
+        &self,
+        ctx: &Context<'_>,
+        symbol: Option<String>,
+        #[graphql(default = 20)] limit: usize,
+    ) -> async_graphql::Result<Vec<FillInfo>> {
+        Ok(exchange(ctx).get_fills(symbol.as_deref(), Some(limit)).await?.fills)
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Trade {
+    pub symbol: String,
+    pub side: String,
+    pub price: f64,
+    pub size: f64,
+    pub time: u64,
+    pub trade_id: u64,
+    pub hash: String,
+}
+
+pub struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    // live trades for one symbol over the server's shared upstream socket
+    async fn trades(&self, ctx: &Context<'_>, symbol: String) -> async_graphql::Result<impl Stream<Item = Trade>> {
+        let symbol = exchange(ctx).resolve_symbol(&symbol).await?.name;
+        let hub = ctx.data_unchecked::<Arc<WsHub>>();
+        let subscription = hub.subscribe_owned(SubscriptionKey::for_coin("trades", &symbol));
+        Ok(trade_stream(subscription))
+    }
+}
+
+// the subscription is dropped, and the upstream released, once the client stops listening
+fn trade_stream(subscription: HubSubscription) -> impl Stream<Item = Trade> {
+    futures_util::stream::unfold((subscription, VecDeque::new()), |(mut subscription, mut pending)| async move {
+        loop {
+            if let Some(trade) = pending.pop_front() {
+                return Some((trade, (subscription, pending)));
+            }
+            match subscription.updates.recv().await {
+                Ok(text) => pending.extend(to_trades(&text)),
+                Err(RecvError::Lagged(skipped)) => tracing::warn!(skipped, "graphql trade subscription lagging, trades dropped"),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+pub fn to_trades(text: &str) -> Vec<Trade> {
+    ws_hub::parse_trades(text)
+        .into_iter()
+        .map(|trade| Trade {
+            side: if trade.side == "B" { "BUY" } else { "SELL" }.to_string(),
+            price: trade.px.parse().unwrap_or(0.0),
+            size: trade.sz.parse().unwrap_or(0.0),
+            time: trade.time,
+            trade_id: trade.tid,
+            symbol: trade.coin,
+            hash: trade.hash,
+        })
+        .collect()
+}
+
+fn exchange<'a>(ctx: &Context<'a>) -> &'a ExchangeService {
+    ctx.data_unchecked::<ExchangeService>()
+}
+
+// GET: playground, POST: queries; endpoints from the original uri so they keep any --base-path
+pub async fn graphql_playground(OriginalUri(uri): OriginalUri) -> Html<String> {
+    let endpoint = uri.path().trim_end_matches('/');
+    let subscriptions = format!("{}/ws", endpoint);
+    Html(playground_source(GraphQLPlaygroundConfig::new(endpoint).subscription_endpoint(&subscriptions)))
+}
+
+pub async fn graphql_handler(State(schema): State<HlSchema>, Json(request): Json<async_graphql::Request>) -> Json<async_graphql::Response> {
+    Json(schema.execute(request).await)
+}
+
+// subscriptions over graphql-transport-ws or the older graphql-ws protocol
+pub async fn graphql_ws_handler(State(schema): State<HlSchema>, headers: HeaderMap, ws: WebSocketUpgrade) -> Response {
+    let protocol = headers
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').find_map(|p| p.trim().parse::<WebSocketProtocols>().ok()));
+    let Some(protocol) = protocol else {
+        return (axum::http::StatusCode::BAD_REQUEST, "missing graphql websocket protocol").into_response();
+    };
+
+    ws.protocols(["graphql-transport-ws", "graphql-ws"])
+        .on_upgrade(move |socket| serve_subscriptions(socket, schema, protocol))
+}
+
+async fn serve_subscriptions(socket: WebSocket, schema: HlSchema, protocol: WebSocketProtocols) {
+    let (mut sink, stream) = socket.split();
+    let incoming = stream
+        .take_while(|message| futures_util::future::ready(message.is_ok()))
+        .filter_map(|message| {
+            futures_util::future::ready(match message {
+                Ok(Message::Text(text)) => Some(text.into_bytes()),
+                Ok(Message::Binary(bytes)) => Some(bytes),
+                _ => None,
+            })
+        });
+
+    let mut outgoing = GraphQLWebSocket::new(schema, incoming, protocol);
+    while let Some(message) = outgoing.next().await {
+        let message = match message {
+            WsMessage::Text(text) => Message::Text(text),
+            WsMessage::Close(code, reason) => Message::Close(Some(axum::extract::ws::CloseFrame { code, reason: reason.into() })),
+        };
+        if sink.send(message).await.is_err() {
+            break;
+        }
+    }
+}
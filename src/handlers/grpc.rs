@@ -0,0 +1,268 @@
+// grpc flavour of the http api (proto/hl.proto): same services, api keys and rate limits
+use std::{net::SocketAddr, sync::Arc};
+use anyhow::{Context, Result};
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::transport::{Identity, ServerTlsConfig};
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+use crate::{
+    error::HlError,
+    handlers::{bearer_token, AppState, AuthFailure, ServerAuth, ServerRateLimits},
+    services::{trading::check_slippage, ws_hub::{self, SubscriptionKey}},
+    types::{cloid_hex, parse_cloid, ApiScope, OrderRequest, OrderResult},
+};
+
+pub mod proto {
+    tonic::include_proto!("hl.v1");
+}
+
+use proto::{
+    order_reply::Status as OrderStatus,
+    trading_server::{Trading, TradingServer},
+    BalancesReply, BalancesRequest, CancelReply, CancelRequest, OrderReply, Position, Side, Trade, TradesRequest,
+};
+
+// trades buffered per client before a slow reader starts losing them
+const TRADE_BUFFER: usize = 256;
+
+pub struct GrpcService {
+    state: AppState,
+    auth: Arc<ServerAuth>,
+    rate_limits: Arc<ServerRateLimits>,
+}
+
+impl GrpcService {
+    pub fn new(state: AppState, auth: Arc<ServerAuth>, rate_limits: Arc<ServerRateLimits>) -> Self {
+        Self { state, auth, rate_limits }
+    }
+
+    // tls from the same (cert, key) pem files as https; api keys travel as bearer tokens,
+    // so with keys configured plaintext is refused
+    pub async fn serve(self, addr: SocketAddr, tls: Option<(String, String)>) -> Result<()> {
+        let mut server = tonic::transport::Server::builder();
+        match tls {
+            Some((cert, key)) => {
+                super::tls::install_crypto_provider();
+                let identity = Identity::from_pem(
+                    std::fs::read(&cert).with_context(|| format!("Failed to read TLS certificate {}", cert))?,
+                    std::fs::read(&key).with_context(|| format!("Failed to read TLS key {}", key))?,
+                );
+                server = server
+                    .tls_config(ServerTlsConfig::new().identity(identity))
+                    .with_context(|| format!("Failed to load TLS certificate {} / key {}", cert, key))?;
+            }
+            None if !self.auth.keys.is_empty() => anyhow::bail!(
+                "Refusing to serve gRPC without TLS while server.api_keys are set; pass --tls-cert and --tls-key"
+            ),
+            None => {}
+        }
+        server
+            .trace_fn(|_| tracing::info_span!("grpc"))
+            .add_service(TradingServer::new(self))
+            .serve(addr)
+            .await
+            .with_context(|| format!("gRPC server on {} failed", addr))
+    }
+
+    // the per-client rate limit, then the bearer token in the `authorization` metadata, checked like the http api
+    #[allow(clippy::result_large_err)] // tonic::Status is what the rpcs return anyway
+    fn authorize<T>(&self, request: &Request<T>, required: ApiScope) -> Result<(), Status> {
+        let token = bearer_token(request.metadata().get("authorization").and_then(|value| value.to_str().ok()));
+        // limited before auth, like http, so bad tokens are limited per address
+        let client = self.auth.rate_limit_client(token, request.remote_addr().map(|addr| addr.ip()));
+
+        self.rate_limits
+            .check(&client, required == ApiScope::Read)
+            .map_err(|wait| Status::resource_exhausted(format!("rate limit exceeded, retry in {}s", wait.as_secs_f64().ceil())))?;
+        self.auth.check(token, required).map_err(auth_status)?;
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl Trading for GrpcService {
+    async fn order(&self, request: Request<proto::OrderRequest>) -> Result<Response<OrderReply>, Status> {
+        self.authorize(&request, ApiScope::Trade)?;
+        let mut order_request = to_order_request(request.into_inner())?;
+        // aliases and lowercase names resolve like they do for `hl buy`
+        order_request.symbol = self.state.trading.resolve_symbol(&order_request.symbol).await.map_err(upstream)?.name;
+        let response = self.state.trading.place_order(order_request).await.map_err(upstream)?;
+        let cloid = response.cloid.as_ref().map(cloid_hex).unwrap_or_default();
+
+        let reply = match response.result {
+            OrderResult::Success { order_id, filled_qty, avg_price } => OrderReply {
+                status: OrderStatus::Filled.into(),
+                order_id,
+                filled_qty,
+                avg_price,
+                cloid,
+            },
+            OrderResult::Resting { order_id } => OrderReply {
+                status: OrderStatus::Resting.into(),
+                order_id,
+                cloid,
+                ..Default::default()
+            },
+            OrderResult::DryRun { price, size, .. } => OrderReply {
+                status: OrderStatus::Simulated.into(),
+                filled_qty: size,
+                avg_price: Some(price),
+                cloid,
+                ..Default::default()
+            },
+            OrderResult::Error { message } => return Err(Status::failed_precondition(message)),
+        };
+        Ok(Response::new(reply))
+    }
+
+    async fn cancel(&self, request: Request<CancelRequest>) -> Result<Response<CancelReply>, Status> {
+        self.authorize(&request, ApiScope::Trade)?;
+        let request = request.into_inner();
+        self.state
+            .trading
+            .cancel_order(&request.symbol, request.order_id)
+            .await
+            .map_err(upstream)?;
+        Ok(Response::new(CancelReply {}))
+    }
+
+    async fn balances(&self, request: Request<BalancesRequest>) -> Result<Response<BalancesReply>, Status> {
+        self.authorize(&request, ApiScope::Read)?;
+        let balances = self.state.exchange.get_balances().await.map_err(upstream)?;
+        Ok(Response::new(BalancesReply {
+            account_value: balances.account_value,
+            withdrawable: balances.withdrawable,
+            cross_margin_used: balances.cross_margin_used,
+            maintenance_margin: balances.maintenance_margin,
+            margin_ratio: balances.margin_ratio,
+            free_collateral: balances.free_collateral,
+            positions: balances
+                .positions
+                .into_iter()
+                .map(|p| Position {
+                    symbol: p.symbol,
+                    size: p.size,
+                    entry_price: p.entry_price,
+                    leverage: p.leverage,
+                    margin_mode: p.margin_mode,
+                    unrealized_pnl: p.unrealized_pnl,
+                    position_value: p.position_value,
+                })
+                .collect(),
+        }))
+    }
+
+    type TradesStream = ReceiverStream<Result<Trade, Status>>;
+
+    // rides the server's shared upstream socket, like /ws subscriptions
+    async fn trades(&self, request: Request<TradesRequest>) -> Result<Response<Self::TradesStream>, Status> {
+        self.authorize(&request, ApiScope::Read)?;
+        let symbol = self
+            .state
+            .exchange
+            .resolve_symbol(&request.into_inner().symbol)
+            .await
+            .map_err(|e| Status::invalid_argument(e.to_string()))?
+            .name;
+
+        let mut subscription = self.state.ws_hub.subscribe_owned(SubscriptionKey::for_coin("trades", &symbol));
+        let (sender, receiver) = mpsc::channel(TRADE_BUFFER);
+        tokio::spawn(async move {
+            'stream: loop {
+                let update = tokio::select! {
+                    // the client hung up between trades
+                    _ = sender.closed() => break,
+                    update = subscription.updates.recv() => update,
+                };
+                match update {
+                    Ok(text) => {
+                        for trade in parse_trades(&text) {
+                            if sender.send(Ok(trade)).await.is_err() {
+                                break 'stream;
+                            }
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "grpc trade stream lagging, trades dropped");
+                    }
+                    Err(RecvError::Closed) => {
+                        let _ = sender.send(Err(Status::unavailable("upstream stream closed"))).await;
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+#[allow(clippy::result_large_err)]
+pub fn to_order_request(request: proto::OrderRequest) -> Result<OrderRequest, Status> {
+    let is_buy = match request.side() {
+        Side::Buy => true,
+        Side::Sell => false,
+        Side::Unspecified => return Err(Status::invalid_argument("side is required")),
+    };
+    if !(request.qty > 0.0 && request.qty.is_finite()) {
+        return Err(Status::invalid_argument("qty must be positive"));
+    }
+    if let Some(slippage) = request.slippage {
+        check_slippage(slippage).map_err(|e| Status::invalid_argument(e.to_string()))?;
+    }
+    // generated when missing, so every order placed through the daemon can be looked up and cancelled by cloid
+    let cloid = if request.cloid.is_empty() {
+        Uuid::new_v4()
+    } else {
+        parse_cloid(&request.cloid).ok_or_else(|| Status::invalid_argument("cloid must be a uuid or 0x-prefixed 16-byte hex"))?
+    };
+    let tif = if request.tif.is_empty() { "Gtc".to_string() } else { request.tif };
+
+    Ok(OrderRequest {
+        symbol: request.symbol,
+        is_buy,
+        qty: request.qty,
+        limit_price: request.limit_price,
+        leverage: request.leverage,
+        reduce_only: request.reduce_only,
+        tif,
+        cloid: Some(cloid),
+        slippage: request.slippage,
+        ..Default::default()
+    })
+}
+
+// one upstream trades message, which can carry several trades
+pub fn parse_trades(text: &str) -> Vec<Trade> {
+    ws_hub::parse_trades(text)
+        .into_iter()
+        .map(|trade| Trade {
+            side: if trade.side == "B" { Side::Buy } else { Side::Sell }.into(),
+            price: trade.px.parse().unwrap_or(0.0),
+            size: trade.sz.parse().unwrap_or(0.0),
+            time: trade.time,
+            trade_id: trade.tid,
+            symbol: trade.coin,
+            hash: trade.hash,
+        })
+        .collect()
+}
+
+fn auth_status(failure: AuthFailure) -> Status {
+    if failure.unauthenticated() {
+        Status::unauthenticated(failure.message())
+    } else {
+        Status::permission_denied(failure.message())
+    }
+}
+
+fn upstream(e: anyhow::Error) -> Status {
+    let message = format!("{:#}", e);
+    match HlError::find(&e) {
+        Some(HlError::ValidationError { .. }) => Status::invalid_argument(message),
+        Some(HlError::ExchangeRejection(_)) => Status::failed_precondition(message),
+        Some(HlError::NetworkError(_)) => Status::unavailable(message),
+        _ => Status::internal(message),
+    }
+}
@@ -1,4 +1,22 @@
 // export modules
+pub mod api_error;
 pub mod exchange_api;
+pub mod openapi;
+pub mod auth;
+pub mod cors;
+pub mod graphql;
+pub mod grpc;
+pub mod rate_limit;
+pub mod state;
+pub mod tls;
+pub mod trading_api;
+pub mod ws;
 
+pub use api_error::*;
 pub use exchange_api::*;
+pub use openapi::*;
+pub use auth::*;
+pub use rate_limit::*;
+pub use state::*;
+pub use trading_api::*;
+pub use ws::*;
@@ -0,0 +1,98 @@
+use axum::{extract::OriginalUri, response::Html, Json};
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use crate::types::*;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Hyperliquid CLI API", description = "HTTP API exposed by `hl --server`"),
+    paths(
+        super::exchange_api::health,
+        super::exchange_api::health_deep,
+        super::exchange_api::live,
+        super::exchange_api::ready,
+        super::exchange_api::get_status,
+        super::exchange_api::get_balances,
+        super::exchange_api::get_spot_markets,
+        super::exchange_api::get_orders,
+        super::exchange_api::get_fills,
+        super::exchange_api::get_portfolio,
+        super::exchange_api::metrics,
+        super::trading_api::trigger_panic,
+        super::trading_api::check_risk,
+    ),
+    components(schemas(
+        ErrorResponse,
+        HealthResponse,
+        DeepHealthResponse,
+        HealthCheck,
+        ReadinessResponse,
+        StatusResponse,
+        MarketInfo,
+        BalanceResponse,
+        PositionInfo,
+        SpotResponse,
+        SpotTokenInfo,
+        SpotPairInfo,
+        OpenOrder,
+        OrdersResponse,
+        FillInfo,
+        FillsResponse,
+        FundingPayment,
+        PortfolioResponse,
+        PanicReport,
+        RiskCheckRequest,
+        RiskCheckResult,
+    )),
+    modifiers(&BearerAuth),
+    security(("bearer" = []))
+)]
+pub struct ApiDoc;
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+// raw openapi document, with the --base-path prefix as its server so "try it out" works behind a proxy
+pub async fn openapi_json(OriginalUri(uri): OriginalUri) -> Json<utoipa::openapi::OpenApi> {
+    let mut doc = ApiDoc::openapi();
+    let base_path = uri.path().trim_end_matches("/openapi.json");
+    if !base_path.is_empty() {
+        doc.servers = Some(vec![utoipa::openapi::Server::new(base_path)]);
+    }
+    Json(doc)
+}
+
+// swagger ui served from the public cdn, pointed at /openapi.json
+pub async fn swagger_ui() -> Html<&'static str> {
+    Html(SWAGGER_UI_HTML)
+}
+
+const SWAGGER_UI_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <title>Hyperliquid CLI API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js" crossorigin></script>
+  <script>
+    window.onload = () => {
+      window.ui = SwaggerUIBundle({ url: "openapi.json", dom_id: "#swagger-ui" });
+    };
+  </script>
+</body>
+</html>
+"##;
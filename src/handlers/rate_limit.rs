@@ -0,0 +1,65 @@
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use crate::{
+    handlers::{bearer_token, required_scope, ServerAuth},
+    services::rate_limit::KeyedRateLimiter,
+    types::{ApiScope, RateLimitConfig},
+};
+
+pub struct ServerRateLimits {
+    orders: KeyedRateLimiter,
+    info: KeyedRateLimiter,
+}
+
+impl ServerRateLimits {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            orders: KeyedRateLimiter::new(config.orders_per_second, Duration::from_secs(1)),
+            info: KeyedRateLimiter::new(config.info_per_minute, Duration::from_secs(60)),
+        }
+    }
+
+    // reads and writes have separate budgets per client
+    pub fn check(&self, client: &str, read: bool) -> Result<(), Duration> {
+        if read {
+            self.info.check(client)
+        } else {
+            self.orders.check(client)
+        }
+    }
+}
+
+// limits per api key when the token matches one, otherwise per client ip; runs before auth
+pub async fn rate_limit(
+    State((limits, auth)): State<(Arc<ServerRateLimits>, Arc<ServerAuth>)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let token = bearer_token(request.headers().get(header::AUTHORIZATION).and_then(|value| value.to_str().ok()));
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| addr.ip());
+    let client = auth.rate_limit_client(token, peer);
+
+    // the same read/trade split auth uses, so /risk/check counts as a read
+    let read = required_scope(request.method(), request.uri().path()) == ApiScope::Read;
+    if let Err(wait) = limits.check(&client, read) {
+        let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+        let mut response = (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(json!({ "error": "rate limit exceeded", "code": "rate_limited", "retry_after_secs": retry_after })),
+        )
+            .into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        return response;
+    }
+
+    next.run(request).await
+}
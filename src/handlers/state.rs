@@ -0,0 +1,30 @@
+use std::sync::Arc;
+use axum::extract::FromRef;
+use crate::services::{health::Readiness, ws_hub::WsHub, ExchangeService, TradingService};
+
+// shared router state, handlers extract only the part they need
+#[derive(Clone)]
+pub struct AppState {
+    pub exchange: ExchangeService,
+    pub ws_hub: Arc<WsHub>,
+    pub trading: Arc<TradingService>,
+    pub readiness: Arc<Readiness>,
+}
+
+impl FromRef<AppState> for ExchangeService {
+    fn from_ref(state: &AppState) -> Self {
+        state.exchange.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<WsHub> {
+    fn from_ref(state: &AppState) -> Self {
+        state.ws_hub.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<TradingService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.trading.clone()
+    }
+}
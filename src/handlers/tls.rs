@@ -0,0 +1,17 @@
+// tls for the http and grpc servers, both on rustls with the ring crypto provider
+use anyhow::{Context, Result};
+use axum_server::tls_rustls::RustlsConfig;
+
+// rustls can't pick a default when more than one provider is compiled in, so name it once per
+// process; a later call finds it installed and does nothing
+pub fn install_crypto_provider() {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+}
+
+// https config from the same (cert, key) pem files grpc serves with
+pub async fn https_config(cert: &str, key: &str) -> Result<RustlsConfig> {
+    install_crypto_provider();
+    RustlsConfig::from_pem_file(cert, key)
+        .await
+        .with_context(|| format!("Failed to load TLS certificate {} / key {}", cert, key))
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use axum::{extract::{rejection::JsonRejection, State}, Json};
+use crate::{
+    handlers::ApiError,
+    services::{kill_switch, ExchangeService, TradingService},
+    types::*,
+};
+
+// kill switch: cancel all orders and close all positions
+#[utoipa::path(
+    post,
+    path = "/panic",
+    responses(
+        (status = 200, description = "What was cancelled and closed, and whether the account ended flat", body = PanicReport),
+        (status = 422, description = "The exchange rejected the request", body = ErrorResponse),
+        (status = 500, description = "Request failed", body = ErrorResponse),
+        (status = 502, description = "Exchange unreachable or its reply didn't parse", body = ErrorResponse)
+    )
+)]
+pub async fn trigger_panic(
+    State(trading): State<Arc<TradingService>>,
+    State(exchange): State<ExchangeService>,
+) -> Result<Json<PanicReport>, ApiError> {
+    match kill_switch::panic(&trading, &exchange).await {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => Err(ApiError::new("Panic failed", e)),
+    }
+}
+
+// pre-trade check: full validation plus a margin estimate, nothing is placed
+#[utoipa::path(
+    post,
+    path = "/risk/check",
+    request_body = RiskCheckRequest,
+    responses(
+        (status = 400, description = "Malformed request body", body = ErrorResponse),
+        (status = 200, description = "Whether the order would pass risk checks, and why not", body = RiskCheckResult),
+        (status = 422, description = "The exchange rejected the request", body = ErrorResponse),
+        (status = 500, description = "Request failed", body = ErrorResponse),
+        (status = 502, description = "Exchange unreachable or its reply didn't parse", body = ErrorResponse)
+    )
+)]
+pub async fn check_risk(
+    State(trading): State<Arc<TradingService>>,
+    request: Result<Json<RiskCheckRequest>, JsonRejection>,
+) -> Result<Json<RiskCheckResult>, ApiError> {
+    let Json(request) = request?;
+    // aliases and lowercase names resolve like they do for `hl check`
+    let symbol = match trading.resolve_symbol(&request.symbol).await {
+        Ok(resolved) => resolved.name,
+        Err(e) => return Err(ApiError::new("Risk check failed", e)),
+    };
+    let order_request = OrderRequest {
+        symbol,
+        is_buy: request.is_buy,
+        qty: request.qty,
+        limit_price: request.limit_price,
+        leverage: request.leverage,
+        reduce_only: request.reduce_only,
+        tif: "Gtc".to_string(),
+        ..Default::default()
+    };
+
+    match trading.check_order(&order_request).await {
+        Ok(result) => Ok(Json(result)),
+        Err(e) => Err(ApiError::new("Risk check failed", e)),
+    }
+}
@@ -0,0 +1,135 @@
+use std::{collections::HashMap, sync::Arc};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::{sync::{broadcast, mpsc, Notify}, task::JoinHandle};
+use crate::services::ws_hub::{SubscriptionKey, WsHub};
+
+// messages waiting to be written to one client; a client this far behind is disconnected
+// instead of buffered without bound
+const CLIENT_QUEUE: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForwardEnd {
+    // the hub feed or the client went away
+    Closed,
+    // the client's queue was full
+    Overflowed,
+}
+
+// {"type":"trades","coin":"BTC"} subscribes, add "method":"unsubscribe" to leave
+#[derive(Deserialize)]
+struct ClientMessage {
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(flatten)]
+    key: SubscriptionKey,
+}
+
+pub async fn ws_handler(ws: WebSocketUpgrade, State(hub): State<Arc<WsHub>>) -> Response {
+    ws.on_upgrade(move |socket| handle_socket(socket, hub))
+}
+
+async fn handle_socket(socket: WebSocket, hub: Arc<WsHub>) {
+    let (mut sink, mut stream) = socket.split();
+    let (outgoing, mut outgoing_rx) = mpsc::channel::<String>(CLIENT_QUEUE);
+    let overflowed = Arc::new(Notify::new());
+
+    let writer = tokio::spawn(async move {
+        while let Some(text) = outgoing_rx.recv().await {
+            if sink.send(Message::Text(text)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut forwarders: HashMap<SubscriptionKey, JoinHandle<()>> = HashMap::new();
+
+    loop {
+        let message = tokio::select! {
+            message = stream.next() => message,
+            _ = overflowed.notified() => {
+                tracing::warn!(queued = CLIENT_QUEUE, "websocket client fell behind, disconnecting");
+                break;
+            }
+        };
+        let Some(Ok(message)) = message else {
+            break;
+        };
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        let request = match serde_json::from_str::<ClientMessage>(&text) {
+            Ok(request) => request,
+            Err(e) => {
+                let _ = outgoing.try_send(json!({ "error": format!("invalid message: {}", e) }).to_string());
+                continue;
+            }
+        };
+
+        if let Err(e) = request.key.validate() {
+            let _ = outgoing.try_send(json!({ "error": e }).to_string());
+            continue;
+        }
+
+        match request.method.as_deref().unwrap_or("subscribe") {
+            "subscribe" => {
+                if forwarders.contains_key(&request.key) {
+                    continue;
+                }
+                let receiver = hub.subscribe(&request.key);
+                let (queue, overflowed) = (outgoing.clone(), overflowed.clone());
+                let handle = tokio::spawn(async move {
+                    if forward(receiver, queue).await == ForwardEnd::Overflowed {
+                        overflowed.notify_one();
+                    }
+                });
+                forwarders.insert(request.key.clone(), handle);
+                let _ = outgoing.try_send(json!({ "subscribed": request.key }).to_string());
+            }
+            "unsubscribe" => {
+                if let Some(handle) = forwarders.remove(&request.key) {
+                    handle.abort();
+                    hub.unsubscribe(&request.key);
+                }
+                let _ = outgoing.try_send(json!({ "unsubscribed": request.key }).to_string());
+            }
+            other => {
+                let _ = outgoing.try_send(json!({ "error": format!("unknown method '{}'", other) }).to_string());
+            }
+        }
+    }
+
+    // release every subscription this client still held
+    for (key, handle) in forwarders {
+        handle.abort();
+        hub.unsubscribe(&key);
+    }
+    writer.abort();
+}
+
+// copies one hub feed into a client's queue without waiting on the client
+pub async fn forward(mut receiver: broadcast::Receiver<String>, outgoing: mpsc::Sender<String>) -> ForwardEnd {
+    loop {
+        match receiver.recv().await {
+            Ok(text) => match outgoing.try_send(text) {
+                Ok(()) => {}
+                Err(mpsc::error::TrySendError::Full(_)) => return ForwardEnd::Overflowed,
+                Err(mpsc::error::TrySendError::Closed(_)) => return ForwardEnd::Closed,
+            },
+            // this forwarder fell behind the hub, skip what it missed
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return ForwardEnd::Closed,
+        }
+    }
+}
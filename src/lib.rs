@@ -1,8 +1,11 @@
+pub mod error;
 pub mod types;
 pub mod services;
 pub mod handlers;
 pub mod config;
 pub mod cli;
+pub mod logging;
 
 pub use services::*;
 pub use config::*;
+pub use error::HlError;
@@ -0,0 +1,43 @@
+// tracing setup: diagnostics go to stderr (or a file), decorative cli output stays on stdout
+use std::{fs::OpenOptions, sync::Mutex};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+// RUST_LOG overrides --log-level when set
+pub fn init(level: &str, format: LogFormat, file: Option<&str>) -> Result<()> {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(level))
+        .with_context(|| format!("Invalid log level: {}", level))?;
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(false);
+
+    let writer = match file {
+        Some(path) => Some(Mutex::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Failed to open log file {}", path))?,
+        )),
+        None => None,
+    };
+
+    let result = match (format, writer) {
+        (LogFormat::Json, Some(writer)) => builder.json().with_writer(writer).try_init(),
+        (LogFormat::Json, None) => builder.json().with_writer(std::io::stderr).try_init(),
+        (LogFormat::Text, Some(writer)) => builder.with_ansi(false).with_writer(writer).try_init(),
+        (LogFormat::Text, None) => builder.with_writer(std::io::stderr).try_init(),
+    };
+
+    result.map_err(|e| anyhow::anyhow!("Failed to initialise logging: {}", e))
+}
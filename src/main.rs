@@ -1,127 +1,86 @@
+use std::{net::{IpAddr, SocketAddr}, sync::Arc};
 use axum::{
-    routing::get,
+    middleware,
+    routing::{get, post},
     Router,
 };
-use clap::{Parser, Subcommand};
-use tower_http::cors::CorsLayer;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use tracing::Instrument;
+use tower_http::trace::TraceLayer;
 use anyhow::Result;
-use types::Config;
-
-mod types;
-mod services;
-mod handlers;
-mod cli;
-mod config;
+use hyperliquid_cli::{cli, config::loader, error, handlers, logging::{self, LogFormat}, services, types::Config};
 
 #[derive(Parser)]
 #[command(name = "hl")]
 #[command(about = "Hyperliquid Testnet Trader")]
 struct Args {
     #[command(subcommand)]
-    command: Option<Commands>,
+    command: Option<cli::Commands>,
 
     #[arg(long)]
     server: bool,
     
     #[arg(long, default_value = "8080")]
     port: u16,
-}
 
-#[derive(Subcommand)]
-enum Commands {
-    Status,
-    Balances,
-    Spot,
-    Stream {
-        symbol: String,
-        #[arg(short, long, default_value = "30")]
-        duration: u64,
-    },
-    Buy {
-        symbol: String,
-        qty: f64,
-        #[arg(long, help = "Limit price (if not specified, places market order)")]
-        limit: Option<f64>,
-        #[arg(long, help = "Leverage multiplier")]
-        leverage: Option<u32>,
-        #[arg(long, help = "Reduce only order")]
-        reduce_only: bool,
-        #[arg(long, default_value = "Gtc", help = "Time in force (Gtc, Ioc, Alo)")]
-        tif: String,
-        #[arg(long, help = "Slippage tolerance for market orders (e.g., 0.01 = 1%)")]
-        slippage: Option<f64>,
-        #[arg(long, help = "Custom tick size for price rounding (e.g., 0.01, 0.1, 1.0)")]
-        tick_size: Option<f64>,
-    },
-    Sell {
-        symbol: String,
-        qty: f64,
-        #[arg(long, help = "Limit price (if not specified, places market order)")]
-        limit: Option<f64>,
-        #[arg(long, help = "Leverage multiplier")]
-        leverage: Option<u32>,
-        #[arg(long, help = "Reduce only order")]
-        reduce_only: bool,
-        #[arg(long, default_value = "Gtc", help = "Time in force (Gtc, Ioc, Alo)")]
-        tif: String,
-        #[arg(long, help = "Slippage tolerance for market orders (e.g., 0.01 = 1%)")]
-        slippage: Option<f64>,
-        #[arg(long, help = "Custom tick size for price rounding (e.g., 0.01, 0.1, 1.0)")]
-        tick_size: Option<f64>,
-    },
-    Cancel {
-        symbol: String,
-        order_id: u64,
-    }
+    #[arg(long, help = "Address to listen on (default 127.0.0.1, or server.bind); 0.0.0.0 for every interface")]
+    bind: Option<String>,
+
+    #[arg(long, help = "Serve every route under this prefix, e.g. /hl (or server.base_path)")]
+    base_path: Option<String>,
+
+    #[arg(long, help = "Serve the gRPC API on this port, alongside --server or on its own")]
+    grpc_port: Option<u16>,
+
+    #[arg(long, help = "Without server.api_keys, open the trading routes to anyone who can connect")]
+    insecure_no_auth: bool,
+
+    #[arg(long, requires = "tls_key", help = "PEM certificate chain to serve HTTPS and gRPC over TLS")]
+    tls_cert: Option<String>,
+
+    #[arg(long, requires = "tls_cert", help = "PEM private key for --tls-cert")]
+    tls_key: Option<String>,
+
+    #[arg(long, global = true, help = "Validate and price orders without sending them (or HL_DRY_RUN=1)")]
+    dry_run: bool,
+
+    #[arg(long, global = true, help = "Simulate fills with a local paper account (or HL_PAPER=1)")]
+    paper: bool,
+
+    #[arg(short = 'y', long, global = true, help = "Skip confirmation prompts (or confirm = false in hl.toml)")]
+    yes: bool,
+
+    #[arg(long, global = true, default_value = "info", help = "Log level or filter (e.g. debug, hyperliquid_cli=trace)")]
+    log_level: String,
+
+    #[arg(long, global = true, value_enum, default_value = "text", help = "Log output format")]
+    log_format: LogFormat,
+
+    #[arg(long, global = true, help = "Append logs to this file instead of stderr")]
+    log_file: Option<String>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches)?;
+    logging::init(&args.log_level, args.log_format, args.log_file.as_deref())?;
     
-    if args.server {
-        start_server(args.port).await
+    if args.server || args.grpc_port.is_some() {
+        start_server(&args).instrument(tracing::info_span!("server", port = args.port)).await
     } else {
+        let command_name = matches.subcommand_name().unwrap_or_default().to_string();
         match args.command {
-            Some(Commands::Status) => {
-                let cli = cli::Cli { command: cli::Commands::Status };
-                cli::run_cli(cli).await
-            },
-            Some(Commands::Balances) => {
-                let cli = cli::Cli { command: cli::Commands::Balances };
-                cli::run_cli(cli).await
-            },
-            Some(Commands::Spot) => {
-                let cli = cli::Cli { command: cli::Commands::Spot };
-                cli::run_cli(cli).await
-            },
-            Some(Commands::Stream { symbol, duration }) => {
-                let cli = cli::Cli {
-                    command: cli::Commands::Stream { symbol, duration }
-                };
-                cli::run_cli(cli).await
-            },
-            Some(Commands::Buy { symbol, qty, limit, leverage, reduce_only, tif, slippage, tick_size }) => {
-                let cli = cli::Cli {
-                    command: cli::Commands::Buy { 
-                        symbol, qty, limit, leverage, reduce_only, tif, slippage, tick_size
-                    }
-                };
-                cli::run_cli(cli).await
-            },
-            Some(Commands::Sell { symbol, qty, limit, leverage, reduce_only, tif, slippage, tick_size }) => {
-                let cli = cli::Cli {
-                    command: cli::Commands::Sell { 
-                        symbol, qty, limit, leverage, reduce_only, tif, slippage, tick_size
-                    }
-                };
-                cli::run_cli(cli).await
-            },
-            Some(Commands::Cancel { symbol, order_id }) => {
-                let cli = cli::Cli {
-                    command: cli::Commands::Cancel { symbol, order_id }
-                };
-                cli::run_cli(cli).await
+            Some(command) => {
+                let outcome = cli::run_cli(cli::Cli { command, dry_run: args.dry_run, paper: args.paper, yes: args.yes })
+                    .instrument(tracing::info_span!("command", name = %command_name))
+                    .await;
+                // the same report main would print, with an exit code per error kind
+                if let Err(e) = outcome {
+                    eprintln!("Error: {:?}", e);
+                    std::process::exit(error::exit_code(&e).into());
+                }
+                Ok(())
             },
             None => {
                 eprintln!("Please specify a command or use --server");
@@ -129,53 +88,359 @@ async fn main() -> Result<()> {
                 eprintln!();
                 eprintln!("Available commands:");
                 eprintln!("  status                    - Get exchange status");
+                eprintln!("    -v, --verbose           - Also show the API rate-limit weight left");
                 eprintln!("  balances                  - Get account balances");
+                eprintln!("    --addresses <a,b,...>   - Several wallets, per address and summed");
+                eprintln!("  positions                 - Open positions and unrealized PnL");
+                eprintln!("    --watch [secs]          - Redraw status/balances/positions every N seconds (default: 5)");
                 eprintln!("  spot                      - Get spot markets");
+                eprintln!("  symbol <input>            - Resolve a perp or spot symbol (btc, UBTC/USDC, @142), --json");
+                eprintln!("  spot-balances             - Spot token balances valued in USDC (--json)");
+                eprintln!("  price <symbol> [--watch]  - Mid, mark, oracle, 24h change and premium");
+                eprintln!("  monitor spread <symbols..> - Flag wide spreads and thin books (--threshold-bps)");
+                eprintln!("  vol <symbol> [--window 24h] - Realized volatility, ATR and typical hourly range");
+                eprintln!("  ta <symbol>               - Indicators and signals (--interval 1h --indicators rsi,ema20,ema50,atr)");
+                eprintln!("  scan funding              - Rank perps by annualized funding (--min-oi, --min-volume, --json)");
+                eprintln!("  monitor positions         - Liquidation distance per position, escalating alerts (--warn-at 15%)");
+                eprintln!("  monitor markets           - Journal open interest/volume, alert on moves (--threshold-pct, --window)");
                 eprintln!("  buy <symbol> <qty>        - Place buy order");
+                eprintln!("    --notional <usd>        - Size in USD instead of qty");
+                eprintln!("    --size-pct <pct>        - Size as % of account equity (times --leverage)");
                 eprintln!("    --limit <price>         - Limit price (market order if not specified)");
+                eprintln!("    --report                - Implementation shortfall of a market order, saved to the journal");
+                eprintln!("    --smart                 - Cross with a capped IOC or rest at the touch, from spread and depth");
+                eprintln!("    --peg mid               - Rest pegged to mid, re-priced as it drifts (--offset-bps, --reprice-bps)");
                 eprintln!("    --leverage <n>          - Leverage multiplier");
+                eprintln!("    --margin <cross|isolated> - Margin mode for the position");
                 eprintln!("    --reduce-only           - Reduce only order");
                 eprintln!("    --tif <Gtc|Ioc|Alo>     - Time in force");
                 eprintln!("    --slippage <pct>        - Slippage tolerance (0.01 = 1%)");
                 eprintln!("    --tick-size <size>      - Custom price tick size");
+                eprintln!("    --cloid <0x...>         - Client order ID");
+                eprintln!("    --group <id>            - Add the order to a named group");
+                eprintln!("    --preset <name>         - Defaults from [presets.<name>] in hl.toml");
                 eprintln!("  sell <symbol> <qty>       - Place sell order (same options as buy)");
                 eprintln!("  cancel <symbol> <id>      - Cancel order");
+                eprintln!("  cancel-cloid <symbol> <cloid> - Cancel order by client order ID");
+                eprintln!("  cancel-group <id>         - Bulk-cancel the resting orders of a --group");
+                eprintln!("  margin <symbol>           - Adjust isolated margin (--add <usd> | --remove <usd>)");
+                eprintln!("  panic                     - Cancel every order and market-close every position");
+                eprintln!("  close-all [--except BTC]  - Market-close each position with per-symbol PnL (confirms each unless --yes)");
+                eprintln!("  check <symbol> <qty>      - Pre-trade risk check without placing (--sell, --limit, --leverage)");
+                eprintln!("  impact <symbol> <qty>     - Expected fill and slippage of a market order (--sell, --slippage)");
+                eprintln!("  hedge <symbol> [--ratio 1.0] - Market order offsetting the perp position (--dry-run to preview)");
+                eprintln!("  breakeven <symbol>        - Move the stop to average entry (--offset-bps 5), creating one if missing");
+                eprintln!("  protect --sl 2% --tp 4%   - Keep reduce-only SL/TP triggers on every position opened or increased");
+                eprintln!("  flip <symbol>             - Reverse the position with one 2x market order (--or-close if limits block it)");
+                eprintln!("  size <symbol> --risk <r> --stop <px> - Size a position from a stop and equity risk (--entry, --take-profit, --execute)");
+                eprintln!("  risk <status|override|unlock> - Daily loss limit and trading lock");
+                eprintln!("  guard --max-drawdown <pct> - Flatten and lock trading on a drawdown from the session high");
+                eprintln!("  deadman --timeout <60s>   - Cancel all orders unless re-armed (--keepalive, --off)");
+                eprintln!("  order <symbol> <id>       - Status and fills of one order (id or cloid)");
                 eprintln!("  orders                    - List open orders");
-                eprintln!("    --open                  - Show only open orders");
-                eprintln!("  stream <symbol>           - Stream live trades");
+                eprintln!("    --symbol <symbol>       - Only show orders for a symbol");
+                eprintln!("  fills                     - List recent fills");
+                eprintln!("    --limit <n>             - Number of fills (default: 20)");
+                eprintln!("  portfolio                 - Balances, positions, orders, fills and funding in one view");
+                eprintln!("  fees                      - Fee rates, 14d volume, tier progress and fees paid (--window 7d)");
+                eprintln!("  referral <set|status>     - Use a referral code or show referral rewards");
+                eprintln!("  builder <approve|status>  - Approve a builder fee (orders take --builder, --builder-fee-bps)");
+                eprintln!("  transfer <usd> <to>       - Send USDC to a saved contact or a checksummed address");
+                eprintln!("  doctor                    - End-to-end checks: DNS, TLS, REST, WebSocket, wallet, clock, collateral");
+                eprintln!("  config validate           - Check the config, key, URLs, risk limits and symbols (nonzero on errors)");
+                eprintln!("  env <list|use|show>       - Switch between mainnet, testnet and custom profiles, or show what's in effect");
+                eprintln!("  withdrawals               - Recent withdrawals and whether they're final (--window, --watch)");
+                eprintln!("  contacts <add|remove|list> - Manage labelled transfer recipients");
+                eprintln!("  vault <deposit|withdraw|info> - Move USDC into or out of a vault, or show its equity and APR");
+                eprintln!("  stake <delegate|undelegate|summary> - Delegate staked HYPE and show rewards");
+                eprintln!("  stream <symbol>           - Stream live trades (--stats, --min-size, --min-notional, --side)");
+                eprintln!("  tape [--top 10]           - Merged trade tape for the top perps by volume (--min-notional)");
                 eprintln!("    --duration <secs>       - Stream duration (default: 30s)");
+                eprintln!("  pnl-summary               - Send daily PnL summary to notification sinks");
+                eprintln!("  sync                      - Sync account history into the journal");
+                eprintln!("  refresh-meta              - Drop the cached asset metadata and fetch it again");
+                eprintln!("  journal query <sql>       - Query the trade journal");
+                eprintln!("  strategy run <file.rhai>  - Run a strategy script (--symbol <symbol>)");
+                eprintln!("  strategy mm <symbol>      - Market maker (--qty, --spread-bps, --skew-bps)");
+                eprintln!("  strategy momentum <symbol> - MA crossover (--qty, --interval, --fast, --slow)");
+                eprintln!("    --deadman <60s>         - Keep a dead man's switch armed while running");
+                eprintln!("  paper <status|reset|run>  - Local paper account (use --paper on buy/sell/cancel)");
+                eprintln!("  stats [--json]            - Sharpe, drawdown, win rate from the journal");
+                eprintln!("  export tax --year <yyyy>  - CSV of realized gains (--method fifo|average)");
+                eprintln!("  snapshot                  - Balances, positions, orders and risk state as JSON (hl snapshot > before.json)");
+                eprintln!("  diff <before> <after>     - What changed between two snapshots (--json)");
+                eprintln!("  record book <symbol> --out <jsonl> - Sample the L2 book (--every 1s, --depth, --duration)");
+                eprintln!("  record equity [--every 60s] - Snapshot account value, margin and uPnL into the journal");
+                eprintln!("  download trades|candles <symbol> --from <date> --out <csv> - Backtest datasets, resumable (--record)");
+                eprintln!("  bridge mqtt [--symbols]   - Publish trades, BBO, fills and positions to mqtt_broker");
+                eprintln!("  bridge redis --channels trades:BTC,fills - Forward streams to Redis pub/sub (--streams)");
+                eprintln!("  bridge kafka [--symbols]  - Produce trades, candles and fills to kafka_brokers");
+                eprintln!("  bridge influx [--interval] - Write prices, funding and account PnL to influx_url");
                 eprintln!("  --server                  - Start HTTP API server");
                 eprintln!("    --port <port>           - Server port (default: 8080)");
+                eprintln!("    --bind <ip>             - Listen address (default: 127.0.0.1)");
+                eprintln!("    --base-path <path>      - Serve every route under a prefix, e.g. /hl");
+                eprintln!("  --grpc-port <port>        - Serve the gRPC API (proto/hl.proto), with or without --server");
+                eprintln!("    --tls-cert <pem>        - Serve HTTPS (and gRPC over TLS) with this certificate");
+                eprintln!("    --tls-key <pem>         - Private key for --tls-cert");
+                eprintln!("    --insecure-no-auth      - Without API keys, open the trading routes too");
+                eprintln!("  -y, --yes                 - Skip confirmation prompts");
+                eprintln!("  --log-level <level>       - Log level (default: info)");
+                eprintln!("  --log-format <text|json>  - Log output format");
+                eprintln!("  --log-file <path>         - Write logs to a file");
                 std::process::exit(1);
             }
         }
     }
 }
 
-async fn start_server(port: u16) -> Result<()> {
-    let config = Config::load()?;
-    let exchange_service = services::ExchangeService::new(config)?;
+async fn start_server(args: &Args) -> Result<()> {
+    let port = args.port;
+    let mut config = Config::load()?;
+    config.dry_run |= args.dry_run;
+    config.paper.enabled |= args.paper;
+    let tls_files = match (
+        args.tls_cert.clone().or_else(|| config.server.tls_cert.clone()),
+        args.tls_key.clone().or_else(|| config.server.tls_key.clone()),
+    ) {
+        (Some(cert), Some(key)) => Some((cert, key)),
+        (None, None) => None,
+        _ => anyhow::bail!("TLS needs both a certificate and a key"),
+    };
+    let bind = match args.bind.as_deref().or(config.server.bind.as_deref()) {
+        Some(bind) => loader::parse_bind(bind)?,
+        // other machines only reach the server when asked for with --bind 0.0.0.0
+        None => IpAddr::from([127, 0, 0, 1]),
+    };
+    let base_path = match &args.base_path {
+        Some(path) => loader::parse_base_path(path)?,
+        None => config.server.base_path.clone().unwrap_or_default(),
+    };
+    let auth = Arc::new(handlers::ServerAuth { keys: config.server.api_keys.clone(), insecure_no_auth: args.insecure_no_auth });
+    let rate_limits = Arc::new(handlers::ServerRateLimits::new(&config.server.rate_limits));
+
+    let ws_hub = services::ws_hub::WsHub::start(config.ws_url.clone(), config.http.clone());
+    // one client pool and metadata cache behind every handler
+    let exchange_service = services::ExchangeService::new(config.clone())?;
+    let trading_service = Arc::new(services::TradingService::with_exchange(config.clone(), exchange_service.clone()).await?);
+    // tighten limits on a running server by editing hl.toml
+    let _risk_watcher = services::risk_reload::watch(trading_service.shared_risk_limits())?;
+    if config.journal.path.is_some() || config.database_url.is_some() {
+        spawn_journal_sync(&config, exchange_service.clone()).await?;
+        if let Some(secs) = config.journal.equity_interval_secs {
+            spawn_equity_recorder(&config, exchange_service.clone(), secs).await?;
+        }
+    }
+    if let Some(broker) = config.mqtt_broker.clone() {
+        spawn_mqtt_bridge(broker, &config, exchange_service.clone(), ws_hub.clone());
+    }
+    if let Some(url) = config.influx_url.clone() {
+        spawn_influx_sink(url, &config, exchange_service.clone());
+    }
+    if let Some(brokers) = config.kafka_brokers.clone() {
+        spawn_kafka_sink(brokers, &config, exchange_service.clone(), ws_hub.clone());
+    }
+    if let Some(url) = config.redis_url.clone().filter(|_| !config.redis.channels.is_empty()) {
+        spawn_redis_bridge(url, &config, exchange_service.clone(), ws_hub.clone());
+    }
+    // /ready stays 503 until this has cached the metadata
+    let readiness = Arc::new(services::health::Readiness::default());
+    tokio::spawn({
+        let (readiness, exchange) = (readiness.clone(), exchange_service.clone());
+        async move { readiness.warm_up(&exchange).await }
+    });
+    let state = handlers::AppState {
+        exchange: exchange_service,
+        ws_hub,
+        trading: trading_service,
+        readiness,
+    };
+    let grpc = args.grpc_port.map(|grpc_port| {
+        let addr = SocketAddr::new(bind, grpc_port);
+        let scheme = if tls_files.is_some() { "TLS" } else { "plaintext" };
+        println!("gRPC server running on {} ({}; Order, Cancel, Balances, Trades)", addr, scheme);
+        handlers::grpc::GrpcService::new(state.clone(), auth.clone(), rate_limits.clone()).serve(addr, tls_files.clone())
+    });
+    let grpc = match grpc {
+        // --grpc-port on its own skips the http server
+        Some(grpc) if !args.server => return grpc.await,
+        grpc => grpc,
+    };
     
-    let app = Router::new()
+    let mut app = Router::new()
         .route("/health", get(handlers::health))
+        .route("/health/deep", get(handlers::health_deep))
+        .route("/live", get(handlers::live))
+        .route("/ready", get(handlers::ready))
         .route("/status", get(handlers::get_status))
         .route("/balances", get(handlers::get_balances))
         .route("/spot", get(handlers::get_spot_markets))
-        .layer(CorsLayer::permissive())
-        .with_state(exchange_service);
+        .route("/orders", get(handlers::get_orders))
+        .route("/fills", get(handlers::get_fills))
+        .route("/portfolio", get(handlers::get_portfolio))
+        .route("/metrics", get(handlers::metrics))
+        .route("/openapi.json", get(handlers::openapi_json))
+        .route("/docs", get(handlers::swagger_ui))
+        .route("/ws", get(handlers::ws_handler))
+        .route("/panic", post(handlers::trigger_panic))
+        .route("/risk/check", post(handlers::check_risk));
+    if config.server.graphql {
+        let schema = handlers::graphql::schema(state.exchange.clone(), state.ws_hub.clone());
+        app = app.merge(
+            Router::new()
+                .route("/graphql", get(handlers::graphql::graphql_playground).post(handlers::graphql::graphql_handler))
+                .route("/graphql/ws", get(handlers::graphql::graphql_ws_handler))
+                .with_state(schema),
+        );
+    }
+    let app = app
+        .layer(middleware::from_fn_with_state(auth.clone(), handlers::require_api_key))
+        // outermost, so bad or missing tokens are limited per address before auth answers them
+        .layer(middleware::from_fn_with_state((rate_limits, auth), handlers::rate_limit))
+        .with_state(state);
+    // nested routes see their path without the prefix, so auth and rate limits match as at the root
+    let app = if base_path.is_empty() { app } else { Router::new().nest(&base_path, app) };
+    let app = app
+        .layer(handlers::cors::cors_layer(&config.server.cors)?)
+        .layer(TraceLayer::new_for_http());
     
-    let listener = tokio::net::TcpListener::bind(&format!("0.0.0.0:{}", port)).await?;
+    let addr = SocketAddr::new(bind, port);
+    let tls = match &tls_files {
+        Some((cert, key)) => Some(handlers::tls::https_config(cert, key).await?),
+        None => None,
+    };
+    let scheme = if tls.is_some() { "https" } else { "http" };
     
-    println!("Hyperliquid Server running on http://localhost:{}", port);
+    println!("Hyperliquid Server running on {}://{}{}", scheme, addr, base_path);
     println!("Available endpoints:");
     println!("   GET  /health       - Health check");
+    println!("   GET  /health/deep  - Upstream REST/WebSocket, wallet and clock checks");
+    println!("   GET  /live, /ready - Liveness and readiness probes");
     println!("   GET  /status       - Exchange status");
     println!("   GET  /balances     - Account balances");
     println!("   GET  /spot         - Spot markets");
+    println!("   GET  /orders       - Open orders");
+    println!("   GET  /fills        - Recent fills");
+    println!("   GET  /metrics      - Rate-limit budget (Prometheus)");
+    println!("   GET  /openapi.json - OpenAPI document");
+    println!("   GET  /docs         - Swagger UI");
+    println!("   GET  /ws           - WebSocket market streams");
+    println!("   POST /panic        - Cancel all orders and close all positions");
+    println!("   POST /risk/check   - Dry-run an order against the risk rules, nothing is placed");
+    if config.server.graphql {
+        println!("   POST /graphql      - GraphQL queries (GET for the playground, /graphql/ws for subscriptions)");
+    }
     println!();
+    if config.server.api_keys.is_empty() && args.insecure_no_auth {
+        println!("WARNING: --insecure-no-auth, every route including POST /panic is open to anyone who can reach {}", addr);
+    } else if config.server.api_keys.is_empty() {
+        println!("No API keys configured: read routes are open, trading routes answer 403");
+        println!("(add server.api_keys, or start with --insecure-no-auth to open them)");
+    } else {
+        println!("Authentication: {} API key(s), send 'Authorization: Bearer <key>'", config.server.api_keys.len());
+    }
     println!("Press Ctrl+C to stop the server");
     
-    axum::serve(listener, app).await?;
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    let http = async {
+        match tls {
+            Some(tls_config) => {
+                axum_server::bind_rustls(addr, tls_config).serve(service).await?;
+            }
+            None => {
+                let listener = tokio::net::TcpListener::bind(addr).await?;
+                axum::serve(listener, service).await?;
+            }
+        }
+        Ok::<_, anyhow::Error>(())
+    };
+    match grpc {
+        Some(grpc) => {
+            tokio::try_join!(http, grpc)?;
+        }
+        None => http.await?,
+    }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+// passive journal recording while the server runs
+async fn spawn_journal_sync(config: &Config, exchange: services::ExchangeService) -> Result<()> {
+    let journal = services::journal::Journal::from_config(config).await?;
+    let location = journal.location().to_string();
+    let interval = std::time::Duration::from_secs(config.journal.sync_interval_secs.max(10));
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match services::journal::sync(&journal, &exchange).await {
+                Ok(summary) => tracing::info!(
+                    new_fills = summary.new_fills,
+                    new_funding = summary.new_funding_payments,
+                    "journal synced"
+                ),
+                Err(e) => tracing::warn!("journal sync failed: {:#}", e),
+            }
+        }
+    });
+
+    println!("Journal: recording to {} every {}s", location, interval.as_secs());
+    Ok(())
+}
+
+// equity snapshots between full syncs, so the stats and drawdown guard see a finer curve
+async fn spawn_equity_recorder(config: &Config, exchange: services::ExchangeService, secs: u64) -> Result<()> {
+    let journal = services::journal::Journal::from_config(config).await?;
+    let every = std::time::Duration::from_secs(secs.max(1));
+    tokio::spawn(async move {
+        if let Err(e) = services::journal::record_equity(&journal, &exchange, every, |_| {}).await {
+            tracing::error!("equity recorder stopped: {:#}", e);
+        }
+    });
+
+    println!("Journal: equity snapshots every {}s", every.as_secs());
+    Ok(())
+}
+
+// shares the server's upstream socket, so /ws clients and the broker see the same stream
+fn spawn_mqtt_bridge(broker: String, config: &Config, exchange: services::ExchangeService, hub: Arc<services::ws_hub::WsHub>) {
+    let mqtt_config = config.mqtt.clone();
+    println!("MQTT: publishing to {} under {}/", broker, mqtt_config.topic_prefix);
+    tokio::spawn(async move {
+        if let Err(e) = services::mqtt::run(&broker, &mqtt_config, exchange, hub).await {
+            tracing::error!("mqtt bridge stopped: {:#}", e);
+        }
+    });
+}
+
+fn spawn_redis_bridge(url: String, config: &Config, exchange: services::ExchangeService, hub: Arc<services::ws_hub::WsHub>) {
+    let redis_config = config.redis.clone();
+    println!("Redis: forwarding {} to {}", redis_config.channels.join(", "), url);
+    tokio::spawn(async move {
+        if let Err(e) = services::redis_bridge::run(&url, &redis_config, exchange, hub).await {
+            tracing::error!("redis bridge stopped: {:#}", e);
+        }
+    });
+}
+
+fn spawn_influx_sink(url: String, config: &Config, exchange: services::ExchangeService) {
+    let influx_config = config.influx.clone();
+    let http = config.http.clone();
+    println!("Influx: writing to {} every {}s", url, influx_config.interval_secs.max(1));
+    tokio::spawn(async move {
+        if let Err(e) = services::influx::run(&url, &influx_config, &http, exchange).await {
+            tracing::error!("influx sink stopped: {:#}", e);
+        }
+    });
+}
+
+fn spawn_kafka_sink(brokers: String, config: &Config, exchange: services::ExchangeService, hub: Arc<services::ws_hub::WsHub>) {
+    let kafka_config = config.kafka.clone();
+    println!("Kafka: producing to {}", brokers);
+    tokio::spawn(async move {
+        if let Err(e) = services::kafka::run(&brokers, &kafka_config, exchange, hub).await {
+            tracing::error!("kafka sink stopped: {:#}", e);
+        }
+    });
+}
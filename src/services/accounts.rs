@@ -0,0 +1,70 @@
+// balances across several wallets, fetched together and summed
+use std::collections::BTreeMap;
+use anyhow::Result;
+use crate::types::{AccountBalances, BalanceResponse, MultiBalanceResponse, SymbolExposure};
+
+pub fn parse_address(address: &str) -> Result<String> {
+    let address = address.trim();
+    let hex = address.strip_prefix("0x").unwrap_or(address);
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!("Invalid address '{}', expected 0x + 40 hex digits", address);
+    }
+    Ok(format!("0x{}", hex.to_lowercase()))
+}
+
+// entries may themselves be comma-separated, duplicates are dropped keeping the first
+pub fn parse_addresses<S: AsRef<str>>(addresses: &[S]) -> Result<Vec<String>> {
+    let mut parsed: Vec<String> = Vec::new();
+    for address in addresses.iter().flat_map(|a| a.as_ref().split(',')) {
+        if address.trim().is_empty() {
+            continue;
+        }
+        let address = parse_address(address)?;
+        if !parsed.contains(&address) {
+            parsed.push(address);
+        }
+    }
+    if parsed.is_empty() {
+        anyhow::bail!("No addresses given");
+    }
+    Ok(parsed)
+}
+
+pub fn aggregate(accounts: Vec<(String, BalanceResponse)>) -> MultiBalanceResponse {
+    let mut exposure: BTreeMap<String, SymbolExposure> = BTreeMap::new();
+    for (_, balances) in &accounts {
+        for pos in &balances.positions {
+            let entry = exposure.entry(pos.symbol.clone()).or_insert_with(|| SymbolExposure {
+                symbol: pos.symbol.clone(),
+                net_size: 0.0,
+                long_value: 0.0,
+                short_value: 0.0,
+                unrealized_pnl: 0.0,
+                accounts: 0,
+            });
+            entry.net_size += pos.size;
+            if pos.size > 0.0 {
+                entry.long_value += pos.position_value.abs();
+            } else {
+                entry.short_value += pos.position_value.abs();
+            }
+            entry.unrealized_pnl += pos.unrealized_pnl;
+            entry.accounts += 1;
+        }
+    }
+    let mut exposure: Vec<SymbolExposure> = exposure.into_values().collect();
+    exposure.sort_by(|a, b| (b.long_value + b.short_value).total_cmp(&(a.long_value + a.short_value)));
+
+    let sum = |field: fn(&BalanceResponse) -> f64| accounts.iter().map(|(_, b)| field(b)).sum::<f64>();
+    MultiBalanceResponse {
+        total_account_value: sum(|b| b.account_value),
+        total_withdrawable: sum(|b| b.withdrawable),
+        total_margin_used: sum(|b| b.cross_margin_used),
+        total_unrealized_pnl: sum(|b| b.positions.iter().map(|p| p.unrealized_pnl).sum()),
+        exposure,
+        accounts: accounts
+            .into_iter()
+            .map(|(address, balances)| AccountBalances { address, balances })
+            .collect(),
+    }
+}
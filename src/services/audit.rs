@@ -0,0 +1,82 @@
+// append-only jsonl record of every order action, for post-mortems
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    sync::Mutex,
+    time::Duration,
+};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct AuditRecord {
+    pub timestamp: String,
+    pub action: &'static str,
+    pub request: serde_json::Value,
+    pub validation: String,
+    pub response: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub latency_ms: u64,
+}
+
+impl AuditRecord {
+    pub fn new<T: Serialize>(
+        action: &'static str,
+        request: serde_json::Value,
+        validation: Option<&anyhow::Error>,
+        outcome: &anyhow::Result<T>,
+        latency: Duration,
+    ) -> Self {
+        let (response, error) = match outcome {
+            Ok(response) => (serde_json::to_value(response).ok(), None),
+            Err(e) => (None, Some(format!("{:#}", e))),
+        };
+
+        Self {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            action,
+            request,
+            validation: match validation {
+                Some(e) => format!("rejected: {}", e),
+                None => "passed".to_string(),
+            },
+            response,
+            error,
+            latency_ms: latency.as_millis() as u64,
+        }
+    }
+}
+
+pub struct AuditLog {
+    path: Option<PathBuf>,
+    write_lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(path: Option<&str>) -> Self {
+        Self {
+            path: path.filter(|p| !p.is_empty()).map(PathBuf::from),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    // audit failures are logged but never block trading
+    pub fn record(&self, record: &AuditRecord) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let result = serde_json::to_string(record)
+            .map_err(anyhow::Error::from)
+            .and_then(|line| {
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}", line)?;
+                Ok(())
+            });
+
+        if let Err(e) = result {
+            tracing::error!(path = %path.display(), "failed to write audit record: {}", e);
+        }
+    }
+}
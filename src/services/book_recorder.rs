@@ -0,0 +1,108 @@
+// samples the live l2 book at a fixed interval into jsonl, for offline microstructure work
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast::error::RecvError;
+use crate::services::ws_hub::{SubscriptionKey, WsHub};
+use crate::types::{exchange::RawL2Book, BookLevel, OrderBook};
+
+// a book this old when a sample is due means the stream has stalled, so the sample is skipped
+const STALE_AFTER: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Serialize)]
+pub struct BookSnapshot<'a> {
+    // local time the sample was taken
+    pub ts: u64,
+    pub symbol: &'a str,
+    // exchange time of the book it was taken from
+    pub book_time: u64,
+    pub bids: &'a [BookLevel],
+    pub asks: &'a [BookLevel],
+}
+
+impl<'a> BookSnapshot<'a> {
+    // `depth` levels per side, 0 for everything the stream carries
+    pub fn new(book: &'a OrderBook, ts: u64, depth: usize) -> Self {
+        let limit = |levels: &'a [BookLevel]| if depth == 0 { levels } else { &levels[..depth.min(levels.len())] };
+        Self {
+            ts,
+            symbol: &book.symbol,
+            book_time: book.time,
+            bids: limit(&book.bids),
+            asks: limit(&book.asks),
+        }
+    }
+}
+
+// every l2Book message carries the whole (20 level) book, so the latest one is the current state
+pub fn parse_book(text: &str) -> Option<OrderBook> {
+    let message: Value = serde_json::from_str(text).ok()?;
+    if message.get("channel")?.as_str()? != "l2Book" {
+        return None;
+    }
+    let book: RawL2Book = serde_json::from_value(message.get("data")?.clone()).ok()?;
+    Some(book.into_order_book())
+}
+
+// appends one line per interval until `duration` runs out (or forever), returns the lines written
+pub async fn run(
+    hub: Arc<WsHub>,
+    symbol: &str,
+    every: Duration,
+    depth: usize,
+    duration: Option<Duration>,
+    out: &Path,
+) -> Result<u64> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(out)
+        .with_context(|| format!("Failed to open {}", out.display()))?;
+    let mut writer = BufWriter::new(file);
+    let mut subscription = hub.subscribe_owned(SubscriptionKey::for_coin("l2Book", symbol));
+    let mut latest: Option<(OrderBook, std::time::Instant)> = None;
+    let mut ticker = tokio::time::interval(every);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let deadline = duration.map(|d| tokio::time::Instant::now() + d);
+    let mut written = 0;
+
+    loop {
+        tokio::select! {
+            update = subscription.updates.recv() => match update {
+                Ok(text) => {
+                    if let Some(book) = parse_book(&text) {
+                        latest = Some((book, std::time::Instant::now()));
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => tracing::warn!(skipped, "book recorder falling behind, updates dropped"),
+                Err(RecvError::Closed) => anyhow::bail!("Upstream stream closed"),
+            },
+            _ = ticker.tick() => {
+                if deadline.is_some_and(|deadline| tokio::time::Instant::now() >= deadline) {
+                    break;
+                }
+                match &latest {
+                    Some((book, received)) if received.elapsed() < STALE_AFTER => {
+                        let snapshot = BookSnapshot::new(book, Utc::now().timestamp_millis() as u64, depth);
+                        serde_json::to_writer(&mut writer, &snapshot)?;
+                        writeln!(writer)?;
+                        // flushed per line so an interrupted run leaves whole lines behind
+                        writer.flush().context("Failed to write snapshot")?;
+                        written += 1;
+                    }
+                    Some(_) => tracing::warn!(symbol, "no book update for 30s, skipping sample"),
+                    None => {}
+                }
+            }
+        }
+    }
+    Ok(written)
+}
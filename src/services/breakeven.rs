@@ -0,0 +1,30 @@
+// moving a position's stop to its average entry, used by `hl breakeven`
+use anyhow::Result;
+use crate::types::TriggerOrder;
+
+// entry nudged `offset_bps` into profit so the exit also covers fees: above entry for a long, below for a short
+pub fn breakeven_price(entry: f64, position: f64, offset_bps: f64) -> f64 {
+    let offset = entry * offset_bps / 10_000.0;
+    if position > 0.0 { entry + offset } else { entry - offset }
+}
+
+// the protective stop of a position, a stop trigger on the closing side; take profits are left alone
+pub fn find_stop<'a>(orders: &'a [TriggerOrder], symbol: &str, position: f64) -> Option<&'a TriggerOrder> {
+    let closing_side = if position > 0.0 { "SELL" } else { "BUY" };
+    orders
+        .iter()
+        .find(|o| o.symbol.eq_ignore_ascii_case(symbol) && o.side == closing_side && o.order_type.starts_with("Stop"))
+}
+
+// a stop already through the mark would trigger the moment it is placed
+pub fn validate(stop: f64, mark: f64, position: f64) -> Result<()> {
+    let through = if position > 0.0 { stop >= mark } else { stop <= mark };
+    if through {
+        anyhow::bail!(
+            "Breakeven stop ${:.4} is already through the mark ${:.4}; the position isn't far enough in profit",
+            stop,
+            mark
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,218 @@
+// `hl config validate`: loads the config and checks what loading alone doesn't
+use alloy::signers::local::PrivateKeySigner;
+use serde::Serialize;
+use crate::config::loader;
+use crate::services::ExchangeService;
+use crate::types::{Config, RiskLimits};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckLevel {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigCheck {
+    pub name: &'static str,
+    pub level: CheckLevel,
+    pub detail: String,
+}
+
+impl ConfigCheck {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, level: CheckLevel::Ok, detail: detail.into() }
+    }
+
+    fn warning(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, level: CheckLevel::Warning, detail: detail.into() }
+    }
+
+    fn error(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, level: CheckLevel::Error, detail: detail.into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigReport {
+    pub path: String,
+    pub checks: Vec<ConfigCheck>,
+}
+
+impl ConfigReport {
+    pub fn count(&self, level: CheckLevel) -> usize {
+        self.checks.iter().filter(|check| check.level == level).count()
+    }
+}
+
+// everything that can be checked without the exchange, in report order
+pub fn check_local(config: &Config) -> Vec<ConfigCheck> {
+    let mut checks = vec![check_private_key(&config.private_key)];
+    checks.push(check_url("api_url", &config.api_url, &["http", "https"]));
+    checks.push(check_url("ws_url", &config.ws_url, &["ws", "wss"]));
+    if config.follows_profile() {
+        checks.push(ConfigCheck::ok("profile", format!("{} ({})", config.profile, config.network())));
+    } else {
+        checks.push(ConfigCheck::warning(
+            "profile",
+            format!("{} is selected but api_url / ws_url override it, the effective network is {}", config.profile, config.network()),
+        ));
+    }
+    checks.extend(check_risk_limits(&config.risk_limits));
+    checks
+}
+
+pub fn check_private_key(key: &str) -> ConfigCheck {
+    match key.parse::<PrivateKeySigner>() {
+        Ok(signer) => ConfigCheck::ok("private key", format!("wallet {:?}", signer.address())),
+        Err(_) => ConfigCheck::error("private key", "PRIVATE_KEY doesn't parse as a 32 byte hex key"),
+    }
+}
+
+pub fn check_url(name: &'static str, url: &str, schemes: &[&str]) -> ConfigCheck {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if !schemes.contains(&parsed.scheme()) => {
+            ConfigCheck::error(name, format!("{} should be {}", url, schemes.iter().map(|s| format!("{}://", s)).collect::<Vec<_>>().join(" or ")))
+        }
+        Ok(parsed) if parsed.host().is_none() => ConfigCheck::error(name, format!("{} has no host", url)),
+        Ok(_) => ConfigCheck::ok(name, url),
+        Err(e) => ConfigCheck::error(name, format!("{} is not a valid url: {}", url, e)),
+    }
+}
+
+// one check per problem, or a single ok when the limits agree with each other
+pub fn check_risk_limits(limits: &RiskLimits) -> Vec<ConfigCheck> {
+    let mut problems = Vec::new();
+    let positive = |value: f64| value > 0.0 && value.is_finite();
+    if !positive(limits.max_notional_per_order) || !positive(limits.max_notional_per_symbol) {
+        problems.push(ConfigCheck::error("risk limits", "max_notional_per_order and max_notional_per_symbol must be positive"));
+    }
+    if limits.max_notional_per_order > limits.max_notional_per_symbol {
+        problems.push(ConfigCheck::error(
+            "risk limits",
+            format!(
+                "max_notional_per_order ${:.2} is above max_notional_per_symbol ${:.2}",
+                limits.max_notional_per_order, limits.max_notional_per_symbol
+            ),
+        ));
+    }
+    if let Some(max_total) = limits.max_total_notional
+        && max_total < limits.max_notional_per_order
+    {
+        problems.push(ConfigCheck::error(
+            "risk limits",
+            format!("max_total_notional ${:.2} is below max_notional_per_order ${:.2}", max_total, limits.max_notional_per_order),
+        ));
+    }
+    if limits.max_daily_loss.is_some_and(|loss| !positive(loss)) {
+        problems.push(ConfigCheck::error("risk limits", "max_daily_loss must be positive"));
+    }
+    if limits.max_open_positions == Some(0) || limits.max_orders_per_minute == Some(0) {
+        problems.push(ConfigCheck::error("risk limits", "max_open_positions and max_orders_per_minute must be at least 1"));
+    }
+
+    let mut symbols: Vec<_> = limits.symbol_limits.iter().collect();
+    symbols.sort_by(|a, b| a.0.cmp(b.0));
+    for (symbol, symbol_limits) in symbols {
+        if symbol_limits.max_leverage == 0 {
+            problems.push(ConfigCheck::error("risk limits", format!("{} max_leverage must be at least 1", symbol)));
+        }
+        if !positive(symbol_limits.max_notional) {
+            problems.push(ConfigCheck::error("risk limits", format!("{} max_notional must be positive", symbol)));
+        } else if symbol_limits.max_notional < limits.max_notional_per_order {
+            problems.push(ConfigCheck::warning(
+                "risk limits",
+                format!(
+                    "{} max_notional ${:.2} is below max_notional_per_order ${:.2}, so it caps orders there",
+                    symbol, symbol_limits.max_notional, limits.max_notional_per_order
+                ),
+            ));
+        }
+        if symbol_limits.enabled && !symbol_limits.allow_long && !symbol_limits.allow_short {
+            problems.push(ConfigCheck::warning("risk limits", format!("{} is enabled but allows neither longs nor shorts", symbol)));
+        }
+    }
+
+    if problems.is_empty() {
+        vec![ConfigCheck::ok(
+            "risk limits",
+            format!(
+                "${:.0} per order, ${:.0} per symbol, {} symbol(s) configured",
+                limits.max_notional_per_order,
+                limits.max_notional_per_symbol,
+                limits.symbol_limits.len()
+            ),
+        )]
+    } else {
+        problems
+    }
+}
+
+// symbols named under [risk.symbols] have to exist, and their leverage cap should be reachable;
+// every [aliases] entry has to point at something listed
+pub async fn check_symbols(exchange: &ExchangeService, limits: &RiskLimits) -> Vec<ConfigCheck> {
+    let resolver = match exchange.symbol_resolver().await {
+        Ok(resolver) => resolver,
+        Err(e) => return vec![ConfigCheck::error("symbols", format!("Couldn't fetch exchange metadata: {}", e.root_cause()))],
+    };
+    let mut symbols: Vec<_> = limits.symbol_limits.iter().collect();
+    symbols.sort_by(|a, b| a.0.cmp(b.0));
+    let mut problems = Vec::new();
+    for (symbol, symbol_limits) in &symbols {
+        match resolver.resolve(symbol) {
+            Ok(resolved) => {
+                if let Some(max_leverage) = resolved.max_leverage
+                    && symbol_limits.max_leverage > max_leverage
+                {
+                    problems.push(ConfigCheck::warning(
+                        "symbols",
+                        format!("{} max_leverage {}x is above the exchange's {}x", symbol, symbol_limits.max_leverage, max_leverage),
+                    ));
+                }
+            }
+            Err(e) => problems.push(ConfigCheck::error("symbols", format!("{}: {}", symbol, e))),
+        }
+    }
+    let aliases = &exchange.config().aliases;
+    for alias in aliases.keys() {
+        if let Err(e) = resolver.resolve(alias) {
+            problems.push(ConfigCheck::error("symbols", e.to_string()));
+        }
+    }
+    if problems.is_empty() {
+        let mut detail = format!("all {} listed on the exchange", symbols.len());
+        if !aliases.is_empty() {
+            detail.push_str(&format!(", {} alias(es) resolve", aliases.len()));
+        }
+        vec![ConfigCheck::ok("symbols", detail)]
+    } else {
+        problems
+    }
+}
+
+// a config that doesn't load is reported as a single error rather than returned as one
+pub async fn validate() -> ConfigReport {
+    let path = loader::config_file_path();
+    let mut report = ConfigReport { path: path.display().to_string(), checks: Vec::new() };
+    if !path.exists() {
+        report.checks.push(ConfigCheck::warning("config file", format!("{} not found, using defaults and env vars", path.display())));
+    }
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            report.checks.push(ConfigCheck::error("load", format!("{:#}", e)));
+            return report;
+        }
+    };
+    report.checks.push(ConfigCheck::ok("load", "parsed and passed the load-time checks"));
+    report.checks.extend(check_local(&config));
+    let endpoints_ok = report.checks.iter().all(|check| check.name != "api_url" || check.level != CheckLevel::Error);
+    if endpoints_ok {
+        match ExchangeService::new(config.clone()) {
+            Ok(exchange) => report.checks.extend(check_symbols(&exchange, &config.risk_limits).await),
+            Err(e) => report.checks.push(ConfigCheck::error("symbols", format!("{:#}", e))),
+        }
+    }
+    report
+}
@@ -0,0 +1,121 @@
+// labelled addresses for `hl transfer`, kept in the [contacts] table of the config file
+use std::{collections::BTreeMap, path::Path};
+use anyhow::{Context, Result};
+use ethers::{types::Address, utils::to_checksum};
+use toml_edit::{Item, Table};
+use crate::config::loader;
+
+const MAX_LABEL_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recipient {
+    pub label: Option<String>,
+    // eip-55 checksummed
+    pub address: String,
+}
+
+impl Recipient {
+    pub fn describe(&self) -> String {
+        match &self.label {
+            Some(label) => format!("{} ({})", label, self.address),
+            None => self.address.clone(),
+        }
+    }
+}
+
+// eip-55: all lower or all upper case hex carries no checksum, mixed case has to match it
+pub fn checksum_address(address: &str) -> Result<String> {
+    let address = address.trim();
+    let hex = address.strip_prefix("0x").unwrap_or(address);
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!("Invalid address '{}', expected 0x + 40 hex digits", address);
+    }
+    let parsed: Address = format!("0x{}", hex.to_lowercase()).parse()?;
+    let checksummed = to_checksum(&parsed, None);
+    let mixed_case = hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+    if mixed_case && checksummed[2..] != *hex {
+        anyhow::bail!("Address {} fails its checksum, it was probably mistyped", address);
+    }
+    Ok(checksummed)
+}
+
+// labels are matched case-insensitively and can't be mistaken for an address
+pub fn parse_label(label: &str) -> Result<String> {
+    let label = label.trim().to_lowercase();
+    if label.is_empty() || label.len() > MAX_LABEL_LEN {
+        anyhow::bail!("Contact labels must be 1 to {} characters", MAX_LABEL_LEN);
+    }
+    if !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        anyhow::bail!("Invalid contact label '{}', use letters, digits, '-' and '_'", label);
+    }
+    if label.starts_with("0x") {
+        anyhow::bail!("Contact labels can't start with 0x");
+    }
+    Ok(label)
+}
+
+// [contacts] as read from the config file, every entry validated
+pub fn parse_contacts(raw: &BTreeMap<String, String>) -> Result<BTreeMap<String, String>> {
+    raw.iter()
+        .map(|(label, address)| {
+            let address = checksum_address(address).with_context(|| format!("Invalid contact '{}'", label))?;
+            Ok((parse_label(label)?, address))
+        })
+        .collect()
+}
+
+// a saved label, or a raw address (named if it's one of the contacts)
+pub fn resolve(contacts: &BTreeMap<String, String>, recipient: &str) -> Result<Recipient> {
+    let recipient = recipient.trim();
+    if recipient.starts_with("0x") {
+        let address = checksum_address(recipient)?;
+        let label = contacts.iter().find(|(_, saved)| **saved == address).map(|(label, _)| label.clone());
+        return Ok(Recipient { label, address });
+    }
+    let label = recipient.to_lowercase();
+    match contacts.get(&label) {
+        Some(address) => Ok(Recipient { label: Some(label), address: address.clone() }),
+        None if contacts.is_empty() => anyhow::bail!("Unknown contact '{}', add it with `hl contacts add {} <address>`", recipient, label),
+        None => anyhow::bail!(
+            "Unknown contact '{}', expected an address or one of: {}",
+            recipient,
+            contacts.keys().map(String::as_str).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+// saves `label`, refusing to silently repoint an existing one; returns the checksummed address
+pub fn add(path: &Path, label: &str, address: &str) -> Result<String> {
+    let label = parse_label(label)?;
+    let address = checksum_address(address)?;
+    edit_contacts(path, |contacts| {
+        if let Some(existing) = contacts.get(&label).and_then(Item::as_str) {
+            if checksum_address(existing).ok().as_deref() == Some(address.as_str()) {
+                return Ok(());
+            }
+            anyhow::bail!("Contact '{}' already points at {}, remove it first", label, existing);
+        }
+        contacts.insert(&label, toml_edit::value(address.as_str()));
+        Ok(())
+    })?;
+    Ok(address)
+}
+
+pub fn remove(path: &Path, label: &str) -> Result<()> {
+    let label = parse_label(label)?;
+    edit_contacts(path, |contacts| {
+        contacts.remove(&label).with_context(|| format!("No contact named '{}'", label))?;
+        Ok(())
+    })
+}
+
+fn edit_contacts(path: &Path, edit: impl FnOnce(&mut Table) -> Result<()>) -> Result<()> {
+    loader::edit_config_file(path, |document| {
+        let contacts = document
+            .entry("contacts")
+            .or_insert_with(toml_edit::table)
+            .as_table_mut()
+            .with_context(|| format!("`contacts` in {} is not a table", path.display()))?;
+        edit(contacts)
+    })
+}
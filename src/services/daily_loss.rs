@@ -0,0 +1,80 @@
+// per-utc-day realized loss limit, with the day's state persisted to disk
+use std::{path::Path, sync::Mutex};
+use anyhow::{Context, Result};
+use crate::types::DailyLossState;
+
+pub struct DailyLossGuard {
+    path: String,
+    state: Mutex<DailyLossState>,
+}
+
+impl DailyLossGuard {
+    pub fn load(path: &str) -> Result<Self> {
+        let state = if Path::new(path).exists() {
+            let raw = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read risk state {}", path))?;
+            serde_json::from_str(&raw).with_context(|| format!("Failed to parse risk state {}", path))?
+        } else {
+            DailyLossState::default()
+        };
+
+        Ok(Self {
+            path: path.to_string(),
+            state: Mutex::new(state),
+        })
+    }
+
+    // records today's realized pnl and errors once it is below -limit, unless overridden for today
+    pub fn check(&self, today: &str, realized_pnl: f64, limit: f64) -> Result<()> {
+        {
+            let mut state = self.lock();
+            state.day = today.to_string();
+            state.realized_pnl = realized_pnl;
+            self.save(&state)?;
+        }
+        self.evaluate(today, realized_pnl, limit)
+    }
+
+    // same verdict as check, without touching the state
+    pub fn evaluate(&self, today: &str, realized_pnl: f64, limit: f64) -> Result<()> {
+        if realized_pnl > -limit || self.lock().override_day.as_deref() == Some(today) {
+            return Ok(());
+        }
+
+        anyhow::bail!(
+            "Daily loss limit reached: realized ${:.2} today exceeds -${:.2}; only reduce-only orders are allowed until 00:00 UTC or `hl risk override`",
+            realized_pnl,
+            limit
+        )
+    }
+
+    pub fn set_override(&self, day: Option<&str>) -> Result<()> {
+        let mut state = self.lock();
+        state.override_day = day.map(str::to_string);
+        self.save(&state)
+    }
+
+    pub fn state(&self) -> DailyLossState {
+        self.lock().clone()
+    }
+
+    fn save(&self, state: &DailyLossState) -> Result<()> {
+        std::fs::write(&self.path, serde_json::to_string_pretty(state)?)
+            .with_context(|| format!("Failed to write risk state {}", self.path))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, DailyLossState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// "YYYY-MM-DD" and the millisecond timestamp of 00:00 utc today
+pub fn utc_today() -> (String, u64) {
+    let now = chrono::Utc::now();
+    let midnight = now
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .map(|dt| dt.and_utc().timestamp_millis() as u64)
+        .unwrap_or(0);
+    (now.format("%Y-%m-%d").to_string(), midnight)
+}
@@ -0,0 +1,67 @@
+// dead man's switch: hyperliquid's scheduleCancel cancels every open order at a set time
+// unless it is pushed back, so a client that stops heartbeating can't leave orders behind
+use std::time::Duration;
+use anyhow::{Context, Result};
+use crate::services::TradingService;
+
+// the exchange rejects cancel times less than 5s out
+pub const MIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+// "60s", "5m", "1h" or plain seconds
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => value.split_at(i),
+        None => (value, "s"),
+    };
+    let number: u64 = number
+        .parse()
+        .with_context(|| format!("Invalid duration '{}', expected e.g. 60s or 5m", value))?;
+    let seconds = match unit {
+        "s" => number,
+        "m" => number * 60,
+        "h" => number * 3600,
+        "d" => number * 86_400,
+        _ => anyhow::bail!("Invalid duration '{}', expected e.g. 60s or 5m", value),
+    };
+    Ok(Duration::from_secs(seconds))
+}
+
+pub fn check_timeout(timeout: Duration) -> Result<()> {
+    if timeout < MIN_TIMEOUT {
+        anyhow::bail!("Dead man's switch timeout must be at least {}s", MIN_TIMEOUT.as_secs());
+    }
+    Ok(())
+}
+
+// re-arm well before the deadline so one slow request doesn't trip it
+pub fn heartbeat_interval(timeout: Duration) -> Duration {
+    timeout / 3
+}
+
+// (re)schedules the cancel `timeout` from now, returns the cancel time in ms
+pub async fn arm(trading: &TradingService, timeout: Duration) -> Result<u64> {
+    let cancel_at = chrono::Utc::now().timestamp_millis() as u64 + timeout.as_millis() as u64;
+    trading.schedule_cancel(Some(cancel_at)).await?;
+    Ok(cancel_at)
+}
+
+pub async fn disarm(trading: &TradingService) -> Result<()> {
+    trading.schedule_cancel(None).await
+}
+
+// keeps pushing the cancel back until ctrl-c, then leaves it armed to fire
+pub async fn keepalive(trading: &TradingService, timeout: Duration) -> Result<()> {
+    let mut heartbeat = tokio::time::interval(heartbeat_interval(timeout));
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = heartbeat.tick() => {
+                if let Err(e) = arm(trading, timeout).await {
+                    // the previous deadline still stands, keep trying until it passes
+                    tracing::warn!(error = %e, "dead man's switch heartbeat failed");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+// csv datasets for backtesting that pick up where an earlier run stopped
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::Arc,
+};
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, Utc};
+use tokio::sync::broadcast::error::RecvError;
+use crate::services::{
+    strategy::interval_millis,
+    ws_hub::{self, SubscriptionKey, WsHub},
+    ExchangeService,
+};
+use crate::types::{streaming::TradeData, Candle, FillInfo};
+
+pub const TRADES_HEADER: &str = "time,symbol,side,price,size,trade_id,source";
+pub const CANDLES_HEADER: &str = "open_time,symbol,interval,open,high,low,close,volume";
+// rows per response before userFillsByTime / candleSnapshot need another page
+const FILLS_PAGE: usize = 2000;
+const CANDLES_PAGE: usize = 5000;
+
+#[derive(Debug, Default)]
+pub struct DownloadSummary {
+    pub rows: u64,
+    pub resumed_from: Option<u64>,
+}
+
+// "now", unix ms, 2024-06-01 (midnight utc) or rfc3339
+pub fn parse_time(value: &str) -> Result<u64> {
+    let value = value.trim();
+    if value == "now" {
+        return Ok(Utc::now().timestamp_millis() as u64);
+    }
+    if let Ok(ms) = value.parse::<u64>() {
+        return Ok(ms);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp_millis() as u64);
+    }
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|time| time.timestamp_millis() as u64)
+        .with_context(|| format!("Invalid time '{}', expected YYYY-MM-DD, RFC 3339, unix ms or now", value))
+}
+
+// appends to an existing file with the same header, or starts a new one
+pub struct CsvSink {
+    file: File,
+    // time and trade id of the last row (id 0 for candles)
+    pub last: Option<(u64, u64)>,
+}
+
+impl CsvSink {
+    pub fn open(path: &Path, header: &str) -> Result<Self> {
+        let mut last = None;
+        if path.exists() {
+            let reader = BufReader::new(File::open(path).with_context(|| format!("Failed to read {}", path.display()))?);
+            let mut lines = reader.lines();
+            if let Some(existing) = lines.next().transpose()? {
+                if existing.trim() != header {
+                    anyhow::bail!("{} has a different header, refusing to append (expected {})", path.display(), header);
+                }
+                let last_row = lines.map_while(Result::ok).filter(|line| !line.trim().is_empty()).last();
+                last = last_row.map(|line| row_key(&line)).transpose()?;
+            }
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", header)?;
+        }
+        Ok(Self { file, last })
+    }
+
+    pub fn write(&mut self, row: &str) -> Result<()> {
+        writeln!(self.file, "{}", row).context("Failed to write row")
+    }
+
+    // rows survive a ctrl-c between pages
+    pub fn flush(&mut self) -> Result<()> {
+        self.file.flush().context("Failed to flush output")
+    }
+}
+
+fn row_key(line: &str) -> Result<(u64, u64)> {
+    let columns: Vec<&str> = line.split(',').collect();
+    let time = columns[0].parse().with_context(|| format!("Can't resume from malformed row: {}", line))?;
+    let trade_id = columns.get(5).and_then(|id| id.parse().ok()).unwrap_or(0);
+    Ok((time, trade_id))
+}
+
+pub fn fill_row(fill: &FillInfo) -> String {
+    format!("{},{},{},{},{},{},fill", fill.timestamp, fill.symbol, fill.side, fill.price, fill.size, fill.trade_id)
+}
+
+pub fn trade_row(trade: &TradeData) -> String {
+    let side = if trade.side == "B" { "BUY" } else { "SELL" };
+    format!("{},{},{},{},{},{},live", trade.time, trade.coin, side, trade.px, trade.sz, trade.tid)
+}
+
+pub fn candle_row(candle: &Candle) -> String {
+    format!(
+        "{},{},{},{},{},{},{},{}",
+        candle.open_time, candle.symbol, candle.interval, candle.open, candle.high, candle.low, candle.close, candle.volume
+    )
+}
+
+// rows are written in (time, trade id) order, so anything at or before the last one is a repeat
+fn after(last: Option<(u64, u64)>, time: u64, trade_id: u64) -> bool {
+    last.is_none_or(|last| (time, trade_id) > last)
+}
+
+// the account's own fills from the exchange, then, given a hub, live public trades until `to`
+pub async fn trades(
+    exchange: &ExchangeService,
+    hub: Option<Arc<WsHub>>,
+    symbol: &str,
+    from: u64,
+    to: u64,
+    out: &Path,
+) -> Result<DownloadSummary> {
+    let mut sink = CsvSink::open(out, TRADES_HEADER)?;
+    let mut summary = DownloadSummary { resumed_from: sink.last.map(|(time, _)| time), ..Default::default() };
+    let mut start = summary.resumed_from.unwrap_or(from).max(from);
+
+    loop {
+        let page = exchange.get_fills_between(start, to).await?;
+        let full = page.len() >= FILLS_PAGE;
+        let newest = page.last().map(|fill| fill.timestamp);
+        for fill in page.iter().filter(|f| f.symbol.eq_ignore_ascii_case(symbol)) {
+            if after(sink.last, fill.timestamp, fill.trade_id) {
+                sink.write(&fill_row(fill))?;
+                sink.last = Some((fill.timestamp, fill.trade_id));
+                summary.rows += 1;
+            }
+        }
+        sink.flush()?;
+        // a page ending mid-millisecond is fetched again from that millisecond
+        match newest {
+            Some(newest) if full && newest > start => start = newest,
+            _ => break,
+        }
+    }
+
+    let Some(hub) = hub else {
+        return Ok(summary);
+    };
+    let mut subscription = hub.subscribe_owned(SubscriptionKey::for_coin("trades", symbol));
+    while (Utc::now().timestamp_millis() as u64) < to {
+        let remaining = std::time::Duration::from_millis(to.saturating_sub(Utc::now().timestamp_millis() as u64));
+        let text = match tokio::time::timeout(remaining, subscription.updates.recv()).await {
+            Err(_) => break,
+            Ok(Ok(text)) => text,
+            Ok(Err(RecvError::Lagged(skipped))) => {
+                tracing::warn!(skipped, "download falling behind the live stream, trades dropped");
+                continue;
+            }
+            Ok(Err(RecvError::Closed)) => anyhow::bail!("Upstream stream closed"),
+        };
+        for trade in ws_hub::parse_trades(&text) {
+            if trade.time >= from && trade.time <= to && after(sink.last, trade.time, trade.tid) {
+                sink.write(&trade_row(&trade))?;
+                sink.last = Some((trade.time, trade.tid));
+                summary.rows += 1;
+            }
+        }
+        sink.flush()?;
+    }
+    Ok(summary)
+}
+
+// closed candles only, so a resumed run never has to rewrite a row
+pub async fn candles(
+    exchange: &ExchangeService,
+    symbol: &str,
+    interval: &str,
+    from: u64,
+    to: u64,
+    out: &Path,
+) -> Result<DownloadSummary> {
+    let interval_ms = interval_millis(interval).ok_or_else(|| anyhow::anyhow!("Unsupported candle interval: {}", interval))?;
+    let mut sink = CsvSink::open(out, CANDLES_HEADER)?;
+    let mut summary = DownloadSummary { resumed_from: sink.last.map(|(time, _)| time), ..Default::default() };
+    let mut start = summary.resumed_from.map(|t| t + interval_ms).unwrap_or(from).max(from);
+    let now = Utc::now().timestamp_millis() as u64;
+
+    while start < to {
+        let page = exchange.get_candles(symbol, interval, start, to).await?;
+        let full = page.len() >= CANDLES_PAGE;
+        let closed: Vec<&Candle> = page.iter().filter(|c| c.open_time >= start && c.close_time < now.min(to)).collect();
+        for candle in &closed {
+            sink.write(&candle_row(candle))?;
+            summary.rows += 1;
+        }
+        sink.flush()?;
+        match closed.last() {
+            Some(last) if full => start = last.open_time + interval_ms,
+            _ => break,
+        }
+    }
+    Ok(summary)
+}
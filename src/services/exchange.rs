@@ -1,24 +1,69 @@
+use crate::error::HlError;
 use crate::types::*;
 use anyhow::{Context, Result};
 use alloy::signers::{local::PrivateKeySigner};
 use reqwest::Client;
+use crate::services::{accounts, rate_budget, spot::{self, SpotNames}, symbols::SymbolResolver, withdrawals};
+use crate::services::pool::ClientPool;
+use crate::services::retry::{Idempotency, RetryPolicy};
 
 
 #[derive(Clone)]
 pub struct ExchangeService {
     client: Client,
+    pool: std::sync::Arc<ClientPool>,
+    retry: RetryPolicy,
     config: Config,
 }
 
 impl ExchangeService {
     // client initialization
     pub fn new(config: Config) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .context("Failed to create HTTP client")?;
+        let pool = ClientPool::new(&config)?;
+        Ok(Self::with_pool(config, pool))
+    }
+
+    // shares the pool's http client, for services living side by side (the server, trading)
+    pub fn with_pool(config: Config, pool: std::sync::Arc<ClientPool>) -> Self {
+        Self {
+            client: pool.http().clone(),
+            pool,
+            retry: RetryPolicy::from(&config.http),
+            config,
+        }
+    }
+
+    pub fn pool(&self) -> &std::sync::Arc<ClientPool> {
+        &self.pool
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
 
-        Ok(Self { client, config })
+    // one allMids round trip without retries, for health checks: latency and the server's clock
+    pub async fn ping(&self) -> Result<(std::time::Duration, Option<chrono::DateTime<chrono::Utc>>)> {
+        let request = InfoRequest { request_type: "allMids".to_string(), ..Default::default() };
+        self.pool.budget().acquire(rate_budget::info_weight(&request.request_type)).await;
+        let started = std::time::Instant::now();
+        let response = self
+            .client
+            .post(format!("{}/info", self.config.api_url))
+            .json(&request)
+            .send()
+            .await
+            .context(HlError::NetworkError("Info endpoint unreachable".to_string()))?;
+        let latency = started.elapsed();
+        if !response.status().is_success() {
+            return Err(HlError::NetworkError(format!("Info endpoint returned {}", response.status())).into());
+        }
+        let server_time = response
+            .headers()
+            .get(reqwest::header::DATE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| chrono::DateTime::parse_from_rfc2822(value).ok())
+            .map(|time| time.with_timezone(&chrono::Utc));
+        Ok((latency, server_time))
     }
      
     // get metadata of markets and ctxs
@@ -64,44 +109,47 @@ impl ExchangeService {
     // get balances and positions
     pub async fn get_balances(&self) -> Result<BalanceResponse> {
         let wallet_address = self.get_wallet_address()?;
-        let state = self.get_clearinghouse_state(&wallet_address).await?;
+        self.get_balances_for(&wallet_address).await
+    }
+
+    // several wallets at once, in the order given
+    pub async fn get_balances_many(&self, addresses: &[String]) -> Result<MultiBalanceResponse> {
+        let balances = futures_util::future::try_join_all(addresses.iter().map(|address| async move {
+            let balances = self
+                .get_balances_for(address)
+                .await
+                .with_context(|| format!("Failed to fetch balances for {}", address))?;
+            Ok::<_, anyhow::Error>((address.clone(), balances))
+        }))
+        .await?;
+        Ok(accounts::aggregate(balances))
+    }
 
-        let positions: Vec<PositionInfo> = state
+    pub async fn get_balances_for(&self, address: &str) -> Result<BalanceResponse> {
+        let state = self.get_clearinghouse_state(address).await?;
+        Ok(balances_from_state(&state))
+    }
+
+    // liquidation prices come with the clearinghouse state, not with the balances summary
+    pub async fn get_liquidation_prices(&self) -> Result<Vec<PositionLiquidation>> {
+        let state = self.get_clearinghouse_state(&self.get_wallet_address()?).await?;
+        Ok(state
             .asset_positions
             .iter()
-            .filter(|asset_pos| asset_pos.position.szi.parse::<f64>().unwrap_or(0.0).abs() > 0.0001)
-            .map(|asset_pos| {
-                let pos = &asset_pos.position;
-                PositionInfo {
-                    symbol: pos.coin.clone(),
-                    size: pos.szi.parse().unwrap_or(0.0),
-                    entry_price: pos
-                        .entry_px
-                        .as_ref()
-                        .and_then(|p| p.parse().ok())
-                        .unwrap_or(0.0),
-                    leverage: pos.leverage.value,
-                    unrealized_pnl: pos.unrealized_pnl.parse().unwrap_or(0.0),
-                    position_value: pos.position_value.parse().unwrap_or(0.0),
-                }
+            .map(|asset_pos| &asset_pos.position)
+            .filter(|pos| pos.szi.parse::<f64>().unwrap_or(0.0).abs() > 0.0001)
+            .map(|pos| PositionLiquidation {
+                symbol: pos.coin.clone(),
+                size: pos.szi.parse().unwrap_or(0.0),
+                liquidation_price: pos.liquidation_px.as_ref().and_then(|p| p.parse().ok()),
             })
-            .collect();
-
-        Ok(BalanceResponse {
-            account_value: state.margin_summary.account_value.parse().unwrap_or(0.0),
-            withdrawable: state.withdrawable.parse().unwrap_or(0.0),
-            cross_margin_used: state
-                .cross_margin_used
-                .as_ref()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(0.0),
-            positions,
-        })
+            .collect())
     }
 
     // get spot markets
     pub async fn get_spot_markets(&self) -> Result<SpotResponse> {
         let (spot_meta, spot_contexts) = self.get_spot_meta_and_asset_ctxs().await?;
+        let names = SpotNames::from_meta(&spot_meta);
 
         let tokens: Vec<SpotTokenInfo> = spot_meta
             .tokens
@@ -118,7 +166,7 @@ impl ExchangeService {
             .iter()
             .zip(spot_contexts.iter())
             .map(|(pair, context)| SpotPairInfo {
-                name: pair.name.clone(),
+                name: names.display(&pair.name),
                 mark_price: context
                     .mark_px
                     .as_ref()
@@ -140,22 +188,673 @@ impl ExchangeService {
         Ok(SpotResponse { tokens, pairs })
     }
 
+    // spot token balances of the configured wallet, valued at each token's usdc pair
+    pub async fn get_spot_balances(&self) -> Result<SpotBalancesResponse> {
+        let wallet_address = self.get_wallet_address()?;
+        let (state, (meta, contexts)) = tokio::try_join!(
+            self.post_info::<RawSpotClearinghouseState>("spotClearinghouseState", &wallet_address),
+            self.get_spot_meta_and_asset_ctxs()
+        )?;
+        Ok(spot::balances(&state.balances, &spot::token_prices(&meta, &contexts)))
+    }
+
+    pub async fn spot_names(&self) -> Result<SpotNames> {
+        Ok(SpotNames::from_meta(&self.spot_meta().await?))
+    }
+
+    // the exchange's coin for a symbol filter, so "HYPE/USDC" finds the orders and fills of "@107"
+    async fn spot_coin(&self, symbol: &str) -> Result<String> {
+        if !symbol.contains('/') {
+            return Ok(symbol.to_string());
+        }
+        Ok(self.spot_names().await?.coin(symbol))
+    }
+
+    // replaces "@107" coins with their BASE/QUOTE names; perp-only results never load spot metadata
+    async fn name_spot_coins<'a>(&self, coins: impl IntoIterator<Item = &'a mut String>) -> Result<()> {
+        let coins: Vec<&mut String> = coins.into_iter().filter(|coin| coin.starts_with('@')).collect();
+        if coins.is_empty() {
+            return Ok(());
+        }
+        let names = self.spot_names().await?;
+        for coin in coins {
+            *coin = names.display(coin);
+        }
+        Ok(())
+    }
+
+    // open orders for the configured wallet, optionally filtered by symbol
+    pub async fn get_open_orders(&self, symbol: Option<&str>) -> Result<OrdersResponse> {
+        let wallet_address = self.get_wallet_address()?;
+        let raw_orders: Vec<RawOpenOrder> = self.post_info("openOrders", &wallet_address).await?;
+        let symbol = match symbol {
+            Some(symbol) => Some(self.spot_coin(symbol).await?),
+            None => None,
+        };
+
+        let mut orders: Vec<OpenOrder> = raw_orders
+            .into_iter()
+            .filter(|order| symbol.as_deref().is_none_or(|s| order.coin.eq_ignore_ascii_case(s)))
+            .map(|order| {
+                let remaining: f64 = order.sz.parse().unwrap_or(0.0);
+                let original: f64 = order
+                    .orig_sz
+                    .as_ref()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(remaining);
+                OpenOrder {
+                    order_id: order.oid,
+                    symbol: order.coin,
+                    side: side_name(&order.side).to_string(),
+                    qty: original,
+                    price: order.limit_px.parse().unwrap_or(0.0),
+                    filled_qty: original - remaining,
+                    remaining_qty: remaining,
+                    status: "open".to_string(),
+                    timestamp: order.timestamp,
+                }
+            })
+            .collect();
+        self.name_spot_coins(orders.iter_mut().map(|order| &mut order.symbol)).await?;
+
+        Ok(OrdersResponse {
+            total_orders: orders.len(),
+            orders,
+        })
+    }
+
+    // stops and take profits only; openOrders leaves out the trigger details
+    pub async fn get_trigger_orders(&self, symbol: Option<&str>) -> Result<Vec<TriggerOrder>> {
+        let wallet_address = self.get_wallet_address()?;
+        let raw_orders: Vec<RawFrontendOrder> = self.post_info("frontendOpenOrders", &wallet_address).await?;
+        let symbol = match symbol {
+            Some(symbol) => Some(self.spot_coin(symbol).await?),
+            None => None,
+        };
+
+        let mut orders: Vec<TriggerOrder> = raw_orders
+            .into_iter()
+            .filter(|order| order.is_trigger && symbol.as_deref().is_none_or(|s| order.coin.eq_ignore_ascii_case(s)))
+            .map(|order| TriggerOrder {
+                order_id: order.oid,
+                symbol: order.coin,
+                side: side_name(&order.side).to_string(),
+                size: order.sz.parse().unwrap_or(0.0),
+                trigger_px: order.trigger_px.parse().unwrap_or(0.0),
+                order_type: order.order_type,
+                reduce_only: order.reduce_only,
+            })
+            .collect();
+        self.name_spot_coins(orders.iter_mut().map(|order| &mut order.symbol)).await?;
+        Ok(orders)
+    }
+
+    // most recent fills first, optionally filtered by symbol and capped at limit
+    pub async fn get_fills(&self, symbol: Option<&str>, limit: Option<usize>) -> Result<FillsResponse> {
+        let wallet_address = self.get_wallet_address()?;
+        let mut raw_fills: Vec<UserFill> = self.post_info("userFills", &wallet_address).await?;
+        raw_fills.sort_by_key(|fill| std::cmp::Reverse(fill.time));
+        let symbol = match symbol {
+            Some(symbol) => Some(self.spot_coin(symbol).await?),
+            None => None,
+        };
+
+        let mut fills: Vec<FillInfo> = raw_fills
+            .into_iter()
+            .filter(|fill| symbol.as_deref().is_none_or(|s| fill.coin.eq_ignore_ascii_case(s)))
+            .take(limit.unwrap_or(usize::MAX))
+            .map(to_fill_info)
+            .collect();
+        self.name_spot_coins(fills.iter_mut().map(|fill| &mut fill.symbol)).await?;
+
+        Ok(FillsResponse {
+            total_fills: fills.len(),
+            fills,
+        })
+    }
+
+    // None when the exchange doesn't know the oid
+    pub async fn get_order_status(&self, order: &OrderRef) -> Result<Option<OrderStatusInfo>> {
+        let wallet_address = self.get_wallet_address()?;
+        let response: RawOrderStatusResponse = self
+            .post_info_request(&InfoRequest {
+                request_type: "orderStatus".to_string(),
+                user: Some(wallet_address),
+                oid: Some(order.clone()),
+                ..Default::default()
+            })
+            .await?;
+
+        let Some(entry) = response.order else {
+            return Ok(None);
+        };
+
+        let remaining: f64 = entry.order.sz.parse().unwrap_or(0.0);
+        let original: f64 = entry
+            .order
+            .orig_sz
+            .as_ref()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(remaining);
+
+        let mut fills = self.get_fills(None, None).await?.fills;
+        fills.retain(|fill| fill.order_id == entry.order.oid);
+        fills.reverse();
+        let filled: f64 = fills.iter().map(|f| f.size).sum();
+        let avg_fill_price = (filled > 0.0)
+            .then(|| fills.iter().map(|f| f.price * f.size).sum::<f64>() / filled);
+
+        let mut info = OrderStatusInfo {
+            order: OpenOrder {
+                order_id: entry.order.oid,
+                symbol: entry.order.coin,
+                side: side_name(&entry.order.side).to_string(),
+                qty: original,
+                price: entry.order.limit_px.parse().unwrap_or(0.0),
+                filled_qty: original - remaining,
+                remaining_qty: remaining,
+                status: entry.status,
+                timestamp: entry.order.timestamp,
+            },
+            status_timestamp: entry.status_timestamp,
+            cloid: entry.order.cloid,
+            fills,
+            avg_fill_price,
+        };
+        self.name_spot_coins([&mut info.order.symbol]).await?;
+        Ok(Some(info))
+    }
+
+    // fills at or after start_time (ms), oldest first
+    pub async fn get_fills_since(&self, start_time: u64) -> Result<Vec<FillInfo>> {
+        let wallet_address = self.get_wallet_address()?;
+        let raw_fills: Vec<UserFill> = self
+            .post_info_request(&InfoRequest {
+                request_type: "userFillsByTime".to_string(),
+                user: Some(wallet_address),
+                start_time: Some(start_time),
+                ..Default::default()
+            })
+            .await?;
+
+        let mut fills: Vec<FillInfo> = raw_fills.into_iter().map(to_fill_info).collect();
+        self.name_spot_coins(fills.iter_mut().map(|fill| &mut fill.symbol)).await?;
+        Ok(fills)
+    }
+
+    // one page of fills in [start, end] (ms), at most 2000 and oldest first
+    pub async fn get_fills_between(&self, start_time: u64, end_time: u64) -> Result<Vec<FillInfo>> {
+        let wallet_address = self.get_wallet_address()?;
+        let raw_fills: Vec<UserFill> = self
+            .post_info_request(&InfoRequest {
+                request_type: "userFillsByTime".to_string(),
+                user: Some(wallet_address),
+                start_time: Some(start_time),
+                end_time: Some(end_time),
+                ..Default::default()
+            })
+            .await?;
+
+        let mut fills: Vec<FillInfo> = raw_fills.into_iter().map(to_fill_info).collect();
+        fills.sort_by_key(|fill| (fill.timestamp, fill.trade_id));
+        self.name_spot_coins(fills.iter_mut().map(|fill| &mut fill.symbol)).await?;
+        Ok(fills)
+    }
+
+    // the whole account view in one round of concurrent requests
+    pub async fn get_portfolio(&self, fill_limit: usize, funding_since: u64) -> Result<PortfolioResponse> {
+        let (balances, orders, fills, mut funding) = tokio::try_join!(
+            self.get_balances(),
+            self.get_open_orders(None),
+            self.get_fills(None, Some(fill_limit)),
+            self.get_funding_payments(funding_since),
+        )?;
+        funding.sort_by_key(|payment| std::cmp::Reverse(payment.timestamp));
+
+        Ok(PortfolioResponse {
+            unrealized_pnl: balances.positions.iter().map(|p| p.unrealized_pnl).sum(),
+            funding_total: funding.iter().map(|p| p.usdc).sum(),
+            balances,
+            orders,
+            fills,
+            funding,
+            funding_since,
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        })
+    }
+
+    // funding paid/received since start_time (ms)
+    pub async fn get_funding_payments(&self, start_time: u64) -> Result<Vec<FundingPayment>> {
+        let wallet_address = self.get_wallet_address()?;
+        let entries: Vec<UserFundingEntry> = self
+            .post_info_request(&InfoRequest {
+                request_type: "userFunding".to_string(),
+                user: Some(wallet_address),
+                start_time: Some(start_time),
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(entries
+            .into_iter()
+            .map(|entry| FundingPayment {
+                usdc: entry.delta.usdc.parse().unwrap_or(0.0),
+                position_size: entry.delta.szi.parse().unwrap_or(0.0),
+                funding_rate: entry.delta.funding_rate.parse().unwrap_or(0.0),
+                timestamp: entry.time,
+                symbol: entry.delta.coin,
+            })
+            .collect())
+    }
+
+    // withdrawals requested since start_time (ms), newest first
+    pub async fn get_withdrawals(&self, start_time: u64) -> Result<Vec<Withdrawal>> {
+        let wallet_address = self.get_wallet_address()?;
+        let updates: Vec<RawLedgerUpdate> = self
+            .post_info_request(&InfoRequest {
+                request_type: "userNonFundingLedgerUpdates".to_string(),
+                user: Some(wallet_address),
+                start_time: Some(start_time),
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(withdrawals::from_ledger(&updates, chrono::Utc::now().timestamp_millis() as u64))
+    }
+
+    // ohlcv candles between start and end (ms), oldest first
+    pub async fn get_candles(&self, symbol: &str, interval: &str, start_time: u64, end_time: u64) -> Result<Vec<Candle>> {
+        let raw: Vec<RawCandle> = self
+            .post_info_request(&InfoRequest {
+                request_type: "candleSnapshot".to_string(),
+                req: Some(CandleRequest {
+                    coin: symbol.to_string(),
+                    interval: interval.to_string(),
+                    start_time,
+                    end_time,
+                }),
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(raw.iter().map(RawCandle::to_candle).collect())
+    }
+
+    // mid price of every perp market keyed by symbol
+    pub async fn get_mids(&self) -> Result<std::collections::HashMap<String, f64>> {
+        let mids: std::collections::HashMap<String, String> = self
+            .post_info_request(&InfoRequest {
+                request_type: "allMids".to_string(),
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(mids
+            .into_iter()
+            .filter_map(|(symbol, mid)| mid.parse().ok().map(|mid| (symbol, mid)))
+            .collect())
+    }
+
+    pub async fn get_price(&self, symbol: &str) -> Result<PriceInfo> {
+        let (universe, contexts) = self.get_meta_and_asset_ctxs().await?;
+        let ctx = universe
+            .iter()
+            .position(|asset| asset.name == symbol)
+            .and_then(|i| contexts.get(i))
+            .with_context(|| format!("Unknown symbol {}", symbol))?;
+        let parse = |value: &Option<String>| value.as_deref().and_then(|v| v.parse::<f64>().ok());
+
+        let mut price = PriceInfo {
+            symbol: symbol.to_string(),
+            mid: parse(&ctx.mid_px),
+            oracle: parse(&ctx.oracle_px).unwrap_or(0.0),
+            prev_day: parse(&ctx.prev_day_px).unwrap_or(0.0),
+            premium: parse(&ctx.premium).unwrap_or(0.0),
+            funding: parse(&ctx.funding).unwrap_or(0.0),
+            ..Default::default()
+        };
+        price.set_mark(parse(&ctx.mark_px).unwrap_or(0.0));
+        Ok(price)
+    }
+
+    // effective rates, 14 day volume and the vip tiers, tier 0 being the base schedule
+    pub async fn get_fee_schedule(&self) -> Result<FeeSchedule> {
+        let wallet_address = self.get_wallet_address()?;
+        let raw: RawUserFees = self.post_info("userFees", &wallet_address).await?;
+        let parse = |value: &str| value.parse::<f64>().unwrap_or(0.0);
+
+        let mut tiers = vec![FeeTier {
+            tier: 0,
+            volume_cutoff: 0.0,
+            taker_rate: parse(&raw.fee_schedule.cross),
+            maker_rate: parse(&raw.fee_schedule.add),
+        }];
+        tiers.extend(raw.fee_schedule.tiers.vip.iter().enumerate().map(|(i, tier)| FeeTier {
+            tier: i + 1,
+            volume_cutoff: parse(&tier.ntl_cutoff),
+            taker_rate: parse(&tier.cross),
+            maker_rate: parse(&tier.add),
+        }));
+
+        Ok(FeeSchedule {
+            taker_rate: parse(&raw.user_cross_rate),
+            maker_rate: parse(&raw.user_add_rate),
+            referral_discount: raw.active_referral_discount.as_deref().map(parse).unwrap_or(0.0),
+            volume_14d: raw
+                .daily_user_vlm
+                .iter()
+                .map(|day| parse(&day.user_cross) + parse(&day.user_add))
+                .sum(),
+            tiers,
+        })
+    }
+
+    pub async fn get_referral_status(&self) -> Result<ReferralStatus> {
+        let wallet_address = self.get_wallet_address()?;
+        let raw: RawReferral = self.post_info("referral", &wallet_address).await?;
+        let parse = |value: &str| value.parse::<f64>().unwrap_or(0.0);
+        let data = raw.referrer_state.data;
+
+        Ok(ReferralStatus {
+            referred_by: raw.referred_by.as_ref().map(|r| r.code.clone()),
+            referrer: raw.referred_by.map(|r| r.referrer),
+            cum_volume: parse(&raw.cum_vlm),
+            unclaimed_rewards: parse(&raw.unclaimed_rewards),
+            claimed_rewards: parse(&raw.claimed_rewards),
+            builder_rewards: raw.builder_rewards.as_deref().map(parse).unwrap_or(0.0),
+            own_code: data.as_ref().and_then(|d| d.code.clone()),
+            stage: raw.referrer_state.stage,
+            referred_users: data.map_or(0, |d| d.referral_states.len()),
+        })
+    }
+
+    // highest fee `builder` may charge this account, in tenths of a basis point (0 if never approved)
+    pub async fn get_max_builder_fee(&self, builder: &str) -> Result<u64> {
+        let wallet_address = self.get_wallet_address()?;
+        self.post_info_request(&InfoRequest {
+            request_type: "maxBuilderFee".to_string(),
+            user: Some(wallet_address),
+            builder: Some(builder.to_string()),
+            ..Default::default()
+        })
+        .await
+    }
+
+    // vault summary plus this account's deposit, equity taken from the vault's clearinghouse state
+    pub async fn get_vault(&self, vault_address: &str) -> Result<VaultInfo> {
+        let wallet_address = self.get_wallet_address()?;
+        let vault_address = vault_address.to_lowercase();
+        let details_request = InfoRequest {
+            request_type: "vaultDetails".to_string(),
+            user: Some(wallet_address),
+            vault_address: Some(vault_address.clone()),
+            ..Default::default()
+        };
+        let (details, state) = tokio::try_join!(
+            self.post_info_request::<RawVaultDetails>(&details_request),
+            self.get_clearinghouse_state(&vault_address),
+        )?;
+        let equity: f64 = state.margin_summary.account_value.parse().unwrap_or(0.0);
+        let parse = |value: &str| value.parse::<f64>().unwrap_or(0.0);
+
+        Ok(VaultInfo {
+            my_position: details.follower_state.map(|follower| {
+                let my_equity = parse(&follower.vault_equity);
+                VaultPosition {
+                    equity: my_equity,
+                    share: if equity > 0.0 { my_equity / equity } else { 0.0 },
+                    pnl: parse(&follower.pnl),
+                    all_time_pnl: parse(&follower.all_time_pnl),
+                    days_following: follower.days_following,
+                    lockup_until: follower.lockup_until,
+                }
+            }),
+            name: details.name,
+            address: details.vault_address,
+            leader: details.leader,
+            description: details.description,
+            equity,
+            apr: details.apr,
+            leader_commission: details.leader_commission,
+            max_withdrawable: details.max_withdrawable,
+            closed: details.is_closed,
+            allow_deposits: details.allow_deposits,
+        })
+    }
+
+    pub async fn get_validators(&self) -> Result<Vec<ValidatorInfo>> {
+        let raw: Vec<RawValidatorSummary> = self
+            .post_info_request(&InfoRequest {
+                request_type: "validatorSummaries".to_string(),
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(raw
+            .into_iter()
+            .map(|v| ValidatorInfo {
+                address: v.validator,
+                name: v.name,
+                // stake is reported in 8-decimal wei
+                stake: v.stake as f64 / 1e8,
+                commission: v.commission.parse().unwrap_or(0.0),
+                active: v.is_active,
+                jailed: v.is_jailed,
+            })
+            .collect())
+    }
+
+    // staking balances, delegations (with validator names) and rewards received so far
+    pub async fn get_staking_summary(&self) -> Result<StakingSummary> {
+        let wallet_address = self.get_wallet_address()?;
+        let (summary, delegations, rewards, validators) = tokio::try_join!(
+            self.post_info::<RawDelegatorSummary>("delegatorSummary", &wallet_address),
+            self.post_info::<Vec<RawDelegation>>("delegations", &wallet_address),
+            self.post_info::<Vec<RawDelegatorReward>>("delegatorRewards", &wallet_address),
+            self.get_validators(),
+        )?;
+        let parse = |value: &str| value.parse::<f64>().unwrap_or(0.0);
+
+        Ok(StakingSummary {
+            delegated: parse(&summary.delegated),
+            undelegated: parse(&summary.undelegated),
+            pending_withdrawal: parse(&summary.total_pending_withdrawal),
+            pending_withdrawals: summary.n_pending_withdrawals,
+            delegations: delegations
+                .into_iter()
+                .map(|d| Delegation {
+                    name: validators
+                        .iter()
+                        .find(|v| v.address.eq_ignore_ascii_case(&d.validator))
+                        .map(|v| v.name.clone()),
+                    validator: d.validator,
+                    amount: parse(&d.amount),
+                    locked_until: d.locked_until_timestamp,
+                })
+                .collect(),
+            rewards_total: rewards.iter().map(|r| parse(&r.total_amount)).sum(),
+        })
+    }
+
+    // submits an action signed outside the sdk
+    pub async fn post_exchange(&self, action: &serde_json::Value, nonce: u64, signature: serde_json::Value) -> Result<()> {
+        let action_type = action["type"].as_str().unwrap_or("action").to_string();
+        let body = serde_json::json!({
+            "action": action,
+            "nonce": nonce,
+            "signature": signature,
+        });
+        self.pool.budget().acquire(rate_budget::action_weight(batch_len(action))).await;
+        let reply: RawExchangeReply = serde_json::from_value(self.post_signed(&body).await?)
+            .with_context(|| HlError::ParseError(format!("Failed to parse {} response", action_type)))?;
+
+        if reply.status != "ok" {
+            return Err(HlError::ExchangeRejection(format!("{} rejected: {}", action_type, reply.response)).into());
+        }
+        Ok(())
+    }
+
+    // sends a signed {action, nonce, signature} body as is, the reply is the caller's to read
+    pub async fn post_signed(&self, payload: &serde_json::Value) -> Result<serde_json::Value> {
+        let action_type = payload["action"]["type"].as_str().unwrap_or("action").to_string();
+        self.retry
+            .send(&action_type, Idempotency::NotIdempotent, || {
+                self.client.post(format!("{}/exchange", self.config.api_url)).json(payload)
+            })
+            .await
+            .with_context(|| format!("Failed to send {} action", action_type))?
+            .json()
+            .await
+            .with_context(|| HlError::ParseError(format!("Failed to parse {} response", action_type)))
+    }
+
+    pub async fn get_l2_book(&self, symbol: &str) -> Result<OrderBook> {
+        let book: RawL2Book = self
+            .post_info_request(&InfoRequest {
+                request_type: "l2Book".to_string(),
+                coin: Some(symbol.to_string()),
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(book.into_order_book())
+    }
+
     // Private helper methods
+    // perp universe (names, size decimals, max leverage) from the metadata cache, fetched when stale
+    pub async fn perp_meta(&self) -> Result<Vec<AssetInfo>> {
+        if let Some(universe) = self.pool.meta().perp() {
+            return Ok(universe);
+        }
+        let meta: RawMeta = self
+            .post_info_request(&InfoRequest {
+                request_type: "meta".to_string(),
+                ..Default::default()
+            })
+            .await?;
+        self.pool.meta().store_perp(meta.universe.clone());
+        Ok(meta.universe)
+    }
+
+    pub async fn spot_meta(&self) -> Result<SpotMeta> {
+        if let Some(meta) = self.pool.meta().spot() {
+            return Ok(meta);
+        }
+        let meta: SpotMeta = self
+            .post_info_request(&InfoRequest {
+                request_type: "spotMeta".to_string(),
+                ..Default::default()
+            })
+            .await?;
+        self.pool.meta().store_spot(meta.clone());
+        Ok(meta)
+    }
+
+    // drops the cached metadata and fetches it again, returning (perps, spot pairs)
+    pub async fn refresh_meta(&self) -> Result<(usize, usize)> {
+        self.pool.meta().clear()?;
+        let (perp, spot) = tokio::try_join!(self.perp_meta(), self.spot_meta())?;
+        Ok((perp.len(), spot.universe.len()))
+    }
+
+    // size decimals per perp asset, prices and sizes must respect them
+    pub async fn get_sz_decimals(&self) -> Result<std::collections::HashMap<String, u32>> {
+        Ok(self
+            .perp_meta()
+            .await?
+            .into_iter()
+            .filter(|asset| !asset.is_delisted)
+            .map(|asset| (asset.name, asset.sz_decimals))
+            .collect())
+    }
+
+    // perp asset id (position in the universe), for actions signed outside the sdk
+    pub async fn get_asset_id(&self, symbol: &str) -> Result<u32> {
+        self.perp_meta()
+            .await?
+            .iter()
+            .position(|asset| asset.name == symbol)
+            .map(|i| i as u32)
+            .with_context(|| format!("No perp asset id for {}", symbol))
+    }
+
+    // current leverage and margin mode for a symbol, set even without a position
+    pub async fn get_active_leverage(&self, symbol: &str) -> Result<(u32, MarginMode)> {
+        let user = self.get_wallet_address()?;
+        let data: ActiveAssetData = self
+            .post_info_request(&InfoRequest {
+                request_type: "activeAssetData".to_string(),
+                user: Some(user),
+                coin: Some(symbol.to_string()),
+                ..Default::default()
+            })
+            .await?;
+
+        let mode = MarginMode::parse(&data.leverage.leverage_type)
+            .with_context(|| format!("Unknown margin mode {}", data.leverage.leverage_type))?;
+        Ok((data.leverage.value, mode))
+    }
+
+    // perp and spot metadata from the cache, fetched together when stale
+    pub async fn symbol_resolver(&self) -> Result<SymbolResolver> {
+        let (perps, spot) = tokio::try_join!(self.perp_meta(), self.spot_meta())?;
+        Ok(SymbolResolver::new(perps, spot).with_aliases(self.config.aliases.clone()))
+    }
+
+    // the canonical asset for user input, erroring with a suggestion when nothing matches
+    pub async fn resolve_symbol(&self, symbol: &str) -> Result<ResolvedSymbol> {
+        self.symbol_resolver().await?.resolve(symbol)
+    }
+
+    async fn post_info<T: serde::de::DeserializeOwned>(&self, request_type: &str, user: &str) -> Result<T> {
+        self.post_info_request(&InfoRequest {
+            request_type: request_type.to_string(),
+            user: Some(user.to_string()),
+            ..Default::default()
+        })
+        .await
+    }
+
+    async fn post_info_request<T: serde::de::DeserializeOwned>(&self, request: &InfoRequest) -> Result<T> {
+        let response = self
+            .info(request)
+            .await
+            .with_context(|| format!("Failed to send {} request", request.request_type))?;
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .with_context(|| HlError::ParseError(format!("Failed to parse {} response", request.request_type)))?;
+        if let Some(items) = json.as_array() {
+            self.pool.budget().charge(rate_budget::response_weight(&request.request_type, items.len()));
+        }
+        serde_json::from_value(json).with_context(|| HlError::ParseError(format!("Failed to parse {} response", request.request_type)))
+    }
+
+    // every /info query goes through here, they are all safe to retry
+    async fn info(&self, request: &InfoRequest) -> Result<reqwest::Response> {
+        self.pool.budget().acquire(rate_budget::info_weight(&request.request_type)).await;
+        self.retry
+            .send(&request.request_type, Idempotency::Idempotent, || {
+                self.client.post(format!("{}/info", self.config.api_url)).json(request)
+            })
+            .await
+    }
+
     async fn get_meta_and_asset_ctxs(&self) -> Result<(Vec<AssetInfo>, Vec<AssetContext>)> {
         let request = InfoRequest {
             request_type: "metaAndAssetCtxs".to_string(),
             user: None,
+            ..Default::default()
         };
 
         let response = self
-            .client
-            .post(&format!("{}/info", self.config.api_url))
-            .json(&request)
-            .send()
+            .info(&request)
             .await
             .context("Failed to send metaAndAssetCtxs request")?;
 
-        let json: serde_json::Value = response.json().await.context("Failed to parse response")?;
+        let json: serde_json::Value =
+            response.json().await.context(HlError::ParseError("Failed to parse metaAndAssetCtxs response".to_string()))?;
 
         let array = json.as_array().context("Expected array response")?;
         if array.len() != 2 {
@@ -170,9 +869,13 @@ impl ExchangeService {
             .and_then(|v| v.as_array())
             .context("Expected universe array")?;
 
-        let universe: Vec<AssetInfo> =
-            serde_json::from_value(serde_json::Value::Array(universe_array.clone()))?;
-        let contexts: Vec<AssetContext> = serde_json::from_value(array[1].clone())?;
+        let universe: Vec<AssetInfo> = serde_json::from_value(serde_json::Value::Array(universe_array.clone()))
+            .context(HlError::ParseError("Failed to parse perp universe".to_string()))?;
+        let contexts: Vec<AssetContext> = serde_json::from_value(array[1].clone())
+            .context(HlError::ParseError("Failed to parse perp asset contexts".to_string()))?;
+        if self.pool.meta().perp().is_none() {
+            self.pool.meta().store_perp(universe.clone());
+        }
 
         Ok((universe, contexts))
     }
@@ -181,13 +884,11 @@ impl ExchangeService {
         let request = InfoRequest {
             request_type: "clearinghouseState".to_string(),
             user: Some(user_address.to_string()),
+            ..Default::default()
         };
 
         let response = self
-            .client
-            .post(&format!("{}/info", self.config.api_url))
-            .json(&request)
-            .send()
+            .info(&request)
             .await
             .context("Failed to send clearinghouseState request")?;
 
@@ -211,13 +912,11 @@ impl ExchangeService {
         let request = InfoRequest {
             request_type: "spotMetaAndAssetCtxs".to_string(),
             user: None,
+            ..Default::default()
         };
 
         let response = self
-            .client
-            .post(&format!("{}/info", self.config.api_url))
-            .json(&request)
-            .send()
+            .info(&request)
             .await
             .context("Failed to send request")?;
 
@@ -229,11 +928,14 @@ impl ExchangeService {
 
         let spot_meta: SpotMeta = serde_json::from_value(array[0].clone())?;
         let spot_contexts: Vec<SpotAssetContext> = serde_json::from_value(array[1].clone())?;
+        if self.pool.meta().spot().is_none() {
+            self.pool.meta().store_spot(spot_meta.clone());
+        }
 
         Ok((spot_meta, spot_contexts))
     }
 
-    fn get_wallet_address(&self) -> Result<String> {
+    pub fn get_wallet_address(&self) -> Result<String> {
         let wallet: PrivateKeySigner = self
             .config
             .private_key
@@ -241,4 +943,70 @@ impl ExchangeService {
             .context("Failed to parse private key")?;
         Ok(format!("{:?}", wallet.address()))
     }
-}
\ No newline at end of file
+}
+
+pub fn balances_from_state(state: &ClearinghouseState) -> BalanceResponse {
+    let parse = |value: Option<&String>| value.and_then(|v| v.parse::<f64>().ok()).unwrap_or(0.0);
+    let positions: Vec<PositionInfo> = state
+        .asset_positions
+        .iter()
+        .filter(|asset_pos| asset_pos.position.szi.parse::<f64>().unwrap_or(0.0).abs() > 0.0001)
+        .map(|asset_pos| {
+            let pos = &asset_pos.position;
+            PositionInfo {
+                symbol: pos.coin.clone(),
+                size: pos.szi.parse().unwrap_or(0.0),
+                entry_price: parse(pos.entry_px.as_ref()),
+                leverage: pos.leverage.value,
+                margin_mode: pos.leverage.leverage_type.clone(),
+                unrealized_pnl: pos.unrealized_pnl.parse().unwrap_or(0.0),
+                position_value: pos.position_value.parse().unwrap_or(0.0),
+            }
+        })
+        .collect();
+
+    let account_value = parse(Some(&state.margin_summary.account_value));
+    let maintenance_margin = parse(state.cross_maintenance_margin_used.as_ref());
+    let cross_account_value = state
+        .cross_margin_summary
+        .as_ref()
+        .map_or(account_value, |summary| parse(Some(&summary.account_value)));
+    BalanceResponse {
+        account_value,
+        withdrawable: parse(Some(&state.withdrawable)),
+        cross_margin_used: parse(state.cross_margin_used.as_ref()),
+        maintenance_margin,
+        margin_ratio: if cross_account_value > 0.0 { maintenance_margin / cross_account_value } else { 0.0 },
+        free_collateral: (account_value - parse(state.margin_summary.total_margin_used.as_ref())).max(0.0),
+        positions,
+    }
+}
+
+pub fn to_fill_info(fill: UserFill) -> FillInfo {
+    FillInfo {
+        side: side_name(&fill.side).to_string(),
+        price: fill.px.parse().unwrap_or(0.0),
+        size: fill.sz.parse().unwrap_or(0.0),
+        direction: fill.dir,
+        closed_pnl: fill.closed_pnl.parse().unwrap_or(0.0),
+        fee: fill.fee.parse().unwrap_or(0.0),
+        order_id: fill.oid,
+        trade_id: fill.tid,
+        timestamp: fill.time,
+        symbol: fill.coin,
+        hash: fill.hash,
+    }
+}
+
+// exchange side codes: B = bid/buy, A = ask/sell
+fn side_name(side: &str) -> &'static str {
+    if side == "B" { "BUY" } else { "SELL" }
+}
+
+// orders or cancels batched in one action, what its rate-limit weight scales with
+fn batch_len(action: &serde_json::Value) -> usize {
+    ["orders", "cancels", "modifies"]
+        .iter()
+        .find_map(|key| action.get(key).and_then(|v| v.as_array()).map(Vec::len))
+        .unwrap_or(1)
+}
@@ -0,0 +1,83 @@
+// post-trade execution quality: what the fills paid against the mid at arrival and the market's vwap
+use std::collections::HashSet;
+use anyhow::Result;
+use crate::services::ExchangeService;
+use crate::types::{Candle, ExecutionReport, FillInfo};
+
+// fills can reach the info api a moment after the order response
+pub const SETTLE_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+// signed so that a positive number is always a cost
+pub fn cost_bps(price: f64, reference: f64, is_buy: bool) -> f64 {
+    let difference = if is_buy { price - reference } else { reference - price };
+    difference / reference * 10_000.0
+}
+
+// typical price of each 1m candle overlapping [start, end], weighted by its volume
+pub fn interval_vwap(candles: &[Candle], start: u64, end: u64) -> Option<f64> {
+    let overlapping = candles.iter().filter(|c| c.open_time <= end && c.close_time >= start);
+    let (notional, volume) = overlapping.fold((0.0, 0.0), |(notional, volume), c| {
+        (notional + (c.high + c.low + c.close) / 3.0 * c.volume, volume + c.volume)
+    });
+    (volume > 0.0).then(|| notional / volume)
+}
+
+// None when nothing filled
+#[allow(clippy::too_many_arguments)]
+pub fn build(
+    symbol: &str,
+    is_buy: bool,
+    requested_qty: f64,
+    arrival_mid: f64,
+    fills: &[FillInfo],
+    candles: &[Candle],
+    started: u64,
+    finished: u64,
+) -> Option<ExecutionReport> {
+    let filled_qty: f64 = fills.iter().map(|f| f.size).sum();
+    if filled_qty <= 0.0 || arrival_mid <= 0.0 {
+        return None;
+    }
+    let avg_price = fills.iter().map(|f| f.price * f.size).sum::<f64>() / filled_qty;
+    let last_fill = fills.iter().map(|f| f.timestamp).max().unwrap_or(finished);
+    let interval_vwap = interval_vwap(candles, started, last_fill);
+    Some(ExecutionReport {
+        started,
+        finished,
+        symbol: symbol.to_string(),
+        side: if is_buy { "BUY" } else { "SELL" }.to_string(),
+        requested_qty,
+        filled_qty,
+        fills: fills.len(),
+        avg_price,
+        arrival_mid,
+        interval_vwap,
+        fees: fills.iter().map(|f| f.fee).sum(),
+        shortfall_bps: cost_bps(avg_price, arrival_mid, is_buy),
+        vs_vwap_bps: interval_vwap.map(|vwap| cost_bps(avg_price, vwap, is_buy)),
+    })
+}
+
+// the fills of `order_ids` since `started`, measured against `arrival_mid`
+pub async fn collect(
+    exchange: &ExchangeService,
+    symbol: &str,
+    is_buy: bool,
+    requested_qty: f64,
+    arrival_mid: f64,
+    order_ids: &[u64],
+    started: u64,
+) -> Result<Option<ExecutionReport>> {
+    tokio::time::sleep(SETTLE_DELAY).await;
+    let finished = chrono::Utc::now().timestamp_millis() as u64;
+    let ids: HashSet<u64> = order_ids.iter().copied().collect();
+    let fills: Vec<FillInfo> = exchange
+        .get_fills_between(started, finished)
+        .await?
+        .into_iter()
+        .filter(|f| ids.contains(&f.order_id))
+        .collect();
+    // a minute either side so the candles holding the first and last fill are included
+    let candles = exchange.get_candles(symbol, "1m", started.saturating_sub(60_000), finished + 60_000).await?;
+    Ok(build(symbol, is_buy, requested_qty, arrival_mid, &fills, &candles, started, finished))
+}
@@ -0,0 +1,37 @@
+// fee tier progress and fees paid from fills
+use crate::types::{FeeSchedule, FeeSummary, FeeTier, FillInfo};
+
+// highest tier whose volume cutoff the 14 day volume has reached
+pub fn current_tier(tiers: &[FeeTier], volume_14d: f64) -> usize {
+    tiers
+        .iter()
+        .rposition(|tier| volume_14d >= tier.volume_cutoff)
+        .unwrap_or(0)
+}
+
+pub fn summarize(schedule: FeeSchedule, fills: &[FillInfo], window_start: u64) -> FeeSummary {
+    let index = current_tier(&schedule.tiers, schedule.volume_14d);
+    let tier = schedule.tiers.get(index).cloned().unwrap_or(FeeTier {
+        tier: 0,
+        volume_cutoff: 0.0,
+        taker_rate: schedule.taker_rate,
+        maker_rate: schedule.maker_rate,
+    });
+    let next_tier = schedule.tiers.get(index + 1).cloned();
+    let volume_to_next = next_tier
+        .as_ref()
+        .map(|next| (next.volume_cutoff - schedule.volume_14d).max(0.0));
+
+    let fills: Vec<&FillInfo> = fills.iter().filter(|fill| fill.timestamp >= window_start).collect();
+    FeeSummary {
+        tier,
+        next_tier,
+        volume_to_next,
+        window_start,
+        window_fills: fills.len(),
+        window_notional: fills.iter().map(|fill| fill.price * fill.size).sum(),
+        // negative fees are maker rebates
+        window_fees: fills.iter().map(|fill| fill.fee).sum(),
+        schedule,
+    }
+}
@@ -0,0 +1,146 @@
+// drawdown circuit breaker: flattens the account and locks trading when equity drops too far
+use std::{path::Path, time::Duration};
+use anyhow::{Context, Result};
+use crate::{
+    services::{kill_switch, ExchangeService, Notifier, TradingService},
+    types::{Config, NotificationEvent, PanicReport, TradingLock},
+};
+
+// tracks the session high and the current drawdown from it
+#[derive(Debug, Default)]
+pub struct DrawdownTracker {
+    high: f64,
+}
+
+impl DrawdownTracker {
+    // picks up from a high recorded before this session
+    pub fn with_high(high: f64) -> Self {
+        Self { high }
+    }
+
+    // returns the drawdown from the session high as a fraction (0.1 = 10%)
+    pub fn update(&mut self, equity: f64) -> f64 {
+        self.high = self.high.max(equity);
+        if self.high <= 0.0 {
+            return 0.0;
+        }
+        ((self.high - equity) / self.high).max(0.0)
+    }
+
+    pub fn high(&self) -> f64 {
+        self.high
+    }
+}
+
+// highest recorded account value at or after `since`, from the journal's equity snapshots
+pub fn equity_high(equity: &[(u64, f64)], since: u64) -> Option<f64> {
+    equity.iter().filter(|(time, _)| *time >= since).map(|(_, value)| *value).reduce(f64::max)
+}
+
+// "10%" or "0.1" -> 0.1
+pub fn parse_fraction(value: &str) -> Result<f64> {
+    let value = value.trim();
+    let fraction = match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map(|p| p / 100.0),
+        None => value.parse::<f64>(),
+    }
+    .with_context(|| format!("Invalid percentage '{}', expected e.g. 10% or 0.1", value))?;
+
+    if !(fraction > 0.0 && fraction < 1.0) {
+        anyhow::bail!("Percentage must be between 0% and 100%, got {}", value);
+    }
+    Ok(fraction)
+}
+
+pub fn read_lock(path: &str) -> Result<Option<TradingLock>> {
+    if !Path::new(path).exists() {
+        return Ok(None);
+    }
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read trading lock {}", path))?;
+    // a hand-made or truncated lock file still locks
+    Ok(Some(serde_json::from_str(&raw).unwrap_or_else(|_| TradingLock {
+        reason: raw.trim().to_string(),
+        locked_at: "unknown".to_string(),
+    })))
+}
+
+pub fn write_lock(path: &str, reason: &str) -> Result<()> {
+    let lock = TradingLock {
+        reason: reason.to_string(),
+        locked_at: chrono::Utc::now().to_rfc3339(),
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&lock)?)
+        .with_context(|| format!("Failed to write trading lock {}", path))
+}
+
+pub fn clear_lock(path: &str) -> Result<bool> {
+    if !Path::new(path).exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(path).with_context(|| format!("Failed to remove trading lock {}", path))?;
+    Ok(true)
+}
+
+// polls account value until the drawdown limit trips, then locks, flattens and notifies
+pub async fn run(
+    config: &Config,
+    trading: &TradingService,
+    exchange: &ExchangeService,
+    max_drawdown: f64,
+    poll_interval: Duration,
+    starting_high: Option<f64>,
+) -> Result<PanicReport> {
+    if trading.is_paper() {
+        anyhow::bail!("guard watches the live account and can't run in paper mode");
+    }
+    if let Some(lock) = read_lock(&config.lock_path)? {
+        anyhow::bail!("Trading is already locked ({}); run `hl risk unlock` first", lock.reason);
+    }
+
+    let notifier = Notifier::new(config.notifications.clone(), &config.http);
+    let mut tracker = starting_high.map(DrawdownTracker::with_high).unwrap_or_default();
+    let mut interval = tokio::time::interval(poll_interval);
+
+    loop {
+        interval.tick().await;
+
+        let equity = match exchange.get_balances().await {
+            Ok(balances) => balances.account_value,
+            Err(e) => {
+                // a missed poll is not a reason to flatten
+                tracing::warn!(error = %e, "guard: failed to fetch account value");
+                continue;
+            }
+        };
+
+        let drawdown = tracker.update(equity);
+        tracing::info!(equity, high = tracker.high(), drawdown = drawdown * 100.0, "guard: account value");
+
+        if drawdown >= max_drawdown {
+            let reason = format!(
+                "drawdown {:.2}% from session high ${:.2} (equity ${:.2}) breached {:.2}%",
+                drawdown * 100.0,
+                tracker.high(),
+                equity,
+                max_drawdown * 100.0
+            );
+            tracing::error!(%reason, "guard: circuit breaker tripped");
+
+            // lock before flattening so nothing new gets in while we close
+            if !trading.is_dry_run() {
+                write_lock(&config.lock_path, &reason)?;
+            }
+            let report = kill_switch::panic(trading, exchange).await?;
+
+            notifier
+                .notify(NotificationEvent::RiskViolation {
+                    symbol: "ACCOUNT".to_string(),
+                    reason: format!("Circuit breaker: {}; trading locked", reason),
+                })
+                .await;
+
+            return Ok(report);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+// deep health: the upstream rest and websocket endpoints, the wallet key and our clock;
+// readiness for probes: warmed up and upstream recently reachable
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use reqwest::Url;
+use tokio_tungstenite::tungstenite::Message;
+use crate::services::{net, ExchangeService};
+use crate::types::{DeepHealthResponse, HealthCheck, HttpConfig, ReadinessResponse};
+
+// the Date header only has whole seconds, so anything under this is noise
+pub const MAX_CLOCK_SKEW_MS: i64 = 2_000;
+// a successful upstream call is trusted this long, so probes don't each hit the exchange
+const UPSTREAM_OK_FOR: Duration = Duration::from_secs(15);
+const WARM_UP_RETRY: Duration = Duration::from_secs(5);
+const SUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// the server exists only once the config loaded, so that part of readiness is a given
+#[derive(Default)]
+pub struct Readiness {
+    metadata: AtomicBool,
+    upstream_ok_at: Mutex<Option<Instant>>,
+}
+
+impl Readiness {
+    // fetches perp and spot metadata into the cache, retrying until it works; it may come from
+    // the disk cache, so it says nothing about the upstream
+    pub async fn warm_up(&self, exchange: &ExchangeService) {
+        loop {
+            match tokio::try_join!(exchange.perp_meta(), exchange.spot_meta()) {
+                Ok(_) => {
+                    self.metadata.store(true, Ordering::Relaxed);
+                    tracing::info!("metadata cached, server ready");
+                    return;
+                }
+                Err(e) => {
+                    tracing::warn!(error = %format!("{:#}", e), "metadata warm-up failed, retrying");
+                    tokio::time::sleep(WARM_UP_RETRY).await;
+                }
+            }
+        }
+    }
+
+    pub async fn check(&self, exchange: &ExchangeService) -> ReadinessResponse {
+        let metadata = self.metadata.load(Ordering::Relaxed);
+        let recent = self.lock().is_some_and(|at| at.elapsed() < UPSTREAM_OK_FOR);
+        let upstream = recent || match exchange.ping().await {
+            Ok(_) => {
+                self.upstream_ok(Instant::now());
+                true
+            }
+            Err(e) => {
+                tracing::debug!(error = %e, "readiness: upstream unreachable");
+                false
+            }
+        };
+        ReadinessResponse { ready: metadata && upstream, config: true, upstream, metadata }
+    }
+
+    fn upstream_ok(&self, at: Instant) {
+        *self.lock() = Some(at);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Instant>> {
+        self.upstream_ok_at.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+pub async fn deep_check(exchange: &ExchangeService) -> DeepHealthResponse {
+    let config = exchange.config();
+    let (rest, websocket) = tokio::join!(exchange.ping(), websocket_check(&config.ws_url, &config.http));
+
+    let (rest, clock) = match rest {
+        Ok((latency, server_time)) => (passed("rest", Some(latency), None), clock_check(Utc::now(), server_time)),
+        Err(e) => (failed("rest", format!("{:#}", e)), failed("clock_skew", "exchange time unavailable".to_string())),
+    };
+    let wallet = match exchange.get_wallet_address() {
+        Ok(_) => passed("wallet", None, None),
+        Err(e) => failed("wallet", format!("{:#}", e)),
+    };
+
+    let checks = vec![rest, websocket, wallet, clock];
+    DeepHealthResponse {
+        status: overall(&checks).to_string(),
+        timestamp: Utc::now().timestamp() as u64,
+        checks,
+    }
+}
+
+pub fn overall(checks: &[HealthCheck]) -> &'static str {
+    if checks.iter().all(|check| check.status == "ok") { "healthy" } else { "degraded" }
+}
+
+// local clock against the exchange's Date header
+pub fn clock_check(local: DateTime<Utc>, server: Option<DateTime<Utc>>) -> HealthCheck {
+    let Some(server) = server else {
+        return failed("clock_skew", "no Date header from the exchange".to_string());
+    };
+    let skew_ms = (local - server).num_milliseconds();
+    let detail = format!("local clock {:+}ms vs exchange", skew_ms);
+    if skew_ms.abs() > MAX_CLOCK_SKEW_MS {
+        failed("clock_skew", detail)
+    } else {
+        passed("clock_skew", None, Some(detail))
+    }
+}
+
+// a fresh connection, not the server's shared one, so a wedged hub doesn't hide an outage
+async fn websocket_check(ws_url: &str, http: &HttpConfig) -> HealthCheck {
+    let started = Instant::now();
+    match net::connect_ws(ws_url, http).await {
+        Ok(mut stream) => {
+            let latency = started.elapsed();
+            let _ = stream.close(None).await;
+            passed("websocket", Some(latency), None)
+        }
+        Err(e) => failed("websocket", format!("{:#}", e)),
+    }
+}
+
+// `hl doctor`: the deep checks broken into the steps a failing setup gets stuck at, plus the
+// account itself; run in order, each one whatever happened before it
+pub async fn doctor(exchange: &ExchangeService) -> Vec<HealthCheck> {
+    let config = exchange.config();
+    let mut checks = vec![dns_check(&config.api_url, &config.http).await, connect_check(&config.api_url, &config.http).await];
+    match exchange.ping().await {
+        Ok((latency, server_time)) => {
+            checks.push(passed("rest", Some(latency), Some("allMids round trip".to_string())));
+            checks.push(clock_check(Utc::now(), server_time));
+        }
+        Err(e) => {
+            checks.push(failed("rest", brief(&e)));
+            checks.push(failed("clock_skew", "exchange time unavailable".to_string()));
+        }
+    }
+    checks.push(subscribe_check(&config.ws_url, &config.http).await);
+    let wallet = exchange.get_wallet_address();
+    checks.push(match &wallet {
+        Ok(address) => passed("wallet", None, Some(address.clone())),
+        Err(e) => failed("wallet", brief(e)),
+    });
+    if wallet.is_ok() {
+        checks.push(collateral_check(exchange).await);
+    }
+    checks
+}
+
+// with a proxy it's the proxy's name that has to resolve here
+pub async fn dns_check(api_url: &str, http: &HttpConfig) -> HealthCheck {
+    let target = http.proxy.as_deref().unwrap_or(api_url);
+    let Some((host, port)) = Url::parse(target).ok().and_then(|url| Some((url.host_str()?.to_string(), url.port_or_known_default()?))) else {
+        return failed("dns", format!("no host in {}", target));
+    };
+    let started = Instant::now();
+    match tokio::net::lookup_host((host.as_str(), port)).await {
+        Ok(addresses) => {
+            let addresses: Vec<String> = addresses.map(|address| address.ip().to_string()).collect();
+            passed("dns", Some(started.elapsed()), Some(format!("{} -> {}", host, addresses.join(", "))))
+        }
+        Err(e) => failed("dns", format!("{} doesn't resolve: {}", host, e)),
+    }
+}
+
+// a fresh client, so this measures tcp + tls from scratch; any http status means the handshake worked
+pub async fn connect_check(api_url: &str, http: &HttpConfig) -> HealthCheck {
+    let name = if api_url.starts_with("https://") { "tls" } else { "connect" };
+    let client = match net::http_client(http) {
+        Ok(client) => client,
+        Err(e) => return failed(name, brief(&e)),
+    };
+    let started = Instant::now();
+    match client.get(api_url).send().await {
+        Ok(response) => passed(name, Some(started.elapsed()), Some(format!("{} answered {}", api_url, response.status()))),
+        Err(e) => {
+            let kind = if e.is_timeout() { "timed out" } else if e.is_connect() { "couldn't connect" } else { "failed" };
+            let cause = std::error::Error::source(&e).map(|source| format!(": {}", source)).unwrap_or_default();
+            failed(name, format!("{} {}{}", api_url, kind, cause))
+        }
+    }
+}
+
+// connect, subscribe to allMids and wait for the exchange to acknowledge it
+async fn subscribe_check(ws_url: &str, http: &HttpConfig) -> HealthCheck {
+    let started = Instant::now();
+    let round_trip = async {
+        let mut stream = net::connect_ws(ws_url, http).await?;
+        let subscribe = serde_json::json!({ "method": "subscribe", "subscription": { "type": "allMids" } });
+        stream.send(Message::Text(subscribe.to_string())).await?;
+        while let Some(message) = stream.next().await {
+            if let Message::Text(text) = message?
+                && text.contains("\"subscriptionResponse\"")
+            {
+                let _ = stream.close(None).await;
+                return Ok(());
+            }
+        }
+        anyhow::bail!("Stream closed before the subscription was acknowledged")
+    };
+    match tokio::time::timeout(SUBSCRIBE_TIMEOUT, round_trip).await {
+        Ok(Ok(())) => passed("websocket", Some(started.elapsed()), Some("connected and subscribed".to_string())),
+        Ok(Err(e)) => failed("websocket", brief(&e)),
+        Err(_) => failed("websocket", format!("no subscription response within {}s", SUBSCRIBE_TIMEOUT.as_secs())),
+    }
+}
+
+// perp account value or spot usdc, either can margin orders
+async fn collateral_check(exchange: &ExchangeService) -> HealthCheck {
+    let (perp, spot) = tokio::join!(exchange.get_balances(), exchange.get_spot_balances());
+    let perp = match perp {
+        Ok(balances) => balances.account_value,
+        Err(e) => return failed("collateral", brief(&e)),
+    };
+    let spot_usdc = spot
+        .map(|spot| spot.balances.iter().filter(|b| b.token == "USDC").map(|b| b.total).sum::<f64>())
+        .unwrap_or(0.0);
+    let detail = format!("${:.2} perp account value, ${:.2} spot USDC on {}", perp, spot_usdc, exchange.config().network());
+    if perp > 0.0 || spot_usdc > 0.0 {
+        passed("collateral", None, Some(detail))
+    } else {
+        failed("collateral", format!("{}; deposit first, or check the network with `hl env show`", detail))
+    }
+}
+
+// the outermost context and the root cause; the chain in between repeats itself a lot
+fn brief(e: &anyhow::Error) -> String {
+    let root = e.root_cause().to_string();
+    let top = e.to_string();
+    if top == root { top } else { format!("{}: {}", top, root) }
+}
+
+fn passed(name: &str, latency: Option<Duration>, detail: Option<String>) -> HealthCheck {
+    HealthCheck {
+        name: name.to_string(),
+        status: "ok".to_string(),
+        latency_ms: latency.map(|l| l.as_millis() as u64),
+        detail,
+    }
+}
+
+fn failed(name: &str, detail: String) -> HealthCheck {
+    HealthCheck { name: name.to_string(), status: "fail".to_string(), latency_ms: None, detail: Some(detail) }
+}
@@ -0,0 +1,43 @@
+// pre-trade impact: walks the opposite side of the book the way a market order would fill
+use anyhow::Result;
+use crate::types::{ImpactEstimate, OrderBook};
+
+pub fn estimate(book: &OrderBook, qty: f64, is_buy: bool) -> Result<ImpactEstimate> {
+    if qty <= 0.0 {
+        anyhow::bail!("Quantity must be greater than 0");
+    }
+    let mid = book
+        .mid()
+        .ok_or_else(|| anyhow::anyhow!("{} book is empty on one side", book.symbol))?;
+    let levels = if is_buy { &book.asks } else { &book.bids };
+
+    let mut remaining = qty;
+    let mut cost = 0.0;
+    let mut worst_price = mid;
+    let mut levels_used = 0;
+    for level in levels {
+        if remaining <= 0.0 {
+            break;
+        }
+        let take = remaining.min(level.size);
+        cost += take * level.price;
+        remaining -= take;
+        worst_price = level.price;
+        levels_used += 1;
+    }
+
+    let fillable_qty = qty - remaining.max(0.0);
+    let avg_price = if fillable_qty > 0.0 { cost / fillable_qty } else { mid };
+
+    Ok(ImpactEstimate {
+        symbol: book.symbol.clone(),
+        is_buy,
+        qty,
+        mid,
+        avg_price,
+        worst_price,
+        slippage: (avg_price - mid).abs() / mid,
+        fillable_qty,
+        levels_used,
+    })
+}
@@ -0,0 +1,229 @@
+// technical indicators over candles, oldest first; used by `hl ta` and available to strategies
+use std::fmt;
+use anyhow::Result;
+use serde::Serialize;
+use crate::types::Candle;
+
+const RSI_OVERBOUGHT: f64 = 70.0;
+const RSI_OVERSOLD: f64 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indicator {
+    Sma(usize),
+    Ema(usize),
+    Rsi(usize),
+    Atr(usize),
+    // 12/26/9
+    Macd,
+    // period, 2 standard deviations
+    Bollinger(usize),
+}
+
+impl Indicator {
+    // "rsi", "rsi7", "ema20", "sma50", "atr", "macd", "bb", "bb20"
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim().to_ascii_lowercase();
+        let split = spec.find(|c: char| c.is_ascii_digit()).unwrap_or(spec.len());
+        let (name, period) = spec.split_at(split);
+        let period = match period {
+            "" => None,
+            digits => match digits.parse::<usize>() {
+                Ok(period) if period > 0 => Some(period),
+                _ => anyhow::bail!("Invalid period in indicator '{}'", spec),
+            },
+        };
+        let indicator = match (name, period) {
+            ("sma", Some(period)) => Indicator::Sma(period),
+            ("ema", Some(period)) => Indicator::Ema(period),
+            ("rsi", period) => Indicator::Rsi(period.unwrap_or(14)),
+            ("atr", period) => Indicator::Atr(period.unwrap_or(14)),
+            ("macd", None) => Indicator::Macd,
+            ("bb", period) => Indicator::Bollinger(period.unwrap_or(20)),
+            ("sma" | "ema", None) => anyhow::bail!("'{}' needs a period, e.g. {}20", spec, spec),
+            _ => anyhow::bail!("Unknown indicator '{}', expected sma<n>, ema<n>, rsi, atr, macd or bb", spec),
+        };
+        Ok(indicator)
+    }
+
+    // candles needed before the first value
+    pub fn lookback(&self) -> usize {
+        match self {
+            Indicator::Sma(period) | Indicator::Ema(period) | Indicator::Bollinger(period) => *period,
+            Indicator::Rsi(period) | Indicator::Atr(period) => period + 1,
+            Indicator::Macd => 26 + 9,
+        }
+    }
+
+    pub fn evaluate(&self, candles: &[Candle]) -> Option<Reading> {
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+        let price = *closes.last()?;
+        let (values, signal) = match *self {
+            Indicator::Sma(period) | Indicator::Ema(period) => {
+                let average = match self {
+                    Indicator::Sma(_) => sma(&closes, period)?,
+                    _ => ema(&closes, period)?,
+                };
+                (vec![("value", average)], Some(if price >= average { "price above" } else { "price below" }))
+            }
+            Indicator::Rsi(period) => {
+                let rsi = rsi(&closes, period)?;
+                let signal = if rsi >= RSI_OVERBOUGHT {
+                    "overbought"
+                } else if rsi <= RSI_OVERSOLD {
+                    "oversold"
+                } else {
+                    "neutral"
+                };
+                (vec![("value", rsi)], Some(signal))
+            }
+            Indicator::Atr(period) => {
+                let atr = atr(candles, period)?;
+                (vec![("value", atr), ("pct_of_price", atr / price * 100.0)], None)
+            }
+            Indicator::Macd => {
+                let macd = macd(&closes)?;
+                let signal = if macd.histogram >= 0.0 { "bullish" } else { "bearish" };
+                (vec![("macd", macd.line), ("signal", macd.signal), ("histogram", macd.histogram)], Some(signal))
+            }
+            Indicator::Bollinger(period) => {
+                let bands = bollinger(&closes, period, 2.0)?;
+                let signal = if price > bands.upper {
+                    "above upper band"
+                } else if price < bands.lower {
+                    "below lower band"
+                } else {
+                    "inside bands"
+                };
+                (vec![("upper", bands.upper), ("middle", bands.middle), ("lower", bands.lower)], Some(signal))
+            }
+        };
+        Some(Reading {
+            indicator: self.to_string(),
+            values: values.into_iter().map(|(name, value)| NamedValue { name: name.to_string(), value }).collect(),
+            signal: signal.map(str::to_string),
+        })
+    }
+}
+
+impl fmt::Display for Indicator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Indicator::Sma(period) => write!(f, "sma{}", period),
+            Indicator::Ema(period) => write!(f, "ema{}", period),
+            Indicator::Rsi(period) => write!(f, "rsi{}", period),
+            Indicator::Atr(period) => write!(f, "atr{}", period),
+            Indicator::Macd => write!(f, "macd"),
+            Indicator::Bollinger(period) => write!(f, "bb{}", period),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Reading {
+    pub indicator: String,
+    pub values: Vec<NamedValue>,
+    pub signal: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NamedValue {
+    pub name: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Macd {
+    pub line: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bands {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+// mean of the last `period` values
+pub fn sma(values: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || values.len() < period {
+        return None;
+    }
+    Some(values[values.len() - period..].iter().sum::<f64>() / period as f64)
+}
+
+// seeded with the sma of the first `period` values, one output per value from there on
+pub fn ema_series(values: &[f64], period: usize) -> Vec<f64> {
+    let Some(seed) = sma(&values[..period.min(values.len())], period) else {
+        return Vec::new();
+    };
+    let k = 2.0 / (period as f64 + 1.0);
+    let mut series = vec![seed];
+    for value in &values[period..] {
+        let previous = series[series.len() - 1];
+        series.push(value * k + previous * (1.0 - k));
+    }
+    series
+}
+
+pub fn ema(values: &[f64], period: usize) -> Option<f64> {
+    ema_series(values, period).last().copied()
+}
+
+// wilder's rsi: gains and losses smoothed with 1/period
+pub fn rsi(closes: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || closes.len() <= period {
+        return None;
+    }
+    let changes: Vec<f64> = closes.windows(2).map(|w| w[1] - w[0]).collect();
+    let mut gain = changes[..period].iter().map(|c| c.max(0.0)).sum::<f64>() / period as f64;
+    let mut loss = changes[..period].iter().map(|c| (-c).max(0.0)).sum::<f64>() / period as f64;
+    for change in &changes[period..] {
+        gain = (gain * (period - 1) as f64 + change.max(0.0)) / period as f64;
+        loss = (loss * (period - 1) as f64 + (-change).max(0.0)) / period as f64;
+    }
+    if loss == 0.0 {
+        return Some(if gain == 0.0 { 50.0 } else { 100.0 });
+    }
+    Some(100.0 - 100.0 / (1.0 + gain / loss))
+}
+
+// wilder's average true range
+pub fn atr(candles: &[Candle], period: usize) -> Option<f64> {
+    if period == 0 || candles.len() <= period {
+        return None;
+    }
+    let ranges: Vec<f64> = candles
+        .windows(2)
+        .map(|w| {
+            let (previous_close, c) = (w[0].close, &w[1]);
+            (c.high - c.low).max((c.high - previous_close).abs()).max((c.low - previous_close).abs())
+        })
+        .collect();
+    let mut atr = ranges[..period].iter().sum::<f64>() / period as f64;
+    for range in &ranges[period..] {
+        atr = (atr * (period - 1) as f64 + range) / period as f64;
+    }
+    Some(atr)
+}
+
+pub fn macd(closes: &[f64]) -> Option<Macd> {
+    let (fast, slow) = (ema_series(closes, 12), ema_series(closes, 26));
+    if slow.is_empty() {
+        return None;
+    }
+    // both series end on the last close, so line up their tails
+    let line: Vec<f64> = fast[fast.len() - slow.len()..].iter().zip(&slow).map(|(f, s)| f - s).collect();
+    let signal = ema(&line, 9)?;
+    let last = line[line.len() - 1];
+    Some(Macd { line: last, signal, histogram: last - signal })
+}
+
+// population standard deviation over the window
+pub fn bollinger(closes: &[f64], period: usize, width: f64) -> Option<Bands> {
+    let middle = sma(closes, period)?;
+    let window = &closes[closes.len() - period..];
+    let deviation = (window.iter().map(|c| (c - middle).powi(2)).sum::<f64>() / period as f64).sqrt();
+    Some(Bands { upper: middle + width * deviation, middle, lower: middle - width * deviation })
+}
@@ -0,0 +1,175 @@
+// line-protocol sink: market, account and position points posted to influxdb (or anything that
+// takes line protocol over http) every interval
+use std::time::Duration;
+use anyhow::{Context, Result};
+use reqwest::Client;
+use crate::services::{
+    net,
+    retry::{Idempotency, RetryPolicy},
+    ExchangeService,
+};
+use crate::types::{BalanceResponse, HttpConfig, InfluxConfig, MarketInfo};
+
+// lines kept for the next write while the endpoint is down, oldest dropped first
+const MAX_PENDING_LINES: usize = 100_000;
+
+// measurements and tag keys/values escape commas, spaces and (tags only) equals signs
+fn escape(value: &str, equals: bool) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == ',' || c == ' ' || (equals && c == '=') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// one point, float fields only; non-finite values aren't valid line protocol and are left out
+pub fn line(measurement: &str, tags: &[(&str, &str)], fields: &[(&str, f64)], timestamp_ns: i64) -> Option<String> {
+    let fields: Vec<String> = fields
+        .iter()
+        .filter(|(_, value)| value.is_finite())
+        .map(|(key, value)| format!("{}={}", escape(key, true), value))
+        .collect();
+    if fields.is_empty() {
+        return None;
+    }
+    let tags: String = tags
+        .iter()
+        .map(|(key, value)| format!(",{}={}", escape(key, true), escape(value, true)))
+        .collect();
+    Some(format!("{}{} {} {}", escape(measurement, false), tags, fields.join(","), timestamp_ns))
+}
+
+pub fn market_lines(prefix: &str, markets: &[MarketInfo], symbols: &[String], timestamp_ns: i64) -> Vec<String> {
+    markets
+        .iter()
+        .filter(|m| symbols.is_empty() || symbols.iter().any(|s| s.eq_ignore_ascii_case(&m.symbol)))
+        .filter_map(|m| {
+            line(
+                &format!("{}_market", prefix),
+                &[("symbol", &m.symbol)],
+                &[
+                    ("mark_price", m.mark_price),
+                    ("funding_rate", m.funding_rate),
+                    ("open_interest", m.open_interest),
+                    ("volume_24h", m.volume_24h),
+                ],
+                timestamp_ns,
+            )
+        })
+        .collect()
+}
+
+pub fn account_lines(prefix: &str, balances: &BalanceResponse, timestamp_ns: i64) -> Vec<String> {
+    let unrealized: f64 = balances.positions.iter().map(|p| p.unrealized_pnl).sum();
+    let account = line(
+        &format!("{}_account", prefix),
+        &[],
+        &[
+            ("account_value", balances.account_value),
+            ("withdrawable", balances.withdrawable),
+            ("margin_used", balances.cross_margin_used),
+            ("unrealized_pnl", unrealized),
+        ],
+        timestamp_ns,
+    );
+    let positions = balances.positions.iter().filter_map(|p| {
+        line(
+            &format!("{}_position", prefix),
+            &[("symbol", &p.symbol)],
+            &[
+                ("size", p.size),
+                ("entry_price", p.entry_price),
+                ("unrealized_pnl", p.unrealized_pnl),
+                ("position_value", p.position_value),
+            ],
+            timestamp_ns,
+        )
+    });
+    account.into_iter().chain(positions).collect()
+}
+
+pub struct InfluxWriter {
+    client: Client,
+    retry: RetryPolicy,
+    url: String,
+    token: Option<String>,
+    pending: Vec<String>,
+}
+
+impl InfluxWriter {
+    pub fn new(url: &str, token: Option<String>, http: &HttpConfig) -> Result<Self> {
+        reqwest::Url::parse(url).with_context(|| format!("Invalid influx_url '{}'", url))?;
+        Ok(Self {
+            client: net::http_client(http)?,
+            retry: RetryPolicy::from(http),
+            url: url.to_string(),
+            token,
+            pending: Vec::new(),
+        })
+    }
+
+    // writes `lines` after whatever is still pending; on failure they're all kept for next time
+    pub async fn write(&mut self, lines: Vec<String>) -> Result<usize> {
+        self.pending.extend(lines);
+        if self.pending.len() > MAX_PENDING_LINES {
+            let excess = self.pending.len() - MAX_PENDING_LINES;
+            self.pending.drain(..excess);
+        }
+        if self.pending.is_empty() {
+            return Ok(0);
+        }
+
+        let body = self.pending.join("\n");
+        let response = self
+            .retry
+            .send("influx write", Idempotency::Idempotent, || {
+                let request = self.client.post(&self.url).body(body.clone());
+                match &self.token {
+                    Some(token) => request.header("Authorization", format!("Token {}", token)),
+                    None => request,
+                }
+            })
+            .await
+            .context("Failed to write to influx")?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Influx write returned {}: {}", status, text.trim());
+        }
+        Ok(std::mem::take(&mut self.pending).len())
+    }
+}
+
+// samples and writes every interval until the caller stops it; a failed sample or write is
+// logged and the loop carries on
+pub async fn run(url: &str, config: &InfluxConfig, http: &HttpConfig, exchange: ExchangeService) -> Result<()> {
+    let mut writer = InfluxWriter::new(url, config.token.clone(), http)?;
+    let mut symbols = Vec::new();
+    for symbol in &config.symbols {
+        symbols.push(exchange.resolve_symbol(symbol).await?.name);
+    }
+    let mut ticker = tokio::time::interval(Duration::from_secs(config.interval_secs.max(1)));
+    tracing::info!(url = %url, interval_secs = config.interval_secs, "influx: sink running");
+
+    loop {
+        ticker.tick().await;
+        let timestamp_ns = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let (status, balances) = tokio::join!(exchange.get_status(), exchange.get_balances());
+        let mut lines = Vec::new();
+        match status {
+            Ok(status) => lines.extend(market_lines(&config.measurement_prefix, &status.markets, &symbols, timestamp_ns)),
+            Err(e) => tracing::warn!(error = %e, "influx: market sample failed"),
+        }
+        match balances {
+            Ok(balances) => lines.extend(account_lines(&config.measurement_prefix, &balances, timestamp_ns)),
+            Err(e) => tracing::warn!(error = %e, "influx: account sample failed"),
+        }
+        match writer.write(lines).await {
+            Ok(written) => tracing::debug!(written, "influx: points written"),
+            Err(e) => tracing::warn!(error = %format!("{:#}", e), pending = writer.pending.len(), "influx: write failed, retrying next interval"),
+        }
+    }
+}
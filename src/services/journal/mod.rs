@@ -0,0 +1,168 @@
+// trade journal of fills, orders, funding, account value and market context history,
+// in a local sqlite file or, with database_url set, a shared postgres database
+mod postgres;
+mod sqlite;
+
+use std::{collections::HashMap, time::Duration};
+use anyhow::Result;
+use crate::{
+    services::ExchangeService,
+    types::{BalanceResponse, Config, ExecutionReport, FillInfo, FundingPayment, MarketInfo, MarketSample, OpenOrder, SyncSummary},
+};
+
+pub use postgres::{redact, PostgresJournal};
+pub use sqlite::{query, SqliteJournal};
+
+pub const DEFAULT_JOURNAL_PATH: &str = "hl-journal.db";
+
+pub enum Journal {
+    Sqlite(SqliteJournal),
+    Postgres(PostgresJournal),
+}
+
+impl Journal {
+    pub fn open(path: &str) -> Result<Self> {
+        Ok(Journal::Sqlite(SqliteJournal::open(path)?))
+    }
+
+    // database_url wins, otherwise journal.path or hl-journal.db
+    pub async fn from_config(config: &Config) -> Result<Self> {
+        match &config.database_url {
+            Some(url) => Ok(Journal::Postgres(PostgresJournal::connect(url).await?)),
+            None => Self::open(config.journal.path.as_deref().unwrap_or(DEFAULT_JOURNAL_PATH)),
+        }
+    }
+
+    // where it's stored, for messages
+    pub fn location(&self) -> &str {
+        match self {
+            Journal::Sqlite(journal) => journal.path(),
+            Journal::Postgres(journal) => journal.url(),
+        }
+    }
+
+    pub async fn record_fills(&self, fills: &[FillInfo]) -> Result<usize> {
+        match self {
+            Journal::Sqlite(journal) => journal.record_fills(fills),
+            Journal::Postgres(journal) => journal.record_fills(fills).await,
+        }
+    }
+
+    pub async fn record_orders(&self, orders: &[OpenOrder]) -> Result<usize> {
+        match self {
+            Journal::Sqlite(journal) => journal.record_orders(orders),
+            Journal::Postgres(journal) => journal.record_orders(orders).await,
+        }
+    }
+
+    pub async fn record_funding(&self, payments: &[FundingPayment]) -> Result<usize> {
+        match self {
+            Journal::Sqlite(journal) => journal.record_funding(payments),
+            Journal::Postgres(journal) => journal.record_funding(payments).await,
+        }
+    }
+
+    pub async fn record_equity(&self, balances: &BalanceResponse) -> Result<()> {
+        match self {
+            Journal::Sqlite(journal) => journal.record_equity(balances),
+            Journal::Postgres(journal) => journal.record_equity(balances).await,
+        }
+    }
+
+    pub async fn record_execution(&self, report: &ExecutionReport) -> Result<()> {
+        match self {
+            Journal::Sqlite(journal) => journal.record_execution(report),
+            Journal::Postgres(journal) => journal.record_execution(report).await,
+        }
+    }
+
+    pub async fn record_market_contexts(&self, time: u64, markets: &[MarketInfo]) -> Result<usize> {
+        match self {
+            Journal::Sqlite(journal) => journal.record_market_contexts(time, markets),
+            Journal::Postgres(journal) => journal.record_market_contexts(time, markets).await,
+        }
+    }
+
+    // each symbol's oldest market context at or after `since` ms
+    pub async fn market_baselines(&self, since: u64) -> Result<HashMap<String, MarketSample>> {
+        match self {
+            Journal::Sqlite(journal) => journal.market_baselines(since),
+            Journal::Postgres(journal) => journal.market_baselines(since).await,
+        }
+    }
+
+    // fills in chronological order, optionally up to (excluding) `until` ms
+    pub async fn load_fills(&self, until: Option<u64>) -> Result<Vec<FillInfo>> {
+        match self {
+            Journal::Sqlite(journal) => journal.load_fills(until),
+            Journal::Postgres(journal) => journal.load_fills(until).await,
+        }
+    }
+
+    // (timestamp ms, account value) snapshots in chronological order
+    pub async fn load_equity(&self) -> Result<Vec<(u64, f64)>> {
+        match self {
+            Journal::Sqlite(journal) => journal.load_equity(),
+            Journal::Postgres(journal) => journal.load_equity().await,
+        }
+    }
+
+    pub async fn last_funding_time(&self) -> Result<Option<u64>> {
+        match self {
+            Journal::Sqlite(journal) => journal.last_funding_time(),
+            Journal::Postgres(journal) => journal.last_funding_time().await,
+        }
+    }
+
+    // read-only ad-hoc query, column names and stringified rows
+    pub async fn query(&self, sql: &str) -> Result<(Vec<String>, Vec<Vec<String>>)> {
+        match self {
+            Journal::Sqlite(journal) => query(journal.path(), sql),
+            Journal::Postgres(journal) => journal.query(sql).await,
+        }
+    }
+}
+
+// an equity snapshot (account value, margin used, unrealized pnl) every `every`, for the stats and
+// the drawdown guard; a failed sample is logged and the next one tried
+pub async fn record_equity(
+    journal: &Journal,
+    exchange: &ExchangeService,
+    every: Duration,
+    mut on_sample: impl FnMut(&BalanceResponse),
+) -> Result<()> {
+    let mut ticker = tokio::time::interval(every);
+    loop {
+        ticker.tick().await;
+        let balances = match exchange.get_balances().await {
+            Ok(balances) => balances,
+            Err(e) => {
+                tracing::warn!(error = %e, "equity sample failed");
+                continue;
+            }
+        };
+        journal.record_equity(&balances).await?;
+        on_sample(&balances);
+    }
+}
+
+// pulls fills, open orders, funding and an equity snapshot into the journal
+pub async fn sync(journal: &Journal, exchange: &ExchangeService) -> Result<SyncSummary> {
+    let funding_since = journal.last_funding_time().await?.map(|t| t + 1).unwrap_or(0);
+    let (fills, orders, funding, balances) = tokio::try_join!(
+        exchange.get_fills(None, None),
+        exchange.get_open_orders(None),
+        exchange.get_funding_payments(funding_since),
+        exchange.get_balances(),
+    )?;
+
+    let summary = SyncSummary {
+        new_fills: journal.record_fills(&fills.fills).await?,
+        open_orders: journal.record_orders(&orders.orders).await?,
+        new_funding_payments: journal.record_funding(&funding).await?,
+        account_value: balances.account_value,
+    };
+    journal.record_equity(&balances).await?;
+
+    Ok(summary)
+}
@@ -0,0 +1,303 @@
+// postgres journal for a shared server, same tables as sqlite with migrations under migrations/postgres
+use std::collections::HashMap;
+use anyhow::{Context, Result};
+use sqlx::{
+    migrate::Migrator,
+    postgres::{PgConnectOptions, PgPool, PgPoolOptions, PgRow},
+    Column, Executor, Row, TypeInfo, ValueRef,
+};
+use crate::types::{BalanceResponse, ExecutionReport, FillInfo, FundingPayment, MarketInfo, MarketSample, OpenOrder};
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+const MAX_CONNECTIONS: u32 = 5;
+
+pub struct PostgresJournal {
+    pool: PgPool,
+    url: String,
+}
+
+impl PostgresJournal {
+    // connects and applies any migrations the database hasn't seen yet
+    pub async fn connect(url: &str) -> Result<Self> {
+        let options = url
+            .parse::<PgConnectOptions>()
+            .with_context(|| format!("Invalid database_url {}", redact(url)))?
+            // keeps "already exists, skipping" notices from the migrations out of the logs
+            .options([("client_min_messages", "warning")]);
+        let pool = PgPoolOptions::new()
+            .max_connections(MAX_CONNECTIONS)
+            .connect_with(options)
+            .await
+            .with_context(|| format!("Failed to connect to {}", redact(url)))?;
+        MIGRATOR.run(&pool).await.context("Failed to migrate the journal database")?;
+
+        Ok(Self { pool, url: redact(url) })
+    }
+
+    // the url without its password, for messages
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub async fn record_fills(&self, fills: &[FillInfo]) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+        let mut inserted = 0;
+        for fill in fills {
+            inserted += sqlx::query(
+                "INSERT INTO fills
+                 (time, symbol, side, price, size, direction, closed_pnl, fee, order_id, trade_id, hash)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(fill.timestamp as i64)
+            .bind(&fill.symbol)
+            .bind(&fill.side)
+            .bind(fill.price)
+            .bind(fill.size)
+            .bind(&fill.direction)
+            .bind(fill.closed_pnl)
+            .bind(fill.fee)
+            .bind(fill.order_id as i64)
+            .bind(fill.trade_id as i64)
+            .bind(&fill.hash)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected() as usize;
+        }
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    pub async fn record_orders(&self, orders: &[OpenOrder]) -> Result<usize> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut tx = self.pool.begin().await?;
+        for order in orders {
+            sqlx::query(
+                "INSERT INTO orders (order_id, symbol, side, price, size, filled, placed_at, last_seen)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                 ON CONFLICT (order_id) DO UPDATE SET filled = excluded.filled, last_seen = excluded.last_seen",
+            )
+            .bind(order.order_id as i64)
+            .bind(&order.symbol)
+            .bind(&order.side)
+            .bind(order.price)
+            .bind(order.qty)
+            .bind(order.filled_qty)
+            .bind(order.timestamp as i64)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(orders.len())
+    }
+
+    pub async fn record_funding(&self, payments: &[FundingPayment]) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+        let mut inserted = 0;
+        for payment in payments {
+            inserted += sqlx::query(
+                "INSERT INTO funding (time, symbol, usdc, position_size, funding_rate)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(payment.timestamp as i64)
+            .bind(&payment.symbol)
+            .bind(payment.usdc)
+            .bind(payment.position_size)
+            .bind(payment.funding_rate)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected() as usize;
+        }
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    pub async fn record_equity(&self, balances: &BalanceResponse) -> Result<()> {
+        let unrealized: f64 = balances.positions.iter().map(|p| p.unrealized_pnl).sum();
+        sqlx::query(
+            "INSERT INTO equity (time, account_value, margin_used, unrealized_pnl, open_positions)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (time) DO UPDATE SET account_value = excluded.account_value,
+                 margin_used = excluded.margin_used, unrealized_pnl = excluded.unrealized_pnl,
+                 open_positions = excluded.open_positions",
+        )
+        .bind(chrono::Utc::now().timestamp_millis())
+        .bind(balances.account_value)
+        .bind(balances.cross_margin_used)
+        .bind(unrealized)
+        .bind(balances.positions.len() as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn record_execution(&self, report: &ExecutionReport) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO executions
+             (started, finished, symbol, side, requested_qty, filled_qty, fills, avg_price, arrival_mid,
+              interval_vwap, fees, shortfall_bps, vs_vwap_bps)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)",
+        )
+        .bind(report.started as i64)
+        .bind(report.finished as i64)
+        .bind(&report.symbol)
+        .bind(&report.side)
+        .bind(report.requested_qty)
+        .bind(report.filled_qty)
+        .bind(report.fills as i64)
+        .bind(report.avg_price)
+        .bind(report.arrival_mid)
+        .bind(report.interval_vwap)
+        .bind(report.fees)
+        .bind(report.shortfall_bps)
+        .bind(report.vs_vwap_bps)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn record_market_contexts(&self, time: u64, markets: &[MarketInfo]) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+        let mut inserted = 0;
+        for market in markets {
+            inserted += sqlx::query(
+                "INSERT INTO market_contexts (time, symbol, mark_price, open_interest, volume_24h, funding_rate)
+                 VALUES ($1, $2, $3, $4, $5, $6)
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(time as i64)
+            .bind(&market.symbol)
+            .bind(market.mark_price)
+            .bind(market.open_interest)
+            .bind(market.volume_24h)
+            .bind(market.funding_rate)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected() as usize;
+        }
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    pub async fn market_baselines(&self, since: u64) -> Result<HashMap<String, MarketSample>> {
+        let rows = sqlx::query(
+            "SELECT DISTINCT ON (symbol) symbol, time, open_interest, volume_24h
+             FROM market_contexts WHERE time >= $1 ORDER BY symbol, time",
+        )
+        .bind(since as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let sample = MarketSample {
+                    time: row.try_get::<i64, _>(1)? as u64,
+                    open_interest: row.try_get(2)?,
+                    volume_24h: row.try_get(3)?,
+                };
+                Ok((row.try_get(0)?, sample))
+            })
+            .collect()
+    }
+
+    pub async fn load_fills(&self, until: Option<u64>) -> Result<Vec<FillInfo>> {
+        let rows = sqlx::query(
+            "SELECT symbol, side, price, size, direction, closed_pnl, fee, order_id, trade_id, hash, time
+             FROM fills WHERE time < $1 ORDER BY time, id",
+        )
+        .bind(until.map(|t| t as i64).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(FillInfo {
+                    symbol: row.try_get(0)?,
+                    side: row.try_get(1)?,
+                    price: row.try_get(2)?,
+                    size: row.try_get(3)?,
+                    direction: row.try_get(4)?,
+                    closed_pnl: row.try_get(5)?,
+                    fee: row.try_get(6)?,
+                    order_id: row.try_get::<i64, _>(7)? as u64,
+                    trade_id: row.try_get::<i64, _>(8)? as u64,
+                    hash: row.try_get(9)?,
+                    timestamp: row.try_get::<i64, _>(10)? as u64,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn load_equity(&self) -> Result<Vec<(u64, f64)>> {
+        let rows: Vec<(i64, f64)> = sqlx::query_as("SELECT time, account_value FROM equity ORDER BY time")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(|(time, value)| (time as u64, value)).collect())
+    }
+
+    pub async fn last_funding_time(&self) -> Result<Option<u64>> {
+        let time: Option<i64> = sqlx::query_scalar("SELECT MAX(time) FROM funding")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(time.map(|t| t as u64))
+    }
+
+    // like the sqlite query, inside a read-only transaction
+    pub async fn query(&self, sql: &str) -> Result<(Vec<String>, Vec<Vec<String>>)> {
+        let mut tx = self.pool.begin().await?;
+        tx.execute("SET TRANSACTION READ ONLY").await?;
+        let columns: Vec<String> = (&mut *tx)
+            .describe(sql)
+            .await
+            .context("Invalid query")?
+            .columns()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect();
+        let rows = sqlx::query(sql)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Query failed: {}", e))?;
+        tx.rollback().await?;
+
+        let rows = rows
+            .iter()
+            .map(|row| (0..columns.len()).map(|i| cell(row, i)).collect())
+            .collect();
+        Ok((columns, rows))
+    }
+}
+
+// the journal's own column types as text, anything else (numeric, timestamps) by type name
+fn cell(row: &PgRow, i: usize) -> String {
+    let type_name = match row.try_get_raw(i) {
+        Ok(raw) if raw.is_null() => return "NULL".to_string(),
+        Ok(raw) => raw.type_info().name().to_string(),
+        Err(_) => return "?".to_string(),
+    };
+    let value = match type_name.as_str() {
+        "INT2" => row.try_get::<i16, _>(i).map(|v| v.to_string()),
+        "INT4" => row.try_get::<i32, _>(i).map(|v| v.to_string()),
+        "INT8" => row.try_get::<i64, _>(i).map(|v| v.to_string()),
+        "FLOAT4" => row.try_get::<f32, _>(i).map(|v| v.to_string()),
+        "FLOAT8" => row.try_get::<f64, _>(i).map(|v| v.to_string()),
+        "BOOL" => row.try_get::<bool, _>(i).map(|v| v.to_string()),
+        "TEXT" | "VARCHAR" | "BPCHAR" | "NAME" => row.try_get::<String, _>(i),
+        _ => return format!("<{}>", type_name),
+    };
+    value.unwrap_or_else(|_| format!("<{}>", type_name))
+}
+
+pub fn redact(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) if parsed.password().is_some() => {
+            let _ = parsed.set_password(Some("***"));
+            parsed.to_string()
+        }
+        Ok(parsed) => parsed.to_string(),
+        Err(_) => url.to_string(),
+    }
+}
@@ -0,0 +1,344 @@
+// embedded sqlite journal, the default when no database_url is set
+use std::{collections::HashMap, sync::Mutex};
+use anyhow::{Context, Result};
+use rusqlite::{params, types::ValueRef, Connection, OpenFlags};
+use crate::types::{BalanceResponse, ExecutionReport, FillInfo, FundingPayment, MarketInfo, MarketSample, OpenOrder};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS fills (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    time INTEGER NOT NULL,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL,
+    price REAL NOT NULL,
+    size REAL NOT NULL,
+    direction TEXT NOT NULL,
+    closed_pnl REAL NOT NULL,
+    fee REAL NOT NULL,
+    order_id INTEGER NOT NULL,
+    trade_id INTEGER NOT NULL,
+    hash TEXT NOT NULL,
+    UNIQUE (hash, trade_id, order_id, time)
+);
+CREATE TABLE IF NOT EXISTS orders (
+    order_id INTEGER PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL,
+    price REAL NOT NULL,
+    size REAL NOT NULL,
+    filled REAL NOT NULL,
+    placed_at INTEGER NOT NULL,
+    last_seen INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS funding (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    time INTEGER NOT NULL,
+    symbol TEXT NOT NULL,
+    usdc REAL NOT NULL,
+    position_size REAL NOT NULL,
+    funding_rate REAL NOT NULL,
+    UNIQUE (time, symbol)
+);
+CREATE TABLE IF NOT EXISTS equity (
+    time INTEGER PRIMARY KEY,
+    account_value REAL NOT NULL,
+    margin_used REAL NOT NULL,
+    unrealized_pnl REAL NOT NULL,
+    open_positions INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS market_contexts (
+    time INTEGER NOT NULL,
+    symbol TEXT NOT NULL,
+    mark_price REAL NOT NULL,
+    open_interest REAL NOT NULL,
+    volume_24h REAL NOT NULL,
+    funding_rate REAL NOT NULL,
+    PRIMARY KEY (time, symbol)
+);
+CREATE TABLE IF NOT EXISTS executions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    started INTEGER NOT NULL,
+    finished INTEGER NOT NULL,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL,
+    requested_qty REAL NOT NULL,
+    filled_qty REAL NOT NULL,
+    fills INTEGER NOT NULL,
+    avg_price REAL NOT NULL,
+    arrival_mid REAL NOT NULL,
+    interval_vwap REAL,
+    fees REAL NOT NULL,
+    shortfall_bps REAL NOT NULL,
+    vs_vwap_bps REAL
+);
+CREATE INDEX IF NOT EXISTS idx_fills_time ON fills (time);
+CREATE INDEX IF NOT EXISTS idx_funding_time ON funding (time);
+";
+
+pub struct SqliteJournal {
+    conn: Mutex<Connection>,
+    path: String,
+}
+
+impl SqliteJournal {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open journal database {}", path))?;
+        conn.execute_batch(SCHEMA)
+            .context("Failed to initialise journal schema")?;
+
+        Ok(Self { conn: Mutex::new(conn), path: path.to_string() })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn record_fills(&self, fills: &[FillInfo]) -> Result<usize> {
+        let mut conn = self.lock();
+        let tx = conn.transaction()?;
+        let mut inserted = 0;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR IGNORE INTO fills
+                 (time, symbol, side, price, size, direction, closed_pnl, fee, order_id, trade_id, hash)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            )?;
+            for fill in fills {
+                inserted += stmt.execute(params![
+                    fill.timestamp as i64,
+                    fill.symbol,
+                    fill.side,
+                    fill.price,
+                    fill.size,
+                    fill.direction,
+                    fill.closed_pnl,
+                    fill.fee,
+                    fill.order_id as i64,
+                    fill.trade_id as i64,
+                    fill.hash,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    pub fn record_orders(&self, orders: &[OpenOrder]) -> Result<usize> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut conn = self.lock();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO orders (order_id, symbol, side, price, size, filled, placed_at, last_seen)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT (order_id) DO UPDATE SET filled = excluded.filled, last_seen = excluded.last_seen",
+            )?;
+            for order in orders {
+                stmt.execute(params![
+                    order.order_id as i64,
+                    order.symbol,
+                    order.side,
+                    order.price,
+                    order.qty,
+                    order.filled_qty,
+                    order.timestamp as i64,
+                    now,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(orders.len())
+    }
+
+    pub fn record_funding(&self, payments: &[FundingPayment]) -> Result<usize> {
+        let mut conn = self.lock();
+        let tx = conn.transaction()?;
+        let mut inserted = 0;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR IGNORE INTO funding (time, symbol, usdc, position_size, funding_rate)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for payment in payments {
+                inserted += stmt.execute(params![
+                    payment.timestamp as i64,
+                    payment.symbol,
+                    payment.usdc,
+                    payment.position_size,
+                    payment.funding_rate,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    pub fn record_equity(&self, balances: &BalanceResponse) -> Result<()> {
+        let unrealized: f64 = balances.positions.iter().map(|p| p.unrealized_pnl).sum();
+        self.lock().execute(
+            "INSERT OR REPLACE INTO equity (time, account_value, margin_used, unrealized_pnl, open_positions)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                chrono::Utc::now().timestamp_millis(),
+                balances.account_value,
+                balances.cross_margin_used,
+                unrealized,
+                balances.positions.len() as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_execution(&self, report: &ExecutionReport) -> Result<()> {
+        self.lock().execute(
+            "INSERT INTO executions
+             (started, finished, symbol, side, requested_qty, filled_qty, fills, avg_price, arrival_mid,
+              interval_vwap, fees, shortfall_bps, vs_vwap_bps)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                report.started as i64,
+                report.finished as i64,
+                report.symbol,
+                report.side,
+                report.requested_qty,
+                report.filled_qty,
+                report.fills as i64,
+                report.avg_price,
+                report.arrival_mid,
+                report.interval_vwap,
+                report.fees,
+                report.shortfall_bps,
+                report.vs_vwap_bps,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_market_contexts(&self, time: u64, markets: &[MarketInfo]) -> Result<usize> {
+        let mut conn = self.lock();
+        let tx = conn.transaction()?;
+        let mut inserted = 0;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR IGNORE INTO market_contexts (time, symbol, mark_price, open_interest, volume_24h, funding_rate)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for market in markets {
+                inserted += stmt.execute(params![
+                    time as i64,
+                    market.symbol,
+                    market.mark_price,
+                    market.open_interest,
+                    market.volume_24h,
+                    market.funding_rate,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    // each symbol's oldest market context at or after `since` ms
+    pub fn market_baselines(&self, since: u64) -> Result<HashMap<String, MarketSample>> {
+        let conn = self.lock();
+        // sqlite fills the bare columns from the row that holds MIN(time)
+        let mut stmt = conn.prepare(
+            "SELECT symbol, MIN(time), open_interest, volume_24h
+             FROM market_contexts WHERE time >= ?1 GROUP BY symbol",
+        )?;
+        let baselines = stmt
+            .query_map(params![since as i64], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    MarketSample {
+                        time: row.get::<_, i64>(1)? as u64,
+                        open_interest: row.get(2)?,
+                        volume_24h: row.get(3)?,
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+        Ok(baselines)
+    }
+
+    // fills in chronological order, optionally up to (excluding) `until` ms
+    pub fn load_fills(&self, until: Option<u64>) -> Result<Vec<FillInfo>> {
+        let conn = self.lock();
+        let mut stmt = conn.prepare(
+            "SELECT symbol, side, price, size, direction, closed_pnl, fee, order_id, trade_id, hash, time
+             FROM fills WHERE time < ?1 ORDER BY time, id",
+        )?;
+        let until = until.map(|t| t as i64).unwrap_or(i64::MAX);
+
+        let fills = stmt
+            .query_map(params![until], |row| {
+                Ok(FillInfo {
+                    symbol: row.get(0)?,
+                    side: row.get(1)?,
+                    price: row.get(2)?,
+                    size: row.get(3)?,
+                    direction: row.get(4)?,
+                    closed_pnl: row.get(5)?,
+                    fee: row.get(6)?,
+                    order_id: row.get::<_, i64>(7)? as u64,
+                    trade_id: row.get::<_, i64>(8)? as u64,
+                    hash: row.get(9)?,
+                    timestamp: row.get::<_, i64>(10)? as u64,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(fills)
+    }
+
+    // (timestamp ms, account value) snapshots in chronological order
+    pub fn load_equity(&self) -> Result<Vec<(u64, f64)>> {
+        let conn = self.lock();
+        let mut stmt = conn.prepare("SELECT time, account_value FROM equity ORDER BY time")?;
+        let equity = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(equity)
+    }
+
+    pub fn last_funding_time(&self) -> Result<Option<u64>> {
+        let time: Option<i64> = self
+            .lock()
+            .query_row("SELECT MAX(time) FROM funding", [], |row| row.get(0))?;
+        Ok(time.map(|t| t as u64))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// runs an ad-hoc statement on a read-only connection, returns column names and stringified rows
+pub fn query(path: &str, sql: &str) -> Result<(Vec<String>, Vec<Vec<String>>)> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open journal database {}", path))?;
+
+    let mut stmt = conn.prepare(sql).context("Invalid query")?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let column_count = columns.len();
+
+    let mut rows = stmt.query([])?;
+    let mut result = Vec::new();
+    while let Some(row) = rows.next()? {
+        let mut values = Vec::with_capacity(column_count);
+        for i in 0..column_count {
+            values.push(match row.get_ref(i)? {
+                ValueRef::Null => "NULL".to_string(),
+                ValueRef::Integer(v) => v.to_string(),
+                ValueRef::Real(v) => v.to_string(),
+                ValueRef::Text(v) => String::from_utf8_lossy(v).into_owned(),
+                ValueRef::Blob(v) => format!("<{} bytes>", v.len()),
+            });
+        }
+        result.push(values);
+    }
+
+    Ok((columns, result))
+}
@@ -0,0 +1,216 @@
+// kafka sink: trades, candles and our fills as versioned json records, keyed for per-symbol ordering
+use serde::Serialize;
+use serde_json::Value;
+use crate::services::{exchange::to_fill_info, ws_hub};
+use crate::types::{Candle, FillInfo, RawCandle, UserFill};
+
+// bumped whenever a field is renamed or removed, new fields don't change it
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+pub struct Record<T> {
+    pub schema_version: u32,
+    pub kind: &'static str,
+    #[serde(flatten)]
+    pub data: T,
+}
+
+#[derive(Serialize)]
+pub struct TradeRecord {
+    pub symbol: String,
+    pub side: String,
+    pub price: f64,
+    pub size: f64,
+    pub time: u64,
+    pub trade_id: u64,
+    pub hash: String,
+}
+
+#[derive(Serialize)]
+pub struct FillRecord {
+    pub user: String,
+    #[serde(flatten)]
+    pub fill: FillInfo,
+}
+
+fn record<T>(kind: &'static str, data: T) -> Record<T> {
+    Record { schema_version: SCHEMA_VERSION, kind, data }
+}
+
+// (key, payload) pairs for one upstream trades message
+pub fn trade_records(text: &str) -> Vec<(String, String)> {
+    ws_hub::parse_trades(text)
+        .into_iter()
+        .map(|trade| {
+            let data = TradeRecord {
+                side: if trade.side == "B" { "BUY" } else { "SELL" }.to_string(),
+                price: trade.px.parse().unwrap_or(0.0),
+                size: trade.sz.parse().unwrap_or(0.0),
+                time: trade.time,
+                trade_id: trade.tid,
+                hash: trade.hash,
+                symbol: trade.coin,
+            };
+            (data.symbol.clone(), json(&record("trade", data)))
+        })
+        .collect()
+}
+
+// the open candle is re-sent as it updates, consumers keep the last one per open_time
+pub fn candle_records(text: &str) -> Vec<(String, String)> {
+    let Ok(message) = serde_json::from_str::<Value>(text) else {
+        return Vec::new();
+    };
+    if message["channel"] != "candle" {
+        return Vec::new();
+    }
+    serde_json::from_value::<RawCandle>(message["data"].clone())
+        .map(|raw| {
+            let candle: Candle = raw.to_candle();
+            vec![(format!("{}:{}", candle.symbol, candle.interval), json(&record("candle", candle)))]
+        })
+        .unwrap_or_default()
+}
+
+// live fills only, keyed by account so one account's events stay in order
+pub fn fill_records(text: &str) -> Vec<(String, String)> {
+    let Ok(message) = serde_json::from_str::<Value>(text) else {
+        return Vec::new();
+    };
+    let data = &message["data"];
+    if message["channel"] != "userFills" || data["isSnapshot"].as_bool().unwrap_or(false) {
+        return Vec::new();
+    }
+    let user = data["user"].as_str().unwrap_or_default().to_lowercase();
+    data["fills"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|fill| serde_json::from_value::<UserFill>(fill.clone()).ok())
+        .map(|fill| {
+            let data = FillRecord { user: user.clone(), fill: to_fill_info(fill) };
+            (user.clone(), json(&record("fill", data)))
+        })
+        .collect()
+}
+
+fn json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+#[cfg(feature = "kafka")]
+pub use producer::run;
+
+#[cfg(feature = "kafka")]
+mod producer {
+    use std::{sync::Arc, time::Duration};
+    use anyhow::{Context, Result};
+    use rdkafka::{
+        error::{KafkaError, RDKafkaErrorCode},
+        message::Message,
+        producer::{BaseRecord, DeliveryResult, Producer, ProducerContext, ThreadedProducer},
+        ClientConfig, ClientContext,
+    };
+    use tokio::sync::broadcast::error::RecvError;
+    use crate::services::{
+        ws_hub::{HubSubscription, SubscriptionKey, WsHub},
+        ExchangeService,
+    };
+    use crate::types::KafkaConfig;
+
+    const QUEUE_FULL_BACKOFF: Duration = Duration::from_millis(200);
+
+    type KafkaProducer = ThreadedProducer<DeliveryLog>;
+
+    // librdkafka retries delivery itself, this only hears about messages it gave up on
+    struct DeliveryLog;
+
+    impl ClientContext for DeliveryLog {}
+
+    impl ProducerContext for DeliveryLog {
+        type DeliveryOpaque = ();
+
+        fn delivery(&self, result: &DeliveryResult<'_>, _: ()) {
+            if let Err((e, message)) = result {
+                tracing::warn!(error = %e, topic = message.topic(), "kafka: message dropped after retries");
+            }
+        }
+    }
+
+    // runs until the upstream stream closes, callers stop it on ctrl-c or shutdown
+    pub async fn run(brokers: &str, config: &KafkaConfig, exchange: ExchangeService, hub: Arc<WsHub>) -> Result<()> {
+        let producer: Arc<KafkaProducer> = Arc::new(
+            ClientConfig::new()
+                .set("bootstrap.servers", brokers)
+                .set("queue.buffering.max.messages", config.buffer_messages.to_string())
+                .set("message.timeout.ms", (config.delivery_timeout_secs * 1000).to_string())
+                .set("enable.idempotence", "true")
+                .create_with_context(DeliveryLog)
+                .with_context(|| format!("Failed to create a Kafka producer for {}", brokers))?,
+        );
+
+        let mut forwarders = tokio::task::JoinSet::new();
+        for symbol in &config.symbols {
+            let symbol = &exchange.resolve_symbol(symbol).await?.name;
+            let trades = hub.subscribe_owned(SubscriptionKey::for_coin("trades", symbol));
+            forwarders.spawn(forward(producer.clone(), trades, config.trades_topic.clone(), super::trade_records));
+            let candle_key = SubscriptionKey {
+                interval: Some(config.candle_interval.clone()),
+                ..SubscriptionKey::for_coin("candle", symbol)
+            };
+            let candles = hub.subscribe_owned(candle_key);
+            forwarders.spawn(forward(producer.clone(), candles, config.candles_topic.clone(), super::candle_records));
+        }
+        let fills = hub.subscribe_owned(SubscriptionKey::for_user("userFills", &exchange.get_wallet_address()?));
+        forwarders.spawn(forward(producer.clone(), fills, config.user_events_topic.clone(), super::fill_records));
+
+        tracing::info!(brokers = %brokers, symbols = config.symbols.len(), "kafka: sink running");
+        let result = forwarders.join_next().await.map(|joined| joined.map_err(anyhow::Error::from).and_then(|r| r));
+        // give buffered messages a moment to go out
+        let _ = tokio::task::spawn_blocking(move || producer.flush(Duration::from_secs(5))).await;
+        result.unwrap_or(Ok(()))
+    }
+
+    async fn forward(
+        producer: Arc<KafkaProducer>,
+        mut subscription: HubSubscription,
+        topic: String,
+        to_records: fn(&str) -> Vec<(String, String)>,
+    ) -> Result<()> {
+        loop {
+            let text = match subscription.updates.recv().await {
+                Ok(text) => text,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, topic = %topic, "kafka: falling behind, messages dropped");
+                    continue;
+                }
+                Err(RecvError::Closed) => anyhow::bail!("Upstream stream for {} closed", topic),
+            };
+            for (key, payload) in to_records(&text) {
+                let mut record = BaseRecord::to(&topic).key(&key).payload(&payload);
+                // the buffer is full while the brokers are down, wait for room instead of dropping
+                loop {
+                    match producer.send(record) {
+                        Ok(()) => break,
+                        Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), returned)) => {
+                            record = returned;
+                            tokio::time::sleep(QUEUE_FULL_BACKOFF).await;
+                        }
+                        Err((e, _)) => return Err(e).with_context(|| format!("Failed to produce to {}", topic)),
+                    }
+                }
+            }
+        }
+    }
+}
+
+// without the feature the sink only explains how to get it
+#[cfg(not(feature = "kafka"))]
+pub async fn run(
+    _brokers: &str,
+    _config: &crate::types::KafkaConfig,
+    _exchange: crate::services::ExchangeService,
+    _hub: std::sync::Arc<ws_hub::WsHub>,
+) -> anyhow::Result<()> {
+    anyhow::bail!("This build has no Kafka support, rebuild with `cargo build --features kafka`")
+}
@@ -0,0 +1,91 @@
+// emergency flatten: cancel every open order, market-close every position, then verify
+use std::time::Duration;
+use anyhow::Result;
+use crate::{
+    services::{ExchangeService, TradingService},
+    types::{FillInfo, PanicReport, PositionInfo},
+};
+
+const MAX_ATTEMPTS: usize = 3;
+// lets cancels and closes settle before the verification pass
+pub const SETTLE_DELAY: Duration = Duration::from_secs(1);
+
+// open positions minus the `except` symbols (case-insensitive), for `hl close-all`
+pub fn closable<'a>(positions: &'a [PositionInfo], except: &[String]) -> Vec<&'a PositionInfo> {
+    positions
+        .iter()
+        .filter(|p| p.size != 0.0 && !except.iter().any(|e| e.eq_ignore_ascii_case(&p.symbol)))
+        .collect()
+}
+
+// realized pnl net of fees from the `symbol` fills at or after `since`
+pub fn realized_pnl(fills: &[FillInfo], symbol: &str, since: u64) -> f64 {
+    fills
+        .iter()
+        .filter(|f| f.timestamp >= since && f.symbol.eq_ignore_ascii_case(symbol))
+        .map(|f| f.closed_pnl - f.fee)
+        .sum()
+}
+
+pub async fn panic(trading: &TradingService, exchange: &ExchangeService) -> Result<PanicReport> {
+    if trading.is_paper() {
+        anyhow::bail!("panic acts on the live account; use `hl paper reset` for the paper account");
+    }
+
+    let mut report = PanicReport {
+        dry_run: trading.is_dry_run(),
+        ..Default::default()
+    };
+
+    while report.attempts < MAX_ATTEMPTS {
+        report.attempts += 1;
+
+        let (orders, balances) = tokio::try_join!(exchange.get_open_orders(None), exchange.get_balances())?;
+        let positions = balances.positions;
+        if orders.orders.is_empty() && positions.is_empty() {
+            report.clean = true;
+            break;
+        }
+
+        tracing::warn!(
+            attempt = report.attempts,
+            orders = orders.orders.len(),
+            positions = positions.len(),
+            "panic: flattening account"
+        );
+
+        // cancel first so resting orders can't reopen what we close
+        for order in &orders.orders {
+            match trading.cancel_order(&order.symbol, order.order_id).await {
+                Ok(()) => report.cancelled_orders += 1,
+                Err(e) => report.errors.push(format!("cancel {} #{}: {}", order.symbol, order.order_id, e)),
+            }
+        }
+
+        for position in &positions {
+            match trading.close_position(&position.symbol, position.size).await {
+                Ok(()) => {
+                    if !report.closed_positions.contains(&position.symbol) {
+                        report.closed_positions.push(position.symbol.clone());
+                    }
+                }
+                Err(e) => report.errors.push(format!("close {}: {}", position.symbol, e)),
+            }
+        }
+
+        // nothing was sent, so there is nothing to verify
+        if report.dry_run {
+            break;
+        }
+        tokio::time::sleep(SETTLE_DELAY).await;
+    }
+
+    if !report.clean && !report.dry_run && report.attempts == MAX_ATTEMPTS {
+        // final verification after the last attempt
+        let (orders, balances) = tokio::try_join!(exchange.get_open_orders(None), exchange.get_balances())?;
+        let positions = balances.positions;
+        report.clean = orders.orders.is_empty() && positions.is_empty();
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,227 @@
+// l1 actions signed locally, so every nonce comes from the process-wide allocator and the
+// same payload can go over rest or the websocket post method
+use anyhow::{Context, Result};
+use ethers::signers::LocalWallet;
+use ethers::types::{H160, H256};
+use ethers::utils::keccak256;
+use hyperliquid_rust_sdk::{
+    ClientOrder, ClientOrderRequest, ExchangeDataStatus, ExchangeDataStatuses, ExchangeResponse, ExchangeResponseStatus,
+    FilledOrder, RestingOrder,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use crate::services::user_actions::{self, SigningChain};
+use crate::types::{cloid_hex, BuilderFee};
+
+// l1 actions are signed by a phantom agent in this fixed domain, whatever the network
+const AGENT_CHAIN_ID: u64 = 1337;
+// tells the exchange which network the phantom agent signed for
+const MAINNET_AGENT_SOURCE: &str = "a";
+const TESTNET_AGENT_SOURCE: &str = "b";
+const AGENT_TYPES: &[(&str, &str)] = &[("source", "string"), ("connectionId", "bytes32")];
+
+// field names and order follow the exchange's wire format, the action hash depends on both
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Action {
+    Order(BulkOrder),
+    Cancel { cancels: Vec<CancelWire> },
+    CancelByCloid { cancels: Vec<CancelByCloidWire> },
+    #[serde(rename_all = "camelCase")]
+    UpdateLeverage { asset: u32, is_cross: bool, leverage: u32 },
+    // ntli is the usdc amount with 6 decimals, negative to remove margin
+    #[serde(rename_all = "camelCase")]
+    UpdateIsolatedMargin { asset: u32, is_buy: bool, ntli: i64 },
+    // no time disarms a scheduled cancel
+    ScheduleCancel {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time: Option<u64>,
+    },
+    SetReferrer { code: String },
+    // usd is an integer with 6 decimals
+    #[serde(rename_all = "camelCase")]
+    VaultTransfer { vault_address: String, is_deposit: bool, usd: u64 },
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkOrder {
+    pub orders: Vec<OrderWire>,
+    pub grouping: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builder: Option<BuilderWire>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrderWire {
+    #[serde(rename = "a")]
+    pub asset: u32,
+    #[serde(rename = "b")]
+    pub is_buy: bool,
+    #[serde(rename = "p")]
+    pub limit_px: String,
+    #[serde(rename = "s")]
+    pub sz: String,
+    #[serde(rename = "r")]
+    pub reduce_only: bool,
+    #[serde(rename = "t")]
+    pub order_type: OrderTypeWire,
+    #[serde(rename = "c", skip_serializing_if = "Option::is_none")]
+    pub cloid: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderTypeWire {
+    Limit { tif: String },
+    Trigger {
+        #[serde(rename = "isMarket")]
+        is_market: bool,
+        #[serde(rename = "triggerPx")]
+        trigger_px: String,
+        tpsl: String,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelWire {
+    #[serde(rename = "a")]
+    pub asset: u32,
+    #[serde(rename = "o")]
+    pub oid: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelByCloidWire {
+    pub asset: u32,
+    pub cloid: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BuilderWire {
+    #[serde(rename = "b")]
+    pub builder: String,
+    #[serde(rename = "f")]
+    pub fee: u64,
+}
+
+// prices and sizes go over the wire as decimal strings without trailing zeros
+pub fn float_to_wire(value: f64) -> String {
+    let mut s = format!("{:.8}", value);
+    while s.ends_with('0') {
+        s.pop();
+    }
+    if s.ends_with('.') {
+        s.pop();
+    }
+    if s == "-0" { "0".to_string() } else { s }
+}
+
+pub fn order_wire(order: &ClientOrderRequest, asset: u32) -> OrderWire {
+    OrderWire {
+        asset,
+        is_buy: order.is_buy,
+        limit_px: float_to_wire(order.limit_px),
+        sz: float_to_wire(order.sz),
+        reduce_only: order.reduce_only,
+        order_type: match &order.order_type {
+            ClientOrder::Limit(limit) => OrderTypeWire::Limit { tif: limit.tif.clone() },
+            ClientOrder::Trigger(trigger) => OrderTypeWire::Trigger {
+                is_market: trigger.is_market,
+                trigger_px: float_to_wire(trigger.trigger_px),
+                tpsl: trigger.tpsl.clone(),
+            },
+        },
+        cloid: order.cloid.as_ref().map(cloid_hex),
+    }
+}
+
+pub fn order_action(orders: Vec<OrderWire>, builder: Option<&BuilderFee>) -> Action {
+    Action::Order(BulkOrder {
+        orders,
+        grouping: "na".to_string(),
+        builder: builder.map(|builder| BuilderWire {
+            builder: builder.address.clone(),
+            fee: builder.tenths_bps(),
+        }),
+    })
+}
+
+// keccak of msgpack(action) ++ nonce ++ vault flag, what the agent signature commits to
+pub fn action_hash(action: &Action, nonce: u64, vault_address: Option<H160>) -> Result<H256> {
+    let mut bytes = rmp_serde::to_vec_named(action).context("Failed to encode action")?;
+    bytes.extend(nonce.to_be_bytes());
+    match vault_address {
+        Some(vault) => {
+            bytes.push(1);
+            bytes.extend(vault.to_fixed_bytes());
+        }
+        None => bytes.push(0),
+    }
+    Ok(H256(keccak256(bytes)))
+}
+
+pub fn agent_message(chain: SigningChain, connection_id: H256) -> Value {
+    let source = if chain.is_mainnet() { MAINNET_AGENT_SOURCE } else { TESTNET_AGENT_SOURCE };
+    json!({ "source": source, "connectionId": format!("{:#x}", connection_id) })
+}
+
+pub async fn sign_connection_id(wallet: &LocalWallet, chain: SigningChain, connection_id: H256) -> Result<Value> {
+    user_actions::sign_typed(wallet, ("Exchange", AGENT_CHAIN_ID), "Agent", AGENT_TYPES, agent_message(chain, connection_id)).await
+}
+
+// signed /exchange payload, the same body rest and the websocket post method take
+pub async fn signed_payload(wallet: &LocalWallet, chain: SigningChain, action: &Action, nonce: u64) -> Result<Value> {
+    let signature = sign_connection_id(wallet, chain, action_hash(action, nonce, None)?).await?;
+    Ok(json!({
+        "action": action,
+        "nonce": nonce,
+        "signature": signature,
+        "vaultAddress": null,
+    }))
+}
+
+// reads an order reply ({"status":"ok","response":{"type":"order","data":{"statuses":[...]}}}) the way the sdk would;
+// cancels answer in the same shape and other actions without data
+pub fn parse_order_reply(reply: &Value) -> Result<ExchangeResponseStatus> {
+    if reply["status"] != "ok" {
+        let error = reply["response"].as_str().map(str::to_string).unwrap_or_else(|| reply["response"].to_string());
+        return Ok(ExchangeResponseStatus::Err(error));
+    }
+    let response = &reply["response"];
+    let statuses = match response["data"]["statuses"].as_array() {
+        Some(statuses) => Some(ExchangeDataStatuses {
+            statuses: statuses.iter().map(parse_status).collect::<Result<_>>()?,
+        }),
+        None => None,
+    };
+    Ok(ExchangeResponseStatus::Ok(ExchangeResponse {
+        response_type: response["type"].as_str().unwrap_or_default().to_string(),
+        data: statuses,
+    }))
+}
+
+fn parse_status(status: &Value) -> Result<ExchangeDataStatus> {
+    if let Some(name) = status.as_str() {
+        return Ok(match name {
+            "success" => ExchangeDataStatus::Success,
+            "waitingForFill" => ExchangeDataStatus::WaitingForFill,
+            "waitingForTrigger" => ExchangeDataStatus::WaitingForTrigger,
+            other => anyhow::bail!("Unknown order status {}", other),
+        });
+    }
+    if let Some(error) = status["error"].as_str() {
+        return Ok(ExchangeDataStatus::Error(error.to_string()));
+    }
+    if let Some(oid) = status["resting"]["oid"].as_u64() {
+        return Ok(ExchangeDataStatus::Resting(RestingOrder { oid }));
+    }
+    let filled = &status["filled"];
+    match filled["oid"].as_u64() {
+        Some(oid) => Ok(ExchangeDataStatus::Filled(FilledOrder {
+            total_sz: filled["totalSz"].as_str().unwrap_or("0").to_string(),
+            avg_px: filled["avgPx"].as_str().unwrap_or("0").to_string(),
+            oid,
+        })),
+        None => anyhow::bail!("Unknown order status {}", status),
+    }
+}
@@ -0,0 +1,101 @@
+// distance to liquidation for every open position, escalating notifications as the buffer shrinks
+use std::{collections::HashMap, time::Duration};
+use anyhow::Result;
+use crate::{
+    services::{ExchangeService, Notifier},
+    types::{LiquidationLevel, LiquidationRisk, NotificationEvent, PositionLiquidation},
+};
+
+// fraction of the mark the price can still move against the position before liquidation, negative once past it
+pub fn distance(mark: f64, liquidation_price: f64, size: f64) -> f64 {
+    if size > 0.0 { (mark - liquidation_price) / mark } else { (liquidation_price - mark) / mark }
+}
+
+// warn at `warn_at`, info from twice that and critical from half of it
+pub fn level(distance: f64, warn_at: f64) -> LiquidationLevel {
+    if distance <= warn_at / 2.0 {
+        LiquidationLevel::Critical
+    } else if distance <= warn_at {
+        LiquidationLevel::Warn
+    } else if distance <= warn_at * 2.0 {
+        LiquidationLevel::Info
+    } else {
+        LiquidationLevel::Safe
+    }
+}
+
+// positions without a liquidation price or a mark are left out
+pub fn assess(positions: &[PositionLiquidation], marks: &HashMap<String, f64>, warn_at: f64) -> Vec<LiquidationRisk> {
+    positions
+        .iter()
+        .filter_map(|position| {
+            let liquidation_price = position.liquidation_price?;
+            let mark = *marks.get(&position.symbol).filter(|mark| **mark > 0.0)?;
+            let distance = distance(mark, liquidation_price, position.size);
+            Some(LiquidationRisk {
+                symbol: position.symbol.clone(),
+                size: position.size,
+                mark,
+                liquidation_price,
+                distance,
+                level: level(distance, warn_at),
+            })
+        })
+        .collect()
+}
+
+// the level last reported per symbol, so each step up notifies once
+#[derive(Debug, Default)]
+pub struct Escalation {
+    levels: HashMap<String, LiquidationLevel>,
+}
+
+impl Escalation {
+    // Some(level) when it is worse than the last one for the symbol; improvements are remembered
+    // silently so that sliding back notifies again
+    pub fn update(&mut self, symbol: &str, level: LiquidationLevel) -> Option<LiquidationLevel> {
+        let previous = self.levels.insert(symbol.to_string(), level).unwrap_or(LiquidationLevel::Safe);
+        (level > previous).then_some(level)
+    }
+
+    // forget closed positions, a reopened one starts from safe
+    pub fn retain(&mut self, open: &[LiquidationRisk]) {
+        self.levels.retain(|symbol, _| open.iter().any(|risk| &risk.symbol == symbol));
+    }
+}
+
+// runs until ctrl-c, handing each round to `on_round` and every escalation to the notifier
+pub async fn run(
+    exchange: &ExchangeService,
+    notifier: &Notifier,
+    warn_at: f64,
+    interval: Duration,
+    mut on_round: impl FnMut(&[LiquidationRisk]),
+) -> Result<()> {
+    let mut ticker = tokio::time::interval(interval);
+    let mut escalation = Escalation::default();
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = ticker.tick() => {}
+        }
+
+        let (positions, status) = match tokio::try_join!(exchange.get_liquidation_prices(), exchange.get_status()) {
+            Ok(sampled) => sampled,
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to sample positions and marks");
+                continue;
+            }
+        };
+        let marks: HashMap<String, f64> = status.markets.into_iter().map(|m| (m.symbol, m.mark_price)).collect();
+        let risks = assess(&positions, &marks, warn_at);
+        escalation.retain(&risks);
+        on_round(&risks);
+        for risk in &risks {
+            if escalation.update(&risk.symbol, risk.level).is_some() {
+                notifier.notify(NotificationEvent::Liquidation(risk.clone())).await;
+            }
+        }
+    }
+}
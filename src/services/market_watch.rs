@@ -0,0 +1,96 @@
+// samples every perp's open interest and 24h volume into the journal and flags sharp moves
+use std::{collections::HashMap, time::Duration};
+use anyhow::Result;
+use crate::{
+    services::{journal::Journal, ExchangeService, Notifier},
+    types::{MarketAnomaly, MarketInfo, MarketSample, NotificationEvent},
+};
+
+pub struct WatchParams {
+    // fractional change that counts as an anomaly, 0.25 = 25%
+    pub threshold: f64,
+    pub window: Duration,
+    pub interval: Duration,
+    // skip markets with less open interest (USD) than this
+    pub min_open_interest: f64,
+}
+
+// compares each market to its oldest sample in the window
+pub fn detect(
+    baselines: &HashMap<String, MarketSample>,
+    markets: &[MarketInfo],
+    threshold: f64,
+    min_open_interest: f64,
+) -> Vec<MarketAnomaly> {
+    let mut anomalies = Vec::new();
+    for market in markets {
+        if market.open_interest * market.mark_price < min_open_interest {
+            continue;
+        }
+        let Some(baseline) = baselines.get(&market.symbol) else {
+            continue;
+        };
+        for (metric, from, to) in [
+            ("open_interest", baseline.open_interest, market.open_interest),
+            ("volume_24h", baseline.volume_24h, market.volume_24h),
+        ] {
+            if from <= 0.0 {
+                continue;
+            }
+            let change = to / from - 1.0;
+            if change.abs() >= threshold {
+                anomalies.push(MarketAnomaly {
+                    symbol: market.symbol.clone(),
+                    metric: metric.to_string(),
+                    from,
+                    to,
+                    change,
+                    since: baseline.time,
+                });
+            }
+        }
+    }
+    anomalies
+}
+
+// runs until ctrl-c, handing each new anomaly to `on_anomaly` and the notifier
+pub async fn run(
+    exchange: &ExchangeService,
+    journal: &Journal,
+    notifier: &Notifier,
+    params: &WatchParams,
+    mut on_anomaly: impl FnMut(&MarketAnomaly),
+) -> Result<()> {
+    let mut ticker = tokio::time::interval(params.interval);
+    // (symbol, metric) -> when it last alerted, so a move is reported once per window
+    let mut alerted: HashMap<(String, String), u64> = HashMap::new();
+    let window_ms = params.window.as_millis() as u64;
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = ticker.tick() => {}
+        }
+
+        let markets = match exchange.get_status().await {
+            Ok(status) => status.markets,
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to sample market contexts");
+                continue;
+            }
+        };
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        journal.record_market_contexts(now, &markets).await?;
+
+        let baselines = journal.market_baselines(now.saturating_sub(window_ms)).await?;
+        for anomaly in detect(&baselines, &markets, params.threshold, params.min_open_interest) {
+            let key = (anomaly.symbol.clone(), anomaly.metric.clone());
+            if alerted.get(&key).is_some_and(|at| now.saturating_sub(*at) < window_ms) {
+                continue;
+            }
+            alerted.insert(key, now);
+            on_anomaly(&anomaly);
+            notifier.notify(NotificationEvent::MarketAnomaly(anomaly)).await;
+        }
+    }
+}
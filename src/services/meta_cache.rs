@@ -0,0 +1,134 @@
+// perp and spot metadata (names, size decimals, max leverage), reused in memory and across runs on disk
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use crate::types::{AssetInfo, CacheConfig, SpotMeta};
+
+pub struct MetaCache {
+    ttl: Duration,
+    path: Option<PathBuf>,
+    state: Mutex<CacheFile>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CacheFile {
+    perp: Option<Entry<Vec<AssetInfo>>>,
+    spot: Option<Entry<SpotMeta>>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Entry<T> {
+    // unix millis
+    fetched_at: u64,
+    value: T,
+}
+
+impl MetaCache {
+    // one file per api host, testnet and mainnet list different assets
+    pub fn new(config: &CacheConfig, api_url: &str) -> Self {
+        let path = config.disk.then(|| {
+            let host = api_url
+                .split("://")
+                .last()
+                .unwrap_or(api_url)
+                .trim_end_matches('/')
+                .replace(|c: char| !c.is_ascii_alphanumeric() && c != '.' && c != '-', "_");
+            cache_dir(config).join(format!("meta-{}.json", host))
+        });
+        let state = path.as_deref().and_then(read_file).unwrap_or_default();
+        Self {
+            ttl: Duration::from_secs(config.ttl_secs),
+            path,
+            state: Mutex::new(state),
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn perp(&self) -> Option<Vec<AssetInfo>> {
+        let state = self.lock();
+        state.perp.as_ref().filter(|entry| self.is_fresh(entry)).map(|entry| entry.value.clone())
+    }
+
+    pub fn spot(&self) -> Option<SpotMeta> {
+        let state = self.lock();
+        state.spot.as_ref().filter(|entry| self.is_fresh(entry)).map(|entry| entry.value.clone())
+    }
+
+    pub fn store_perp(&self, universe: Vec<AssetInfo>) {
+        let mut state = self.lock();
+        state.perp = Some(Entry { fetched_at: now_millis(), value: universe });
+        self.write(&state);
+    }
+
+    pub fn store_spot(&self, meta: SpotMeta) {
+        let mut state = self.lock();
+        state.spot = Some(Entry { fetched_at: now_millis(), value: meta });
+        self.write(&state);
+    }
+
+    // forgets everything, in memory and on disk
+    pub fn clear(&self) -> Result<()> {
+        *self.lock() = CacheFile::default();
+        if let Some(path) = &self.path
+            && path.exists()
+        {
+            std::fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    fn is_fresh<T>(&self, entry: &Entry<T>) -> bool {
+        now_millis().saturating_sub(entry.fetched_at) < self.ttl.as_millis() as u64
+    }
+
+    // a cache that can't be written only costs a refetch next run
+    fn write(&self, state: &CacheFile) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| Ok(serde_json::to_vec(state)?))
+            .and_then(|bytes| Ok(std::fs::write(path, bytes)?));
+        if let Err(e) = result {
+            tracing::debug!(error = %e, path = %path.display(), "failed to write metadata cache");
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheFile> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn cache_dir(config: &CacheConfig) -> PathBuf {
+    if let Some(dir) = &config.dir {
+        return PathBuf::from(dir);
+    }
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .unwrap_or_else(std::env::temp_dir)
+        .join("hl")
+}
+
+// an unreadable or outdated file is treated as empty
+fn read_file(path: &Path) -> Option<CacheFile> {
+    let bytes = std::fs::read(path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
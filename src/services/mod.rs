@@ -1,7 +1,62 @@
 // export modules
+pub mod accounts;
+pub mod audit;
+pub mod book_recorder;
+pub mod breakeven;
+pub mod config_check;
+pub mod contacts;
+pub mod daily_loss;
+pub mod deadman;
+pub mod download;
 pub mod exchange;
+pub mod execution_report;
+pub mod fees;
+pub mod guard;
+pub mod health;
+pub mod impact;
+pub mod indicators;
+pub mod influx;
+pub mod journal;
+pub mod kafka;
+pub mod kill_switch;
+pub mod l1_actions;
+pub mod liquidation_watch;
+pub mod market_watch;
+pub mod mqtt;
+pub mod meta_cache;
+pub mod net;
+pub mod nonce;
 pub mod trading;
 pub mod streaming;
+pub mod notifications;
+pub mod order_groups;
+pub mod paper;
+pub mod peg;
+pub mod pool;
+pub mod price;
+pub mod protect;
+pub mod retry;
+pub mod rate_budget;
+pub mod rate_limit;
+pub mod redis_bridge;
+pub mod scanner;
+pub mod smart_order;
+pub mod snapshot;
+pub mod risk_reload;
+pub mod spot;
+pub mod spread_monitor;
+pub mod strategy;
+pub mod symbols;
+pub mod tape;
+pub mod stats;
+pub mod tax;
+pub mod user_actions;
+pub mod volatility;
+pub mod withdrawals;
+pub mod watch;
+pub mod ws_hub;
+pub mod ws_post;
 
 pub use exchange::*;
-pub use trading::*;
\ No newline at end of file
+pub use trading::*;
+pub use notifications::Notifier;
@@ -0,0 +1,227 @@
+// mqtt bridge: trades, bbo, fills and positions published under <prefix>/<kind>/<symbol>
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use anyhow::{Context, Result};
+use rumqttc::{AsyncClient, MqttOptions, QoS, Transport};
+use serde_json::{json, Value};
+use tokio::sync::broadcast::error::RecvError;
+use crate::services::{
+    exchange::to_fill_info,
+    ws_hub::{self, HubSubscription, SubscriptionKey, WsHub},
+    ExchangeService,
+};
+use crate::types::{BalanceResponse, MqttConfig, PositionInfo, UserFill};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+// requests queued for the broker before publishing waits
+const QUEUE_CAPACITY: usize = 1024;
+
+// broker url -> connection options, also used to validate `mqtt_broker` at config load
+pub fn mqtt_options(broker: &str, client_id: &str) -> Result<MqttOptions> {
+    let url = reqwest::Url::parse(broker).with_context(|| format!("Invalid mqtt_broker '{}'", broker))?;
+    let (tls, default_port) = match url.scheme() {
+        "mqtt" | "tcp" => (false, 1883),
+        "mqtts" | "ssl" => (true, 8883),
+        other => anyhow::bail!("Unsupported mqtt_broker scheme '{}', use mqtt:// or mqtts://", other),
+    };
+    let host = url.host_str().with_context(|| format!("mqtt_broker '{}' has no host", broker))?;
+
+    let mut options = MqttOptions::new(client_id, host, url.port().unwrap_or(default_port));
+    options.set_keep_alive(Duration::from_secs(30));
+    if !url.username().is_empty() {
+        options.set_credentials(url.username(), url.password().unwrap_or_default());
+    }
+    if tls {
+        options.set_transport(Transport::tls_with_default_config());
+    }
+    Ok(options)
+}
+
+pub fn topic(prefix: &str, kind: &str, symbol: &str) -> String {
+    // '/' in spot pair names would add a topic level
+    format!("{}/{}/{}", prefix, kind, symbol.replace('/', "-"))
+}
+
+// one upstream trades message -> one message per trade
+pub fn trade_messages(prefix: &str, text: &str) -> Vec<(String, Value)> {
+    ws_hub::parse_trades(text)
+        .into_iter()
+        .map(|trade| {
+            let payload = json!({
+                "symbol": trade.coin,
+                "side": if trade.side == "B" { "BUY" } else { "SELL" },
+                "price": trade.px.parse::<f64>().unwrap_or(0.0),
+                "size": trade.sz.parse::<f64>().unwrap_or(0.0),
+                "time": trade.time,
+                "trade_id": trade.tid,
+            });
+            (topic(prefix, "trades", &trade.coin), payload)
+        })
+        .collect()
+}
+
+// best bid and offer, either side missing on an empty book
+pub fn bbo_message(prefix: &str, text: &str) -> Option<(String, Value)> {
+    let message: Value = serde_json::from_str(text).ok()?;
+    if message.get("channel")?.as_str()? != "bbo" {
+        return None;
+    }
+    let data = &message["data"];
+    let coin = data.get("coin")?.as_str()?;
+    let level = |i: usize| {
+        let level = data.get("bbo")?.get(i)?;
+        let px = level.get("px")?.as_str()?.parse::<f64>().ok()?;
+        let sz = level.get("sz")?.as_str()?.parse::<f64>().ok()?;
+        Some((px, sz))
+    };
+    let (bid, ask) = (level(0), level(1));
+    let payload = json!({
+        "symbol": coin,
+        "bid": bid.map(|(px, _)| px),
+        "bid_size": bid.map(|(_, sz)| sz),
+        "ask": ask.map(|(px, _)| px),
+        "ask_size": ask.map(|(_, sz)| sz),
+        "time": data.get("time"),
+    });
+    Some((topic(prefix, "bbo", coin), payload))
+}
+
+// live fills only, the snapshot sent on subscribe is history
+pub fn fill_messages(prefix: &str, text: &str) -> Vec<(String, Value)> {
+    let Ok(message) = serde_json::from_str::<Value>(text) else {
+        return Vec::new();
+    };
+    let data = &message["data"];
+    if message["channel"] != "userFills" || data["isSnapshot"].as_bool().unwrap_or(false) {
+        return Vec::new();
+    }
+    data["fills"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|fill| serde_json::from_value::<UserFill>(fill.clone()).ok())
+        .map(to_fill_info)
+        .map(|fill| (topic(prefix, "fills", &fill.symbol), json!(fill)))
+        .collect()
+}
+
+// positions that changed since `previous`, None for ones that were closed (clears the retained message)
+pub fn position_messages(
+    prefix: &str,
+    previous: &HashMap<String, PositionInfo>,
+    balances: &BalanceResponse,
+) -> Vec<(String, Option<Value>)> {
+    let mut messages: Vec<(String, Option<Value>)> = balances
+        .positions
+        .iter()
+        .filter(|p| {
+            previous
+                .get(&p.symbol)
+                .is_none_or(|old| (old.size, old.entry_price, old.leverage) != (p.size, p.entry_price, p.leverage))
+        })
+        .map(|p| (topic(prefix, "positions", &p.symbol), Some(json!(p))))
+        .collect();
+    messages.extend(
+        previous
+            .keys()
+            .filter(|symbol| !balances.positions.iter().any(|p| &p.symbol == *symbol))
+            .map(|symbol| (topic(prefix, "positions", symbol), None)),
+    );
+    messages
+}
+
+// runs until a forwarder fails, callers stop it on ctrl-c or shutdown
+pub async fn run(broker: &str, config: &MqttConfig, exchange: ExchangeService, hub: Arc<WsHub>) -> Result<()> {
+    let client_id = format!("hl-{}", std::process::id());
+    let (client, mut event_loop) = AsyncClient::new(mqtt_options(broker, &client_id)?, QUEUE_CAPACITY);
+    let qos = if config.qos == 0 { QoS::AtMostOnce } else { QoS::AtLeastOnce };
+    let publisher = Publisher { client, qos, prefix: config.topic_prefix.clone() };
+
+    // the event loop does the network io and reconnects on its own when polled again
+    let connection = tokio::spawn(async move {
+        loop {
+            if let Err(e) = event_loop.poll().await {
+                tracing::warn!(error = %e, "mqtt: broker connection failed, retrying");
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    });
+
+    let mut forwarders = tokio::task::JoinSet::new();
+    for symbol in &config.symbols {
+        let symbol = &exchange.resolve_symbol(symbol).await?.name;
+        let trades = hub.subscribe_owned(SubscriptionKey::for_coin("trades", symbol));
+        forwarders.spawn(publisher.clone().forward(trades, trade_messages));
+        let bbo = hub.subscribe_owned(SubscriptionKey::for_coin("bbo", symbol));
+        forwarders.spawn(publisher.clone().forward(bbo, |prefix, text| bbo_message(prefix, text).into_iter().collect()));
+    }
+    let fills = hub.subscribe_owned(SubscriptionKey::for_user("userFills", &exchange.get_wallet_address()?));
+    forwarders.spawn(publisher.clone().forward(fills, fill_messages));
+    let interval = Duration::from_secs(config.position_interval_secs.max(1));
+    forwarders.spawn(publisher.clone().poll_positions(exchange, interval));
+
+    tracing::info!(broker = %broker, symbols = config.symbols.len(), "mqtt: bridge running");
+    let result = forwarders.join_next().await.map(|joined| joined.map_err(anyhow::Error::from).and_then(|r| r));
+    connection.abort();
+    result.unwrap_or(Ok(()))
+}
+
+#[derive(Clone)]
+struct Publisher {
+    client: AsyncClient,
+    qos: QoS,
+    prefix: String,
+}
+
+impl Publisher {
+    async fn forward(
+        self,
+        mut subscription: HubSubscription,
+        to_messages: impl Fn(&str, &str) -> Vec<(String, Value)>,
+    ) -> Result<()> {
+        loop {
+            match subscription.updates.recv().await {
+                Ok(text) => {
+                    for (topic, payload) in to_messages(&self.prefix, &text) {
+                        self.publish(&topic, Some(payload), false).await?;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => tracing::warn!(skipped, "mqtt: falling behind, messages dropped"),
+                Err(RecvError::Closed) => anyhow::bail!("Upstream stream closed"),
+            }
+        }
+    }
+
+    // positions are retained, so a new subscriber sees the current state straight away
+    async fn poll_positions(self, exchange: ExchangeService, interval: Duration) -> Result<()> {
+        let mut previous: HashMap<String, PositionInfo> = HashMap::new();
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let balances = match exchange.get_balances().await {
+                Ok(balances) => balances,
+                Err(e) => {
+                    tracing::warn!(error = %e, "mqtt: position refresh failed");
+                    continue;
+                }
+            };
+            for (topic, payload) in position_messages(&self.prefix, &previous, &balances) {
+                self.publish(&topic, payload, true).await?;
+            }
+            let account = json!({
+                "account_value": balances.account_value,
+                "withdrawable": balances.withdrawable,
+                "cross_margin_used": balances.cross_margin_used,
+            });
+            self.publish(&format!("{}/account", self.prefix), Some(account), true).await?;
+            previous = balances.positions.into_iter().map(|p| (p.symbol.clone(), p)).collect();
+        }
+    }
+
+    async fn publish(&self, topic: &str, payload: Option<Value>, retain: bool) -> Result<()> {
+        let bytes = payload.map(|p| p.to_string().into_bytes()).unwrap_or_default();
+        self.client
+            .publish(topic, self.qos, retain, bytes)
+            .await
+            .with_context(|| format!("Failed to publish to {}", topic))
+    }
+}
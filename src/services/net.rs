@@ -0,0 +1,123 @@
+// http clients and websocket connections built from the [http] timeouts and proxy settings
+use std::time::Duration;
+use anyhow::{Context, Result};
+use reqwest::{Client, Proxy, Url};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    client_async_tls, connect_async,
+    tungstenite::client::IntoClientRequest,
+    MaybeTlsStream, WebSocketStream,
+};
+use crate::types::HttpConfig;
+
+pub type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+pub fn http_client(config: &HttpConfig) -> Result<Client> {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs));
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(Proxy::all(proxy.as_str()).with_context(|| format!("Invalid proxy {}", proxy))?);
+    }
+    builder.build().context("Failed to create HTTP client")
+}
+
+// checked when the config loads so a typo doesn't surface as a connection error later
+pub fn validate_proxy(proxy: &str) -> Result<()> {
+    let url = Url::parse(proxy).with_context(|| format!("Invalid proxy url {}", proxy))?;
+    if !matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h") {
+        anyhow::bail!("Unsupported proxy scheme {}, expected http, https, socks5 or socks5h", url.scheme());
+    }
+    if url.host_str().is_none() {
+        anyhow::bail!("Proxy url {} has no host", proxy);
+    }
+    Ok(())
+}
+
+// connect_async, but through the configured proxy and bounded by the connect timeout
+pub async fn connect_ws(url: &str, config: &HttpConfig) -> Result<WsStream> {
+    let timeout = Duration::from_secs(config.connect_timeout_secs);
+    let connect = async {
+        match &config.proxy {
+            None => Ok(connect_async(url).await.context("Failed to connect to WebSocket")?.0),
+            Some(proxy) => connect_ws_via(url, proxy).await,
+        }
+    };
+    tokio::time::timeout(timeout, connect)
+        .await
+        .with_context(|| format!("Timed out connecting to {} after {}s", url, timeout.as_secs()))?
+}
+
+async fn connect_ws_via(url: &str, proxy: &str) -> Result<WsStream> {
+    let request = url.into_client_request().context("Invalid WebSocket url")?;
+    let host = request.uri().host().context("WebSocket url has no host")?.to_string();
+    let port = request
+        .uri()
+        .port_u16()
+        .unwrap_or(if request.uri().scheme_str() == Some("wss") { 443 } else { 80 });
+
+    let proxy = Url::parse(proxy).with_context(|| format!("Invalid proxy url {}", proxy))?;
+    let proxy_addr = format!(
+        "{}:{}",
+        proxy.host_str().context("Proxy url has no host")?,
+        proxy.port_or_known_default().unwrap_or(1080)
+    );
+    let stream = match proxy.scheme() {
+        "socks5" | "socks5h" => {
+            let target = (host.as_str(), port);
+            let socks = if proxy.username().is_empty() {
+                tokio_socks::tcp::Socks5Stream::connect(proxy_addr.as_str(), target).await
+            } else {
+                tokio_socks::tcp::Socks5Stream::connect_with_password(
+                    proxy_addr.as_str(),
+                    target,
+                    proxy.username(),
+                    proxy.password().unwrap_or_default(),
+                )
+                .await
+            };
+            socks.with_context(|| format!("SOCKS5 proxy {} failed to reach {}:{}", proxy_addr, host, port))?.into_inner()
+        }
+        _ => http_connect(&proxy, &proxy_addr, &host, port).await?,
+    };
+
+    let (ws_stream, _) = client_async_tls(request, stream)
+        .await
+        .context("Failed to connect to WebSocket through proxy")?;
+    Ok(ws_stream)
+}
+
+// opens a tunnel with CONNECT host:port, the way http proxies carry tls
+async fn http_connect(proxy: &Url, proxy_addr: &str, host: &str, port: u16) -> Result<TcpStream> {
+    let mut stream = TcpStream::connect(proxy_addr)
+        .await
+        .with_context(|| format!("Failed to connect to proxy {}", proxy_addr))?;
+
+    let mut request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+    if !proxy.username().is_empty() {
+        use base64::Engine as _;
+        let credentials = format!("{}:{}", proxy.username(), proxy.password().unwrap_or_default());
+        request.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            base64::engine::general_purpose::STANDARD.encode(credentials)
+        ));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await.context("Failed to send CONNECT to proxy")?;
+
+    let mut reader = BufReader::new(&mut stream);
+    let mut status = String::new();
+    reader.read_line(&mut status).await.context("Failed to read proxy reply")?;
+    if status.split_whitespace().nth(1) != Some("200") {
+        anyhow::bail!("Proxy {} refused CONNECT to {}:{}: {}", proxy_addr, host, port, status.trim());
+    }
+    // skip the remaining headers up to the blank line
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await.context("Failed to read proxy reply")? == 0 || line == "\r\n" {
+            break;
+        }
+    }
+    Ok(stream)
+}
@@ -0,0 +1,55 @@
+// nonces for actions signed in this crate: millisecond timestamps, strictly increasing per process
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// the exchange refuses nonces more than a day ahead of its clock
+const MAX_LEAD_MS: u64 = 24 * 60 * 60 * 1000;
+const DRIFT_WARN_MS: u64 = 1_000;
+
+#[derive(Default)]
+pub struct NonceManager {
+    last: Mutex<u64>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn next(&self) -> u64 {
+        self.next_at(now_millis())
+    }
+
+    // the clock reading when free, one past the last nonce when several land in the same
+    // millisecond or the clock stepped backwards
+    pub fn next_at(&self, now: u64) -> u64 {
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        let nonce = now.max(*last + 1);
+        // bursts only push a few ms ahead, more means the clock stepped back
+        if nonce > now + DRIFT_WARN_MS {
+            tracing::warn!(nonce, now, "nonces running ahead of the system clock");
+        }
+        if nonce > now + MAX_LEAD_MS {
+            tracing::error!(nonce, now, "nonce is more than a day ahead of the clock and will be rejected");
+        }
+        *last = nonce;
+        nonce
+    }
+}
+
+// one allocator for the whole process, so separate services signing for the same wallet can't collide
+pub fn global() -> &'static NonceManager {
+    static NONCES: OnceLock<NonceManager> = OnceLock::new();
+    NONCES.get_or_init(NonceManager::new)
+}
+
+pub fn next() -> u64 {
+    global().next()
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,186 @@
+use crate::services::net;
+use crate::services::retry::{Idempotency, RetryPolicy};
+use crate::types::{HttpConfig, LiquidationLevel, NotificationConfig, NotificationEvent};
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde_json::json;
+
+const SINK_CONSOLE: &str = "console";
+const SINK_DISCORD: &str = "discord";
+
+// discord embed colors
+const COLOR_GREEN: u32 = 0x2ecc71;
+const COLOR_BLUE: u32 = 0x3498db;
+const COLOR_RED: u32 = 0xe74c3c;
+const COLOR_GOLD: u32 = 0xf1c40f;
+
+#[derive(Clone)]
+pub struct Notifier {
+    client: Client,
+    retry: RetryPolicy,
+    config: NotificationConfig,
+}
+
+impl Notifier {
+    pub fn new(config: NotificationConfig, http: &HttpConfig) -> Self {
+        Self {
+            // an unusable proxy is caught at config load, this is only the last resort
+            client: net::http_client(http).unwrap_or_else(|_| Client::new()),
+            retry: RetryPolicy::from(http),
+            config,
+        }
+    }
+
+    // delivers the event to every sink routed for its kind, failures never bubble up
+    pub async fn notify(&self, event: NotificationEvent) {
+        for sink in self.sinks_for(event.kind()) {
+            let result = match sink.as_str() {
+                SINK_CONSOLE => {
+                    eprintln!("[{}] {}: {}", event.kind(), event.title(), event.summary());
+                    Ok(())
+                }
+                SINK_DISCORD => self.send_discord(&event).await,
+                other => Err(anyhow::anyhow!("Unknown notification sink: {}", other)),
+            };
+
+            if let Err(e) = result {
+                tracing::warn!(sink = %sink, kind = event.kind(), "notification failed: {}", e);
+            }
+        }
+    }
+
+    // sinks configured for an event kind, "all" expands to every available sink
+    fn sinks_for(&self, kind: &str) -> Vec<String> {
+        let routes = self
+            .config
+            .routes
+            .get(kind)
+            .or_else(|| self.config.routes.get("default"));
+
+        let Some(routes) = routes else {
+            // no routing configured: send everything to discord if a webhook exists
+            return if self.config.discord_webhook_url.is_some() {
+                vec![SINK_DISCORD.to_string()]
+            } else {
+                Vec::new()
+            };
+        };
+
+        if routes.iter().any(|r| r == "all") {
+            let mut all = vec![SINK_CONSOLE.to_string()];
+            if self.config.discord_webhook_url.is_some() {
+                all.push(SINK_DISCORD.to_string());
+            }
+            return all;
+        }
+
+        routes.clone()
+    }
+
+    async fn send_discord(&self, event: &NotificationEvent) -> Result<()> {
+        let url = self
+            .config
+            .discord_webhook_url
+            .as_ref()
+            .context("discord sink routed but no discord_webhook_url configured")?;
+
+        let payload = json!({ "embeds": [discord_embed(event)] });
+
+        let response = self
+            .retry
+            .send("discord webhook", Idempotency::Idempotent, || self.client.post(url).json(&payload))
+            .await
+            .context("Failed to send Discord webhook")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Discord webhook returned {}", response.status());
+        }
+
+        Ok(())
+    }
+}
+
+fn discord_embed(event: &NotificationEvent) -> serde_json::Value {
+    let timestamp = chrono::Utc::now().to_rfc3339();
+
+    let (color, fields) = match event {
+        NotificationEvent::OrderConfirmation { symbol, side, qty, order_id, price } => (
+            COLOR_BLUE,
+            vec![
+                field("Symbol", symbol),
+                field("Side", side),
+                field("Quantity", &format!("{}", qty)),
+                field(
+                    "Price",
+                    &price.map(|p| format!("${:.4}", p)).unwrap_or_else(|| "market".to_string()),
+                ),
+                field("Order ID", &order_id.to_string()),
+            ],
+        ),
+        NotificationEvent::Fill { symbol, side, filled_qty, avg_price, order_id } => (
+            COLOR_GREEN,
+            vec![
+                field("Symbol", symbol),
+                field("Side", side),
+                field("Filled", &format!("{}", filled_qty)),
+                field("Avg Price", &format!("${:.4}", avg_price)),
+                field("Order ID", &order_id.to_string()),
+            ],
+        ),
+        NotificationEvent::RiskViolation { symbol, reason } => (
+            COLOR_RED,
+            vec![field("Symbol", symbol), field("Reason", reason)],
+        ),
+        NotificationEvent::DailyPnl(summary) => {
+            let mut fields = vec![
+                field("Account Value", &format!("${:.2}", summary.account_value)),
+                field("Unrealized PnL", &format!("${:.2}", summary.unrealized_pnl)),
+                field("Open Positions", &summary.open_positions.to_string()),
+            ];
+            if let Some((symbol, pnl)) = &summary.best_position {
+                fields.push(field("Best", &format!("{} ${:.2}", symbol, pnl)));
+            }
+            if let Some((symbol, pnl)) = &summary.worst_position {
+                fields.push(field("Worst", &format!("{} ${:.2}", symbol, pnl)));
+            }
+            let color = if summary.unrealized_pnl >= 0.0 { COLOR_GREEN } else { COLOR_GOLD };
+            (color, fields)
+        }
+        NotificationEvent::MarketAnomaly(anomaly) => (
+            COLOR_GOLD,
+            vec![
+                field("Symbol", &anomaly.symbol),
+                field("Metric", &anomaly.metric),
+                field("Change", &format!("{:+.1}%", anomaly.change * 100.0)),
+                field("From", &format!("{:.0}", anomaly.from)),
+                field("To", &format!("{:.0}", anomaly.to)),
+            ],
+        ),
+        NotificationEvent::Liquidation(risk) => (
+            match risk.level {
+                LiquidationLevel::Critical => COLOR_RED,
+                LiquidationLevel::Warn => COLOR_GOLD,
+                _ => COLOR_BLUE,
+            },
+            vec![
+                field("Symbol", &risk.symbol),
+                field("Size", &format!("{:+}", risk.size)),
+                field("Mark", &format!("${:.4}", risk.mark)),
+                field("Liquidation", &format!("${:.4}", risk.liquidation_price)),
+                field("Distance", &format!("{:.2}%", risk.distance * 100.0)),
+            ],
+        ),
+    };
+
+    json!({
+        "title": event.title(),
+        "description": event.summary(),
+        "color": color,
+        "fields": fields,
+        "timestamp": timestamp,
+    })
+}
+
+fn field(name: &str, value: &str) -> serde_json::Value {
+    json!({ "name": name, "value": value, "inline": true })
+}
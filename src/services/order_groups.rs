@@ -0,0 +1,80 @@
+// named groups of resting orders, so a ladder placed order by order can be cancelled as a unit
+use std::{collections::BTreeMap, path::Path};
+use anyhow::{Context, Result};
+use crate::{
+    services::TradingService,
+    types::{GroupCancelReport, GroupedOrder},
+};
+
+pub struct OrderGroups {
+    path: String,
+    groups: BTreeMap<String, Vec<GroupedOrder>>,
+}
+
+impl OrderGroups {
+    pub fn load(path: &str) -> Result<Self> {
+        let groups = if Path::new(path).exists() {
+            let raw = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read order groups {}", path))?;
+            serde_json::from_str(&raw).with_context(|| format!("Failed to parse order groups {}", path))?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self { path: path.to_string(), groups })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let tmp = format!("{}.tmp", self.path);
+        std::fs::write(&tmp, serde_json::to_string_pretty(&self.groups)?)
+            .with_context(|| format!("Failed to write order groups {}", tmp))?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to write order groups {}", self.path))
+    }
+
+    pub fn add(&mut self, group: &str, order: GroupedOrder) {
+        self.groups.entry(group.to_string()).or_default().push(order);
+    }
+
+    pub fn get(&self, group: &str) -> Option<&[GroupedOrder]> {
+        self.groups.get(group).map(Vec::as_slice)
+    }
+
+    pub fn remove(&mut self, group: &str) -> Option<Vec<GroupedOrder>> {
+        self.groups.remove(group)
+    }
+}
+
+// bulk-cancels the group's orders that are still resting, then forgets the group
+pub async fn cancel_group(trading: &TradingService, groups: &mut OrderGroups, group: &str) -> Result<GroupCancelReport> {
+    let orders = groups
+        .get(group)
+        .with_context(|| format!("No order group named '{}'", group))?;
+
+    let open = trading.open_order_ids().await?;
+    let live: Vec<GroupedOrder> = orders.iter().filter(|o| open.contains(&o.order_id)).cloned().collect();
+
+    let mut report = GroupCancelReport {
+        not_live: orders.len() - live.len(),
+        dry_run: trading.is_dry_run(),
+        ..Default::default()
+    };
+
+    if !live.is_empty() {
+        let results = trading.bulk_cancel(&live).await?;
+        for (order, result) in live.iter().zip(results) {
+            match result {
+                Ok(()) => report.cancelled += 1,
+                Err(e) => report.errors.push(format!("{} #{}: {}", order.symbol, order.order_id, e)),
+            }
+        }
+    }
+
+    // keep the group around if anything is left to retry
+    if !report.dry_run && report.errors.is_empty() {
+        groups.remove(group);
+        groups.save()?;
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,273 @@
+// local matching simulator: market orders fill at mid +/- slippage, limits when trades print through them
+use std::{collections::HashSet, path::Path, time::Duration};
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::protocol::Message;
+use crate::{
+    services::{net, trading::{MAKER_FEE_RATE, TAKER_FEE_RATE}},
+    types::{
+        streaming::{TradeData, WSMessage},
+        HttpConfig, OrderRequest, OrderResult, PaperAccount, PaperConfig, PaperFill, PaperOrder,
+    },
+};
+
+const SIZE_EPSILON: f64 = 1e-9;
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+pub struct PaperEngine {
+    path: String,
+    slippage: f64,
+    account: PaperAccount,
+}
+
+impl PaperEngine {
+    // loads the persisted account, or starts a fresh one with the configured balance
+    pub fn load(config: &PaperConfig) -> Result<Self> {
+        let account = if Path::new(&config.path).exists() {
+            let raw = std::fs::read_to_string(&config.path)
+                .with_context(|| format!("Failed to read paper account {}", config.path))?;
+            serde_json::from_str(&raw)
+                .with_context(|| format!("Failed to parse paper account {}", config.path))?
+        } else {
+            PaperAccount::new(config.starting_balance)
+        };
+
+        Ok(Self::with_account(config, account))
+    }
+
+    pub fn with_account(config: &PaperConfig, account: PaperAccount) -> Self {
+        Self {
+            path: config.path.clone(),
+            slippage: config.slippage_bps / 10_000.0,
+            account,
+        }
+    }
+
+    pub fn account(&self) -> &PaperAccount {
+        &self.account
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let tmp = format!("{}.tmp", self.path);
+        std::fs::write(&tmp, serde_json::to_string_pretty(&self.account)?)
+            .with_context(|| format!("Failed to write paper account {}", tmp))?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to write paper account {}", self.path))
+    }
+
+    pub fn place(&mut self, request: &OrderRequest, mid: f64, now: u64) -> OrderResult {
+        let size = match self.order_size(&request.symbol, request.is_buy, request.qty, request.reduce_only) {
+            Some(size) => size,
+            None => {
+                return OrderResult::Error {
+                    message: "Reduce only order would increase position".to_string(),
+                }
+            }
+        };
+
+        let order_id = self.account.next_order_id;
+        self.account.next_order_id += 1;
+
+        let slipped = if request.is_buy { mid * (1.0 + self.slippage) } else { mid * (1.0 - self.slippage) };
+        let price = match request.limit_price {
+            None => slipped,
+            Some(limit) => {
+                let marketable = if request.is_buy { limit >= mid } else { limit <= mid };
+                if marketable && request.tif == "Alo" {
+                    return OrderResult::Error {
+                        message: "Post only order would have immediately matched".to_string(),
+                    };
+                }
+                if !marketable && request.tif == "Ioc" {
+                    return OrderResult::Error {
+                        message: "Order could not immediately match against any resting orders".to_string(),
+                    };
+                }
+                if !marketable {
+                    self.account.open_orders.push(PaperOrder {
+                        order_id,
+                        symbol: request.symbol.clone(),
+                        is_buy: request.is_buy,
+                        price: limit,
+                        size,
+                        reduce_only: request.reduce_only,
+                        timestamp: now,
+                    });
+                    return OrderResult::Resting { order_id };
+                }
+                // crosses the book: fill as taker, never worse than the limit
+                if request.is_buy { limit.min(slipped) } else { limit.max(slipped) }
+            }
+        };
+
+        let fill = self.apply_fill(order_id, &request.symbol, request.is_buy, size, price, TAKER_FEE_RATE, now);
+        OrderResult::Success {
+            order_id,
+            filled_qty: fill.size,
+            avg_price: Some(fill.price),
+        }
+    }
+
+    pub fn cancel(&mut self, order_id: u64) -> Result<()> {
+        let before = self.account.open_orders.len();
+        self.account.open_orders.retain(|order| order.order_id != order_id);
+        if self.account.open_orders.len() == before {
+            anyhow::bail!("Paper order {} not found", order_id);
+        }
+        Ok(())
+    }
+
+    // fills resting limits that a trade at `price` printed through, at their limit price
+    pub fn on_trade(&mut self, symbol: &str, price: f64, now: u64) -> Vec<PaperFill> {
+        let (crossed, resting): (Vec<PaperOrder>, Vec<PaperOrder>) = std::mem::take(&mut self.account.open_orders)
+            .into_iter()
+            .partition(|order| {
+                order.symbol == symbol
+                    && if order.is_buy { price <= order.price } else { price >= order.price }
+            });
+        self.account.open_orders = resting;
+
+        crossed
+            .into_iter()
+            .filter_map(|order| {
+                let size = self.order_size(&order.symbol, order.is_buy, order.size, order.reduce_only)?;
+                Some(self.apply_fill(order.order_id, &order.symbol, order.is_buy, size, order.price, MAKER_FEE_RATE, now))
+            })
+            .collect()
+    }
+
+    pub fn symbols_with_orders(&self) -> Vec<String> {
+        let symbols: HashSet<&str> = self.account.open_orders.iter().map(|o| o.symbol.as_str()).collect();
+        symbols.into_iter().map(str::to_string).collect()
+    }
+
+    // reduce-only orders are clipped to the opposite position, None if nothing is left to reduce
+    fn order_size(&self, symbol: &str, is_buy: bool, size: f64, reduce_only: bool) -> Option<f64> {
+        if !reduce_only {
+            return Some(size);
+        }
+        let position = self.account.positions.get(symbol).map(|p| p.size).unwrap_or(0.0);
+        let reducible = if is_buy { (-position).max(0.0) } else { position.max(0.0) };
+        let size = size.min(reducible);
+        (size > SIZE_EPSILON).then_some(size)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn apply_fill(
+        &mut self,
+        order_id: u64,
+        symbol: &str,
+        is_buy: bool,
+        size: f64,
+        price: f64,
+        fee_rate: f64,
+        now: u64,
+    ) -> PaperFill {
+        let signed = if is_buy { size } else { -size };
+        let position = self.account.positions.entry(symbol.to_string()).or_default();
+        let old_size = position.size;
+        let new_size = old_size + signed;
+
+        let mut closed_pnl = 0.0;
+        if old_size != 0.0 && old_size.signum() != signed.signum() {
+            let closing = size.min(old_size.abs());
+            closed_pnl = closing * (price - position.entry_price) * old_size.signum();
+        }
+
+        if old_size == 0.0 || old_size.signum() == signed.signum() {
+            position.entry_price = (old_size.abs() * position.entry_price + size * price) / new_size.abs();
+        } else if new_size.abs() > SIZE_EPSILON && new_size.signum() != old_size.signum() {
+            // flipped through zero, the remainder opens at this price
+            position.entry_price = price;
+        }
+        position.size = new_size;
+        if new_size.abs() <= SIZE_EPSILON {
+            self.account.positions.remove(symbol);
+        }
+
+        let fee = size * price * fee_rate;
+        self.account.balance += closed_pnl - fee;
+
+        let fill = PaperFill {
+            order_id,
+            symbol: symbol.to_string(),
+            is_buy,
+            price,
+            size,
+            fee,
+            closed_pnl,
+            timestamp: now,
+        };
+        self.account.fills.push(fill.clone());
+        fill
+    }
+}
+
+// streams trades for every symbol with resting paper orders and fills them as price trades through
+pub async fn run(engine: &mut PaperEngine, ws_url: &str, http: &HttpConfig, duration: u64) -> Result<Vec<PaperFill>> {
+    let symbols = engine.symbols_with_orders();
+    let mut fills = Vec::new();
+    if symbols.is_empty() {
+        return Ok(fills);
+    }
+
+    let ws_stream = net::connect_ws(ws_url, http).await?;
+    let (mut sender, mut receiver) = ws_stream.split();
+
+    for symbol in &symbols {
+        let subscription = serde_json::json!({
+            "method": "subscribe",
+            "subscription": { "type": "trades", "coin": symbol },
+        });
+        sender
+            .send(Message::Text(subscription.to_string()))
+            .await
+            .context("Failed to send subscription")?;
+    }
+    tracing::info!(symbols = ?symbols, "paper: watching resting orders");
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(duration);
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep_until(deadline) => break,
+            _ = ping.tick() => {
+                let _ = sender.send(Message::Text(serde_json::json!({ "method": "ping" }).to_string())).await;
+            }
+            message = receiver.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let Ok(message) = serde_json::from_str::<WSMessage>(&text) else {
+                        continue;
+                    };
+                    if message.channel != "trades" {
+                        continue;
+                    }
+                    let Ok(trades) = serde_json::from_value::<Vec<TradeData>>(message.data) else {
+                        continue;
+                    };
+
+                    let mut new_fills = Vec::new();
+                    for trade in trades {
+                        let Ok(price) = trade.px.parse::<f64>() else {
+                            continue;
+                        };
+                        new_fills.extend(engine.on_trade(&trade.coin, price, trade.time));
+                    }
+                    if !new_fills.is_empty() {
+                        engine.save()?;
+                        fills.extend(new_fills);
+                    }
+                    if engine.account().open_orders.is_empty() {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Err(e)) => return Err(e).context("WebSocket error"),
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+
+    Ok(fills)
+}
@@ -0,0 +1,149 @@
+// keeps a resting limit order pegged to the mid, re-pricing it when the market drifts away
+use std::{sync::Arc, time::Duration};
+use anyhow::Result;
+use clap::ValueEnum;
+use tokio::sync::broadcast::error::RecvError;
+use crate::services::{
+    rate_limit::TokenBucket,
+    ws_hub::{SubscriptionKey, WsHub},
+    ExchangeService, TradingService,
+};
+use crate::types::{
+    exchange::OrderRef,
+    streaming::{BboData, SpreadQuote, WSMessage},
+    OrderRequest, OrderResult,
+};
+
+// fills between re-prices are caught by polling the order's status
+const STATUS_EVERY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PegReference {
+    Mid,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PegParams {
+    // distance from mid on the passive side
+    pub offset_bps: f64,
+    // how far mid moves from the price the order was pegged at before it is re-priced
+    pub reprice_bps: f64,
+    pub max_reprices_per_minute: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PegOutcome {
+    Filled { order_id: u64 },
+    // cancelled or rejected by something other than the peg
+    Ended { order_id: u64, status: String },
+    // ctrl-c, the order is left resting where it is
+    Stopped { order_id: u64, price: f64 },
+}
+
+// a buy rests below mid and a sell above it
+pub fn peg_price(mid: f64, is_buy: bool, offset_bps: f64) -> f64 {
+    let offset = offset_bps / 10_000.0;
+    if is_buy { mid * (1.0 - offset) } else { mid * (1.0 + offset) }
+}
+
+pub fn drift_bps(anchor: f64, mid: f64) -> f64 {
+    ((mid - anchor) / anchor).abs() * 10_000.0
+}
+
+pub fn parse_mid(text: &str) -> Option<f64> {
+    let message: WSMessage = serde_json::from_str(text).ok()?;
+    if message.channel != "bbo" {
+        return None;
+    }
+    let quote = SpreadQuote::from_bbo(&serde_json::from_value::<BboData>(message.data).ok()?)?;
+    Some((quote.bid + quote.ask) / 2.0)
+}
+
+// follows `order_id` (placed from `request` at a price pegged to `anchor`) until it fills, ends or ctrl-c
+pub async fn run(
+    trading: &TradingService,
+    exchange: &ExchangeService,
+    hub: Arc<WsHub>,
+    mut request: OrderRequest,
+    mut order_id: u64,
+    mut anchor: f64,
+    params: PegParams,
+) -> Result<PegOutcome> {
+    let mut subscription = hub.subscribe_owned(SubscriptionKey::for_coin("bbo", &request.symbol));
+    let mut budget = TokenBucket::new(params.max_reprices_per_minute, Duration::from_secs(60));
+    let mut status = tokio::time::interval(STATUS_EVERY);
+    // so a capped run of drift is reported once rather than on every quote
+    let mut throttled = false;
+    // the cloid stays with the first order, re-placed ones are followed by oid
+    request.cloid = None;
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                return Ok(PegOutcome::Stopped { order_id, price: request.limit_price.unwrap_or_default() });
+            }
+            _ = status.tick() => {
+                if let Some(outcome) = finished(exchange, order_id).await? {
+                    return Ok(outcome);
+                }
+            }
+            update = subscription.updates.recv() => {
+                let mid = match update {
+                    Ok(text) => match parse_mid(&text) {
+                        Some(mid) => mid,
+                        None => continue,
+                    },
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "peg falling behind, quotes dropped");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => anyhow::bail!("Upstream stream closed"),
+                };
+                if drift_bps(anchor, mid) <= params.reprice_bps {
+                    continue;
+                }
+                if budget.try_acquire().is_err() {
+                    if !throttled {
+                        println!("Mid ${:.4} drifted but the reprice rate is capped, holding at ${:.4}", mid, request.limit_price.unwrap_or_default());
+                        throttled = true;
+                    }
+                    continue;
+                }
+                throttled = false;
+
+                // cancel before re-placing so the two can never both fill
+                trading.cancel_order(&request.symbol, order_id).await?;
+                let Some(info) = exchange.get_order_status(&OrderRef::Oid(order_id)).await? else {
+                    anyhow::bail!("Order {} disappeared after cancel", order_id);
+                };
+                if info.order.status == "filled" || info.order.remaining_qty <= 0.0 {
+                    return Ok(PegOutcome::Filled { order_id });
+                }
+                request.qty = info.order.remaining_qty;
+                request.limit_price = Some(peg_price(mid, request.is_buy, params.offset_bps));
+                let response = trading.place_order(request.clone()).await?;
+                match response.result {
+                    OrderResult::Resting { order_id: id } => {
+                        println!("Re-pegged {} {} @ ${:.4} (mid ${:.4}), order {}", request.qty, request.symbol, request.limit_price.unwrap_or_default(), mid, id);
+                        order_id = id;
+                        anchor = mid;
+                    }
+                    OrderResult::Success { order_id, .. } => return Ok(PegOutcome::Filled { order_id }),
+                    OrderResult::Error { message } => anyhow::bail!("Re-peg rejected, nothing resting: {}", message),
+                    OrderResult::DryRun { .. } => anyhow::bail!("Pegged orders can't be followed in dry run"),
+                }
+            }
+        }
+    }
+}
+
+async fn finished(exchange: &ExchangeService, order_id: u64) -> Result<Option<PegOutcome>> {
+    let Some(info) = exchange.get_order_status(&OrderRef::Oid(order_id)).await? else {
+        return Ok(None);
+    };
+    Ok(match info.order.status.as_str() {
+        "open" => None,
+        "filled" => Some(PegOutcome::Filled { order_id }),
+        status => Some(PegOutcome::Ended { order_id, status: status.to_string() }),
+    })
+}
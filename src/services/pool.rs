@@ -0,0 +1,44 @@
+// one http client and one signing wallet per process, shared by every service built from it
+use std::sync::Arc;
+use anyhow::{Context, Result};
+use ethers::signers::LocalWallet;
+use reqwest::Client;
+use crate::services::{meta_cache::MetaCache, net, rate_budget::RateBudget};
+use crate::types::Config;
+
+pub struct ClientPool {
+    // clones of a reqwest client share its connection pool
+    http: Client,
+    meta: MetaCache,
+    // rate-limit weight left this minute, shared by rest, websocket posts and sdk calls
+    budget: RateBudget,
+    private_key: String,
+}
+
+impl ClientPool {
+    pub fn new(config: &Config) -> Result<Arc<Self>> {
+        Ok(Arc::new(Self {
+            http: net::http_client(&config.http)?,
+            meta: MetaCache::new(&config.cache, &config.api_url),
+            budget: RateBudget::new(config.http.weight_per_minute),
+            private_key: config.private_key.clone(),
+        }))
+    }
+
+    pub fn http(&self) -> &Client {
+        &self.http
+    }
+
+    pub fn meta(&self) -> &MetaCache {
+        &self.meta
+    }
+
+    pub fn budget(&self) -> &RateBudget {
+        &self.budget
+    }
+
+    // every action is signed locally, so trading needs the key and nothing from the sdk's clients
+    pub fn wallet(&self) -> Result<LocalWallet> {
+        self.private_key.parse().context("Failed to parse private key")
+    }
+}
@@ -0,0 +1,77 @@
+// live price view: mids from the allMids stream, mark/oracle/premium polled from asset contexts
+use std::{collections::HashMap, time::Duration};
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio_tungstenite::tungstenite::protocol::Message;
+use crate::{
+    services::{net, ExchangeService},
+    types::{streaming::WSMessage, HttpConfig, PriceInfo},
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+// mark and oracle only move every few seconds, no need to poll them every refresh
+const CONTEXT_INTERVAL: Duration = Duration::from_secs(10);
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+struct AllMidsData {
+    mids: HashMap<String, String>,
+}
+
+// calls `on_refresh` every second until ctrl-c or the stream closes
+pub async fn watch(
+    exchange: &ExchangeService,
+    ws_url: &str,
+    http: &HttpConfig,
+    symbol: &str,
+    mut on_refresh: impl FnMut(&PriceInfo),
+) -> Result<()> {
+    let mut price = exchange.get_price(symbol).await?;
+
+    let ws_stream = net::connect_ws(ws_url, http).await?;
+    let (mut sender, mut receiver) = ws_stream.split();
+    let subscription = serde_json::json!({ "method": "subscribe", "subscription": { "type": "allMids" } });
+    sender
+        .send(Message::Text(subscription.to_string()))
+        .await
+        .context("Failed to send subscription")?;
+
+    let mut refresh = tokio::time::interval(REFRESH_INTERVAL);
+    let mut context = tokio::time::interval(CONTEXT_INTERVAL);
+    context.tick().await;
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = refresh.tick() => on_refresh(&price),
+            _ = context.tick() => match exchange.get_price(symbol).await {
+                // keep the streamed mid, it is fresher than the polled one
+                Ok(latest) => price = PriceInfo { mid: price.mid.or(latest.mid), ..latest },
+                Err(e) => tracing::warn!(error = %e, "failed to refresh asset context"),
+            },
+            _ = ping.tick() => {
+                let _ = sender.send(Message::Text(serde_json::json!({ "method": "ping" }).to_string())).await;
+            }
+            message = receiver.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let Ok(message) = serde_json::from_str::<WSMessage>(&text) else {
+                        continue;
+                    };
+                    if message.channel != "allMids" {
+                        continue;
+                    }
+                    if let Ok(data) = serde_json::from_value::<AllMidsData>(message.data)
+                        && let Some(mid) = data.mids.get(symbol).and_then(|m| m.parse().ok())
+                    {
+                        price.mid = Some(mid);
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => return Ok(()),
+                Some(Err(e)) => return Err(e).context("WebSocket error"),
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+// keeps reduce-only stop loss / take profit triggers on every position the account opens or adds to
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use tokio::sync::broadcast::error::RecvError;
+use crate::services::{
+    exchange::to_fill_info,
+    ws_hub::{SubscriptionKey, WsHub},
+    ExchangeService, TradingService,
+};
+use crate::types::{FillInfo, TriggerOrder, UserFill};
+
+// an order often fills in several pieces, so a symbol is protected once its fills go quiet
+pub const SETTLE: Duration = Duration::from_secs(2);
+const CHECK_EVERY: Duration = Duration::from_millis(500);
+
+// distances from the average entry as fractions, 0.02 = 2%
+#[derive(Debug, Clone, Copy)]
+pub struct Protection {
+    pub stop_loss: Option<f64>,
+    pub take_profit: Option<f64>,
+}
+
+impl Protection {
+    // (stop, take profit) prices for a position, below / above entry for a long and mirrored for a short
+    pub fn levels(&self, entry: f64, position: f64) -> (Option<f64>, Option<f64>) {
+        let direction = position.signum();
+        (
+            self.stop_loss.map(|sl| entry * (1.0 - direction * sl)),
+            self.take_profit.map(|tp| entry * (1.0 + direction * tp)),
+        )
+    }
+}
+
+// live fills only, the snapshot sent on subscribe is history
+pub fn parse_fills(text: &str) -> Vec<FillInfo> {
+    let Ok(message) = serde_json::from_str::<Value>(text) else {
+        return Vec::new();
+    };
+    let data = &message["data"];
+    if message["channel"] != "userFills" || data["isSnapshot"].as_bool().unwrap_or(false) {
+        return Vec::new();
+    }
+    data["fills"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|fill| serde_json::from_value::<UserFill>(fill.clone()).ok())
+        .map(to_fill_info)
+        .collect()
+}
+
+// "Open Long", "Open Short" or a flip like "Long > Short"; closes and spot fills don't need new triggers
+pub fn grows_position(fill: &FillInfo) -> bool {
+    fill.direction.starts_with("Open") || fill.direction.contains('>')
+}
+
+// stops and take profits on the closing side of the position, the ones a refresh replaces
+pub fn protective_triggers<'a>(orders: &'a [TriggerOrder], symbol: &str, position: f64) -> Vec<&'a TriggerOrder> {
+    let closing_side = if position > 0.0 { "SELL" } else { "BUY" };
+    orders
+        .iter()
+        .filter(|o| {
+            o.symbol.eq_ignore_ascii_case(symbol)
+                && o.side == closing_side
+                && (o.order_type.starts_with("Stop") || o.order_type.starts_with("Take Profit"))
+        })
+        .collect()
+}
+
+// runs until the upstream stream closes
+pub async fn run(trading: &TradingService, exchange: &ExchangeService, hub: Arc<WsHub>, protection: Protection) -> Result<()> {
+    let mut subscription = hub.subscribe_owned(SubscriptionKey::for_user("userFills", &exchange.get_wallet_address()?));
+    // symbol -> time of its latest position-growing fill
+    let mut pending: HashMap<String, Instant> = HashMap::new();
+    let mut check = tokio::time::interval(CHECK_EVERY);
+
+    loop {
+        tokio::select! {
+            update = subscription.updates.recv() => match update {
+                Ok(text) => {
+                    for fill in parse_fills(&text).iter().filter(|fill| grows_position(fill)) {
+                        pending.insert(fill.symbol.clone(), Instant::now());
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => tracing::warn!(skipped, "protect: falling behind, fills dropped"),
+                Err(RecvError::Closed) => anyhow::bail!("Upstream stream closed"),
+            },
+            _ = check.tick() => {
+                let settled: Vec<String> =
+                    pending.iter().filter(|(_, last)| last.elapsed() >= SETTLE).map(|(symbol, _)| symbol.clone()).collect();
+                for symbol in settled {
+                    pending.remove(&symbol);
+                    // a failed refresh is retried on the next fill rather than stopping the daemon
+                    if let Err(e) = protect(trading, exchange, &symbol, protection).await {
+                        eprintln!("{}: protection failed: {}", symbol, e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// places fresh triggers for the whole position, then cancels the ones they replace so it is never unprotected
+async fn protect(trading: &TradingService, exchange: &ExchangeService, symbol: &str, protection: Protection) -> Result<()> {
+    let (balances, triggers) = tokio::try_join!(exchange.get_balances(), exchange.get_trigger_orders(Some(symbol)))?;
+    let Some(position) = balances.positions.iter().find(|p| p.symbol == symbol && p.size != 0.0) else {
+        return Ok(());
+    };
+    let old = protective_triggers(&triggers, symbol, position.size);
+    let (stop, take_profit) = protection.levels(position.entry_price, position.size);
+    let (is_buy, size) = (position.size < 0.0, position.size.abs());
+
+    let mut placed = Vec::new();
+    for (tpsl, price) in [("sl", stop), ("tp", take_profit)] {
+        let Some(price) = price else {
+            continue;
+        };
+        trading
+            .place_trigger(symbol, is_buy, size, price, tpsl)
+            .await
+            .with_context(|| format!("{} at ${:.4} rejected, old triggers left in place", tpsl.to_uppercase(), price))?;
+        placed.push(format!("{} ${:.4}", tpsl.to_uppercase(), price));
+    }
+    for order in &old {
+        trading.cancel_order(symbol, order.order_id).await?;
+    }
+
+    let dry_run = if trading.is_dry_run() { " (dry run, nothing sent)" } else { "" };
+    println!(
+        "{} {:+} @ ${:.4}: {}, replaced {} old trigger(s){}",
+        symbol,
+        position.size,
+        position.entry_price,
+        placed.join(", "),
+        old.len(),
+        dry_run
+    );
+    Ok(())
+}
@@ -0,0 +1,121 @@
+// hyperliquid's per-ip rest limit is a budget of request weight per minute, tracked here so
+// bulk work waits for room instead of getting 429s
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+use serde::Serialize;
+use crate::services::rate_limit::TokenBucket;
+
+pub const DEFAULT_WEIGHT_PER_MINUTE: u32 = 1200;
+
+pub struct RateBudget {
+    bucket: Mutex<TokenBucket>,
+    used: AtomicU64,
+    throttled_ms: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RateBudgetSnapshot {
+    pub capacity: u32,
+    pub remaining: u32,
+    // weight spent since the process started
+    pub used_total: u64,
+    // time spent waiting for the budget to refill
+    pub throttled_ms_total: u64,
+}
+
+impl RateBudget {
+    pub fn new(weight_per_minute: u32) -> Self {
+        Self {
+            bucket: Mutex::new(TokenBucket::new(weight_per_minute, Duration::from_secs(60))),
+            used: AtomicU64::new(0),
+            throttled_ms: AtomicU64::new(0),
+        }
+    }
+
+    // waits until `weight` fits in the budget, then spends it
+    pub async fn acquire(&self, weight: u32) {
+        loop {
+            let wait = match self.lock().try_acquire_n_at(f64::from(weight), Instant::now()) {
+                Ok(()) => break,
+                Err(wait) => wait,
+            };
+            tracing::debug!(weight, wait_ms = wait.as_millis() as u64, "pacing requests to stay under the rate limit");
+            self.throttled_ms.fetch_add(wait.as_millis() as u64, Ordering::Relaxed);
+            tokio::time::sleep(wait).await;
+        }
+        self.used.fetch_add(u64::from(weight), Ordering::Relaxed);
+    }
+
+    // weight only known from the response (per item returned), spent without waiting
+    pub fn charge(&self, weight: u32) {
+        if weight == 0 {
+            return;
+        }
+        self.lock().charge_at(f64::from(weight), Instant::now());
+        self.used.fetch_add(u64::from(weight), Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> RateBudgetSnapshot {
+        let mut bucket = self.lock();
+        RateBudgetSnapshot {
+            capacity: bucket.capacity() as u32,
+            remaining: bucket.available_at(Instant::now()).floor() as u32,
+            used_total: self.used.load(Ordering::Relaxed),
+            throttled_ms_total: self.throttled_ms.load(Ordering::Relaxed),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TokenBucket> {
+        self.bucket.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// weight of an /info request by type, before anything is returned
+pub fn info_weight(request_type: &str) -> u32 {
+    match request_type {
+        "l2Book" | "allMids" | "clearinghouseState" | "orderStatus" | "spotClearinghouseState" | "exchangeStatus" => 2,
+        "userRole" => 60,
+        _ => 20,
+    }
+}
+
+// extra weight for list responses, charged per 20 items (60 for candles)
+pub fn response_weight(request_type: &str, items: usize) -> u32 {
+    let per = match request_type {
+        "candleSnapshot" => 60,
+        "recentTrades" | "historicalOrders" | "userFills" | "userFillsByTime" | "fundingHistory"
+        | "userFunding" | "nonUserFundingUpdates" | "userNonFundingLedgerUpdates" | "twapHistory" | "userTwapSliceFills"
+        | "userTwapSliceFillsByTime" | "delegatorHistory" | "delegatorRewards" | "validatorStats" => 20,
+        _ => return 0,
+    };
+    (items / per) as u32
+}
+
+// an /exchange action costs 1 plus 1 per 40 orders or cancels it batches
+pub fn action_weight(batch_len: usize) -> u32 {
+    1 + (batch_len / 40) as u32
+}
+
+// prometheus text exposition of the budget, served at /metrics
+pub fn render_metrics(budget: &RateBudgetSnapshot) -> String {
+    let metrics = [
+        ("hl_rate_budget_capacity", "gauge", "Rate-limit weight allowed per minute", budget.capacity as f64),
+        ("hl_rate_budget_remaining", "gauge", "Rate-limit weight left in the current minute", budget.remaining as f64),
+        ("hl_rate_budget_used_total", "counter", "Rate-limit weight spent since start", budget.used_total as f64),
+        (
+            "hl_rate_budget_throttled_seconds_total",
+            "counter",
+            "Time requests spent waiting for the budget to refill",
+            budget.throttled_ms_total as f64 / 1000.0,
+        ),
+    ];
+    metrics
+        .iter()
+        .map(|(name, kind, help, value)| format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n"))
+        .collect()
+}
@@ -0,0 +1,140 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+// classic token bucket: `capacity` tokens refilled evenly over `per`
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, per: Duration) -> Self {
+        let capacity = f64::from(capacity.max(1));
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / per.as_secs_f64().max(f64::EPSILON),
+            last_refill: Instant::now(),
+        }
+    }
+
+    pub fn try_acquire(&mut self) -> Result<(), Duration> {
+        self.try_acquire_at(Instant::now())
+    }
+
+    // takes one token, or returns how long until one becomes available
+    pub fn try_acquire_at(&mut self, now: Instant) -> Result<(), Duration> {
+        self.try_acquire_n_at(1.0, now)
+    }
+
+    // takes `n` tokens at once (capped at the capacity so it can always succeed eventually)
+    pub fn try_acquire_n_at(&mut self, n: f64, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        let n = n.min(self.capacity);
+        if self.tokens >= n {
+            self.tokens -= n;
+            Ok(())
+        } else {
+            let missing = n - self.tokens;
+            Err(Duration::from_secs_f64(missing / self.refill_per_sec))
+        }
+    }
+
+    // takes `n` tokens even if that leaves the bucket in debt, for costs only known afterwards
+    pub fn charge_at(&mut self, n: f64, now: Instant) {
+        self.refill(now);
+        self.tokens = (self.tokens - n).max(-self.capacity);
+    }
+
+    pub fn available_at(&mut self, now: Instant) -> f64 {
+        self.refill(now);
+        self.tokens.max(0.0)
+    }
+
+    pub fn capacity(&self) -> f64 {
+        self.capacity
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+}
+
+// past this many clients, buckets that have refilled are dropped when a new client shows up
+const PRUNE_ABOVE: usize = 1024;
+
+// one bucket per key, created lazily with the same limit
+pub struct KeyedRateLimiter {
+    capacity: u32,
+    per: Duration,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl KeyedRateLimiter {
+    pub fn new(capacity: u32, per: Duration) -> Self {
+        Self {
+            capacity,
+            per,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+
+    pub fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        // a full bucket is what a new client would get, so forgetting it changes nothing
+        if buckets.len() >= PRUNE_ABOVE && !buckets.contains_key(key) {
+            buckets.retain(|_, bucket| bucket.available_at(now) < bucket.capacity());
+        }
+        buckets
+            .entry(key.to_string())
+            .or_insert_with(|| TokenBucket::new(self.capacity, self.per))
+            .try_acquire_n_at(1.0, now)
+    }
+
+    // how many clients currently have a bucket
+    pub fn tracked(&self) -> usize {
+        self.buckets.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+// risk.max_orders_per_minute for one process; a changed limit (risk reload) starts a fresh bucket
+#[derive(Debug, Default)]
+pub struct OrderRateLimit {
+    bucket: Mutex<Option<(u32, TokenBucket)>>,
+}
+
+impl OrderRateLimit {
+    pub fn check(&self, per_minute: Option<u32>) -> anyhow::Result<()> {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(limit) = per_minute else {
+            *bucket = None;
+            return Ok(());
+        };
+
+        if bucket.as_ref().map(|(l, _)| *l) != Some(limit) {
+            *bucket = Some((limit, TokenBucket::new(limit, Duration::from_secs(60))));
+        }
+        if let Some((_, bucket)) = bucket.as_mut()
+            && let Err(wait) = bucket.try_acquire()
+        {
+            anyhow::bail!(
+                "Order rate limit of {}/min reached; next order allowed in {:.1}s",
+                limit,
+                wait.as_secs_f64()
+            );
+        }
+        Ok(())
+    }
+}
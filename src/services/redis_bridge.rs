@@ -0,0 +1,129 @@
+// redis bridge: websocket streams republished on <prefix>:<channel>, optionally kept in redis streams
+use std::sync::Arc;
+use anyhow::{Context, Result};
+use redis::aio::ConnectionManager;
+use serde_json::Value;
+use tokio::sync::broadcast::error::RecvError;
+use crate::services::{
+    ws_hub::{HubSubscription, SubscriptionKey, WsHub},
+    ExchangeService,
+};
+use crate::types::RedisConfig;
+
+// one --channels entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum BridgeChannel {
+    Market(SubscriptionKey),
+    // our own fills, keyed by the wallet once it's known
+    Fills,
+}
+
+impl BridgeChannel {
+    // trades:BTC -> hl:trades:BTC
+    pub fn name(&self, prefix: &str) -> String {
+        match self {
+            BridgeChannel::Fills => format!("{}:fills", prefix),
+            BridgeChannel::Market(key) => [Some(key.sub_type.as_str()), key.coin.as_deref(), key.interval.as_deref()]
+                .into_iter()
+                .flatten()
+                .fold(prefix.to_string(), |name, part| format!("{}:{}", name, part)),
+        }
+    }
+
+    pub fn key(&self, user: &str) -> SubscriptionKey {
+        match self {
+            BridgeChannel::Fills => SubscriptionKey::for_user("userFills", user),
+            BridgeChannel::Market(key) => key.clone(),
+        }
+    }
+}
+
+// trades:BTC, bbo:ETH, l2Book:SOL, candle:BTC:1m, allMids or fills
+pub fn parse_channel(spec: &str) -> Result<BridgeChannel> {
+    let parts: Vec<&str> = spec.trim().split(':').collect();
+    let key = match parts.as_slice() {
+        ["fills"] | ["userFills"] => return Ok(BridgeChannel::Fills),
+        ["allMids"] => SubscriptionKey { sub_type: "allMids".to_string(), coin: None, interval: None, user: None },
+        [sub_type, coin] => SubscriptionKey::for_coin(sub_type, coin),
+        ["candle", coin, interval] => SubscriptionKey {
+            interval: Some(interval.to_string()),
+            ..SubscriptionKey::for_coin("candle", coin)
+        },
+        _ => anyhow::bail!("Invalid channel '{}', expected e.g. trades:BTC, candle:ETH:1m or fills", spec),
+    };
+    key.validate().map_err(|e| anyhow::anyhow!("Invalid channel '{}': {}", spec, e))?;
+    Ok(BridgeChannel::Market(key))
+}
+
+// the upstream message's data as json, None for the fills snapshot sent on subscribe
+pub fn payload(text: &str) -> Option<String> {
+    let message: Value = serde_json::from_str(text).ok()?;
+    let data = message.get("data")?;
+    if data["isSnapshot"].as_bool().unwrap_or(false) {
+        return None;
+    }
+    Some(data.to_string())
+}
+
+pub fn client(url: &str) -> Result<redis::Client> {
+    redis::Client::open(url).with_context(|| format!("Invalid redis_url '{}'", url))
+}
+
+// runs until the upstream stream closes, callers stop it on ctrl-c or shutdown
+pub async fn run(url: &str, config: &RedisConfig, exchange: ExchangeService, hub: Arc<WsHub>) -> Result<()> {
+    if config.channels.is_empty() {
+        anyhow::bail!("No channels to forward, set redis.channels or pass --channels");
+    }
+    let mut channels = config.channels.iter().map(|spec| parse_channel(spec)).collect::<Result<Vec<_>>>()?;
+    // reconnects on its own, a failed publish is logged and the next message retried
+    let connection = client(url)?
+        .get_connection_manager()
+        .await
+        .with_context(|| format!("Failed to connect to {}", url))?;
+
+    let user = if channels.contains(&BridgeChannel::Fills) { exchange.get_wallet_address()? } else { String::new() };
+    let mut forwarders = tokio::task::JoinSet::new();
+    for channel in &mut channels {
+        if let BridgeChannel::Market(SubscriptionKey { coin: Some(coin), .. }) = channel {
+            *coin = exchange.resolve_symbol(coin).await?.name;
+        }
+        let subscription = hub.subscribe_owned(channel.key(&user));
+        let name = channel.name(&config.channel_prefix);
+        let stream_maxlen = config.streams.then_some(config.stream_maxlen);
+        forwarders.spawn(forward(connection.clone(), subscription, name, stream_maxlen));
+    }
+
+    tracing::info!(url = %url, channels = channels.len(), streams = config.streams, "redis: bridge running");
+    let result = forwarders.join_next().await.map(|joined| joined.map_err(anyhow::Error::from).and_then(|r| r));
+    result.unwrap_or(Ok(()))
+}
+
+async fn forward(
+    mut connection: ConnectionManager,
+    mut subscription: HubSubscription,
+    name: String,
+    stream_maxlen: Option<usize>,
+) -> Result<()> {
+    loop {
+        let text = match subscription.updates.recv().await {
+            Ok(text) => text,
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!(skipped, channel = %name, "redis: falling behind, messages dropped");
+                continue;
+            }
+            Err(RecvError::Closed) => anyhow::bail!("Upstream stream for {} closed", name),
+        };
+        let Some(payload) = payload(&text) else {
+            continue;
+        };
+
+        let mut pipe = redis::pipe();
+        pipe.cmd("PUBLISH").arg(&name).arg(&payload).ignore();
+        if let Some(maxlen) = stream_maxlen {
+            pipe.cmd("XADD").arg(&name).arg("MAXLEN").arg("~").arg(maxlen).arg("*").arg("data").arg(&payload).ignore();
+        }
+        if let Err(e) = pipe.query_async::<_, ()>(&mut connection).await {
+            tracing::warn!(error = %e, channel = %name, "redis: publish failed");
+        }
+    }
+}
@@ -0,0 +1,126 @@
+// retries for outbound http: timeouts, connection failures, 5xx and 429 (honouring Retry-After)
+use std::time::Duration;
+use anyhow::{Context, Result};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use crate::error::HlError;
+use crate::types::HttpConfig;
+
+// a Retry-After longer than this is treated as "give up" rather than slept through
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Idempotency {
+    // info queries and webhooks, safe to send again after any transient failure
+    Idempotent,
+    // signed actions, only resent when the first attempt can't have been executed
+    NotIdempotent,
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::from(&HttpConfig::default())
+    }
+}
+
+impl From<&HttpConfig> for RetryPolicy {
+    fn from(config: &HttpConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts.max(1),
+            base_delay: Duration::from_millis(config.retry_base_ms),
+            max_delay: Duration::from_millis(config.retry_max_ms),
+        }
+    }
+}
+
+impl RetryPolicy {
+    // wait before retry number `retry` (1-based): the server's Retry-After if it sent one,
+    // otherwise exponential backoff, both capped
+    pub fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        match retry_after {
+            Some(after) => after.min(MAX_RETRY_AFTER),
+            None => self
+                .base_delay
+                .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+                .min(self.max_delay),
+        }
+    }
+
+    // sends the request built by `build`, again after transient failures; any response that
+    // isn't retried is returned as is for the caller to read
+    pub async fn send(
+        &self,
+        label: &str,
+        idempotency: Idempotency,
+        build: impl Fn() -> RequestBuilder,
+    ) -> Result<Response> {
+        let mut attempt = 1;
+        loop {
+            let outcome = build().send().await;
+            let retryable = match &outcome {
+                Ok(response) => is_retryable_status(response.status(), idempotency),
+                Err(e) => is_retryable_error(e, idempotency),
+            };
+
+            if !retryable || attempt >= self.max_attempts {
+                tracing::debug!(label, attempt, ok = outcome.is_ok(), "http request done");
+                return match outcome {
+                    Ok(response) if retryable => {
+                        Err(HlError::NetworkError(format!("{} failed after {} attempts: {}", label, attempt, response.status())).into())
+                    }
+                    Ok(response) => Ok(response),
+                    Err(e) => Err(e).with_context(|| HlError::NetworkError(format!("{} failed after {} attempts", label, attempt))),
+                };
+            }
+
+            let retry_after = outcome.as_ref().ok().and_then(retry_after);
+            if let Some(after) = retry_after
+                && after > MAX_RETRY_AFTER
+            {
+                return Err(HlError::NetworkError(format!("{} rate limited, server asked to wait {}s", label, after.as_secs())).into());
+            }
+            let delay = self.delay(attempt, retry_after);
+            match &outcome {
+                Ok(response) => tracing::warn!(label, attempt, status = %response.status(), ?delay, "retrying request"),
+                Err(e) => tracing::warn!(label, attempt, error = %e, ?delay, "retrying request"),
+            }
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+pub fn is_retryable_status(status: StatusCode, idempotency: Idempotency) -> bool {
+    // a rate limited request was refused before it was looked at
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return true;
+    }
+    status.is_server_error() && idempotency == Idempotency::Idempotent
+}
+
+fn is_retryable_error(error: &reqwest::Error, idempotency: Idempotency) -> bool {
+    // nothing reached the server if the connection never came up
+    if error.is_connect() {
+        return true;
+    }
+    error.is_timeout() && idempotency == Idempotency::Idempotent
+}
+
+// Retry-After in seconds; the http-date form isn't used by the api
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
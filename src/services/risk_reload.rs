@@ -0,0 +1,58 @@
+// watches the config file and swaps in new risk limits without a restart
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use crate::{config::loader::config_file_path, services::SharedRiskLimits, types::{Config, RiskLimits}};
+
+// keep the returned watcher alive for as long as reloads should happen
+pub fn watch(limits: SharedRiskLimits) -> Result<RecommendedWatcher> {
+    let path = config_file_path();
+    let file_name = path.file_name().map(|name| name.to_os_string());
+    // editors often replace the file, so watch its directory rather than the inode
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::warn!(error = %e, "risk config watcher error");
+                return;
+            }
+        };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            return;
+        }
+        if !event.paths.iter().any(|p| p.file_name().map(|n| n.to_os_string()) == file_name) {
+            return;
+        }
+
+        apply(&limits, Config::reload_risk_limits());
+    })
+    .context("Failed to create config file watcher")?;
+
+    watcher
+        .watch(Path::new(&dir), RecursiveMode::NonRecursive)
+        .with_context(|| format!("Failed to watch {}", dir.display()))?;
+    tracing::info!(path = %path.display(), "watching config file for risk limit changes");
+
+    Ok(watcher)
+}
+
+// swaps in a successful reload, a failed one leaves the current limits in place
+pub fn apply(limits: &SharedRiskLimits, reloaded: Result<RiskLimits>) -> bool {
+    match reloaded {
+        Ok(new_limits) => {
+            *limits.write().unwrap_or_else(|e| e.into_inner()) = std::sync::Arc::new(new_limits);
+            tracing::info!("risk limits reloaded from config file");
+            true
+        }
+        // half-written files land here, the next write event picks up the full one
+        Err(e) => {
+            tracing::warn!(error = %e, "keeping previous risk limits, config reload failed");
+            false
+        }
+    }
+}
@@ -0,0 +1,28 @@
+// market scans over the perp universe
+use crate::types::{FundingCandidate, MarketInfo};
+
+// hyperliquid funding settles hourly
+pub const HOURS_PER_YEAR: f64 = 24.0 * 365.0;
+
+// markets with enough open interest and volume, highest absolute funding first
+pub fn rank_funding(markets: &[MarketInfo], min_open_interest: f64, min_volume: f64, min_apr: f64) -> Vec<FundingCandidate> {
+    let mut ranked: Vec<FundingCandidate> = markets
+        .iter()
+        .map(|market| {
+            let annualized = market.funding_rate * HOURS_PER_YEAR;
+            FundingCandidate {
+                symbol: market.symbol.clone(),
+                funding_rate: market.funding_rate,
+                annualized,
+                open_interest_usd: market.open_interest * market.mark_price,
+                volume_24h: market.volume_24h,
+                collect_side: if market.funding_rate >= 0.0 { "short" } else { "long" }.to_string(),
+                candidate: annualized.abs() >= min_apr,
+            }
+        })
+        .filter(|c| c.open_interest_usd >= min_open_interest && c.volume_24h >= min_volume)
+        .collect();
+
+    ranked.sort_by(|a, b| b.annualized.abs().total_cmp(&a.annualized.abs()));
+    ranked
+}
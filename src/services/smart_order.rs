@@ -0,0 +1,69 @@
+// picks between crossing the spread and resting at the touch from the live book
+use anyhow::Result;
+use crate::services::impact;
+use crate::types::{ImpactEstimate, OrderBook};
+
+// at or under this the half spread is cheaper than the risk of not getting filled
+pub const TIGHT_SPREAD_BPS: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmartChoice {
+    // ioc limit at the worst level the order needs
+    Cross,
+    // post-only at the best price on the order's own side
+    Rest,
+}
+
+#[derive(Debug, Clone)]
+pub struct SmartPlan {
+    pub choice: SmartChoice,
+    pub price: f64,
+    pub tif: &'static str,
+    pub spread_bps: f64,
+    pub estimate: ImpactEstimate,
+    pub reasons: Vec<String>,
+}
+
+// `tolerance` is the most a cross may pay over mid, as a fraction
+pub fn plan(book: &OrderBook, qty: f64, is_buy: bool, tolerance: f64) -> Result<SmartPlan> {
+    let estimate = impact::estimate(book, qty, is_buy)?;
+    let (bid, ask) = match (book.bids.first(), book.asks.first()) {
+        (Some(bid), Some(ask)) => (bid.price, ask.price),
+        _ => anyhow::bail!("{} book is empty on one side", book.symbol),
+    };
+    let spread_bps = (ask - bid) / estimate.mid * 10_000.0;
+    let worst_slippage = (estimate.worst_price - estimate.mid).abs() / estimate.mid;
+    let mut reasons = Vec::new();
+
+    let tight = spread_bps <= TIGHT_SPREAD_BPS;
+    reasons.push(format!(
+        "spread {:.2} bps is {} ({} {:.0} bps)",
+        spread_bps,
+        if tight { "tight" } else { "wide" },
+        if tight { "<=" } else { ">" },
+        TIGHT_SPREAD_BPS
+    ));
+    let deep = estimate.fully_fillable() && worst_slippage <= tolerance;
+    if !estimate.fully_fillable() {
+        reasons.push(format!("only {} of {} is on the visible book", estimate.fillable_qty, qty));
+    } else {
+        reasons.push(format!(
+            "crossing takes {} level(s), worst ${:.4} is {:.3}% from mid ({} the {:.2}% cap)",
+            estimate.levels_used,
+            estimate.worst_price,
+            worst_slippage * 100.0,
+            if deep { "within" } else { "beyond" },
+            tolerance * 100.0
+        ));
+    }
+
+    let plan = if tight && deep {
+        reasons.push(format!("cross with an IOC limit at ${:.4}", estimate.worst_price));
+        SmartPlan { choice: SmartChoice::Cross, price: estimate.worst_price, tif: "Ioc", spread_bps, estimate, reasons }
+    } else {
+        let touch = if is_buy { bid } else { ask };
+        reasons.push(format!("rest post-only at the touch, ${:.4}", touch));
+        SmartPlan { choice: SmartChoice::Rest, price: touch, tif: "Alo", spread_bps, estimate, reasons }
+    };
+    Ok(plan)
+}
@@ -0,0 +1,114 @@
+// one json document of the account (balances, positions, open orders, risk state) and the
+// differences between two of them, for before/after checks around automated runs
+use std::{collections::BTreeSet, path::Path};
+use anyhow::{Context, Result};
+use crate::services::{daily_loss, guard, ExchangeService, TradingService};
+use crate::types::{
+    AccountSnapshot, OpenOrder, OrderChange, PositionChange, PositionChangeKind, RiskSnapshot, SnapshotDiff,
+};
+
+pub async fn capture(trading: &TradingService, exchange: &ExchangeService, lock_path: &str) -> Result<AccountSnapshot> {
+    let (today, midnight) = daily_loss::utc_today();
+    let (balances, orders, realized) =
+        tokio::try_join!(exchange.get_balances(), exchange.get_open_orders(None), trading.realized_pnl_since(midnight))?;
+    let limits = trading.risk_limits();
+    let state = trading.daily_loss_guard().state();
+
+    Ok(AccountSnapshot {
+        taken_at: chrono::Utc::now().timestamp_millis() as u64,
+        network: exchange.config().network().to_string(),
+        address: exchange.get_wallet_address()?,
+        balances,
+        open_orders: orders.orders,
+        risk: RiskSnapshot {
+            realized_pnl_today: realized,
+            max_daily_loss: limits.max_daily_loss,
+            daily_loss_override: state.override_day.as_deref() == Some(today.as_str()),
+            day: today,
+            max_open_positions: limits.max_open_positions,
+            max_total_notional: limits.max_total_notional,
+            max_notional_per_order: limits.max_notional_per_order,
+            lock: guard::read_lock(lock_path)?,
+        },
+    })
+}
+
+pub fn load(path: &Path) -> Result<AccountSnapshot> {
+    let raw = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("{} is not an hl snapshot", path.display()))
+}
+
+fn order_change(order: &OpenOrder) -> OrderChange {
+    OrderChange {
+        order_id: order.order_id,
+        symbol: order.symbol.clone(),
+        side: order.side.clone(),
+        price: order.price,
+        qty: order.remaining_qty,
+    }
+}
+
+pub fn diff(before: &AccountSnapshot, after: &AccountSnapshot) -> SnapshotDiff {
+    let upnl = |snapshot: &AccountSnapshot| snapshot.balances.positions.iter().map(|p| p.unrealized_pnl).sum::<f64>();
+    let position = |snapshot: &AccountSnapshot, symbol: &str| {
+        snapshot.balances.positions.iter().find(|p| p.symbol == symbol).map_or((0.0, 0.0), |p| (p.size, p.unrealized_pnl))
+    };
+
+    let symbols: BTreeSet<&str> = before
+        .balances
+        .positions
+        .iter()
+        .chain(&after.balances.positions)
+        .map(|p| p.symbol.as_str())
+        .collect();
+    let positions = symbols
+        .into_iter()
+        .filter_map(|symbol| {
+            let ((size_before, upnl_before), (size_after, upnl_after)) = (position(before, symbol), position(after, symbol));
+            let kind = if size_before == size_after {
+                return None;
+            } else if size_before == 0.0 {
+                PositionChangeKind::Opened
+            } else if size_after == 0.0 {
+                PositionChangeKind::Closed
+            } else if size_before.signum() != size_after.signum() {
+                PositionChangeKind::Flipped
+            } else if size_after.abs() > size_before.abs() {
+                PositionChangeKind::Increased
+            } else {
+                PositionChangeKind::Reduced
+            };
+            Some(PositionChange {
+                symbol: symbol.to_string(),
+                kind,
+                size_before,
+                size_after,
+                unrealized_pnl_before: upnl_before,
+                unrealized_pnl_after: upnl_after,
+            })
+        })
+        .collect();
+
+    let missing_from = |orders: &[OpenOrder], other: &[OpenOrder]| -> Vec<OrderChange> {
+        orders
+            .iter()
+            .filter(|order| !other.iter().any(|o| o.order_id == order.order_id))
+            .map(order_change)
+            .collect()
+    };
+
+    SnapshotDiff {
+        elapsed_ms: after.taken_at.saturating_sub(before.taken_at),
+        account_value: (before.balances.account_value, after.balances.account_value),
+        withdrawable: (before.balances.withdrawable, after.balances.withdrawable),
+        unrealized_pnl: (upnl(before), upnl(after)),
+        margin_ratio: (before.balances.margin_ratio, after.balances.margin_ratio),
+        realized_pnl_today: (before.risk.realized_pnl_today, after.risk.realized_pnl_today),
+        same_day: before.risk.day == after.risk.day,
+        positions,
+        new_orders: missing_from(&after.open_orders, &before.open_orders),
+        gone_orders: missing_from(&before.open_orders, &after.open_orders),
+        lock_before: before.risk.lock.clone(),
+        lock_after: after.risk.lock.clone(),
+    }
+}
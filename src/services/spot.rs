@@ -0,0 +1,88 @@
+// spot naming and balances: pairs past the first are listed as "@<index>", shown here as BASE/QUOTE
+use std::collections::HashMap;
+use crate::types::{
+    exchange::{RawSpotBalance, SpotAssetContext, SpotMeta},
+    SpotBalance, SpotBalancesResponse,
+};
+
+// token 0 is the quote currency every pair here is priced in
+const USDC_TOKEN: u32 = 0;
+
+#[derive(Debug, Clone, Default)]
+pub struct SpotNames {
+    // "@107" -> "HYPE/USDC"
+    by_id: HashMap<String, String>,
+    // "HYPE/USDC" (upper case) -> "@107"
+    by_name: HashMap<String, String>,
+}
+
+impl SpotNames {
+    pub fn from_meta(meta: &SpotMeta) -> Self {
+        let tokens: HashMap<u32, &str> = meta.tokens.iter().map(|t| (t.index, t.name.as_str())).collect();
+        let mut names = Self::default();
+        for pair in &meta.universe {
+            let (Some(base), Some(quote)) = (tokens.get(&pair.tokens[0]), tokens.get(&pair.tokens[1])) else {
+                continue;
+            };
+            let name = format!("{}/{}", base, quote);
+            names.by_name.insert(name.to_ascii_uppercase(), pair.name.clone());
+            names.by_id.insert(pair.name.clone(), name);
+        }
+        names
+    }
+
+    // human name for an exchange coin; perps and pairs already named like "PURR/USDC" pass through
+    pub fn display(&self, coin: &str) -> String {
+        self.by_id.get(coin).cloned().unwrap_or_else(|| coin.to_string())
+    }
+
+    // the exchange's coin for a pair typed either way, for info requests that only know "@107"
+    pub fn coin(&self, name: &str) -> String {
+        self.by_name.get(&name.to_ascii_uppercase()).cloned().unwrap_or_else(|| name.to_string())
+    }
+}
+
+// mid (or mark) of each token's pair against usdc, usdc itself at 1
+pub fn token_prices(meta: &SpotMeta, contexts: &[SpotAssetContext]) -> HashMap<u32, f64> {
+    let mut prices = HashMap::from([(USDC_TOKEN, 1.0)]);
+    for (pair, context) in meta.universe.iter().zip(contexts) {
+        if pair.tokens[1] != USDC_TOKEN {
+            continue;
+        }
+        let price = [&context.mid_px, &context.mark_px]
+            .into_iter()
+            .find_map(|px| px.as_deref().and_then(|px| px.parse::<f64>().ok()))
+            .filter(|px| *px > 0.0);
+        if let Some(price) = price {
+            prices.insert(pair.tokens[0], price);
+        }
+    }
+    prices
+}
+
+// non-zero balances, largest usd value first
+pub fn balances(raw: &[RawSpotBalance], prices: &HashMap<u32, f64>) -> SpotBalancesResponse {
+    let mut balances: Vec<SpotBalance> = raw
+        .iter()
+        .map(|balance| {
+            let total: f64 = balance.total.parse().unwrap_or(0.0);
+            let hold: f64 = balance.hold.parse().unwrap_or(0.0);
+            let price = prices.get(&balance.token).copied();
+            SpotBalance {
+                token: balance.coin.clone(),
+                total,
+                hold,
+                available: total - hold,
+                entry_notional: balance.entry_ntl.as_deref().and_then(|n| n.parse().ok()).unwrap_or(0.0),
+                price,
+                value: price.map(|price| price * total),
+            }
+        })
+        .filter(|balance| balance.total != 0.0)
+        .collect();
+    balances.sort_by(|a, b| b.value.unwrap_or(0.0).total_cmp(&a.value.unwrap_or(0.0)));
+    SpotBalancesResponse {
+        total_value: balances.iter().filter_map(|b| b.value).sum(),
+        balances,
+    }
+}
@@ -0,0 +1,141 @@
+// watches bbo for several symbols and flags wide spreads or a collapsing top of book
+use std::{collections::HashMap, time::Duration};
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::protocol::Message;
+use crate::services::net;
+use crate::types::streaming::{BboData, SpreadAlert, SpreadQuote, WSMessage};
+use crate::types::HttpConfig;
+
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+// weight of each new quote in the running top-of-book average
+const AVERAGE_WEIGHT: f64 = 0.05;
+// quotes seen before the average is trusted for collapse alerts
+const WARMUP_QUOTES: u32 = 20;
+
+#[derive(Debug, Default)]
+struct SymbolState {
+    average: f64,
+    quotes: u32,
+    wide: bool,
+    thin: bool,
+}
+
+pub struct SpreadTracker {
+    threshold_bps: f64,
+    // thin once top size drops below this fraction of its average
+    collapse_ratio: f64,
+    states: HashMap<String, SymbolState>,
+}
+
+impl SpreadTracker {
+    pub fn new(threshold_bps: f64, collapse_ratio: f64) -> Self {
+        Self {
+            threshold_bps,
+            collapse_ratio,
+            states: HashMap::new(),
+        }
+    }
+
+    // alerts only on changes, so a spread that stays wide is reported once
+    pub fn update(&mut self, quote: &SpreadQuote) -> Vec<SpreadAlert> {
+        let state = self.states.entry(quote.symbol.clone()).or_default();
+        let spread_bps = quote.spread_bps();
+        let top_notional = quote.top_notional();
+        let mut alerts = Vec::new();
+
+        let wide = spread_bps > self.threshold_bps;
+        if wide && !state.wide {
+            alerts.push(SpreadAlert::Wide {
+                symbol: quote.symbol.clone(),
+                spread_bps,
+            });
+        }
+
+        let thin = state.quotes >= WARMUP_QUOTES && top_notional < state.average * self.collapse_ratio;
+        if thin && !state.thin {
+            alerts.push(SpreadAlert::Thin {
+                symbol: quote.symbol.clone(),
+                top_notional,
+                average: state.average,
+            });
+        }
+
+        if (state.wide || state.thin) && !wide && !thin {
+            alerts.push(SpreadAlert::Recovered {
+                symbol: quote.symbol.clone(),
+                spread_bps,
+                top_notional,
+            });
+        }
+
+        // a collapse shouldn't drag down the baseline it is measured against
+        if !thin {
+            state.average = if state.quotes == 0 {
+                top_notional
+            } else {
+                state.average + AVERAGE_WEIGHT * (top_notional - state.average)
+            };
+            state.quotes += 1;
+        }
+        state.wide = wide;
+        state.thin = thin;
+        alerts
+    }
+}
+
+// streams bbo for `symbols` for `duration` seconds, handing every quote and its alerts to `on_quote`
+pub async fn run(
+    ws_url: &str,
+    http: &HttpConfig,
+    symbols: &[String],
+    tracker: &mut SpreadTracker,
+    duration: u64,
+    mut on_quote: impl FnMut(&SpreadQuote, &[SpreadAlert]),
+) -> Result<()> {
+    let ws_stream = net::connect_ws(ws_url, http).await?;
+    let (mut sender, mut receiver) = ws_stream.split();
+
+    for symbol in symbols {
+        let message = serde_json::json!({ "method": "subscribe", "subscription": { "type": "bbo", "coin": symbol } });
+        sender
+            .send(Message::Text(message.to_string()))
+            .await
+            .context("Failed to send subscription")?;
+    }
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(duration);
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+
+    loop {
+        let text = tokio::select! {
+            _ = tokio::time::sleep_until(deadline) => return Ok(()),
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = ping.tick() => {
+                let _ = sender.send(Message::Text(serde_json::json!({ "method": "ping" }).to_string())).await;
+                continue;
+            }
+            message = receiver.next() => match message {
+                Some(Ok(Message::Text(text))) => text,
+                Some(Ok(Message::Close(_))) | None => return Ok(()),
+                Some(Err(e)) => return Err(e).context("WebSocket error"),
+                Some(Ok(_)) => continue,
+            }
+        };
+
+        let Ok(message) = serde_json::from_str::<WSMessage>(&text) else {
+            continue;
+        };
+        if message.channel != "bbo" {
+            continue;
+        }
+        let Some(quote) = serde_json::from_value::<BboData>(message.data)
+            .ok()
+            .and_then(|data| SpreadQuote::from_bbo(&data))
+        else {
+            continue;
+        };
+        let alerts = tracker.update(&quote);
+        on_quote(&quote, &alerts);
+    }
+}
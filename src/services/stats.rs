@@ -0,0 +1,87 @@
+// performance metrics over the journal's equity curve and closed trades
+use std::collections::BTreeMap;
+use crate::types::{ClosedTrade, PortfolioStats};
+
+// crypto trades every day of the year
+const PERIODS_PER_YEAR: f64 = 365.0;
+
+// `equity` is (timestamp ms, account value) in chronological order
+pub fn compute(equity: &[(u64, f64)], trades: &[ClosedTrade]) -> PortfolioStats {
+    let daily = daily_closes(equity);
+    let returns: Vec<f64> = daily
+        .windows(2)
+        .filter(|w| w[0] > 0.0)
+        .map(|w| w[1] / w[0] - 1.0)
+        .collect();
+
+    let total_return = match (daily.first(), daily.last()) {
+        (Some(first), Some(last)) if *first > 0.0 => last / first - 1.0,
+        _ => 0.0,
+    };
+
+    let wins: Vec<f64> = trades.iter().map(|t| t.gain).filter(|g| *g > 0.0).collect();
+    let losses: Vec<f64> = trades.iter().map(|t| t.gain).filter(|g| *g < 0.0).collect();
+    let gross_win: f64 = wins.iter().sum();
+    let gross_loss: f64 = losses.iter().sum::<f64>().abs();
+
+    PortfolioStats {
+        days: daily.len(),
+        total_return,
+        sharpe: sharpe(&returns),
+        sortino: sortino(&returns),
+        max_drawdown: max_drawdown(equity.iter().map(|(_, v)| *v)),
+        closed_trades: trades.len(),
+        win_rate: (!trades.is_empty()).then(|| wins.len() as f64 / trades.len() as f64),
+        avg_win: mean(&wins),
+        avg_loss: mean(&losses),
+        profit_factor: (gross_loss > 0.0).then(|| gross_win / gross_loss),
+        realized_pnl: trades.iter().map(|t| t.gain).sum(),
+    }
+}
+
+// last snapshot of each utc day
+fn daily_closes(equity: &[(u64, f64)]) -> Vec<f64> {
+    let mut days = BTreeMap::new();
+    for (time, value) in equity {
+        days.insert(time / 86_400_000, *value);
+    }
+    days.into_values().collect()
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn sharpe(returns: &[f64]) -> Option<f64> {
+    if returns.len() < 2 {
+        return None;
+    }
+    let avg = mean(returns)?;
+    let variance = returns.iter().map(|r| (r - avg).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    let std_dev = variance.sqrt();
+    (std_dev > 0.0).then(|| avg / std_dev * PERIODS_PER_YEAR.sqrt())
+}
+
+// like sharpe but only penalises downside deviation
+fn sortino(returns: &[f64]) -> Option<f64> {
+    if returns.len() < 2 {
+        return None;
+    }
+    let avg = mean(returns)?;
+    let downside = returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / returns.len() as f64;
+    let downside_dev = downside.sqrt();
+    (downside_dev > 0.0).then(|| avg / downside_dev * PERIODS_PER_YEAR.sqrt())
+}
+
+// largest peak-to-trough decline as a fraction of the peak
+pub fn max_drawdown(values: impl IntoIterator<Item = f64>) -> f64 {
+    let mut peak = f64::MIN;
+    let mut worst = 0.0_f64;
+    for value in values {
+        peak = peak.max(value);
+        if peak > 0.0 {
+            worst = worst.max((peak - value) / peak);
+        }
+    }
+    worst
+}
@@ -0,0 +1,99 @@
+// reference market maker: symmetric post-only quotes around mid, skewed against inventory
+use anyhow::Result;
+use crate::{
+    services::{
+        strategy::{Strategy, StrategyContext},
+        trading::round_significant,
+    },
+    types::{OrderRequest, StrategyFill, Tick},
+};
+
+#[derive(Debug, Clone)]
+pub struct MarketMakerParams {
+    pub spread_bps: f64,
+    pub qty: f64,
+    // re-quote once mid moves this far from the mid we last quoted around
+    pub requote_bps: f64,
+    // stop quoting the side that would grow inventory past this
+    pub max_position: f64,
+    // how far the quote center shifts at full inventory
+    pub skew_bps: f64,
+}
+
+pub struct MarketMaker {
+    params: MarketMakerParams,
+    quoted_mid: Option<f64>,
+}
+
+impl MarketMaker {
+    pub fn new(params: MarketMakerParams) -> Self {
+        Self {
+            params,
+            quoted_mid: None,
+        }
+    }
+
+    // (bid, ask) for the given mid and inventory, None for a side that is switched off
+    pub fn quotes(&self, mid: f64, position: f64) -> (Option<f64>, Option<f64>) {
+        let inventory = if self.params.max_position > 0.0 {
+            (position / self.params.max_position).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+        // long inventory lowers both quotes so we sell more readily than we buy
+        let center = mid * (1.0 - inventory * self.params.skew_bps / 10_000.0);
+        let half_spread = self.params.spread_bps / 2.0 / 10_000.0;
+
+        let bid = (position < self.params.max_position).then(|| round_significant(center * (1.0 - half_spread), 5));
+        let ask = (position > -self.params.max_position).then(|| round_significant(center * (1.0 + half_spread), 5));
+        (bid, ask)
+    }
+
+    fn requote(&mut self, ctx: &mut StrategyContext, mid: f64) {
+        ctx.cancel_all();
+
+        let (bid, ask) = self.quotes(mid, ctx.position);
+        for (is_buy, price) in [(true, bid), (false, ask)] {
+            let Some(price) = price else {
+                continue;
+            };
+            ctx.place(OrderRequest {
+                symbol: ctx.symbol.clone(),
+                is_buy,
+                qty: self.params.qty,
+                limit_price: Some(price),
+                leverage: None,
+                reduce_only: false,
+                tif: "Alo".to_string(),
+                ..Default::default()
+            });
+        }
+        self.quoted_mid = Some(mid);
+    }
+}
+
+impl Strategy for MarketMaker {
+    fn name(&self) -> &str {
+        "market-maker"
+    }
+
+    fn on_tick(&mut self, ctx: &mut StrategyContext, tick: &Tick) -> Result<()> {
+        let stale = match self.quoted_mid {
+            Some(quoted) => ((tick.mid - quoted) / quoted).abs() * 10_000.0 >= self.params.requote_bps,
+            None => true,
+        };
+        // a side may have filled or been rejected, either way refresh the book
+        if stale || ctx.open_orders.is_empty() {
+            self.requote(ctx, tick.mid);
+        }
+        Ok(())
+    }
+
+    fn on_fill(&mut self, ctx: &mut StrategyContext, _fill: &StrategyFill) -> Result<()> {
+        // inventory changed, so the skew did too
+        if let Some(mid) = ctx.last_mid {
+            self.requote(ctx, mid);
+        }
+        Ok(())
+    }
+}
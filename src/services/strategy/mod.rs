@@ -0,0 +1,453 @@
+// strategy trait plus the runner that feeds it live market data and executes its actions
+pub mod market_maker;
+pub mod momentum;
+pub mod script;
+
+use std::time::Duration;
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio_tungstenite::tungstenite::protocol::Message;
+use crate::{
+    services::{deadman, net, ExchangeService, TradingService},
+    types::{
+        streaming::{TradeData, WSMessage},
+        Candle, HttpConfig, MarketTrade, OrderRequest, OrderResult, RawCandle, StrategyAction, StrategyFill, Tick, UserFill,
+        WorkingOrder,
+    },
+};
+
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+// bounds how many rounds of follow-up actions one event may trigger
+const MAX_ACTION_ROUNDS: usize = 8;
+const SIZE_EPSILON: f64 = 1e-9;
+// candles of history handed to warm_up before going live
+const WARMUP_CANDLES: u64 = 200;
+
+// hooks are synchronous; orders are queued on the context and executed by the runner
+pub trait Strategy {
+    fn name(&self) -> &str;
+
+    // candle interval to stream (e.g. "1m"), None for strategies that don't use candles
+    fn candle_interval(&self) -> Option<&str> {
+        None
+    }
+
+    // recent history, oldest first, the last candle is still forming
+    fn warm_up(&mut self, _candles: &[Candle]) {}
+
+    // every update of the forming candle, including the first update of a new one
+    fn on_candle(&mut self, _ctx: &mut StrategyContext, _candle: &Candle) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_tick(&mut self, _ctx: &mut StrategyContext, _tick: &Tick) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_trade(&mut self, _ctx: &mut StrategyContext, _trade: &MarketTrade) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_fill(&mut self, _ctx: &mut StrategyContext, _fill: &StrategyFill) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StrategyContext {
+    pub symbol: String,
+    // signed, negative is short
+    pub position: f64,
+    pub open_orders: Vec<WorkingOrder>,
+    pub last_mid: Option<f64>,
+    actions: Vec<StrategyAction>,
+}
+
+impl StrategyContext {
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn buy(&mut self, qty: f64, limit_price: Option<f64>) {
+        self.place(self.order(true, qty, limit_price));
+    }
+
+    pub fn sell(&mut self, qty: f64, limit_price: Option<f64>) {
+        self.place(self.order(false, qty, limit_price));
+    }
+
+    pub fn place(&mut self, order: OrderRequest) {
+        self.actions.push(StrategyAction::Place(order));
+    }
+
+    pub fn cancel(&mut self, order_id: u64) {
+        self.actions.push(StrategyAction::Cancel { order_id });
+    }
+
+    pub fn cancel_all(&mut self) {
+        self.actions.push(StrategyAction::CancelAll);
+    }
+
+    pub fn take_actions(&mut self) -> Vec<StrategyAction> {
+        std::mem::take(&mut self.actions)
+    }
+
+    fn order(&self, is_buy: bool, qty: f64, limit_price: Option<f64>) -> OrderRequest {
+        OrderRequest {
+            symbol: self.symbol.clone(),
+            is_buy,
+            qty,
+            limit_price,
+            leverage: None,
+            reduce_only: false,
+            tif: "Gtc".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn apply_fill(&mut self, fill: &StrategyFill) {
+        self.position += if fill.is_buy { fill.size } else { -fill.size };
+        if let Some(order) = self.open_orders.iter_mut().find(|o| o.order_id == fill.order_id) {
+            order.size -= fill.size;
+        }
+        self.open_orders.retain(|o| o.size > SIZE_EPSILON);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BboData {
+    coin: String,
+    time: u64,
+    bbo: Vec<Option<BboLevel>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BboLevel {
+    px: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UserFillsData {
+    #[serde(default)]
+    is_snapshot: bool,
+    fills: Vec<UserFill>,
+}
+
+// runs a strategy on one symbol until `duration` elapses, cancelling its resting orders on exit
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    strategy: &mut dyn Strategy,
+    trading: &TradingService,
+    exchange: &ExchangeService,
+    ws_url: &str,
+    http: &HttpConfig,
+    symbol: &str,
+    duration: u64,
+    deadman_timeout: Option<Duration>,
+) -> Result<()> {
+    let mut ctx = StrategyContext::new(symbol);
+    // paper fills come from the simulator, live fills from the userFills stream
+    let live = !trading.is_paper() && !trading.is_dry_run();
+
+    ctx.position = if trading.is_paper() {
+        trading.paper_position(symbol)?
+    } else {
+        exchange
+            .get_balances()
+            .await?
+            .positions
+            .iter()
+            .find(|p| p.symbol == symbol)
+            .map(|p| p.size)
+            .unwrap_or(0.0)
+    };
+
+    let candle_interval = strategy.candle_interval().map(str::to_string);
+    if let Some(interval) = &candle_interval {
+        let interval_ms = interval_millis(interval)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported candle interval: {}", interval))?;
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let history = exchange
+            .get_candles(symbol, interval, now.saturating_sub(interval_ms * WARMUP_CANDLES), now)
+            .await?;
+        strategy.warm_up(&history);
+    }
+
+    let ws_stream = net::connect_ws(ws_url, http).await?;
+    let (mut sender, mut receiver) = ws_stream.split();
+
+    let mut subscriptions = vec![
+        serde_json::json!({ "type": "bbo", "coin": symbol }),
+        serde_json::json!({ "type": "trades", "coin": symbol }),
+    ];
+    if let Some(interval) = &candle_interval {
+        subscriptions.push(serde_json::json!({ "type": "candle", "coin": symbol, "interval": interval }));
+    }
+    if live {
+        subscriptions.push(serde_json::json!({ "type": "userFills", "user": exchange.get_wallet_address()? }));
+    }
+    for subscription in subscriptions {
+        let message = serde_json::json!({ "method": "subscribe", "subscription": subscription });
+        sender
+            .send(Message::Text(message.to_string()))
+            .await
+            .context("Failed to send subscription")?;
+    }
+
+    // only live orders can be left behind on the exchange
+    let deadman_timeout = deadman_timeout.filter(|_| live);
+    if let Some(timeout) = deadman_timeout {
+        deadman::arm(trading, timeout).await?;
+    }
+
+    tracing::info!(strategy = strategy.name(), symbol, position = ctx.position, "strategy started");
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(duration);
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    let mut heartbeat = tokio::time::interval(deadman::heartbeat_interval(deadman_timeout.unwrap_or(deadman::MIN_TIMEOUT)));
+
+    loop {
+        let text = tokio::select! {
+            _ = tokio::time::sleep_until(deadline) => break,
+            _ = ping.tick() => {
+                let _ = sender.send(Message::Text(serde_json::json!({ "method": "ping" }).to_string())).await;
+                continue;
+            }
+            _ = heartbeat.tick(), if deadman_timeout.is_some() => {
+                if let Some(timeout) = deadman_timeout
+                    && let Err(e) = deadman::arm(trading, timeout).await
+                {
+                    tracing::warn!(error = %e, "dead man's switch heartbeat failed");
+                }
+                continue;
+            }
+            message = receiver.next() => match message {
+                Some(Ok(Message::Text(text))) => text,
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Err(e)) => return Err(e).context("WebSocket error"),
+                Some(Ok(_)) => continue,
+            }
+        };
+
+        let Ok(message) = serde_json::from_str::<WSMessage>(&text) else {
+            continue;
+        };
+
+        let mut fills = Vec::new();
+        match message.channel.as_str() {
+            "bbo" => {
+                let Some(tick) = serde_json::from_value::<BboData>(message.data).ok().and_then(to_tick) else {
+                    continue;
+                };
+                ctx.last_mid = Some(tick.mid);
+                let result = strategy.on_tick(&mut ctx, &tick);
+                report(strategy.name(), "on_tick", result);
+            }
+            "trades" => {
+                let Ok(trades) = serde_json::from_value::<Vec<TradeData>>(message.data) else {
+                    continue;
+                };
+                for trade in trades.iter().filter(|t| t.coin == symbol) {
+                    let Some(trade) = to_market_trade(trade) else {
+                        continue;
+                    };
+                    let result = strategy.on_trade(&mut ctx, &trade);
+                    report(strategy.name(), "on_trade", result);
+                    if trading.is_paper() {
+                        fills.extend(
+                            trading
+                                .paper_on_trade(symbol, trade.price, trade.time)?
+                                .into_iter()
+                                .map(|f| StrategyFill {
+                                    symbol: f.symbol,
+                                    order_id: f.order_id,
+                                    is_buy: f.is_buy,
+                                    price: f.price,
+                                    size: f.size,
+                                    closed_pnl: f.closed_pnl,
+                                    time: f.timestamp,
+                                }),
+                        );
+                    }
+                }
+            }
+            "candle" => {
+                let Ok(candle) = serde_json::from_value::<RawCandle>(message.data) else {
+                    continue;
+                };
+                let result = strategy.on_candle(&mut ctx, &candle.to_candle());
+                report(strategy.name(), "on_candle", result);
+            }
+            "userFills" => {
+                let Ok(data) = serde_json::from_value::<UserFillsData>(message.data) else {
+                    continue;
+                };
+                if data.is_snapshot {
+                    continue;
+                }
+                fills.extend(data.fills.into_iter().filter(|f| f.coin == symbol).map(|f| StrategyFill {
+                    order_id: f.oid,
+                    is_buy: f.side == "B",
+                    price: f.px.parse().unwrap_or(0.0),
+                    size: f.sz.parse().unwrap_or(0.0),
+                    closed_pnl: f.closed_pnl.parse().unwrap_or(0.0),
+                    time: f.time,
+                    symbol: f.coin,
+                }));
+            }
+            _ => continue,
+        }
+
+        for fill in fills {
+            deliver_fill(strategy, &mut ctx, &fill);
+        }
+        execute(strategy, &mut ctx, trading, live).await;
+    }
+
+    if !ctx.open_orders.is_empty() {
+        ctx.cancel_all();
+        execute(strategy, &mut ctx, trading, live).await;
+    }
+    if deadman_timeout.is_some() {
+        deadman::disarm(trading).await?;
+    }
+    tracing::info!(strategy = strategy.name(), symbol, position = ctx.position, "strategy stopped");
+
+    Ok(())
+}
+
+// drains queued actions through the risk-checked trading service
+async fn execute(strategy: &mut dyn Strategy, ctx: &mut StrategyContext, trading: &TradingService, live: bool) {
+    for _ in 0..MAX_ACTION_ROUNDS {
+        let actions = ctx.take_actions();
+        if actions.is_empty() {
+            return;
+        }
+
+        for action in actions {
+            match action {
+                StrategyAction::Place(mut order) => {
+                    // unattended orders always get a cloid so they can be traced and cancelled
+                    order.cloid.get_or_insert_with(uuid::Uuid::new_v4);
+                    let (is_buy, qty, limit_price) = (order.is_buy, order.qty, order.limit_price);
+                    match trading.place_order(order).await {
+                        Ok(response) => match response.result {
+                            OrderResult::Resting { order_id } => {
+                                ctx.open_orders.push(WorkingOrder {
+                                    order_id,
+                                    is_buy,
+                                    price: limit_price.unwrap_or(0.0),
+                                    size: qty,
+                                });
+                            }
+                            // live fills arrive on the userFills stream instead
+                            OrderResult::Success { order_id, filled_qty, avg_price } if !live && filled_qty > 0.0 => {
+                                let fill = StrategyFill {
+                                    symbol: ctx.symbol.clone(),
+                                    order_id,
+                                    is_buy,
+                                    price: avg_price.unwrap_or(0.0),
+                                    size: filled_qty,
+                                    closed_pnl: 0.0,
+                                    time: response.timestamp,
+                                };
+                                deliver_fill(strategy, ctx, &fill);
+                            }
+                            OrderResult::Error { message } => {
+                                tracing::warn!(strategy = strategy.name(), "order rejected: {}", message);
+                            }
+                            _ => {}
+                        },
+                        Err(e) => tracing::warn!(strategy = strategy.name(), "order failed: {}", e),
+                    }
+                }
+                StrategyAction::Cancel { order_id } => cancel(strategy.name(), ctx, trading, order_id).await,
+                StrategyAction::CancelAll => {
+                    let order_ids: Vec<u64> = ctx.open_orders.iter().map(|o| o.order_id).collect();
+                    for order_id in order_ids {
+                        cancel(strategy.name(), ctx, trading, order_id).await;
+                    }
+                }
+            }
+        }
+    }
+
+    tracing::warn!(strategy = strategy.name(), "dropping actions after {} rounds", MAX_ACTION_ROUNDS);
+    ctx.take_actions();
+}
+
+async fn cancel(name: &str, ctx: &mut StrategyContext, trading: &TradingService, order_id: u64) {
+    match trading.cancel_order(&ctx.symbol, order_id).await {
+        Ok(()) => ctx.open_orders.retain(|o| o.order_id != order_id),
+        Err(e) => tracing::warn!(strategy = name, order_id, "cancel failed: {}", e),
+    }
+}
+
+fn deliver_fill(strategy: &mut dyn Strategy, ctx: &mut StrategyContext, fill: &StrategyFill) {
+    tracing::info!(
+        strategy = strategy.name(),
+        order_id = fill.order_id,
+        is_buy = fill.is_buy,
+        price = fill.price,
+        size = fill.size,
+        "strategy fill"
+    );
+    ctx.apply_fill(fill);
+    let result = strategy.on_fill(ctx, fill);
+    report(strategy.name(), "on_fill", result);
+}
+
+// a failing hook is logged and the strategy keeps running
+fn report(name: &str, hook: &str, result: Result<()>) {
+    if let Err(e) = result {
+        tracing::warn!(strategy = name, hook, "strategy error: {:#}", e);
+    }
+}
+
+pub fn interval_millis(interval: &str) -> Option<u64> {
+    const MINUTE: u64 = 60_000;
+    let minutes = match interval {
+        "1m" => 1,
+        "3m" => 3,
+        "5m" => 5,
+        "15m" => 15,
+        "30m" => 30,
+        "1h" => 60,
+        "2h" => 120,
+        "4h" => 240,
+        "8h" => 480,
+        "12h" => 720,
+        "1d" => 1_440,
+        "3d" => 4_320,
+        "1w" => 10_080,
+        _ => return None,
+    };
+    Some(minutes * MINUTE)
+}
+
+fn to_tick(data: BboData) -> Option<Tick> {
+    let bid: f64 = data.bbo.first()?.as_ref()?.px.parse().ok()?;
+    let ask: f64 = data.bbo.get(1)?.as_ref()?.px.parse().ok()?;
+    Some(Tick {
+        symbol: data.coin,
+        bid,
+        ask,
+        mid: (bid + ask) / 2.0,
+        time: data.time,
+    })
+}
+
+fn to_market_trade(trade: &TradeData) -> Option<MarketTrade> {
+    Some(MarketTrade {
+        symbol: trade.coin.clone(),
+        is_buy: trade.side == "B",
+        price: trade.px.parse().ok()?,
+        size: trade.sz.parse().ok()?,
+        time: trade.time,
+    })
+}
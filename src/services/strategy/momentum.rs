@@ -0,0 +1,128 @@
+// reference signal strategy: moving-average crossover on closed candles, traded with market orders
+use anyhow::Result;
+use crate::{
+    services::strategy::{Strategy, StrategyContext},
+    types::{Candle, OrderRequest},
+};
+
+const SIZE_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone)]
+pub struct MomentumParams {
+    pub interval: String,
+    pub fast: usize,
+    pub slow: usize,
+    pub qty: f64,
+    // exit to flat on a bearish cross instead of going short
+    pub long_only: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Bullish,
+    Bearish,
+}
+
+pub struct Momentum {
+    params: MomentumParams,
+    closes: Vec<f64>,
+    forming: Option<Candle>,
+}
+
+impl Momentum {
+    pub fn new(params: MomentumParams) -> Self {
+        Self {
+            params,
+            closes: Vec::new(),
+            forming: None,
+        }
+    }
+
+    // records a closed candle and reports a fresh crossover, if any
+    pub fn push_close(&mut self, close: f64) -> Option<Signal> {
+        self.closes.push(close);
+        // only the last slow + 1 closes are ever needed
+        let keep = self.params.slow + 1;
+        if self.closes.len() > keep {
+            self.closes.drain(..self.closes.len() - keep);
+        }
+        if self.closes.len() < keep {
+            return None;
+        }
+
+        let (previous, current) = (&self.closes[..self.closes.len() - 1], &self.closes[..]);
+        let was_above = sma(previous, self.params.fast) > sma(previous, self.params.slow);
+        let is_above = sma(current, self.params.fast) > sma(current, self.params.slow);
+
+        match (was_above, is_above) {
+            (false, true) => Some(Signal::Bullish),
+            (true, false) => Some(Signal::Bearish),
+            _ => None,
+        }
+    }
+
+    fn trade_towards(&self, ctx: &mut StrategyContext, signal: Signal) {
+        let target = match signal {
+            Signal::Bullish => self.params.qty,
+            Signal::Bearish if self.params.long_only => 0.0,
+            Signal::Bearish => -self.params.qty,
+        };
+        let delta = target - ctx.position;
+        if delta.abs() <= SIZE_EPSILON {
+            return;
+        }
+
+        tracing::info!(symbol = %ctx.symbol, ?signal, position = ctx.position, target, "momentum signal");
+        ctx.place(OrderRequest {
+            symbol: ctx.symbol.clone(),
+            is_buy: delta > 0.0,
+            qty: delta.abs(),
+            limit_price: None,
+            leverage: None,
+            // moving to flat only ever reduces
+            reduce_only: target == 0.0,
+            tif: "Ioc".to_string(),
+            ..Default::default()
+        });
+    }
+}
+
+impl Strategy for Momentum {
+    fn name(&self) -> &str {
+        "momentum"
+    }
+
+    fn candle_interval(&self) -> Option<&str> {
+        Some(&self.params.interval)
+    }
+
+    fn warm_up(&mut self, candles: &[Candle]) {
+        let Some((forming, closed)) = candles.split_last() else {
+            return;
+        };
+        for candle in closed {
+            // history only primes the averages, it never trades
+            self.push_close(candle.close);
+        }
+        self.forming = Some(forming.clone());
+    }
+
+    fn on_candle(&mut self, ctx: &mut StrategyContext, candle: &Candle) -> Result<()> {
+        // a new open time means the previous candle has closed
+        let closed = match &self.forming {
+            Some(forming) if forming.open_time != candle.open_time => Some(forming.close),
+            _ => None,
+        };
+        self.forming = Some(candle.clone());
+
+        if let Some(signal) = closed.and_then(|close| self.push_close(close)) {
+            self.trade_towards(ctx, signal);
+        }
+        Ok(())
+    }
+}
+
+fn sma(values: &[f64], period: usize) -> f64 {
+    let window = &values[values.len().saturating_sub(period)..];
+    window.iter().sum::<f64>() / window.len().max(1) as f64
+}
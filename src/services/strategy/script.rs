@@ -0,0 +1,155 @@
+// rhai-backed strategy: a .rhai file defines on_tick/on_trade/on_fill and keeps state on `this`
+use std::{
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+use anyhow::{Context, Result};
+use rhai::{CallFnOptions, Dynamic, Engine, Map, Scope, AST, FLOAT, INT};
+use serde::Serialize;
+use crate::{
+    services::strategy::{Strategy, StrategyContext},
+    types::{MarketTrade, StrategyFill, Tick},
+};
+
+// keeps a runaway script from stalling the event loop
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+pub struct ScriptStrategy {
+    name: String,
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    // bound as `this` in every hook
+    state: Dynamic,
+    // the context is swapped in here while a hook runs so registered functions can reach it
+    shared: Arc<Mutex<StrategyContext>>,
+}
+
+impl ScriptStrategy {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read strategy script {}", path.display()))?;
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "script".to_string());
+        Self::compile(&name, &source)
+    }
+
+    pub fn compile(name: &str, source: &str) -> Result<Self> {
+        let shared = Arc::new(Mutex::new(StrategyContext::default()));
+        let engine = build_engine(name, &shared);
+
+        let ast = engine
+            .compile(source)
+            .map_err(|e| anyhow::anyhow!("Failed to compile {}: {}", name, e))?;
+
+        let mut strategy = Self {
+            name: name.to_string(),
+            engine,
+            ast,
+            scope: Scope::new(),
+            state: Dynamic::from_map(Map::new()),
+            shared,
+        };
+
+        strategy
+            .engine
+            .run_ast_with_scope(&mut strategy.scope, &strategy.ast)
+            .map_err(|e| anyhow::anyhow!("Failed to run {}: {}", name, e))?;
+        if strategy.has_hook("init", 0) {
+            strategy.call_hook("init", ())?;
+        }
+
+        Ok(strategy)
+    }
+
+    fn has_hook(&self, hook: &str, params: usize) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == hook && f.params.len() == params)
+    }
+
+    fn call_hook(&mut self, hook: &str, args: impl rhai::FuncArgs) -> Result<()> {
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+
+        self.engine
+            .call_fn_with_options::<Dynamic>(options, &mut self.scope, &self.ast, hook, args)
+            .map(|_| ())
+            .map_err(|e| anyhow::anyhow!("{} failed: {}", hook, e))
+    }
+
+    fn dispatch<T: Serialize>(&mut self, ctx: &mut StrategyContext, hook: &str, event: &T) -> Result<()> {
+        if !self.has_hook(hook, 1) {
+            return Ok(());
+        }
+        let event = rhai::serde::to_dynamic(event).map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        std::mem::swap(&mut *lock(&self.shared), ctx);
+        let result = self.call_hook(hook, (event,));
+        std::mem::swap(&mut *lock(&self.shared), ctx);
+        result
+    }
+}
+
+impl Strategy for ScriptStrategy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_tick(&mut self, ctx: &mut StrategyContext, tick: &Tick) -> Result<()> {
+        self.dispatch(ctx, "on_tick", tick)
+    }
+
+    fn on_trade(&mut self, ctx: &mut StrategyContext, trade: &MarketTrade) -> Result<()> {
+        self.dispatch(ctx, "on_trade", trade)
+    }
+
+    fn on_fill(&mut self, ctx: &mut StrategyContext, fill: &StrategyFill) -> Result<()> {
+        self.dispatch(ctx, "on_fill", fill)
+    }
+}
+
+// script api: buy/sell(qty[, price]), cancel(id), cancel_all(), position(), open_orders(), symbol(), mid()
+fn build_engine(name: &str, shared: &Arc<Mutex<StrategyContext>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+
+    let script = name.to_string();
+    engine.on_print(move |text| tracing::info!(strategy = %script, "{}", text));
+    let script = name.to_string();
+    engine.on_debug(move |text, _, pos| tracing::debug!(strategy = %script, %pos, "{}", text));
+
+    let ctx = shared.clone();
+    engine.register_fn("buy", move |qty: FLOAT| lock(&ctx).buy(qty, None));
+    let ctx = shared.clone();
+    engine.register_fn("buy", move |qty: FLOAT, price: FLOAT| lock(&ctx).buy(qty, Some(price)));
+    let ctx = shared.clone();
+    engine.register_fn("sell", move |qty: FLOAT| lock(&ctx).sell(qty, None));
+    let ctx = shared.clone();
+    engine.register_fn("sell", move |qty: FLOAT, price: FLOAT| lock(&ctx).sell(qty, Some(price)));
+    let ctx = shared.clone();
+    engine.register_fn("cancel", move |order_id: INT| lock(&ctx).cancel(order_id as u64));
+    let ctx = shared.clone();
+    engine.register_fn("cancel_all", move || lock(&ctx).cancel_all());
+    let ctx = shared.clone();
+    engine.register_fn("position", move || -> FLOAT { lock(&ctx).position });
+    let ctx = shared.clone();
+    engine.register_fn("symbol", move || -> String { lock(&ctx).symbol.clone() });
+    let ctx = shared.clone();
+    engine.register_fn("mid", move || -> Dynamic {
+        lock(&ctx).last_mid.map(Dynamic::from_float).unwrap_or(Dynamic::UNIT)
+    });
+    let ctx = shared.clone();
+    engine.register_fn("open_orders", move || -> Dynamic {
+        rhai::serde::to_dynamic(&lock(&ctx).open_orders).unwrap_or(Dynamic::UNIT)
+    });
+
+    engine
+}
+
+fn lock(shared: &Arc<Mutex<StrategyContext>>) -> MutexGuard<'_, StrategyContext> {
+    shared.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -1,9 +1,72 @@
+use std::collections::VecDeque;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use futures_util::{SinkExt, StreamExt};
 use crate::types::{Config, streaming::*};
 use tokio::time::{Duration};
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use tokio_tungstenite::tungstenite::protocol::Message;
+use crate::services::net;
+
+// running totals over every trade seen since the stream started
+#[derive(Debug, Default)]
+pub struct TradeStats {
+    pub trades: u64,
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    notional: f64,
+    // (price, size, is_buy)
+    pub largest: Option<(f64, f64, bool)>,
+    // trade times (ms) within the last minute
+    recent: VecDeque<u64>,
+}
+
+impl TradeStats {
+    pub fn record(&mut self, price: f64, size: f64, is_buy: bool, time_ms: u64) {
+        self.trades += 1;
+        if is_buy {
+            self.buy_volume += size;
+        } else {
+            self.sell_volume += size;
+        }
+        self.notional += price * size;
+        if self.largest.is_none_or(|(_, largest, _)| size > largest) {
+            self.largest = Some((price, size, is_buy));
+        }
+        self.recent.push_back(time_ms);
+        let cutoff = time_ms.saturating_sub(60_000);
+        while self.recent.front().is_some_and(|&t| t <= cutoff) {
+            self.recent.pop_front();
+        }
+    }
+
+    pub fn vwap(&self) -> Option<f64> {
+        let volume = self.buy_volume + self.sell_volume;
+        (volume > 0.0).then(|| self.notional / volume)
+    }
+
+    pub fn trades_last_minute(&self) -> usize {
+        self.recent.len()
+    }
+
+    pub fn summary(&self) -> String {
+        let Some(vwap) = self.vwap() else {
+            return "no trades yet".to_string();
+        };
+        let largest = self
+            .largest
+            .map(|(price, size, is_buy)| format!("{} {:.4} @ ${:.4}", if is_buy { "BUY" } else { "SELL" }, size, price))
+            .unwrap_or_default();
+        format!(
+            "VWAP ${:.4}  buy {:.4} / sell {:.4}  largest {}  trades {} ({}/min)",
+            vwap,
+            self.buy_volume,
+            self.sell_volume,
+            largest,
+            self.trades,
+            self.trades_last_minute()
+        )
+    }
+}
 
 #[derive(Clone)]
 pub struct StreamingService {
@@ -16,14 +79,21 @@ impl StreamingService {
     }
    
 
-    pub async fn stream_data(&self, symbol: &str, _stream_type: &str, duration: u64) -> Result<()> {
+    // `stats_every` adds a running vwap / volume summary line to the tape at that interval;
+    // `filter` only hides prints from the tape, the stats still count every trade
+    pub async fn stream_data(
+        &self,
+        symbol: &str,
+        _stream_type: &str,
+        duration: u64,
+        stats_every: Option<Duration>,
+        filter: &TradeFilter,
+    ) -> Result<()> {
         let ws_url = self.config.ws_url.clone();
 
-        println!("Connecting to WebSocket: {}", ws_url);
+        tracing::info!(url = %ws_url, "connecting to websocket");
 
-        let (ws_stream, _) = connect_async(ws_url)
-            .await
-            .context("Failed to connect to WebSocket")?;
+        let ws_stream = net::connect_ws(&ws_url, &self.config.http).await?;
 
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
 
@@ -43,17 +113,21 @@ impl StreamingService {
             .await
             .context("Failed to send subscription")?;
 
-        println!("Subscribed to trades for {}", symbol);
+        tracing::info!(symbol, "subscribed to trades");
 
-        self.print_stream_header(symbol, duration);
+        self.print_stream_header(symbol, duration, filter);
 
         let start_time = std::time::Instant::now();
         let mut message_count = 0;
         let mut trade_count = 0;
+        let mut shown_count = 0;
 
         let ping_interval = Duration::from_secs(30);
         let mut last_ping = std::time::Instant::now();
 
+        let mut stats = TradeStats::default();
+        let mut last_summary = std::time::Instant::now();
+
         let mut subscription_confirmed = false;
         let mut no_message_count = 0;
         let max_no_message_cycles = 100; 
@@ -64,6 +138,13 @@ impl StreamingService {
                 break;
             }
 
+            if let Some(every) = stats_every
+                && last_summary.elapsed() >= every
+            {
+                println!("── {}  {}", Utc::now().format("%H:%M:%S"), stats.summary());
+                last_summary = std::time::Instant::now();
+            }
+
             if last_ping.elapsed() >= ping_interval {
                 let ping_msg = serde_json::json!({"method": "ping"});
                 if let Ok(ping_str) = serde_json::to_string(&ping_msg) {
@@ -96,7 +177,12 @@ impl StreamingService {
                                     if let Ok(trades_resp) = serde_json::from_value::<TradesResponse>(ws_msg) {
                                         for trade in trades_resp.data {
                                             trade_count += 1;
-                                            self.print_trade(&trade);
+                                            let (price, size) = (trade.px.parse().unwrap_or(0.0), trade.sz.parse().unwrap_or(0.0));
+                                            stats.record(price, size, trade.side == "B", trade.time);
+                                            if filter.matches(price, size, trade.side == "B") {
+                                                shown_count += 1;
+                                                self.print_trade(&trade);
+                                            }
                                         }
                                     }
                                 }
@@ -105,18 +191,18 @@ impl StreamingService {
                         Message::Pong(_) => {
                         }
                         Message::Close(_) => {
-                            println!("WebSocket connection closed by server");
+                            tracing::warn!("websocket connection closed by server");
                             break;
                         }
                         _ => {}
                     }
                 }
                 Ok(Some(Err(e))) => {
-                    eprintln!("WebSocket error: {}", e);
+                    tracing::error!("websocket error: {}", e);
                     break;
                 }
                 Ok(None) => {
-                    println!("WebSocket connection ended");
+                    tracing::warn!("websocket connection ended");
                     break;
                 }
                 Err(_) => {
@@ -156,6 +242,12 @@ impl StreamingService {
         println!("Duration: {}s", start_time.elapsed().as_secs());
         println!("Total WebSocket messages: {}", message_count);
         println!("Total trades received: {}", trade_count);
+        if !filter.is_empty() {
+            println!("Trades matching filters: {}", shown_count);
+        }
+        if stats_every.is_some() {
+            println!("Session: {}", stats.summary());
+        }
         
         if trade_count == 0 {
             println!("No trades received - this could mean:");
@@ -167,7 +259,7 @@ impl StreamingService {
         Ok(())
     }
 
-    fn print_stream_header(&self, symbol: &str, duration: u64) {
+    fn print_stream_header(&self, symbol: &str, duration: u64, filter: &TradeFilter) {
         let network = if self.config.api_url.contains("testnet") {
             "TESTNET"
         } else {
@@ -180,6 +272,19 @@ impl StreamingService {
         println!("Symbol: {}", symbol);
         println!("Type: TRADES");
         println!("Duration: {}s", duration);
+        if !filter.is_empty() {
+            let mut parts = Vec::new();
+            if let Some(side) = filter.side {
+                parts.push(if side == TradeSide::Buy { "buys only" } else { "sells only" }.to_string());
+            }
+            if let Some(min) = filter.min_size {
+                parts.push(format!("size >= {}", min));
+            }
+            if let Some(min) = filter.min_notional {
+                parts.push(format!("notional >= ${}", min));
+            }
+            println!("Filters: {}", parts.join(", "));
+        }
         println!("Started: {}", Utc::now().format("%H:%M:%S UTC"));
         println!("═══════════════════════════════════════════════");
         println!("{:<12} {:<6} {:<12} {:<12} {:<10} {:<8}", 
@@ -0,0 +1,180 @@
+// symbol resolution and validation against exchange metadata, with "did you mean" suggestions for typos
+use std::collections::{BTreeMap, HashMap};
+use anyhow::{Context, Result};
+use crate::error::HlError;
+use crate::services::spot::SpotNames;
+use crate::types::{
+    exchange::{AssetInfo, SpotMeta, SpotPair, SpotToken},
+    MarketType, ResolvedSymbol,
+};
+
+// suggestions further away than this are more confusing than helpful
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+// closest known symbol, compared case-insensitively
+pub fn suggest<'a>(symbol: &str, known: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let wanted = symbol.to_uppercase();
+    known
+        .into_iter()
+        .map(|candidate| (levenshtein(&wanted, &candidate.to_uppercase()), candidate))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|(distance, candidate)| (*distance, candidate.len()))
+        .map(|(_, candidate)| candidate)
+}
+
+pub fn check_symbol<'a>(symbol: &str, known: impl IntoIterator<Item = &'a str> + Clone) -> Result<()> {
+    if known.clone().into_iter().any(|candidate| candidate == symbol) {
+        return Ok(());
+    }
+
+    Err(unknown_symbol(symbol, suggest(symbol, known)))
+}
+
+fn unknown_symbol(symbol: &str, suggestion: Option<&str>) -> anyhow::Error {
+    let reason = match suggestion {
+        Some(candidate) => format!("Unknown symbol {} — did you mean {}?", symbol, candidate),
+        None => format!("Unknown symbol {}", symbol),
+    };
+    HlError::validation("symbol", reason).into()
+}
+
+// [aliases] as read from the config file: names lowercased, targets left for the resolver
+pub fn parse_aliases(raw: &BTreeMap<String, String>) -> Result<BTreeMap<String, String>> {
+    let mut aliases = BTreeMap::new();
+    for (name, target) in raw {
+        let alias = name.trim().to_lowercase();
+        if alias.is_empty() || !alias.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            anyhow::bail!("Invalid alias '{}', use letters, digits, '-' and '_'", name);
+        }
+        if target.trim().is_empty() {
+            anyhow::bail!("Alias '{}' has an empty target", name);
+        }
+        if aliases.insert(alias, target.trim().to_string()).is_some() {
+            anyhow::bail!("Alias '{}' is defined twice", name);
+        }
+    }
+    Ok(aliases)
+}
+
+// perp prices allow 6 decimals minus szDecimals, spot prices 8
+const PERP_PRICE_DECIMALS: u32 = 6;
+const SPOT_PRICE_DECIMALS: u32 = 8;
+
+// one place that turns what a user typed into the asset the exchange knows, perp or spot
+#[derive(Debug, Clone)]
+pub struct SymbolResolver {
+    perps: Vec<AssetInfo>,
+    spot: SpotMeta,
+    // lowercased alias -> what it stands for, from [aliases]
+    aliases: BTreeMap<String, String>,
+}
+
+impl SymbolResolver {
+    pub fn new(perps: Vec<AssetInfo>, spot: SpotMeta) -> Self {
+        Self { perps, spot, aliases: BTreeMap::new() }
+    }
+
+    pub fn with_aliases(mut self, aliases: BTreeMap<String, String>) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    // "BTC", "btc", "kpepe", "PURR/USDC", "ubtc/usdc" and "@142" all resolve; anything else errors
+    // with the closest perp or pair name. a configured alias wins over the exchange's names and is
+    // expanded once, so aliases can't chain
+    pub fn resolve(&self, input: &str) -> Result<ResolvedSymbol> {
+        let input = input.trim();
+        match self.aliases.get(&input.to_lowercase()) {
+            Some(target) => self.resolve_listed(target).map_err(|e| {
+                HlError::validation("symbol", format!("Alias {} points at {}: {}", input.to_lowercase(), target, e)).into()
+            }),
+            None => self.resolve_listed(input),
+        }
+    }
+
+    fn resolve_listed(&self, input: &str) -> Result<ResolvedSymbol> {
+        if input.is_empty() {
+            return Err(HlError::validation("symbol", "Empty symbol").into());
+        }
+        if input.starts_with('@') || input.contains('/') {
+            return self.resolve_spot(input);
+        }
+        let listed = || self.perps.iter().filter(|asset| !asset.is_delisted);
+        let asset = listed()
+            .find(|asset| asset.name == input)
+            .or_else(|| listed().find(|asset| asset.name.eq_ignore_ascii_case(input)));
+        if let Some(asset) = asset {
+            return Ok(ResolvedSymbol {
+                name: asset.name.clone(),
+                display: asset.name.clone(),
+                market: MarketType::Perp,
+                sz_decimals: asset.sz_decimals,
+                price_decimals: PERP_PRICE_DECIMALS.saturating_sub(asset.sz_decimals),
+                max_leverage: Some(asset.max_leverage),
+            });
+        }
+        if self.perps.iter().any(|asset| asset.name.eq_ignore_ascii_case(input)) {
+            return Err(HlError::validation("symbol", format!("{} is delisted", input.to_uppercase())).into());
+        }
+        self.unknown(input)
+    }
+
+    fn resolve_spot(&self, input: &str) -> Result<ResolvedSymbol> {
+        let tokens: HashMap<u32, &SpotToken> = self.spot.tokens.iter().map(|token| (token.index, token)).collect();
+        let pair_name = |pair: &SpotPair| {
+            let (base, quote) = (tokens.get(&pair.tokens[0])?, tokens.get(&pair.tokens[1])?);
+            Some(format!("{}/{}", base.name, quote.name))
+        };
+        let pair = match input.strip_prefix('@') {
+            Some(index) => {
+                let index: u32 = index.parse().with_context(|| format!("Invalid spot pair index {}", input))?;
+                self.spot.universe.iter().find(|pair| pair.index == index)
+            }
+            None => self.spot.universe.iter().find(|pair| {
+                pair.name.eq_ignore_ascii_case(input) || pair_name(pair).is_some_and(|name| name.eq_ignore_ascii_case(input))
+            }),
+        };
+        let Some(pair) = pair else {
+            return self.unknown(input);
+        };
+        let base = tokens.get(&pair.tokens[0]).with_context(|| format!("Spot pair {} has no base token", pair.name))?;
+        Ok(ResolvedSymbol {
+            name: pair.name.clone(),
+            display: pair_name(pair).unwrap_or_else(|| pair.name.clone()),
+            market: MarketType::Spot,
+            sz_decimals: base.sz_decimals,
+            price_decimals: SPOT_PRICE_DECIMALS.saturating_sub(base.sz_decimals),
+            max_leverage: None,
+        })
+    }
+
+    fn unknown(&self, input: &str) -> Result<ResolvedSymbol> {
+        let spot = SpotNames::from_meta(&self.spot);
+        let spot_names: Vec<String> = self.spot.universe.iter().map(|pair| spot.display(&pair.name)).collect();
+        let known = self
+            .perps
+            .iter()
+            .filter(|asset| !asset.is_delisted)
+            .map(|asset| asset.name.as_str())
+            .chain(spot_names.iter().map(String::as_str))
+            .chain(self.aliases.keys().map(String::as_str));
+        Err(unknown_symbol(input, suggest(input, known)))
+    }
+}
@@ -0,0 +1,126 @@
+// merged trade tape across the most traded markets, held briefly so it prints in trade-time order
+use std::{
+    io::IsTerminal,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use crate::services::ws_hub::{self, SubscriptionKey, WsHub};
+use crate::types::{streaming::{TradeData, TradeFilter}, MarketInfo};
+
+// how long a trade waits for slower symbols' earlier trades before printing
+pub const REORDER_HOLD: Duration = Duration::from_millis(500);
+const FLUSH_EVERY: Duration = Duration::from_millis(100);
+const RESET: &str = "\x1b[0m";
+const PALETTE: [&str; 8] = ["\x1b[36m", "\x1b[35m", "\x1b[33m", "\x1b[32m", "\x1b[34m", "\x1b[91m", "\x1b[96m", "\x1b[93m"];
+
+// perps by 24h notional volume, highest first
+pub fn top_symbols(markets: &[MarketInfo], top: usize) -> Vec<String> {
+    let mut markets: Vec<&MarketInfo> = markets.iter().collect();
+    markets.sort_by(|a, b| b.volume_24h.total_cmp(&a.volume_24h));
+    markets.into_iter().take(top).map(|m| m.symbol.clone()).collect()
+}
+
+pub struct TapeBuffer {
+    hold: Duration,
+    pending: Vec<(Instant, TradeData)>,
+}
+
+impl TapeBuffer {
+    pub fn new(hold: Duration) -> Self {
+        Self { hold, pending: Vec::new() }
+    }
+
+    pub fn push(&mut self, trade: TradeData, arrived: Instant) {
+        self.pending.push((arrived, trade));
+    }
+
+    // everything up to the newest trade that has waited out the hold, oldest first
+    pub fn release(&mut self, now: Instant) -> Vec<TradeData> {
+        let Some(cutoff) = self
+            .pending
+            .iter()
+            .filter(|(arrived, _)| now.duration_since(*arrived) >= self.hold)
+            .map(|(_, trade)| (trade.time, trade.tid))
+            .max()
+        else {
+            return Vec::new();
+        };
+        let (mut ready, waiting): (Vec<_>, Vec<_>) =
+            self.pending.drain(..).partition(|(_, trade)| (trade.time, trade.tid) <= cutoff);
+        self.pending = waiting;
+        ready.sort_by_key(|(_, trade)| (trade.time, trade.tid));
+        ready.into_iter().map(|(_, trade)| trade).collect()
+    }
+}
+
+pub fn format_trade(trade: &TradeData, color: Option<&str>) -> String {
+    let time = DateTime::from_timestamp_millis(trade.time as i64).unwrap_or_else(Utc::now);
+    let price: f64 = trade.px.parse().unwrap_or(0.0);
+    let size: f64 = trade.sz.parse().unwrap_or(0.0);
+    let symbol = match color {
+        Some(color) => format!("{}{:<10}{}", color, trade.coin, RESET),
+        None => format!("{:<10}", trade.coin),
+    };
+    format!(
+        "{} {} {:<4} {:>14.4} @ {:<12} ${:>12.0}",
+        time.format("%H:%M:%S%.3f"),
+        symbol,
+        if trade.side == "B" { "BUY" } else { "SELL" },
+        size,
+        trade.px,
+        price * size
+    )
+}
+
+// runs until ctrl-c or the upstream stream closes
+pub async fn run(hub: Arc<WsHub>, symbols: &[String], filter: &TradeFilter) -> Result<()> {
+    let color = std::io::stdout().is_terminal();
+    // the subscribe snapshot is recent history, the tape only shows trades from now on
+    let started = Utc::now().timestamp_millis() as u64;
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    for symbol in symbols {
+        let mut subscription = hub.subscribe_owned(SubscriptionKey::for_coin("trades", symbol));
+        let sender = sender.clone();
+        tokio::spawn(async move {
+            loop {
+                match subscription.updates.recv().await {
+                    Ok(text) => {
+                        for trade in ws_hub::parse_trades(&text) {
+                            if sender.send(trade).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => tracing::warn!(skipped, "tape falling behind, trades dropped"),
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+    drop(sender);
+
+    let mut buffer = TapeBuffer::new(REORDER_HOLD);
+    let mut flush = tokio::time::interval(FLUSH_EVERY);
+    loop {
+        tokio::select! {
+            trade = receiver.recv() => {
+                let Some(trade) = trade else {
+                    anyhow::bail!("Upstream stream closed");
+                };
+                let (price, size) = (trade.px.parse().unwrap_or(0.0), trade.sz.parse().unwrap_or(0.0));
+                if trade.time >= started && filter.matches(price, size, trade.side == "B") {
+                    buffer.push(trade, Instant::now());
+                }
+            }
+            _ = flush.tick() => {
+                for trade in buffer.release(Instant::now()) {
+                    let slot = symbols.iter().position(|s| *s == trade.coin).unwrap_or(0);
+                    println!("{}", format_trade(&trade, color.then(|| PALETTE[slot % PALETTE.len()])));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+// realized gains from fills, matching closes against open lots
+use std::collections::{HashMap, VecDeque};
+use crate::types::{ClosedTrade, CostMethod, FillInfo};
+
+const SIZE_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone)]
+struct Lot {
+    is_long: bool,
+    size: f64,
+    price: f64,
+    // fee paid to open, per unit
+    fee_per_unit: f64,
+    opened_at: u64,
+}
+
+// fills must be in chronological order; every reduction of a position becomes a closed trade
+pub fn closed_trades(fills: &[FillInfo], method: CostMethod) -> Vec<ClosedTrade> {
+    let mut lots: HashMap<&str, VecDeque<Lot>> = HashMap::new();
+    let mut trades = Vec::new();
+
+    for fill in fills {
+        if fill.size <= SIZE_EPSILON {
+            continue;
+        }
+
+        let is_buy = fill.side == "BUY";
+        let fee_per_unit = fill.fee / fill.size;
+        let open_lots = lots.entry(fill.symbol.as_str()).or_default();
+        let mut remaining = fill.size;
+
+        // a buy closes shorts, a sell closes longs
+        while remaining > SIZE_EPSILON {
+            let Some(lot) = open_lots.front_mut() else {
+                break;
+            };
+            if lot.is_long == is_buy {
+                break;
+            }
+
+            let matched = remaining.min(lot.size);
+            let (proceeds, cost_basis) = if lot.is_long {
+                (
+                    matched * (fill.price - fee_per_unit),
+                    matched * (lot.price + lot.fee_per_unit),
+                )
+            } else {
+                (
+                    matched * (lot.price - lot.fee_per_unit),
+                    matched * (fill.price + fee_per_unit),
+                )
+            };
+
+            trades.push(ClosedTrade {
+                symbol: fill.symbol.clone(),
+                direction: if lot.is_long { "long" } else { "short" }.to_string(),
+                quantity: matched,
+                acquired_at: lot.opened_at,
+                closed_at: fill.timestamp,
+                proceeds,
+                cost_basis,
+                gain: proceeds - cost_basis,
+            });
+
+            lot.size -= matched;
+            remaining -= matched;
+            if lot.size <= SIZE_EPSILON {
+                open_lots.pop_front();
+            }
+        }
+
+        if remaining > SIZE_EPSILON {
+            let lot = Lot {
+                is_long: is_buy,
+                size: remaining,
+                price: fill.price,
+                fee_per_unit,
+                opened_at: fill.timestamp,
+            };
+            match method {
+                CostMethod::Fifo => open_lots.push_back(lot),
+                CostMethod::Average => merge_into_average(open_lots, lot),
+            }
+        }
+    }
+
+    trades
+}
+
+// average cost keeps a single pooled lot per symbol
+fn merge_into_average(open_lots: &mut VecDeque<Lot>, lot: Lot) {
+    let Some(pooled) = open_lots.front_mut() else {
+        open_lots.push_back(lot);
+        return;
+    };
+
+    let total = pooled.size + lot.size;
+    pooled.price = (pooled.price * pooled.size + lot.price * lot.size) / total;
+    pooled.fee_per_unit = (pooled.fee_per_unit * pooled.size + lot.fee_per_unit * lot.size) / total;
+    pooled.size = total;
+}
+
+pub fn to_csv(trades: &[ClosedTrade]) -> String {
+    let mut csv = String::from("symbol,direction,quantity,date_acquired,date_closed,proceeds,cost_basis,gain\n");
+    for trade in trades {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{:.2},{:.2},{:.2}\n",
+            trade.symbol,
+            trade.direction,
+            trade.quantity,
+            format_date(trade.acquired_at),
+            format_date(trade.closed_at),
+            trade.proceeds,
+            trade.cost_basis,
+            trade.gain
+        ));
+    }
+    csv
+}
+
+fn format_date(millis: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis as i64)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
@@ -1,62 +1,268 @@
-use crate::types::{Config, OrderRequest, OrderResponse, OrderResult};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, RwLock},
+    time::Instant,
+};
+use crate::services::{
+    audit::{AuditLog, AuditRecord},
+    daily_loss::{self, DailyLossGuard},
+    guard,
+    impact,
+    paper::PaperEngine,
+    rate_budget,
+    rate_limit::OrderRateLimit,
+    smart_order::{self, SmartPlan},
+    l1_actions,
+    nonce,
+    symbols,
+    user_actions::{self, SigningChain},
+    ws_post::{PostError, WsPoster},
+    ExchangeService, Notifier,
+};
+use crate::types::{builder_fee_percent, cloid_hex, parse_cloid, BuilderFee, Config, GroupedOrder, HedgePlan, ImpactEstimate, ResolvedSymbol, RiskSizing, Exposure, MarginMode, NotificationEvent, RiskCheckResult, RiskLimits, OrderRequest, OrderResponse, OrderResult, OrderTransport, PaperFill};
 use anyhow::{Context, Result};
+use crate::config::loader::MAX_MARKET_SLIPPAGE;
+use crate::error::HlError;
 use ethers::signers::LocalWallet;
+use ethers::types::H160;
+use uuid::Uuid;
 use hyperliquid_rust_sdk::{
-    BaseUrl, ClientLimit, ClientOrder, ClientOrderRequest, ExchangeClient, ExchangeDataStatus,
-    ExchangeResponseStatus, InfoClient, MarketCloseParams, MarketOrderParams,
+    ClientLimit, ClientOrder, ClientOrderRequest, ClientTrigger, ExchangeDataStatus,
+    ExchangeResponseStatus,
 };
 
+// hyperliquid base tier fee rates, used for dry-run estimates
+pub(crate) const TAKER_FEE_RATE: f64 = 0.00045;
+pub(crate) const MAKER_FEE_RATE: f64 = 0.00015;
+// perp prices may have at most this many decimals minus the asset's szDecimals
+const MAX_PRICE_DECIMALS: i32 = 6;
+// the exchange rejects orders worth less than this unless they are reduce-only
+const MIN_ORDER_VALUE: f64 = 10.0;
+
 pub struct TradingService {
-    exchange_client: ExchangeClient,
-    info_client: InfoClient,
+    wallet: LocalWallet,
+    exchange: ExchangeService,
+    notifier: Notifier,
+    audit: AuditLog,
+    // set in paper mode, orders are simulated locally instead of sent
+    paper: Option<Mutex<PaperEngine>>,
+    daily_loss: DailyLossGuard,
+    // swapped wholesale on config reload, validation works on one snapshot per order
+    risk_limits: SharedRiskLimits,
+    // shared by every caller of place_order in this process
+    order_rate: OrderRateLimit,
+    // set with order_transport = "ws", orders go over the websocket post method first
+    ws: Option<WsPoster>,
     config: Config,
 }
 
+pub type SharedRiskLimits = Arc<RwLock<Arc<RiskLimits>>>;
+
 impl TradingService {
     pub async fn new(config: Config) -> Result<Self> {
-        let wallet: LocalWallet = config
-            .private_key
-            .parse()
-            .context("Failed to parse private key")?;
-
-        let base_url = BaseUrl::Testnet;
-
-        let exchange_client = ExchangeClient::new(None, wallet, Some(base_url), None, None)
-            .await
-            .context("Failed to create exchange client")?;
+        let exchange = ExchangeService::new(config.clone())?;
+        Self::with_exchange(config, exchange).await
+    }
 
-        let info_client = InfoClient::new(None, Some(base_url))
-            .await
-            .context("Failed to create info client")?;
+    // signs with the wallet of `exchange`'s pool and shares its metadata caches
+    pub async fn with_exchange(config: Config, exchange: ExchangeService) -> Result<Self> {
+        let wallet = exchange.pool().wallet()?;
+        let daily_loss = DailyLossGuard::load(&config.risk_state_path)?;
+        let notifier = Notifier::new(config.notifications.clone(), &config.http);
+        let audit = AuditLog::new(config.audit_log_path.as_deref());
+        let paper = if config.paper.enabled {
+            Some(Mutex::new(PaperEngine::load(&config.paper)?))
+        } else {
+            None
+        };
+        let risk_limits = Arc::new(RwLock::new(Arc::new(config.risk_limits.clone())));
+        let ws = match config.order_transport {
+            OrderTransport::Ws => {
+                let ws = WsPoster::new(&config.ws_url, &config.http);
+                if let Err(e) = ws.connect().await {
+                    tracing::warn!(error = %e, "order websocket unavailable, orders will use rest until it connects");
+                }
+                Some(ws)
+            }
+            OrderTransport::Rest => None,
+        };
 
         Ok(Self {
-            exchange_client,
-            info_client,
+            wallet,
+            exchange,
+            notifier,
+            audit,
+            paper,
+            daily_loss,
+            risk_limits,
+            order_rate: OrderRateLimit::default(),
+            ws,
             config,
         })
     }
 
     // Main order placement with validation
-    pub async fn place_order(&self, order_request: OrderRequest) -> Result<OrderResponse> {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_millis() as u64;
-
-        // Validate order before placement
-        if let Err(validation_error) = self.validate_order(&order_request).await {
-            return Ok(OrderResponse {
-                status: "error".to_string(),
-                result: OrderResult::Error {
-                    message: validation_error.to_string(),
-                },
-                timestamp,
-            });
+    pub async fn place_order(&self, mut order_request: OrderRequest) -> Result<OrderResponse> {
+        let started = Instant::now();
+        let audit_request = serde_json::to_value(&order_request).unwrap_or_default();
+
+        // Round to exchange precision, then validate what would actually be sent
+        let validation = match self.round_order(&mut order_request).await {
+            Ok(()) => self.validate_order(&order_request, false).await.err(),
+            Err(e) => Some(e),
+        };
+
+        // refused orders come back as errors, so callers see the HlError kind instead of a response to inspect
+        let cloid = order_request.cloid;
+        let rejected_locally = validation.is_some();
+        let outcome = match validation {
+            Some(validation_error) => {
+                self.notifier
+                    .notify(NotificationEvent::RiskViolation {
+                        symbol: order_request.symbol.clone(),
+                        reason: validation_error.to_string(),
+                    })
+                    .await;
+                Err(validation_error)
+            }
+            None if self.config.dry_run => self.preview_order(&order_request).await,
+            None if self.paper.is_some() => self.paper_order(&order_request).await,
+            None => self.submit_order(order_request).await,
+        };
+        let outcome = outcome.map(|response| OrderResponse { cloid, ..response });
+
+        self.audit.record(&AuditRecord::new(
+            "place",
+            audit_request,
+            outcome.as_ref().err().filter(|_| rejected_locally),
+            &outcome,
+            started.elapsed(),
+        ));
+
+        outcome
+    }
+
+    // current risk limits, reflecting any reload since startup
+    pub fn risk_limits(&self) -> Arc<RiskLimits> {
+        self.risk_limits.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    // handle for a config watcher to swap in new limits
+    pub fn shared_risk_limits(&self) -> SharedRiskLimits {
+        self.risk_limits.clone()
+    }
+
+    pub async fn resolve_symbol(&self, symbol: &str) -> Result<ResolvedSymbol> {
+        self.exchange.resolve_symbol(symbol).await
+    }
+
+    // bound a market order is sent with: its own --slippage, else the configured default
+    pub fn market_slippage(&self, requested: Option<f64>) -> f64 {
+        requested.unwrap_or(self.config.market_slippage)
+    }
+
+    pub async fn active_leverage(&self, symbol: &str) -> Result<(u32, MarginMode)> {
+        self.exchange.get_active_leverage(symbol).await
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.config.dry_run
+    }
+
+    // prices a validated order the way it would be submitted, without touching the exchange
+    async fn preview_order(&self, order_request: &OrderRequest) -> Result<OrderResponse> {
+        let (price, fee_rate) = match order_request.limit_price {
+            Some(limit_price) if order_request.tif == "Alo" => (limit_price, MAKER_FEE_RATE),
+            Some(limit_price) => (limit_price, TAKER_FEE_RATE),
+            None => {
+                let mid = self.get_market_price(&order_request.symbol).await?;
+                let slippage = self.market_slippage(order_request.slippage);
+                let slippage = if order_request.is_buy { slippage } else { -slippage };
+                (round_significant(mid * (1.0 + slippage), 5), TAKER_FEE_RATE)
+            }
+        };
+        let notional = price * order_request.qty;
+        let fee_rate = fee_rate + order_request.builder.as_ref().map_or(0.0, BuilderFee::rate);
+
+        tracing::info!(
+            symbol = %order_request.symbol,
+            is_buy = order_request.is_buy,
+            price,
+            size = order_request.qty,
+            notional,
+            "dry run: order not submitted"
+        );
+
+        Ok(OrderResponse {
+            status: "dry_run".to_string(),
+            result: OrderResult::DryRun {
+                price,
+                size: order_request.qty,
+                notional,
+                estimated_fee: notional * fee_rate,
+            },
+            timestamp: now_millis(),
+            cloid: None,
+        })
+    }
+
+    pub fn is_paper(&self) -> bool {
+        self.paper.is_some()
+    }
+
+    // fills a validated order against the local simulator at the current mid
+    async fn paper_order(&self, order_request: &OrderRequest) -> Result<OrderResponse> {
+        let mid = self.get_market_price(&order_request.symbol).await?;
+        let timestamp = now_millis();
+
+        let mut engine = self.lock_paper()?;
+        let result = engine.place(order_request, mid, timestamp);
+        engine.save()?;
+
+        if let OrderResult::Error { message } = result {
+            return Err(HlError::ExchangeRejection(message).into());
         }
+        Ok(OrderResponse {
+            status: "success".to_string(),
+            result,
+            timestamp,
+            cloid: None,
+        })
+    }
 
-        // Set leverage if specified
-        if let Some(leverage) = order_request.leverage {
-            self.set_leverage(&order_request.symbol, leverage).await?;
+    // lets a live trade print fill resting paper orders
+    pub fn paper_on_trade(&self, symbol: &str, price: f64, timestamp: u64) -> Result<Vec<PaperFill>> {
+        let mut engine = self.lock_paper()?;
+        let fills = engine.on_trade(symbol, price, timestamp);
+        if !fills.is_empty() {
+            engine.save()?;
         }
+        Ok(fills)
+    }
+
+    pub fn paper_position(&self, symbol: &str) -> Result<f64> {
+        Ok(self
+            .lock_paper()?
+            .account()
+            .positions
+            .get(symbol)
+            .map(|p| p.size)
+            .unwrap_or(0.0))
+    }
+
+    fn lock_paper(&self) -> Result<std::sync::MutexGuard<'_, PaperEngine>> {
+        let paper = self.paper.as_ref().context("Paper trading is not enabled")?;
+        Ok(paper.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    // sends an already validated order to the exchange
+    async fn submit_order(&self, order_request: OrderRequest) -> Result<OrderResponse> {
+        self.apply_leverage(&order_request).await?;
+
+        let symbol = order_request.symbol.clone();
+        let side = if order_request.is_buy { "BUY" } else { "SELL" };
+        let qty = order_request.qty;
+        let limit_price = order_request.limit_price;
 
         let result = if order_request.limit_price.is_some() {
             self.place_limit_order(order_request).await?
@@ -64,72 +270,438 @@ impl TradingService {
             self.place_market_order(order_request).await?
         };
 
-        match result {
-            ExchangeResponseStatus::Ok(response) => {
-                if let Some(data) = response.data {
-                    if let Some(status) = data.statuses.first() {
-                        let order_result = match status {
-                            ExchangeDataStatus::Success => OrderResult::Success {
-                                order_id: 0,
-                                filled_qty: 0.0,
-                                avg_price: None,
-                            },
-                            ExchangeDataStatus::Filled(filled) => OrderResult::Success {
-                                order_id: filled.oid,
-                                filled_qty: filled.total_sz.parse().unwrap_or(0.0),
-                                avg_price: Some(filled.avg_px.parse().unwrap_or(0.0)),
-                            },
-                            ExchangeDataStatus::Resting(resting) => OrderResult::Resting {
-                                order_id: resting.oid,
-                            },
-                            ExchangeDataStatus::Error(msg) => OrderResult::Error {
-                                message: msg.clone(),
-                            },
-                            _ => OrderResult::Error {
-                                message: "Unknown status".to_string(),
-                            },
-                        };
-
-                        return Ok(OrderResponse {
-                            status: "success".to_string(),
-                            result: order_result,
-                            timestamp,
-                        });
-                    }
+        let response = to_order_response(result)?;
+        self.notify_order_result(&symbol, side, qty, limit_price, &response.result)
+            .await;
+
+        Ok(response)
+    }
+
+    async fn notify_order_result(
+        &self,
+        symbol: &str,
+        side: &str,
+        qty: f64,
+        price: Option<f64>,
+        order_result: &OrderResult,
+    ) {
+        let event = match order_result {
+            OrderResult::Success { order_id, filled_qty, avg_price } if *filled_qty > 0.0 => {
+                NotificationEvent::Fill {
+                    symbol: symbol.to_string(),
+                    side: side.to_string(),
+                    filled_qty: *filled_qty,
+                    avg_price: avg_price.unwrap_or(0.0),
+                    order_id: *order_id,
+                }
+            }
+            OrderResult::Success { order_id, .. } | OrderResult::Resting { order_id } => {
+                NotificationEvent::OrderConfirmation {
+                    symbol: symbol.to_string(),
+                    side: side.to_string(),
+                    qty,
+                    order_id: *order_id,
+                    price,
                 }
+            }
+            OrderResult::Error { .. } | OrderResult::DryRun { .. } => return,
+        };
 
-                Ok(OrderResponse {
-                    status: "error".to_string(),
-                    result: OrderResult::Error {
-                        message: "No response data".to_string(),
-                    },
-                    timestamp,
-                })
-            }
-            ExchangeResponseStatus::Err(error) => Ok(OrderResponse {
-                status: "error".to_string(),
-                result: OrderResult::Error { message: error },
-                timestamp,
-            }),
-        }
+        self.notifier.notify(event).await;
     }
 
     // Comprehensive order validation
-    async fn validate_order(&self, order_request: &OrderRequest) -> Result<()> {
-        if !self.config.is_symbol_enabled(&order_request.symbol) {
-            anyhow::bail!("Trading disabled for symbol: {}", order_request.symbol);
+    // check_only leaves the order rate budget untouched
+    async fn validate_order(&self, order_request: &OrderRequest, check_only: bool) -> Result<()> {
+        let limits = self.risk_limits();
+        if !check_only {
+            self.order_rate.check(limits.max_orders_per_minute).map_err(reject("rate"))?;
         }
 
-        self.validate_leverage(&order_request.symbol, order_request.leverage)
-            .await?;
-        self.validate_notional(order_request).await?;
+        if !limits.is_symbol_enabled(&order_request.symbol) {
+            let reason = if limits.strict_symbols && limits.listed_limits(&order_request.symbol).is_none() {
+                format!("Symbol {} is not listed under [risk.symbols] (strict mode)", order_request.symbol)
+            } else {
+                format!("Trading disabled for symbol: {}", order_request.symbol)
+            };
+            return Err(HlError::ValidationError { rule: "symbol", reason }.into());
+        }
+        if order_request.limit_price.is_none() {
+            check_slippage(self.market_slippage(order_request.slippage))?;
+        }
+
+        // reduce-only orders can only shrink exposure, so they stay allowed after a bad day
+        if !order_request.reduce_only {
+            if let Some(lock) = guard::read_lock(&self.config.lock_path)? {
+                return Err(HlError::ValidationError {
+                    rule: "lock",
+                    reason: format!(
+                        "Trading locked since {}: {}; only reduce-only orders are allowed until `hl risk unlock`",
+                        lock.locked_at, lock.reason
+                    ),
+                }
+                .into());
+            }
+            self.validate_daily_loss(&limits, check_only).await.map_err(reject("daily_loss"))?;
+            self.validate_side(&limits, order_request).await.map_err(reject("side"))?;
+        }
+
+        self.validate_leverage(&limits, &order_request.symbol, order_request.leverage)
+            .await
+            .map_err(reject("leverage"))?;
+        let order_notional = self
+            .validate_notional(&limits, order_request)
+            .await
+            .map_err(reject("notional"))?;
+        if !order_request.reduce_only {
+            self.validate_exposure(&limits, &order_request.symbol, order_notional)
+                .await
+                .map_err(reject("exposure"))?;
+        }
 
         Ok(())
     }
 
-    async fn validate_leverage(&self, symbol: &str, requested_leverage: Option<u32>) -> Result<()> {
+    // rounds size to szDecimals and the limit price to the exchange's price precision,
+    // rejecting orders the exchange would refuse for precision or size
+    async fn round_order(&self, order_request: &mut OrderRequest) -> Result<()> {
+        let decimals = self.exchange.get_sz_decimals().await?;
+        let Some(&sz_decimals) = decimals.get(&order_request.symbol) else {
+            let reason = symbols::check_symbol(&order_request.symbol, decimals.keys().map(String::as_str))
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default();
+            return Err(HlError::ValidationError { rule: "symbol", reason }.into());
+        };
+
+        let size = round_size(order_request.qty, sz_decimals);
+        if size <= 0.0 {
+            return Err(HlError::ValidationError {
+                rule: "precision",
+                reason: format!(
+                    "Order size {} rounds to zero; {} trades in increments of {}",
+                    order_request.qty,
+                    order_request.symbol,
+                    10f64.powi(-(sz_decimals as i32))
+                ),
+            }
+            .into());
+        }
+        if size != order_request.qty {
+            tracing::info!(symbol = %order_request.symbol, requested = order_request.qty, size, "size rounded to szDecimals");
+            order_request.qty = size;
+        }
+
+        if let Some(limit_price) = order_request.limit_price {
+            let price = round_price(limit_price, sz_decimals);
+            if price <= 0.0 {
+                return Err(HlError::ValidationError {
+                    rule: "precision",
+                    reason: format!("Limit price {} rounds to zero for {}", limit_price, order_request.symbol),
+                }
+                .into());
+            }
+            if price != limit_price {
+                tracing::info!(symbol = %order_request.symbol, requested = limit_price, price, "limit price rounded to exchange precision");
+                order_request.limit_price = Some(price);
+            }
+        }
+
+        if !order_request.reduce_only {
+            let price = match order_request.limit_price {
+                Some(price) => price,
+                None => self.get_market_price(&order_request.symbol).await?,
+            };
+            let value = price * order_request.qty;
+            if value < MIN_ORDER_VALUE {
+                return Err(HlError::ValidationError {
+                    rule: "precision",
+                    reason: format!("Order value ${:.2} is below the exchange minimum of ${:.0}", value, MIN_ORDER_VALUE),
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    // (notional, equity, free margin) for putting pct% of equity to work at `leverage`
+    pub async fn notional_for_equity_pct(&self, pct: f64, leverage: u32) -> Result<(f64, f64, f64)> {
+        let (equity, free_margin) = if self.paper.is_some() {
+            let balance = self.lock_paper()?.account().balance;
+            (balance, balance)
+        } else {
+            let balances = self.exchange.get_balances().await?;
+            (balances.account_value, balances.withdrawable)
+        };
+
+        let notional = equity_pct_notional(equity, free_margin, pct, leverage);
+        if notional <= 0.0 {
+            anyhow::bail!("No free margin available (equity ${:.2}, free ${:.2})", equity, free_margin);
+        }
+        Ok((notional, equity, free_margin))
+    }
+
+    // paper balance, or the live account value
+    pub async fn account_equity(&self) -> Result<f64> {
+        if self.paper.is_some() {
+            return Ok(self.lock_paper()?.account().balance);
+        }
+        Ok(self.exchange.get_balances().await?.account_value)
+    }
+
+    // size that loses `risk_fraction` of equity if price goes from entry (mid if none) to stop
+    pub async fn size_for_risk(&self, symbol: &str, risk_fraction: f64, entry: Option<f64>, stop: f64) -> Result<RiskSizing> {
+        let entry = match entry {
+            Some(entry) => entry,
+            None => self.get_market_price(symbol).await?,
+        };
+        let sz_decimals = self
+            .exchange
+            .get_sz_decimals()
+            .await?
+            .get(symbol)
+            .copied()
+            .with_context(|| format!("No size decimals for {}", symbol))?;
+        let equity = self.account_equity().await?;
+
+        let size = risk_size(equity, risk_fraction, entry, stop, sz_decimals)?;
+        let notional = size * entry;
+        Ok(RiskSizing {
+            symbol: symbol.to_string(),
+            is_buy: stop < entry,
+            entry,
+            stop,
+            size,
+            equity,
+            risk_amount: size * (entry - stop).abs(),
+            notional,
+            effective_leverage: if equity > 0.0 { notional / equity } else { 0.0 },
+        })
+    }
+
+    // reduce-only stop loss ("sl") or take profit ("tp") that sends a market order at trigger_px
+    pub async fn place_trigger(&self, symbol: &str, is_buy: bool, size: f64, trigger_px: f64, tpsl: &str) -> Result<OrderResponse> {
+        let started = Instant::now();
+        let sz_decimals = self
+            .exchange
+            .get_sz_decimals()
+            .await?
+            .get(symbol)
+            .copied()
+            .with_context(|| format!("No size decimals for {}", symbol))?;
+        let trigger_px = round_price(trigger_px, sz_decimals);
+        // the market order a trigger sends still needs a worst acceptable price
+        let slippage = self.config.market_slippage;
+        let limit_px = round_price(
+            if is_buy { trigger_px * (1.0 + slippage) } else { trigger_px * (1.0 - slippage) },
+            sz_decimals,
+        );
+
+        let outcome = if self.config.dry_run {
+            tracing::info!(symbol, is_buy, size, trigger_px, tpsl, "dry run: trigger order not submitted");
+            Ok(OrderResponse {
+                status: "dry_run".to_string(),
+                result: OrderResult::DryRun {
+                    price: trigger_px,
+                    size,
+                    notional: size * trigger_px,
+                    estimated_fee: size * trigger_px * TAKER_FEE_RATE,
+                },
+                timestamp: now_millis(),
+                cloid: None,
+            })
+        } else if self.paper.is_some() {
+            Err(anyhow::anyhow!("The paper account doesn't simulate trigger orders"))
+        } else {
+            let order = ClientOrderRequest {
+                asset: symbol.to_string(),
+                is_buy,
+                reduce_only: true,
+                limit_px,
+                sz: size,
+                cloid: None,
+                order_type: ClientOrder::Trigger(ClientTrigger {
+                    is_market: true,
+                    trigger_px,
+                    tpsl: tpsl.to_string(),
+                }),
+            };
+            self.send_order(order, None)
+                .await
+                .and_then(to_order_response)
+                .context("Failed to place trigger order")
+        };
+
+        self.audit.record(&AuditRecord::new(
+            "trigger",
+            serde_json::json!({ "symbol": symbol, "is_buy": is_buy, "size": size, "trigger_px": trigger_px, "tpsl": tpsl }),
+            None,
+            &outcome,
+            started.elapsed(),
+        ));
+
+        outcome
+    }
+
+    // size worth `notional` usd at the given price (mid if none), rounded to szDecimals
+    pub async fn size_for_notional(&self, symbol: &str, notional: f64, price: Option<f64>) -> Result<(f64, f64)> {
+        let price = match price {
+            Some(price) => price,
+            None => self.get_market_price(symbol).await?,
+        };
+        let sz_decimals = self
+            .exchange
+            .get_sz_decimals()
+            .await?
+            .get(symbol)
+            .copied()
+            .with_context(|| format!("No size decimals for {}", symbol))?;
+
+        let size = round_size(notional / price, sz_decimals);
+        if size <= 0.0 {
+            anyhow::bail!(
+                "${:.2} of {} at ${:.4} is less than the minimum size increment",
+                notional,
+                symbol,
+                price
+            );
+        }
+        Ok((size, price))
+    }
+
+    // runs the full validation pipeline and a margin estimate without placing anything
+    pub async fn check_order(&self, order_request: &OrderRequest) -> Result<RiskCheckResult> {
+        let mut order_request = order_request.clone();
+        // an unknown symbol or a size that rounds to zero is blocked before any price or balance lookup
+        if let Err(e) = self.round_order(&mut order_request).await {
+            // metadata that couldn't be fetched is an error, not a verdict
+            if HlError::find(&e).and_then(HlError::rule).is_none() {
+                return Err(e);
+            }
+            let price = order_request.limit_price.unwrap_or_default();
+            return Ok(risk_check(&order_request, price, &Exposure::default(), 0.0, None, Err(e)));
+        }
+        let order_request = &order_request;
+
+        let price = match order_request.limit_price {
+            Some(limit_price) => limit_price,
+            None => self.get_market_price(&order_request.symbol).await?,
+        };
+
+        let exposure = self.current_exposure().await?;
+        let (available_margin, position_leverage) = if self.paper.is_some() {
+            // the paper account has no margin model, its cash balance stands in
+            (self.lock_paper()?.account().balance, None)
+        } else {
+            let balances = self.exchange.get_balances().await?;
+            let leverage = balances
+                .positions
+                .iter()
+                .find(|p| p.symbol == order_request.symbol)
+                .map(|p| p.leverage);
+            (balances.withdrawable, leverage)
+        };
+
+        let validation = self.validate_order(order_request, true).await;
+        Ok(risk_check(order_request, price, &exposure, available_margin, position_leverage, validation))
+    }
+
+    // a disabled side only accepts orders that shrink the opposite position
+    async fn validate_side(&self, limits: &RiskLimits, order_request: &OrderRequest) -> Result<()> {
+        let symbol_limits = limits.get_symbol_limits(&order_request.symbol);
+        if symbol_limits.allow_long && symbol_limits.allow_short {
+            return Ok(());
+        }
+
+        let position = self.position_size(&order_request.symbol).await?;
+        if !symbol_limits.allows(order_request.is_buy, order_request.qty, position) {
+            anyhow::bail!(
+                "New {} exposure is disabled for {}; only orders that reduce the current position are allowed",
+                if order_request.is_buy { "long" } else { "short" },
+                order_request.symbol
+            );
+        }
+        Ok(())
+    }
+
+    // signed position size, from the paper account in paper mode
+    pub async fn position_size(&self, symbol: &str) -> Result<f64> {
+        if self.paper.is_some() {
+            return self.paper_position(symbol);
+        }
+
+        let balances = self.exchange.get_balances().await?;
+        Ok(balances
+            .positions
+            .iter()
+            .find(|p| p.symbol == symbol)
+            .map(|p| p.size)
+            .unwrap_or(0.0))
+    }
+
+    // offsetting order for `ratio` of the current position (1.0 leaves it flat)
+    pub async fn hedge_plan(&self, symbol: &str, ratio: f64) -> Result<HedgePlan> {
+        if !(ratio > 0.0 && ratio <= 2.0) {
+            anyhow::bail!("Hedge ratio must be above 0 and at most 2, got {}", ratio);
+        }
+        let position = self.position_size(symbol).await?;
+        let sz_decimals = self
+            .exchange
+            .get_sz_decimals()
+            .await?
+            .get(symbol)
+            .copied()
+            .with_context(|| format!("No size decimals for {}", symbol))?;
+        let order_size = hedge_size(position, ratio, sz_decimals);
+        Ok(HedgePlan {
+            symbol: symbol.to_string(),
+            position,
+            ratio,
+            order_size,
+            resulting_position: round_size(position + order_size, sz_decimals),
+        })
+    }
+
+    // check_only evaluates without recording the day's pnl to the state file
+    async fn validate_daily_loss(&self, limits: &RiskLimits, check_only: bool) -> Result<()> {
+        let Some(limit) = limits.max_daily_loss else {
+            return Ok(());
+        };
+
+        let (today, midnight) = daily_loss::utc_today();
+        let realized = self.realized_pnl_since(midnight).await?;
+        if check_only {
+            self.daily_loss.evaluate(&today, realized, limit)
+        } else {
+            self.daily_loss.check(&today, realized, limit)
+        }
+    }
+
+    // closed pnl net of fees since start (ms), from the paper account in paper mode
+    pub async fn realized_pnl_since(&self, start: u64) -> Result<f64> {
+        if self.paper.is_some() {
+            let engine = self.lock_paper()?;
+            return Ok(engine
+                .account()
+                .fills
+                .iter()
+                .filter(|f| f.timestamp >= start)
+                .map(|f| f.closed_pnl - f.fee)
+                .sum());
+        }
+
+        let fills = self.exchange.get_fills_since(start).await?;
+        Ok(fills.iter().map(|f| f.closed_pnl - f.fee).sum())
+    }
+
+    pub fn daily_loss_guard(&self) -> &DailyLossGuard {
+        &self.daily_loss
+    }
+
+    async fn validate_leverage(&self, limits: &RiskLimits, symbol: &str, requested_leverage: Option<u32>) -> Result<()> {
         if let Some(leverage) = requested_leverage {
-            let config_max_leverage = self.config.get_max_leverage(symbol);
+            let config_max_leverage = limits.get_max_leverage(symbol);
             if leverage > config_max_leverage {
                 anyhow::bail!(
                     "Requested leverage {}x exceeds configured maximum {}x for {}",
@@ -143,7 +715,43 @@ impl TradingService {
         Ok(())
     }
 
-    async fn validate_notional(&self, order_request: &OrderRequest) -> Result<()> {
+    async fn validate_exposure(&self, limits: &RiskLimits, symbol: &str, order_notional: f64) -> Result<()> {
+        if limits.max_open_positions.is_none() && limits.max_total_notional.is_none() {
+            return Ok(());
+        }
+
+        let exposure = self.current_exposure().await?;
+        limits.check_exposure(&exposure, symbol, order_notional)
+    }
+
+    // open positions and resting orders, from the paper account in paper mode
+    pub async fn current_exposure(&self) -> Result<Exposure> {
+        if self.paper.is_some() {
+            let engine = self.lock_paper()?;
+            let account = engine.account();
+            return Ok(Exposure {
+                // valued at entry, the paper account has no mark prices
+                positions: account
+                    .positions
+                    .iter()
+                    .map(|(symbol, p)| (symbol.clone(), (p.size * p.entry_price).abs()))
+                    .collect(),
+                open_order_notional: account.open_orders.iter().map(|o| o.size * o.price).sum(),
+            });
+        }
+
+        let (balances, orders) = tokio::try_join!(self.exchange.get_balances(), self.exchange.get_open_orders(None))?;
+        Ok(Exposure {
+            positions: balances
+                .positions
+                .into_iter()
+                .map(|p| (p.symbol, p.position_value.abs()))
+                .collect(),
+            open_order_notional: orders.orders.iter().map(|o| o.remaining_qty * o.price).sum(),
+        })
+    }
+
+    async fn validate_notional(&self, limits: &RiskLimits, order_request: &OrderRequest) -> Result<f64> {
         let price = if let Some(limit_price) = order_request.limit_price {
             limit_price
         } else {
@@ -152,15 +760,20 @@ impl TradingService {
 
         let order_notional = order_request.qty * price;
 
-        if order_notional > self.config.risk_limits.max_notional_per_order {
+        // closing orders reduce risk, the caps only apply to orders that can add to it
+        if order_request.reduce_only {
+            return Ok(order_notional);
+        }
+
+        if order_notional > limits.max_notional_per_order {
             anyhow::bail!(
                 "Order notional ${:.2} exceeds per-order limit ${:.2}",
                 order_notional,
-                self.config.risk_limits.max_notional_per_order
+                limits.max_notional_per_order
             );
         }
 
-        let symbol_max_notional = self.config.get_max_notional(&order_request.symbol);
+        let symbol_max_notional = limits.get_max_notional(&order_request.symbol);
         if order_notional > symbol_max_notional {
             anyhow::bail!(
                 "Order notional ${:.2} exceeds symbol limit ${:.2} for {}",
@@ -170,49 +783,67 @@ impl TradingService {
             );
         }
 
-        println!(
-            "Order validation: {} {} @ ${:.4} = ${:.2} notional (per-order limit: ${:.2}, symbol limit: ${:.2})",
-            order_request.qty,
-            order_request.symbol,
+        tracing::info!(
+            symbol = %order_request.symbol,
+            qty = order_request.qty,
             price,
-            order_notional,
-            self.config.risk_limits.max_notional_per_order,
-            symbol_max_notional
+            notional = order_notional,
+            per_order_limit = limits.max_notional_per_order,
+            symbol_limit = symbol_max_notional,
+            "order passed notional checks"
         );
 
-        Ok(())
+        Ok(order_notional)
     }
 
+    // mids come from the configured api, so they follow the selected network
     async fn get_market_price(&self, symbol: &str) -> Result<f64> {
         let all_mids = self
-            .info_client
-            .all_mids()
+            .exchange
+            .get_mids()
             .await
             .context("Failed to fetch market prices")?;
 
-        let price_str = all_mids
+        all_mids
             .get(symbol)
-            .ok_or_else(|| anyhow::anyhow!("Price not found for symbol: {}", symbol))?;
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("Price not found for symbol: {}", symbol))
+    }
+
+    // sets leverage and margin mode before submission; the exchange takes both in one action
+    async fn apply_leverage(&self, order_request: &OrderRequest) -> Result<()> {
+        let margin_mode = order_request
+            .margin_mode
+            .or_else(|| self.risk_limits().get_symbol_limits(&order_request.symbol).margin_mode);
 
-        price_str
-            .parse::<f64>()
-            .context("Failed to parse market price")
+        match (order_request.leverage, margin_mode) {
+            (Some(leverage), mode) => {
+                self.set_leverage(&order_request.symbol, leverage, mode.unwrap_or(MarginMode::Cross))
+                    .await
+            }
+            (None, Some(mode)) => {
+                let (leverage, current) = self.exchange.get_active_leverage(&order_request.symbol).await?;
+                if current == mode {
+                    return Ok(());
+                }
+                self.set_leverage(&order_request.symbol, leverage, mode).await
+            }
+            (None, None) => Ok(()),
+        }
     }
 
-    async fn set_leverage(&self, symbol: &str, leverage: u32) -> Result<()> {
-        match self
-            .exchange_client
-            .update_leverage(leverage, symbol, true, None)
-            .await
-        {
+    async fn set_leverage(&self, symbol: &str, leverage: u32, margin_mode: MarginMode) -> Result<()> {
+        let asset = self.exchange.get_asset_id(symbol).await?;
+        let action = l1_actions::Action::UpdateLeverage { asset, is_cross: margin_mode == MarginMode::Cross, leverage };
+        match self.send_action(&action).await {
             Ok(ExchangeResponseStatus::Ok(_)) => {
-                println!("Leverage set to {}x for {}", leverage, symbol);
+                tracing::info!(symbol, leverage, margin_mode = margin_mode.as_str(), "leverage updated");
                 Ok(())
             }
             Ok(ExchangeResponseStatus::Err(error)) => {
-                anyhow::bail!("Failed to set leverage: {}", error)
+                Err(HlError::ExchangeRejection(format!("Failed to set leverage: {}", error)).into())
             }
-            Err(e) => Err(e.into()),
+            Err(e) => Err(e),
         }
     }
 
@@ -227,70 +858,715 @@ impl TradingService {
             reduce_only: order_request.reduce_only,
             limit_px: order_request.limit_price.unwrap(),
             sz: order_request.qty,
-            cloid: None,
+            cloid: order_request.cloid,
             order_type: ClientOrder::Limit(ClientLimit {
                 tif: order_request.tif,
             }),
         };
 
-        self.exchange_client
-            .order(client_order, None)
+        self.send_order(client_order, order_request.builder.as_ref())
             .await
             .context("Failed to place limit order")
     }
 
+    // over the websocket when configured, rest otherwise or when the socket couldn't take it
+    async fn send_order(&self, order: ClientOrderRequest, builder: Option<&BuilderFee>) -> Result<ExchangeResponseStatus> {
+        self.pace(1).await;
+        let asset = self.exchange.get_asset_id(&order.asset).await?;
+        let action = l1_actions::order_action(vec![l1_actions::order_wire(&order, asset)], builder);
+        if let Some(ws) = &self.ws {
+            match self.send_order_ws(ws, &action).await {
+                Ok(result) => return Ok(result),
+                Err(PostError::Undelivered(e)) => {
+                    tracing::warn!(error = %e, "order not sent over websocket, falling back to rest");
+                }
+                // resending could double the order
+                Err(PostError::Unconfirmed(e)) => {
+                    return Err(e.context("Order sent over websocket without a reply, check open orders before retrying"));
+                }
+            }
+        }
+
+        self.send_action(&action).await
+    }
+
+    async fn send_order_ws(&self, ws: &WsPoster, action: &l1_actions::Action) -> std::result::Result<ExchangeResponseStatus, PostError> {
+        let payload = l1_actions::signed_payload(&self.wallet, self.signing_chain(), action, nonce::next())
+            .await
+            .map_err(PostError::Undelivered)?;
+        let reply = ws.post(payload).await?;
+        l1_actions::parse_order_reply(&reply).map_err(PostError::Unconfirmed)
+    }
+
+    // user and l1 actions are signed for the network the config points at
+    fn signing_chain(&self) -> SigningChain {
+        SigningChain::for_network(self.config.network())
+    }
+
+    // signs with a nonce from the shared allocator rather than the sdk's, then posts over rest
+    async fn send_action(&self, action: &l1_actions::Action) -> Result<ExchangeResponseStatus> {
+        let payload = l1_actions::signed_payload(&self.wallet, self.signing_chain(), action, nonce::next()).await?;
+        let reply = self.exchange.post_signed(&payload).await?;
+        l1_actions::parse_order_reply(&reply)
+    }
+
     // Place market order
     async fn place_market_order(
         &self,
         order_request: OrderRequest,
     ) -> Result<ExchangeResponseStatus> {
-        let market_params = MarketOrderParams {
-            asset: &order_request.symbol,
+        let slippage = self.market_slippage(order_request.slippage);
+        // the ioc limit the sdk's market helpers would send, signed here so it can carry a builder and
+        // take its nonce from the shared allocator
+        let mid = self.get_market_price(&order_request.symbol).await?;
+        let sz_decimals = self
+            .exchange
+            .get_sz_decimals()
+            .await?
+            .get(&order_request.symbol)
+            .copied()
+            .with_context(|| format!("No size decimals for {}", order_request.symbol))?;
+        let px = if order_request.is_buy { mid * (1.0 + slippage) } else { mid * (1.0 - slippage) };
+        let client_order = ClientOrderRequest {
+            asset: order_request.symbol.clone(),
             is_buy: order_request.is_buy,
+            reduce_only: order_request.reduce_only,
+            limit_px: round_price(px, sz_decimals),
             sz: order_request.qty,
-            px: None,
-            slippage: Some(0.05),
-            cloid: None,
-            wallet: None,
+            cloid: order_request.cloid,
+            order_type: ClientOrder::Limit(ClientLimit {
+                tif: "Ioc".to_string(),
+            }),
         };
+        self.send_order(client_order, order_request.builder.as_ref())
+            .await
+            .context(if order_request.reduce_only { "Failed to place market close order" } else { "Failed to place market order" })
+    }
 
-        if order_request.reduce_only {
-            let close_params = MarketCloseParams {
-                asset: &order_request.symbol,
-                sz: Some(order_request.qty),
-                px: None,
-                slippage: Some(0.05),
-                cloid: None,
-                wallet: None,
-            };
+    // market-closes `size` of a position, skipping risk checks since it can only reduce exposure
+    pub async fn close_position(&self, symbol: &str, size: f64) -> Result<()> {
+        let started = Instant::now();
 
-            self.exchange_client
-                .market_close(close_params)
-                .await
-                .context("Failed to place market close order")
+        let request = OrderRequest {
+            symbol: symbol.to_string(),
+            is_buy: size < 0.0,
+            qty: size.abs(),
+            limit_price: None,
+            leverage: None,
+            reduce_only: true,
+            tif: "Ioc".to_string(),
+            ..Default::default()
+        };
+        let outcome = if self.config.dry_run {
+            tracing::info!(symbol, size, "dry run: close not submitted");
+            Ok(())
+        } else if self.paper.is_some() {
+            self.paper_order(&request).await.map(|_| ()).context("Close failed")
         } else {
-            self.exchange_client
-                .market_open(market_params)
+            self.place_market_order(request)
                 .await
-                .context("Failed to place market order")
+                .and_then(to_order_response)
+                .map(|_| ())
+                .context("Close failed")
+        };
+
+        self.audit.record(&AuditRecord::new(
+            "close",
+            serde_json::json!({ "symbol": symbol, "size": size }),
+            None,
+            &outcome,
+            started.elapsed(),
+        ));
+
+        outcome
+    }
+
+    // moves usdc into (positive) or out of (negative) an isolated position's margin
+    pub async fn update_isolated_margin(&self, symbol: &str, amount: f64) -> Result<()> {
+        if self.paper.is_some() {
+            anyhow::bail!("The paper account has no isolated margin");
         }
+
+        let position = self
+            .exchange
+            .get_balances()
+            .await?
+            .positions
+            .into_iter()
+            .find(|p| p.symbol == symbol)
+            .with_context(|| format!("No open {} position", symbol))?;
+        if position.margin_mode != MarginMode::Isolated.as_str() {
+            anyhow::bail!("{} position uses {} margin; only isolated positions can be adjusted", symbol, position.margin_mode);
+        }
+
+        let started = Instant::now();
+        let outcome = if self.config.dry_run {
+            tracing::info!(symbol, amount, "dry run: margin update not submitted");
+            Ok(())
+        } else {
+            let asset = self.exchange.get_asset_id(symbol).await?;
+            let action = l1_actions::Action::UpdateIsolatedMargin { asset, is_buy: true, ntli: (amount * 1e6).round() as i64 };
+            self.pace(1).await;
+            match self.send_action(&action).await {
+                Ok(ExchangeResponseStatus::Ok(_)) => {
+                    tracing::info!(symbol, amount, "isolated margin updated");
+                    Ok(())
+                }
+                Ok(ExchangeResponseStatus::Err(error)) => Err(HlError::ExchangeRejection(format!("Failed to update margin: {}", error)).into()),
+                Err(e) => Err(e),
+            }
+        };
+
+        self.audit.record(&AuditRecord::new(
+            "margin",
+            serde_json::json!({ "symbol": symbol, "amount": amount }),
+            None,
+            &outcome,
+            started.elapsed(),
+        ));
+
+        outcome
     }
 
     // Cancel order
     pub async fn cancel_order(&self, symbol: &str, order_id: u64) -> Result<()> {
-        use hyperliquid_rust_sdk::ClientCancelRequest;
+        let started = Instant::now();
 
-        let cancel_request = ClientCancelRequest {
-            asset: symbol.to_string(),
-            oid: order_id,
+        let outcome = if self.config.dry_run {
+            tracing::info!(symbol, order_id, "dry run: cancel not submitted");
+            Ok(())
+        } else if self.paper.is_some() {
+            self.lock_paper().and_then(|mut engine| {
+                engine.cancel(order_id)?;
+                engine.save()
+            })
+        } else {
+            let asset = self.exchange.get_asset_id(symbol).await?;
+            let action = l1_actions::Action::Cancel { cancels: vec![l1_actions::CancelWire { asset, oid: order_id }] };
+            self.pace(1).await;
+            match self.send_action(&action).await {
+                Ok(ExchangeResponseStatus::Ok(_)) => Ok(()),
+                Ok(ExchangeResponseStatus::Err(error)) => {
+                    Err(HlError::ExchangeRejection(format!("Cancel failed: {}", error)).into())
+                }
+                Err(e) => Err(e),
+            }
         };
 
-        match self.exchange_client.cancel(cancel_request, None).await {
-            Ok(ExchangeResponseStatus::Ok(_)) => Ok(()),
-            Ok(ExchangeResponseStatus::Err(error)) => {
-                anyhow::bail!("Cancel failed: {}", error)
+        self.audit.record(&AuditRecord::new(
+            "cancel",
+            serde_json::json!({ "symbol": symbol, "order_id": order_id }),
+            None,
+            &outcome,
+            started.elapsed(),
+        ));
+
+        outcome
+    }
+
+    // expected fill of a market order against the current book
+    pub async fn estimate_impact(&self, symbol: &str, qty: f64, is_buy: bool) -> Result<ImpactEstimate> {
+        let book = self.exchange.get_l2_book(symbol).await?;
+        impact::estimate(&book, qty, is_buy)
+    }
+
+    // cross or rest for `--smart`, crossing no further than the market slippage bound
+    pub async fn smart_plan(&self, symbol: &str, qty: f64, is_buy: bool, slippage: Option<f64>) -> Result<SmartPlan> {
+        let book = self.exchange.get_l2_book(symbol).await?;
+        smart_order::plan(&book, qty, is_buy, self.market_slippage(slippage))
+    }
+
+    // ids of the orders still resting, on the paper account when paper trading
+    pub async fn open_order_ids(&self) -> Result<HashSet<u64>> {
+        if self.paper.is_some() {
+            let engine = self.lock_paper()?;
+            return Ok(engine.account().open_orders.iter().map(|o| o.order_id).collect());
+        }
+        let orders = self.exchange.get_open_orders(None).await?;
+        Ok(orders.orders.iter().map(|o| o.order_id).collect())
+    }
+
+    // sdk actions bypass ExchangeService, so they take their rate-limit weight here
+    async fn pace(&self, batch_len: usize) {
+        self.exchange.pool().budget().acquire(rate_budget::action_weight(batch_len)).await;
+    }
+
+    // cancels several orders in one exchange action, one result per order in the same order
+    pub async fn bulk_cancel(&self, orders: &[GroupedOrder]) -> Result<Vec<Result<()>>> {
+        let started = Instant::now();
+        let outcome = if self.config.dry_run {
+            tracing::info!(orders = orders.len(), "dry run: bulk cancel not submitted");
+            Ok(orders.iter().map(|_| Ok(())).collect())
+        } else if self.paper.is_some() {
+            self.lock_paper().and_then(|mut engine| {
+                let results: Vec<Result<()>> = orders.iter().map(|o| engine.cancel(o.order_id)).collect();
+                engine.save()?;
+                Ok(results)
+            })
+        } else {
+            let mut cancels = Vec::with_capacity(orders.len());
+            for o in orders {
+                let cloid = parse_cloid(&o.cloid).with_context(|| format!("Invalid client order id {}", o.cloid))?;
+                let asset = self.exchange.get_asset_id(&o.symbol).await?;
+                cancels.push(l1_actions::CancelByCloidWire { asset, cloid: cloid_hex(&cloid) });
+            }
+            self.pace(cancels.len()).await;
+            match self.send_action(&l1_actions::Action::CancelByCloid { cancels }).await {
+                Ok(ExchangeResponseStatus::Ok(response)) => {
+                    let statuses = response.data.map(|data| data.statuses).unwrap_or_default();
+                    Ok((0..orders.len())
+                        .map(|i| match statuses.get(i) {
+                            Some(ExchangeDataStatus::Error(error)) => Err(HlError::ExchangeRejection(format!("Cancel failed: {}", error)).into()),
+                            _ => Ok(()),
+                        })
+                        .collect())
+                }
+                Ok(ExchangeResponseStatus::Err(error)) => Err(HlError::ExchangeRejection(format!("Bulk cancel failed: {}", error)).into()),
+                Err(e) => Err(e),
+            }
+        };
+
+        // per-order errors, null where the cancel went through
+        let audit_outcome = outcome
+            .as_ref()
+            .map(|results| results.iter().map(|r| r.as_ref().err().map(|e| e.to_string())).collect::<Vec<_>>())
+            .map_err(|e| anyhow::anyhow!("{:#}", e));
+        self.audit.record(&AuditRecord::new(
+            "bulk_cancel",
+            serde_json::json!({ "orders": orders }),
+            None,
+            &audit_outcome,
+            started.elapsed(),
+        ));
+
+        outcome
+    }
+
+    // arms the exchange-side cancel of every open order at `time` (ms), None disarms it
+    pub async fn schedule_cancel(&self, time: Option<u64>) -> Result<()> {
+        let started = Instant::now();
+        let outcome = if self.config.dry_run {
+            tracing::info!(time, "dry run: scheduled cancel not submitted");
+            Ok(())
+        } else if self.paper.is_some() {
+            // paper orders never reach the exchange, so there is nothing to schedule
+            Ok(())
+        } else {
+            self.pace(1).await;
+            match self.send_action(&l1_actions::Action::ScheduleCancel { time }).await {
+                Ok(ExchangeResponseStatus::Ok(_)) => Ok(()),
+                Ok(ExchangeResponseStatus::Err(error)) => {
+                    Err(HlError::ExchangeRejection(format!("Failed to schedule cancel: {}", error)).into())
+                }
+                Err(e) => Err(e),
+            }
+        };
+
+        self.audit.record(&AuditRecord::new(
+            "schedule_cancel",
+            serde_json::json!({ "time": time }),
+            None,
+            &outcome,
+            started.elapsed(),
+        ));
+
+        outcome
+    }
+
+    // binds this account to a referral code, which the exchange only allows once
+    pub async fn set_referrer(&self, code: &str) -> Result<()> {
+        let started = Instant::now();
+        let outcome = if self.config.dry_run {
+            tracing::info!(code, "dry run: referrer not set");
+            Ok(())
+        } else if self.paper.is_some() {
+            Err(anyhow::anyhow!("Referral codes apply to the live account, not the paper one"))
+        } else {
+            self.pace(1).await;
+            match self.send_action(&l1_actions::Action::SetReferrer { code: code.to_string() }).await {
+                Ok(ExchangeResponseStatus::Ok(_)) => Ok(()),
+                Ok(ExchangeResponseStatus::Err(error)) => Err(HlError::ExchangeRejection(format!("Failed to set referrer: {}", error)).into()),
+                Err(e) => Err(e),
             }
-            Err(e) => Err(e.into()),
+        };
+
+        self.audit.record(&AuditRecord::new(
+            "set_referrer",
+            serde_json::json!({ "code": code }),
+            None,
+            &outcome,
+            started.elapsed(),
+        ));
+
+        outcome
+    }
+
+    // lets `builder` charge up to max_fee_bps on this account's orders
+    pub async fn approve_builder_fee(&self, builder: &BuilderFee) -> Result<()> {
+        let started = Instant::now();
+        let max_fee_rate = builder_fee_percent(builder.fee_bps);
+        let outcome = if self.config.dry_run {
+            tracing::info!(builder = %builder.address, max_fee_rate, "dry run: builder fee not approved");
+            Ok(())
+        } else if self.paper.is_some() {
+            Err(anyhow::anyhow!("Builder fees apply to the live account, not the paper one"))
+        } else {
+            let nonce = nonce::next();
+            let action = user_actions::approve_builder_fee(self.signing_chain(), &builder.address, &max_fee_rate, nonce);
+            match user_actions::sign(&self.wallet, self.signing_chain(), "ApproveBuilderFee", user_actions::APPROVE_BUILDER_FEE_TYPES, &action).await {
+                Ok(signature) => self.exchange.post_exchange(&action, nonce, signature).await,
+                Err(e) => Err(e),
+            }
+        };
+
+        self.audit.record(&AuditRecord::new(
+            "approve_builder_fee",
+            serde_json::json!({ "builder": builder.address, "max_fee_rate": max_fee_rate }),
+            None,
+            &outcome,
+            started.elapsed(),
+        ));
+
+        outcome
+    }
+
+    // deposits into or withdraws from a vault this account follows
+    pub async fn vault_transfer(&self, vault_address: &str, usd: f64, is_deposit: bool) -> Result<()> {
+        let started = Instant::now();
+        let outcome = async {
+            if !(usd > 0.0 && usd.is_finite()) {
+                return Err(HlError::validation("amount", format!("Amount must be positive, got {}", usd)).into());
+            }
+            let vault: H160 = vault_address
+                .parse()
+                .with_context(|| format!("Invalid vault address {}", vault_address))?;
+            if self.config.dry_run {
+                tracing::info!(vault_address, usd, is_deposit, "dry run: vault transfer not submitted");
+                return Ok(());
+            }
+            if self.paper.is_some() {
+                anyhow::bail!("Vault transfers apply to the live account, not the paper one");
+            }
+            // the action takes usd as an integer with 6 decimals
+            let raw_usd = (usd * 1e6).round() as u64;
+            self.pace(1).await;
+            let action = l1_actions::Action::VaultTransfer { vault_address: format!("{:#x}", vault), is_deposit, usd: raw_usd };
+            match self.send_action(&action).await {
+                Ok(ExchangeResponseStatus::Ok(_)) => Ok(()),
+                Ok(ExchangeResponseStatus::Err(error)) => Err(HlError::ExchangeRejection(format!("Vault transfer failed: {}", error)).into()),
+                Err(e) => Err(e),
+            }
+        }
+        .await;
+
+        self.audit.record(&AuditRecord::new(
+            if is_deposit { "vault_deposit" } else { "vault_withdraw" },
+            serde_json::json!({ "vault": vault_address, "usd": usd }),
+            None,
+            &outcome,
+            started.elapsed(),
+        ));
+
+        outcome
+    }
+
+    // sends USDC from the perp balance to another address on hyperliquid
+    pub async fn usd_send(&self, destination: &str, usd: f64) -> Result<()> {
+        let started = Instant::now();
+        let outcome = async {
+            if !(usd > 0.0 && usd.is_finite()) {
+                return Err(HlError::validation("amount", format!("Amount must be positive, got {}", usd)).into());
+            }
+            if self.config.dry_run {
+                tracing::info!(destination, usd, "dry run: transfer not submitted");
+                return Ok(());
+            }
+            if self.paper.is_some() {
+                anyhow::bail!("Transfers apply to the live account, not the paper one");
+            }
+            // the action takes the amount as a decimal string, usdc has 6 decimals
+            let amount = format!("{:.6}", usd).trim_end_matches('0').trim_end_matches('.').to_string();
+            let nonce = nonce::next();
+            let action = user_actions::usd_send(self.signing_chain(), destination, &amount, nonce);
+            let signature = user_actions::sign(&self.wallet, self.signing_chain(), "UsdSend", user_actions::USD_SEND_TYPES, &action).await?;
+            self.exchange.post_exchange(&action, nonce, signature).await
+        }
+        .await;
+
+        self.audit.record(&AuditRecord::new(
+            "usd_send",
+            serde_json::json!({ "destination": destination, "usd": usd }),
+            None,
+            &outcome,
+            started.elapsed(),
+        ));
+
+        outcome
+    }
+
+    // moves staked HYPE onto or off a validator; the amount must already be in the staking balance
+    pub async fn delegate(&self, validator: &str, amount: f64, undelegate: bool) -> Result<()> {
+        let started = Instant::now();
+        let outcome = async {
+            let wei = user_actions::hype_to_wei(amount)?;
+            if self.config.dry_run {
+                tracing::info!(validator, amount, undelegate, "dry run: delegation not submitted");
+                return Ok(());
+            }
+            if self.paper.is_some() {
+                anyhow::bail!("Staking applies to the live account, not the paper one");
+            }
+            let nonce = nonce::next();
+            let action = user_actions::token_delegate(self.signing_chain(), validator, wei, undelegate, nonce);
+            let signature = user_actions::sign(
+                &self.wallet,
+                self.signing_chain(),
+                "TokenDelegate",
+                user_actions::TOKEN_DELEGATE_TYPES,
+                &action,
+            )
+            .await?;
+            self.exchange.post_exchange(&action, nonce, signature).await
+        }
+        .await;
+
+        self.audit.record(&AuditRecord::new(
+            if undelegate { "undelegate" } else { "delegate" },
+            serde_json::json!({ "validator": validator, "amount": amount }),
+            None,
+            &outcome,
+            started.elapsed(),
+        ));
+
+        outcome
+    }
+
+    // orders carrying a builder fee above what the user approved are rejected by the exchange
+    pub async fn check_builder_approval(&self, builder: &BuilderFee) -> Result<()> {
+        let approved = self.exchange.get_max_builder_fee(&builder.address).await?;
+        if approved < builder.tenths_bps() {
+            anyhow::bail!(
+                "Builder {} is approved for {} bps, below the requested {} bps; run `hl builder approve {} --max-fee-bps {}` first",
+                builder.address, approved as f64 / 10.0, builder.fee_bps, builder.address, builder.fee_bps
+            );
+        }
+        Ok(())
+    }
+
+    // cancels by client order id, for orders placed with a cloid whose oid isn't known
+    pub async fn cancel_by_cloid(&self, symbol: &str, cloid: Uuid) -> Result<()> {
+        let started = Instant::now();
+        let outcome = if self.config.dry_run {
+            tracing::info!(symbol, cloid = %cloid, "dry run: cancel not submitted");
+            Ok(())
+        } else if self.paper.is_some() {
+            Err(anyhow::anyhow!("Paper orders have no client order ids; cancel by order id instead"))
+        } else {
+            let asset = self.exchange.get_asset_id(symbol).await?;
+            let cancels = vec![l1_actions::CancelByCloidWire { asset, cloid: cloid_hex(&cloid) }];
+            self.pace(1).await;
+            match self.send_action(&l1_actions::Action::CancelByCloid { cancels }).await {
+                Ok(ExchangeResponseStatus::Ok(_)) => Ok(()),
+                Ok(ExchangeResponseStatus::Err(error)) => {
+                    Err(HlError::ExchangeRejection(format!("Cancel failed: {}", error)).into())
+                }
+                Err(e) => Err(e),
+            }
+        };
+
+        self.audit.record(&AuditRecord::new(
+            "cancel",
+            serde_json::json!({ "symbol": symbol, "cloid": cloid_hex(&cloid) }),
+            None,
+            &outcome,
+            started.elapsed(),
+        ));
+
+        outcome
+    }
+}
+
+// tags a validation error with the rule that raised it; failures that are already typed (the
+// exchange unreachable while checking exposure, say) keep their own kind
+fn reject(rule: &'static str) -> impl FnOnce(anyhow::Error) -> anyhow::Error {
+    move |e| if HlError::find(&e).is_some() { e } else { HlError::validation(rule, e.to_string()).into() }
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// hyperliquid prices carry at most five significant figures
+pub(crate) fn round_significant(value: f64, figures: i32) -> f64 {
+    if value == 0.0 {
+        return 0.0;
+    }
+    let magnitude = value.abs().log10().floor() as i32;
+    let factor = 10f64.powi(figures - 1 - magnitude);
+    (value * factor).round() / factor
+}
+
+// perp prices: five significant figures and at most 6 - szDecimals decimals; integers always pass
+pub fn round_price(price: f64, sz_decimals: u32) -> f64 {
+    let decimals = (MAX_PRICE_DECIMALS - sz_decimals as i32).max(0);
+    let factor = 10f64.powi(decimals);
+    let price = if price >= 100_000.0 { price.round() } else { round_significant(price, 5) };
+    (price * factor).round() / factor
+}
+
+// pct% of equity as margin, capped by what is actually free, levered up
+pub fn equity_pct_notional(equity: f64, free_margin: f64, pct: f64, leverage: u32) -> f64 {
+    let margin = (equity * pct / 100.0).min(free_margin).max(0.0);
+    margin * f64::from(leverage.max(1))
+}
+
+// size whose loss from entry to stop is at most risk_fraction of equity, rounded down to szDecimals
+pub fn risk_size(equity: f64, risk_fraction: f64, entry: f64, stop: f64, sz_decimals: u32) -> Result<f64> {
+    let per_unit = (entry - stop).abs();
+    if per_unit <= 0.0 || entry <= 0.0 || stop <= 0.0 {
+        anyhow::bail!("Stop {} must be a positive price different from entry {}", stop, entry);
+    }
+    let factor = 10f64.powi(sz_decimals as i32);
+    // tiny epsilon so 0.3 / 0.1 doesn't floor to 2
+    let size = ((equity * risk_fraction / per_unit) * factor + 1e-9).floor() / factor;
+    if size <= 0.0 {
+        anyhow::bail!("Risking ${:.2} with a {:.4} stop is less than the minimum size increment", equity * risk_fraction, per_unit);
+    }
+    Ok(size)
+}
+
+// signed size that offsets `ratio` of `position`, rounded toward zero so it never over-hedges
+pub fn hedge_size(position: f64, ratio: f64, sz_decimals: u32) -> f64 {
+    let factor = 10f64.powi(sz_decimals as i32);
+    let size = -position * ratio;
+    (size.abs() * factor + 1e-9).floor() / factor * size.signum()
+}
+
+// what-if numbers for a risk check; a failed validation wins over the margin check
+pub fn risk_check(
+    order_request: &OrderRequest,
+    price: f64,
+    exposure: &Exposure,
+    available_margin: f64,
+    position_leverage: Option<u32>,
+    validation: Result<()>,
+) -> RiskCheckResult {
+    let notional = order_request.qty * price;
+    // without a requested or existing leverage assume none, the conservative case
+    let leverage = order_request.leverage.or(position_leverage).unwrap_or(1).max(1);
+
+    let current_exposure = exposure.positions.iter().map(|(_, n)| n).sum::<f64>() + exposure.open_order_notional;
+    let open_positions = exposure.positions.len();
+    let opens_new = !exposure.positions.iter().any(|(s, _)| *s == order_request.symbol);
+    let (resulting_exposure, resulting_positions, margin_required) = if order_request.reduce_only {
+        ((current_exposure - notional).max(0.0), open_positions, 0.0)
+    } else {
+        (
+            current_exposure + notional,
+            open_positions + usize::from(opens_new),
+            notional / f64::from(leverage),
+        )
+    };
+
+    let mut result = RiskCheckResult {
+        allowed: true,
+        price,
+        notional,
+        leverage,
+        margin_required,
+        available_margin,
+        current_exposure,
+        resulting_exposure,
+        open_positions,
+        resulting_positions,
+        ..Default::default()
+    };
+
+    if let Err(e) = validation {
+        result.allowed = false;
+        result.rule = Some(HlError::find(&e).and_then(HlError::rule).unwrap_or("error").to_string());
+        result.reason = Some(e.to_string());
+    } else if margin_required > available_margin {
+        result.allowed = false;
+        result.rule = Some("margin".to_string());
+        result.reason = Some(format!(
+            "Margin required ${:.2} at {}x exceeds available ${:.2}",
+            margin_required, leverage, available_margin
+        ));
+    }
+    result
+}
+
+// a market order's slippage bound has to be in (0, MAX_MARKET_SLIPPAGE]
+pub fn check_slippage(slippage: f64) -> Result<()> {
+    if slippage > 0.0 && slippage <= MAX_MARKET_SLIPPAGE {
+        return Ok(());
+    }
+    Err(HlError::validation(
+        "slippage",
+        format!("Slippage must be above 0 and at most {} ({}%), got {}", MAX_MARKET_SLIPPAGE, MAX_MARKET_SLIPPAGE * 100.0, slippage),
+    )
+    .into())
+}
+
+// floors to the size step, so rounding never trades more than was asked for
+pub fn round_size(size: f64, sz_decimals: u32) -> f64 {
+    let factor = 10f64.powi(sz_decimals as i32);
+    (size.abs() * factor + 1e-9).floor() / factor * size.signum()
+}
+
+pub fn round_to_tick(price: f64, tick_size: f64) -> f64 {
+    let rounded = (price / tick_size).round() * tick_size;
+    // keep the tick's decimals, so 0.1 ticks don't leave 2000.3000000000002
+    let decimals = (0..10)
+        .find(|d| {
+            let scaled = tick_size * 10f64.powi(*d);
+            (scaled - scaled.round()).abs() < 1e-9
+        })
+        .unwrap_or(10);
+    let factor = 10f64.powi(decimals);
+    (rounded * factor).round() / factor
+}
+
+// maps the first exchange status of an order action into our response type
+// an order the exchange refused, as a whole or in its status, is an ExchangeRejection
+fn to_order_response(result: ExchangeResponseStatus) -> Result<OrderResponse> {
+    let timestamp = now_millis();
+
+    match result {
+        ExchangeResponseStatus::Ok(response) => {
+            let Some(status) = response.data.and_then(|data| data.statuses.into_iter().next()) else {
+                return Err(HlError::ExchangeRejection("No response data".to_string()).into());
+            };
+
+            let order_result = match status {
+                ExchangeDataStatus::Success => OrderResult::Success {
+                    order_id: 0,
+                    filled_qty: 0.0,
+                    avg_price: None,
+                },
+                ExchangeDataStatus::Filled(filled) => OrderResult::Success {
+                    order_id: filled.oid,
+                    filled_qty: filled.total_sz.parse().unwrap_or(0.0),
+                    avg_price: Some(filled.avg_px.parse().unwrap_or(0.0)),
+                },
+                ExchangeDataStatus::Resting(resting) => OrderResult::Resting {
+                    order_id: resting.oid,
+                },
+                ExchangeDataStatus::Error(msg) => return Err(HlError::ExchangeRejection(msg).into()),
+                _ => return Err(HlError::ExchangeRejection("Unknown status".to_string()).into()),
+            };
+
+            Ok(OrderResponse {
+                status: "success".to_string(),
+                result: order_result,
+                timestamp,
+                cloid: None,
+            })
         }
+        ExchangeResponseStatus::Err(error) => Err(HlError::ExchangeRejection(error).into()),
     }
 }
@@ -0,0 +1,153 @@
+// user-signed exchange actions the sdk has no helper for, signed as eip-712 typed data
+use anyhow::{Context, Result};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip712::TypedData;
+use serde_json::{json, Value};
+
+// the hyperliquid chain a user action names and the eip-712 chain id it's signed against,
+// a testnet signature is rejected on mainnet and the other way round
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SigningChain {
+    pub hyperliquid_chain: &'static str,
+    pub signature_chain_id: u64,
+}
+
+// arbitrum one and arbitrum sepolia
+pub const MAINNET: SigningChain = SigningChain { hyperliquid_chain: "Mainnet", signature_chain_id: 0xa4b1 };
+pub const TESTNET: SigningChain = SigningChain { hyperliquid_chain: "Testnet", signature_chain_id: 0x66eee };
+
+impl SigningChain {
+    // from Config::network()
+    pub fn for_network(network: &str) -> Self {
+        if network == "mainnet" { MAINNET } else { TESTNET }
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        *self == MAINNET
+    }
+}
+// HYPE amounts in staking actions are integers with 8 decimals
+pub const HYPE_WEI_DECIMALS: i32 = 8;
+
+pub fn hype_to_wei(amount: f64) -> Result<u64> {
+    if !(amount > 0.0 && amount.is_finite()) {
+        anyhow::bail!("Amount must be positive, got {}", amount);
+    }
+    Ok((amount * 10f64.powi(HYPE_WEI_DECIMALS)).round() as u64)
+}
+
+pub fn token_delegate(chain: SigningChain, validator: &str, wei: u64, is_undelegate: bool, nonce: u64) -> Value {
+    json!({
+        "type": "tokenDelegate",
+        "hyperliquidChain": chain.hyperliquid_chain,
+        "signatureChainId": format!("{:#x}", chain.signature_chain_id),
+        "validator": validator.to_lowercase(),
+        "wei": wei,
+        "isUndelegate": is_undelegate,
+        "nonce": nonce,
+    })
+}
+
+// usdc from the perp balance to another address; `time` doubles as the nonce
+pub fn usd_send(chain: SigningChain, destination: &str, amount: &str, nonce: u64) -> Value {
+    json!({
+        "type": "usdSend",
+        "hyperliquidChain": chain.hyperliquid_chain,
+        "signatureChainId": format!("{:#x}", chain.signature_chain_id),
+        "destination": destination,
+        "amount": amount,
+        "time": nonce,
+    })
+}
+
+pub const USD_SEND_TYPES: &[(&str, &str)] = &[
+    ("hyperliquidChain", "string"),
+    ("destination", "string"),
+    ("amount", "string"),
+    ("time", "uint64"),
+];
+
+// lets `builder` charge up to max_fee_rate ("0.01%") on this account's orders
+pub fn approve_builder_fee(chain: SigningChain, builder: &str, max_fee_rate: &str, nonce: u64) -> Value {
+    json!({
+        "type": "approveBuilderFee",
+        "hyperliquidChain": chain.hyperliquid_chain,
+        "signatureChainId": format!("{:#x}", chain.signature_chain_id),
+        "maxFeeRate": max_fee_rate,
+        "builder": builder.to_lowercase(),
+        "nonce": nonce,
+    })
+}
+
+pub const APPROVE_BUILDER_FEE_TYPES: &[(&str, &str)] = &[
+    ("hyperliquidChain", "string"),
+    ("maxFeeRate", "string"),
+    ("builder", "address"),
+    ("nonce", "uint64"),
+];
+
+// field (name, solidity type) pairs of the signed struct, in the order the exchange hashes them
+pub const TOKEN_DELEGATE_TYPES: &[(&str, &str)] = &[
+    ("hyperliquidChain", "string"),
+    ("validator", "address"),
+    ("wei", "uint64"),
+    ("isUndelegate", "bool"),
+    ("nonce", "uint64"),
+];
+
+// signs `action` as HyperliquidTransaction:<primary_type>, returning the {r, s, v} the exchange takes
+pub async fn sign(wallet: &LocalWallet, chain: SigningChain, primary_type: &str, fields: &[(&str, &str)], action: &Value) -> Result<Value> {
+    let primary_type = format!("HyperliquidTransaction:{}", primary_type);
+    let message: serde_json::Map<String, Value> = fields
+        .iter()
+        .map(|(name, _)| {
+            let value = action.get(*name).cloned().with_context(|| format!("Action is missing {}", name))?;
+            Ok((name.to_string(), value))
+        })
+        .collect::<Result<_>>()?;
+
+    sign_typed(wallet, ("HyperliquidSignTransaction", chain.signature_chain_id), &primary_type, fields, Value::Object(message)).await
+}
+
+// eip-712 signature over `message` in the zero-address domain (name, chain id) the exchange uses
+pub async fn sign_typed(
+    wallet: &LocalWallet,
+    (domain_name, chain_id): (&str, u64),
+    primary_type: &str,
+    fields: &[(&str, &str)],
+    message: Value,
+) -> Result<Value> {
+    let typed_data: TypedData = serde_json::from_value(json!({
+        "domain": {
+            "name": domain_name,
+            "version": "1",
+            "chainId": chain_id,
+            "verifyingContract": "0x0000000000000000000000000000000000000000",
+        },
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" },
+            ],
+            primary_type: fields
+                .iter()
+                .map(|(name, ty)| json!({ "name": name, "type": ty }))
+                .collect::<Vec<_>>(),
+        },
+        "primaryType": primary_type,
+        "message": message,
+    }))
+    .context("Failed to build typed data")?;
+
+    let signature = wallet
+        .sign_typed_data(&typed_data)
+        .await
+        .context("Failed to sign action")?;
+    Ok(json!({
+        "r": format!("{:#066x}", signature.r),
+        "s": format!("{:#066x}", signature.s),
+        "v": signature.v,
+    }))
+}
@@ -0,0 +1,87 @@
+// realized volatility, atr and typical hourly range from closed 1h candles
+use std::time::Duration;
+use anyhow::Result;
+use serde::Serialize;
+use crate::services::{indicators, ExchangeService};
+use crate::types::Candle;
+
+const HOUR_MS: u64 = 3_600_000;
+// markets trade around the clock
+const HOURS_PER_YEAR: f64 = 24.0 * 365.0;
+pub const ATR_PERIOD: usize = 14;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VolatilityReport {
+    pub symbol: String,
+    pub window_hours: usize,
+    // closed hourly candles in the window
+    pub candles: usize,
+    pub price: f64,
+    // standard deviation of hourly log returns, annualized and scaled to the window
+    pub realized_vol_annual: f64,
+    pub realized_vol_window: f64,
+    // wilder's 14 hour atr, in price and as a fraction of price
+    pub atr: Option<f64>,
+    pub atr_pct: Option<f64>,
+    // mean high - low of an hour
+    pub avg_hourly_range: f64,
+    pub avg_hourly_range_pct: f64,
+}
+
+impl VolatilityReport {
+    // stop `multiple` atrs away from entry, on the losing side
+    pub fn atr_stop(&self, entry: f64, is_buy: bool, multiple: f64) -> Option<f64> {
+        let distance = self.atr? * multiple;
+        Some(if is_buy { entry - distance } else { entry + distance })
+    }
+}
+
+// `candles` are closed 1h candles, oldest first; the window is the newest `window_hours` of them
+// and the atr may also use the hours before it
+pub fn report(symbol: &str, candles: &[Candle], window_hours: usize) -> Option<VolatilityReport> {
+    let window = &candles[candles.len().saturating_sub(window_hours)..];
+    let price = window.last()?.close;
+    let returns: Vec<f64> = window
+        .windows(2)
+        .filter(|w| w[0].close > 0.0 && w[1].close > 0.0)
+        .map(|w| (w[1].close / w[0].close).ln())
+        .collect();
+    if returns.len() < 2 {
+        return None;
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    // sample standard deviation
+    let hourly = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64).sqrt();
+    let avg_hourly_range = window.iter().map(|c| c.high - c.low).sum::<f64>() / window.len() as f64;
+    let atr = indicators::atr(candles, ATR_PERIOD);
+
+    Some(VolatilityReport {
+        symbol: symbol.to_string(),
+        window_hours,
+        candles: window.len(),
+        price,
+        realized_vol_annual: hourly * HOURS_PER_YEAR.sqrt(),
+        realized_vol_window: hourly * (returns.len() as f64).sqrt(),
+        atr,
+        atr_pct: atr.map(|atr| atr / price),
+        avg_hourly_range,
+        avg_hourly_range_pct: avg_hourly_range / price,
+    })
+}
+
+pub async fn fetch(exchange: &ExchangeService, symbol: &str, window: Duration) -> Result<VolatilityReport> {
+    let window_hours = (window.as_secs() / 3600) as usize;
+    if window_hours < 3 {
+        anyhow::bail!("Volatility window must be at least 3h");
+    }
+    let now = chrono::Utc::now().timestamp_millis() as u64;
+    // enough history for the atr to settle even on a short window
+    let hours = window_hours.max(ATR_PERIOD * 3) as u64 + 1;
+    let candles: Vec<Candle> = exchange
+        .get_candles(symbol, "1h", now.saturating_sub(hours * HOUR_MS), now)
+        .await?
+        .into_iter()
+        .filter(|c| c.close_time < now)
+        .collect();
+    report(symbol, &candles, window_hours).ok_or_else(|| anyhow::anyhow!("Not enough 1h candles for {}", symbol))
+}
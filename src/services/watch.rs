@@ -0,0 +1,93 @@
+// redraw-in-place loop behind --watch, with changed fields highlighted
+use anyhow::Result;
+use std::future::Future;
+use std::time::Duration;
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+const HIGHLIGHT: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+
+// wraps every whitespace-separated field of `next` that differs from the same field of `prev`
+pub fn highlight_changes(prev: &str, next: &str) -> String {
+    let prev_lines: Vec<&str> = prev.lines().collect();
+    next.lines()
+        .enumerate()
+        .map(|(i, line)| match prev_lines.get(i) {
+            Some(old) if *old == line => line.to_string(),
+            Some(old) => highlight_line(old, line),
+            // rows that weren't there before are new as a whole, unless the frame is the first
+            None if prev.is_empty() => line.to_string(),
+            None => format!("{}{}{}", HIGHLIGHT, line, RESET),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn highlight_line(old: &str, new: &str) -> String {
+    let old_fields: Vec<&str> = old.split_whitespace().collect();
+    let mut field = 0;
+    let mut out = String::new();
+    for (is_space, token) in tokens(new) {
+        if is_space {
+            out.push_str(token);
+            continue;
+        }
+        if old_fields.get(field) == Some(&token) {
+            out.push_str(token);
+        } else {
+            out.push_str(HIGHLIGHT);
+            out.push_str(token);
+            out.push_str(RESET);
+        }
+        field += 1;
+    }
+    out
+}
+
+// splits into alternating runs of whitespace and non-whitespace, flagged by is_space
+fn tokens(line: &str) -> Vec<(bool, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut chars = line.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        let is_space = c.is_whitespace();
+        let boundary = chars.peek().is_none_or(|(_, next)| next.is_whitespace() != is_space);
+        if boundary {
+            let end = chars.peek().map_or(line.len(), |(j, _)| *j);
+            tokens.push((is_space, &line[start..end]));
+            start = end;
+        }
+    }
+    tokens
+}
+
+// re-renders every `interval` until Ctrl-C; a failed refresh keeps the last frame and shows the error
+pub async fn run<F, Fut>(interval: Duration, mut render: F) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let mut ticker = tokio::time::interval(interval);
+    let mut previous = String::new();
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = ticker.tick() => {}
+        }
+
+        let header = format!(
+            "Every {}s - {} (Ctrl-C to stop)",
+            interval.as_secs(),
+            chrono::Utc::now().format("%H:%M:%S UTC")
+        );
+        match render().await {
+            Ok(frame) => {
+                print!("{}{}\n{}\n", CLEAR_SCREEN, header, highlight_changes(&previous, &frame));
+                previous = frame;
+            }
+            Err(e) => {
+                print!("{}{}\n{}\nRefresh failed: {}\n", CLEAR_SCREEN, header, previous, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+// withdrawals from the non-funding ledger and how far each has got through the bridge
+use std::time::Duration;
+use crate::types::{exchange::RawLedgerUpdate, Withdrawal, WithdrawalStatus};
+
+// the bridge's dispute period plus finalization; the info api has nothing past the l1 debit,
+// so a withdrawal this old is only expected to have left, nothing here confirms it
+pub const FINALITY_MS: u64 = 5 * 60 * 1000;
+
+// how often `hl withdrawals --watch` checks the ledger again
+pub const POLL_EVERY: Duration = Duration::from_secs(15);
+
+pub fn status(time: u64, now: u64) -> WithdrawalStatus {
+    if now >= time + FINALITY_MS { WithdrawalStatus::ExpectedFinal } else { WithdrawalStatus::Pending }
+}
+
+// newest first
+pub fn from_ledger(updates: &[RawLedgerUpdate], now: u64) -> Vec<Withdrawal> {
+    let parse = |value: &Option<String>| value.as_deref().and_then(|v| v.parse::<f64>().ok()).unwrap_or(0.0);
+    let mut withdrawals: Vec<Withdrawal> = updates
+        .iter()
+        .filter(|update| update.delta.kind == "withdraw")
+        .map(|update| Withdrawal {
+            time: update.time,
+            hash: update.hash.clone(),
+            usdc: parse(&update.delta.usdc),
+            fee: parse(&update.delta.fee),
+            nonce: update.delta.nonce,
+            status: status(update.time, now),
+            final_at: update.time + FINALITY_MS,
+        })
+        .collect();
+    withdrawals.sort_by_key(|w| std::cmp::Reverse(w.time));
+    withdrawals
+}
@@ -0,0 +1,260 @@
+// multiplexes one upstream hyperliquid websocket into many client subscriptions
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::{broadcast, mpsc};
+use tokio_tungstenite::tungstenite::protocol::Message;
+use crate::services::net;
+use crate::types::{streaming::{TradeData, WSMessage}, HttpConfig};
+
+const CHANNEL_CAPACITY: usize = 1024;
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SubscriptionKey {
+    #[serde(rename = "type")]
+    pub sub_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coin: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<String>,
+    // account streams (userFills) are keyed by wallet address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+impl SubscriptionKey {
+    pub fn for_coin(sub_type: &str, coin: &str) -> Self {
+        Self { sub_type: sub_type.to_string(), coin: Some(coin.to_string()), interval: None, user: None }
+    }
+
+    pub fn for_user(sub_type: &str, user: &str) -> Self {
+        Self { sub_type: sub_type.to_string(), coin: None, interval: None, user: Some(user.to_lowercase()) }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self.sub_type.as_str() {
+            "allMids" => Ok(()),
+            "userFills" if self.user.is_some() => Ok(()),
+            "userFills" => Err("'userFills' requires a user".to_string()),
+            "trades" | "l2Book" | "bbo" if self.coin.is_some() => Ok(()),
+            "candle" if self.coin.is_some() && self.interval.is_some() => Ok(()),
+            "trades" | "l2Book" | "bbo" => Err(format!("'{}' requires a coin", self.sub_type)),
+            "candle" => Err("'candle' requires a coin and an interval".to_string()),
+            other => Err(format!("unsupported subscription type '{}'", other)),
+        }
+    }
+
+    fn upstream_message(&self, method: &str) -> String {
+        json!({ "method": method, "subscription": self }).to_string()
+    }
+}
+
+// a hub subscription released when dropped, for consumers that end whenever their client does
+pub struct HubSubscription {
+    hub: Arc<WsHub>,
+    key: SubscriptionKey,
+    pub updates: broadcast::Receiver<String>,
+}
+
+impl Drop for HubSubscription {
+    fn drop(&mut self) {
+        self.hub.unsubscribe(&self.key);
+    }
+}
+
+// trades carried by one upstream "trades" message, empty for anything else
+pub fn parse_trades(text: &str) -> Vec<TradeData> {
+    serde_json::from_str::<WSMessage>(text)
+        .ok()
+        .filter(|message| message.channel == "trades")
+        .and_then(|message| serde_json::from_value(message.data).ok())
+        .unwrap_or_default()
+}
+
+struct Subscription {
+    refs: usize,
+    sender: broadcast::Sender<String>,
+}
+
+pub struct WsHub {
+    subscriptions: Mutex<HashMap<SubscriptionKey, Subscription>>,
+    upstream: mpsc::UnboundedSender<String>,
+}
+
+impl WsHub {
+    // spawns the upstream connection task, which reconnects for as long as the hub lives
+    pub fn start(ws_url: String, http: HttpConfig) -> Arc<Self> {
+        let (upstream, commands) = mpsc::unbounded_channel();
+        let hub = Arc::new(Self {
+            subscriptions: Mutex::new(HashMap::new()),
+            upstream,
+        });
+
+        tokio::spawn(run_upstream(Arc::downgrade(&hub), ws_url, http, commands));
+        hub
+    }
+
+    // first subscriber for a key opens the upstream subscription
+    pub fn subscribe(&self, key: &SubscriptionKey) -> broadcast::Receiver<String> {
+        let mut subscriptions = self.lock();
+        if let Some(existing) = subscriptions.get_mut(key) {
+            existing.refs += 1;
+            return existing.sender.subscribe();
+        }
+
+        let (sender, receiver) = broadcast::channel(CHANNEL_CAPACITY);
+        subscriptions.insert(key.clone(), Subscription { refs: 1, sender });
+        let _ = self.upstream.send(key.upstream_message("subscribe"));
+        receiver
+    }
+
+    pub fn subscribe_owned(self: &Arc<Self>, key: SubscriptionKey) -> HubSubscription {
+        HubSubscription { updates: self.subscribe(&key), hub: self.clone(), key }
+    }
+
+    // last subscriber leaving closes the upstream subscription
+    pub fn unsubscribe(&self, key: &SubscriptionKey) {
+        let mut subscriptions = self.lock();
+        let Some(existing) = subscriptions.get_mut(key) else {
+            return;
+        };
+
+        existing.refs = existing.refs.saturating_sub(1);
+        if existing.refs == 0 {
+            subscriptions.remove(key);
+            let _ = self.upstream.send(key.upstream_message("unsubscribe"));
+        }
+    }
+
+    pub fn active_subscriptions(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<SubscriptionKey, Subscription>> {
+        self.subscriptions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn dispatch(&self, text: &str) {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
+            return;
+        };
+        let Some(channel) = value.get("channel").and_then(|c| c.as_str()) else {
+            return;
+        };
+
+        let data = &value["data"];
+        let coin = match channel {
+            "trades" => data.get(0).and_then(|t| t.get("coin")),
+            "l2Book" | "bbo" => data.get("coin"),
+            "candle" => data.get("s"),
+            _ => None,
+        }
+        .and_then(|c| c.as_str())
+        .map(str::to_string);
+
+        let subscriptions = self.lock();
+        for (key, subscription) in subscriptions.iter() {
+            if key.sub_type != channel {
+                continue;
+            }
+            let matches = match channel {
+                "allMids" => true,
+                "candle" => {
+                    key.coin == coin
+                        && key.interval.as_deref() == data.get("i").and_then(|i| i.as_str())
+                }
+                "userFills" => data
+                    .get("user")
+                    .and_then(|u| u.as_str())
+                    .is_some_and(|user| key.user.as_deref().is_some_and(|k| k.eq_ignore_ascii_case(user))),
+                _ => key.coin == coin,
+            };
+            if matches {
+                let _ = subscription.sender.send(text.to_string());
+            }
+        }
+    }
+
+    fn resubscribe_messages(&self) -> Vec<String> {
+        self.lock()
+            .keys()
+            .map(|key| key.upstream_message("subscribe"))
+            .collect()
+    }
+}
+
+async fn run_upstream(
+    hub: std::sync::Weak<WsHub>,
+    ws_url: String,
+    http: HttpConfig,
+    mut commands: mpsc::UnboundedReceiver<String>,
+) {
+    loop {
+        let Some(active) = hub.upgrade() else {
+            return;
+        };
+
+        let ws_stream = match net::connect_ws(&ws_url, &http).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::warn!("ws hub: upstream connect failed: {}", e);
+                drop(active);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+        let (mut sender, mut receiver) = ws_stream.split();
+
+        tracing::info!(url = %ws_url, "ws hub: upstream connected");
+
+        // replay subscriptions that were live before a reconnect
+        for message in active.resubscribe_messages() {
+            let _ = sender.send(Message::Text(message)).await;
+        }
+        drop(active);
+
+        let mut ping = tokio::time::interval(PING_INTERVAL);
+        loop {
+            tokio::select! {
+                command = commands.recv() => {
+                    let Some(command) = command else {
+                        return;
+                    };
+                    if sender.send(Message::Text(command)).await.is_err() {
+                        break;
+                    }
+                }
+                message = receiver.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        let Some(active) = hub.upgrade() else {
+                            return;
+                        };
+                        active.dispatch(&text);
+                    }
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Err(e)) => {
+                        tracing::warn!("ws hub: upstream error: {}", e);
+                        break;
+                    }
+                    Some(Ok(_)) => {}
+                },
+                _ = ping.tick() => {
+                    let ping_msg = json!({ "method": "ping" }).to_string();
+                    if sender.send(Message::Text(ping_msg)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        tracing::info!("ws hub: upstream disconnected, reconnecting");
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
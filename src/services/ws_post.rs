@@ -0,0 +1,170 @@
+// persistent websocket for the `post` method, signed actions sent without a new http request each time
+use std::{
+    collections::HashMap,
+    sync::{atomic::{AtomicU64, Ordering}, Arc, Mutex},
+    time::Duration,
+};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::protocol::Message;
+use crate::services::net::{self, WsStream};
+use crate::types::HttpConfig;
+
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+// the server drops connections that are quiet for a minute
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<Value>>>>;
+
+// whether the request could have reached the exchange decides if it is safe to resend another way
+#[derive(Debug)]
+pub enum PostError {
+    // never sent, or refused before it was executed
+    Undelivered(anyhow::Error),
+    // sent but no reply came back, the action may or may not have gone through
+    Unconfirmed(anyhow::Error),
+}
+
+#[derive(Clone)]
+struct Connection {
+    outgoing: mpsc::UnboundedSender<Message>,
+    pending: Pending,
+}
+
+pub struct WsPoster {
+    url: String,
+    http: HttpConfig,
+    connection: tokio::sync::Mutex<Option<Connection>>,
+    next_id: AtomicU64,
+}
+
+impl WsPoster {
+    pub fn new(url: &str, http: &HttpConfig) -> Self {
+        Self {
+            url: url.to_string(),
+            http: http.clone(),
+            connection: tokio::sync::Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    // opens the socket ahead of the first post so that one doesn't pay for the handshake
+    pub async fn connect(&self) -> anyhow::Result<()> {
+        self.connection().await.map(|_| ())
+    }
+
+    // sends a signed /exchange payload and returns the exchange's reply to it
+    pub async fn post(&self, payload: Value) -> Result<Value, PostError> {
+        let connection = self.connection().await.map_err(PostError::Undelivered)?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (reply_tx, reply_rx) = oneshot::channel();
+        lock(&connection.pending).insert(id, reply_tx);
+
+        let request = json!({
+            "method": "post",
+            "id": id,
+            "request": { "type": "action", "payload": payload },
+        });
+        if connection.outgoing.send(Message::Text(request.to_string())).is_err() {
+            lock(&connection.pending).remove(&id);
+            return Err(PostError::Undelivered(anyhow::anyhow!("WebSocket connection closed")));
+        }
+
+        let response = match tokio::time::timeout(REPLY_TIMEOUT, reply_rx).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => {
+                return Err(PostError::Unconfirmed(anyhow::anyhow!("WebSocket closed before the reply arrived")));
+            }
+            Err(_) => {
+                lock(&connection.pending).remove(&id);
+                return Err(PostError::Unconfirmed(anyhow::anyhow!(
+                    "No reply within {}s",
+                    REPLY_TIMEOUT.as_secs()
+                )));
+            }
+        };
+
+        // {"type":"action","payload":<exchange reply>} or {"type":"error","payload":"<reason>"}
+        match response["type"].as_str() {
+            Some("action") => Ok(response["payload"].clone()),
+            _ => Err(PostError::Undelivered(anyhow::anyhow!("Post rejected: {}", response["payload"]))),
+        }
+    }
+
+    async fn connection(&self) -> anyhow::Result<Connection> {
+        let mut connection = self.connection.lock().await;
+        if let Some(existing) = connection.as_ref()
+            && !existing.outgoing.is_closed()
+        {
+            return Ok(existing.clone());
+        }
+
+        let ws_stream = net::connect_ws(&self.url, &self.http).await?;
+        let (outgoing, outgoing_rx) = mpsc::unbounded_channel();
+        let pending = Pending::default();
+        tokio::spawn(run(ws_stream, outgoing_rx, pending.clone()));
+
+        let fresh = Connection { outgoing, pending };
+        *connection = Some(fresh.clone());
+        Ok(fresh)
+    }
+}
+
+async fn run(
+    ws_stream: WsStream,
+    mut outgoing: mpsc::UnboundedReceiver<Message>,
+    pending: Pending,
+) {
+    let (mut sender, mut receiver) = ws_stream.split();
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    loop {
+        tokio::select! {
+            message = outgoing.recv() => match message {
+                Some(message) => {
+                    if sender.send(message).await.is_err() {
+                        break;
+                    }
+                }
+                // the poster was dropped
+                None => break,
+            },
+            _ = ping.tick() => {
+                if sender.send(Message::Text(json!({ "method": "ping" }).to_string())).await.is_err() {
+                    break;
+                }
+            }
+            message = receiver.next() => match message {
+                Some(Ok(Message::Text(text))) => dispatch(&pending, &text),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    tracing::warn!(error = %e, "order websocket failed");
+                    break;
+                }
+                None => break,
+            },
+        }
+    }
+    // dropping the waiters' senders tells them the reply isn't coming
+    lock(&pending).clear();
+}
+
+// {"channel":"post","data":{"id":1,"response":{...}}}, everything else (pong) is ignored
+fn dispatch(pending: &Pending, text: &str) {
+    let Ok(message) = serde_json::from_str::<Value>(text) else {
+        return;
+    };
+    if message["channel"] != "post" {
+        return;
+    }
+    let Some(id) = message["data"]["id"].as_u64() else {
+        return;
+    };
+    if let Some(waiter) = lock(pending).remove(&id) {
+        let _ = waiter.send(message["data"]["response"].clone());
+    }
+}
+
+fn lock(pending: &Pending) -> std::sync::MutexGuard<'_, HashMap<u64, oneshot::Sender<Value>>> {
+    pending.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -1,20 +1,58 @@
 // types for status and spot market
 use serde::{Deserialize, Serialize};
+use async_graphql::SimpleObject;
+use utoipa::ToSchema;
+use crate::types::OpenOrder;
 
-#[derive(Serialize, Deserialize)]
+// body of every http error: a message for people and a stable code for clients
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub timestamp: u64,
     pub version: String,
 }
 
-#[derive(Serialize, Deserialize)]
+// /health/deep: "healthy" when every check passed, otherwise "degraded"
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct DeepHealthResponse {
+    pub status: String,
+    pub timestamp: u64,
+    pub checks: Vec<HealthCheck>,
+}
+
+// /ready: true once config is loaded, metadata cached and the exchange reachable
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub config: bool,
+    pub upstream: bool,
+    pub metadata: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HealthCheck {
+    pub name: String,
+    // "ok" or "fail"
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct StatusResponse {
     pub markets: Vec<MarketInfo>,
     pub total_markets: usize,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, ToSchema, SimpleObject)]
 pub struct MarketInfo {
     pub symbol: String,
     pub mark_price: f64,
@@ -24,41 +62,563 @@ pub struct MarketInfo {
     pub open_interest: f64,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema, SimpleObject)]
 pub struct BalanceResponse {
     pub account_value: f64,
     pub withdrawable: f64,
     pub cross_margin_used: f64,
+    // cross maintenance margin; the account is liquidated once it reaches the cross account value
+    pub maintenance_margin: f64,
+    // maintenance margin / cross account value, 1.0 = liquidation
+    pub margin_ratio: f64,
+    // account value not tied up as initial margin
+    pub free_collateral: f64,
     pub positions: Vec<PositionInfo>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema, SimpleObject)]
 pub struct PositionInfo {
     pub symbol: String,
     pub size: f64,
     pub entry_price: f64,
     pub leverage: u32,
+    // "cross" or "isolated"
+    pub margin_mode: String,
     pub unrealized_pnl: f64,
     pub position_value: f64,
 }
 
+// balances of several wallets, each on its own and summed
+#[derive(Serialize, Deserialize)]
+pub struct MultiBalanceResponse {
+    pub accounts: Vec<AccountBalances>,
+    pub total_account_value: f64,
+    pub total_withdrawable: f64,
+    pub total_margin_used: f64,
+    pub total_unrealized_pnl: f64,
+    // per symbol across all accounts, largest gross exposure first
+    pub exposure: Vec<SymbolExposure>,
+}
+
 #[derive(Serialize, Deserialize)]
+pub struct AccountBalances {
+    pub address: String,
+    pub balances: BalanceResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolExposure {
+    pub symbol: String,
+    // signed, longs positive
+    pub net_size: f64,
+    pub long_value: f64,
+    pub short_value: f64,
+    pub unrealized_pnl: f64,
+    pub accounts: usize,
+}
+
+impl SymbolExposure {
+    pub fn net_value(&self) -> f64 {
+        self.long_value - self.short_value
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct SpotResponse {
     pub tokens: Vec<SpotTokenInfo>,
     pub pairs: Vec<SpotPairInfo>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct SpotTokenInfo {
     pub name: String,
     pub decimals: u32,
     pub token_id: String,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct SpotPairInfo {
     pub name: String,
     pub mark_price: f64,
     pub mid_price: f64,
     pub volume_24h: f64,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketType {
+    Perp,
+    Spot,
+}
+
+// user input ("btc", "UBTC/USDC", "@142") resolved against exchange metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ResolvedSymbol {
+    // what the exchange calls it: "BTC", "PURR/USDC", "@142"
+    pub name: String,
+    // "UBTC/USDC" for an "@142" pair, otherwise the name
+    pub display: String,
+    pub market: MarketType,
+    pub sz_decimals: u32,
+    // most decimals a price can have; prices are also held to 5 significant figures
+    pub price_decimals: u32,
+    // perps only
+    pub max_leverage: Option<u32>,
+}
+
+impl ResolvedSymbol {
+    // finest tick the price decimals allow, coarser for prices with many integer digits
+    pub fn tick_size(&self) -> f64 {
+        10f64.powi(-(self.price_decimals as i32))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SpotBalance {
+    pub token: String,
+    pub total: f64,
+    // held by resting orders
+    pub hold: f64,
+    pub available: f64,
+    // usd cost of the balance, 0 when the exchange doesn't track it (usdc)
+    pub entry_notional: f64,
+    // in usdc, None for tokens without a usdc pair
+    pub price: Option<f64>,
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SpotBalancesResponse {
+    pub balances: Vec<SpotBalance>,
+    pub total_value: f64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct OrdersResponse {
+    pub orders: Vec<OpenOrder>,
+    pub total_orders: usize,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, SimpleObject)]
+pub struct FillInfo {
+    pub symbol: String,
+    pub side: String,
+    pub price: f64,
+    pub size: f64,
+    pub direction: String,
+    pub closed_pnl: f64,
+    pub fee: f64,
+    pub order_id: u64,
+    pub trade_id: u64,
+    pub hash: String,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct FillsResponse {
+    pub fills: Vec<FillInfo>,
+    pub total_fills: usize,
+}
+
+// balances, positions, open orders, recent fills and funding fetched together
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct PortfolioResponse {
+    pub balances: BalanceResponse,
+    pub orders: OrdersResponse,
+    pub fills: FillsResponse,
+    // newest first, since funding_since
+    pub funding: Vec<FundingPayment>,
+    pub funding_since: u64,
+    // net funding over the window, positive when received
+    pub funding_total: f64,
+    pub unrealized_pnl: f64,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct PanicReport {
+    pub cancelled_orders: usize,
+    pub closed_positions: Vec<String>,
+    pub errors: Vec<String>,
+    pub attempts: usize,
+    // true once a verification pass saw no open orders or positions
+    pub clean: bool,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GroupCancelReport {
+    pub cancelled: usize,
+    // already filled or cancelled when the group was cancelled
+    pub not_live: usize,
+    pub errors: Vec<String>,
+    pub dry_run: bool,
+}
+
+// one order's lifecycle state plus the fills it produced
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct OrderStatusInfo {
+    // status is open, filled, canceled, triggered, rejected, marginCanceled, ...
+    pub order: OpenOrder,
+    pub status_timestamp: u64,
+    pub cloid: Option<String>,
+    pub fills: Vec<FillInfo>,
+    pub avg_fill_price: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RiskCheckRequest {
+    pub symbol: String,
+    pub qty: f64,
+    #[serde(default = "default_is_buy")]
+    pub is_buy: bool,
+    pub limit_price: Option<f64>,
+    pub leverage: Option<u32>,
+    #[serde(default)]
+    pub reduce_only: bool,
+}
+
+fn default_is_buy() -> bool {
+    true
+}
+
+// verdict of a pre-trade check, nothing is placed
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct RiskCheckResult {
+    pub allowed: bool,
+    // name of the rule that blocked the order, e.g. "notional" or "margin"
+    pub rule: Option<String>,
+    pub reason: Option<String>,
+    pub price: f64,
+    pub notional: f64,
+    pub leverage: u32,
+    pub margin_required: f64,
+    pub available_margin: f64,
+    pub current_exposure: f64,
+    pub resulting_exposure: f64,
+    pub open_positions: usize,
+    pub resulting_positions: usize,
+}
+
+// order that offsets `ratio` of a perp position, sizes signed (negative = short / sell)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HedgePlan {
+    pub symbol: String,
+    pub position: f64,
+    pub ratio: f64,
+    pub order_size: f64,
+    pub resulting_position: f64,
+}
+
+// position size that loses `risk_amount` if the stop is hit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskSizing {
+    pub symbol: String,
+    pub is_buy: bool,
+    pub entry: f64,
+    pub stop: f64,
+    pub size: f64,
+    pub equity: f64,
+    // loss at the stop after rounding the size down
+    pub risk_amount: f64,
+    pub notional: f64,
+    // notional / equity, what the account needs to carry the position
+    pub effective_leverage: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferralStatus {
+    // code and address of whoever referred this account
+    pub referred_by: Option<String>,
+    pub referrer: Option<String>,
+    pub cum_volume: f64,
+    pub unclaimed_rewards: f64,
+    pub claimed_rewards: f64,
+    pub builder_rewards: f64,
+    // this account's own code, once it has created one
+    pub own_code: Option<String>,
+    pub stage: String,
+    pub referred_users: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorInfo {
+    pub address: String,
+    pub name: String,
+    // HYPE staked, in whole tokens
+    pub stake: f64,
+    pub commission: f64,
+    pub active: bool,
+    pub jailed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delegation {
+    pub validator: String,
+    pub name: Option<String>,
+    pub amount: f64,
+    // undelegating before this (ms) is rejected
+    pub locked_until: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakingSummary {
+    pub delegated: f64,
+    // staking balance not delegated to any validator
+    pub undelegated: f64,
+    pub pending_withdrawal: f64,
+    pub pending_withdrawals: u64,
+    pub delegations: Vec<Delegation>,
+    pub rewards_total: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WithdrawalStatus {
+    // debited on hyperliquid, still inside the bridge's dispute period
+    Pending,
+    // past the dispute period, so the bridge should have released it; not checked on arbitrum
+    ExpectedFinal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Withdrawal {
+    pub time: u64,
+    // hyperliquid l1 transaction, what the explorer is searched by
+    pub hash: String,
+    pub usdc: f64,
+    pub fee: f64,
+    pub nonce: Option<u64>,
+    pub status: WithdrawalStatus,
+    // when the bridge should have released it, an estimate rather than an observed release
+    pub final_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultInfo {
+    pub name: String,
+    pub address: String,
+    pub leader: String,
+    pub description: String,
+    pub equity: f64,
+    // fraction, 0.36 = 36%
+    pub apr: f64,
+    pub leader_commission: f64,
+    pub max_withdrawable: f64,
+    pub closed: bool,
+    pub allow_deposits: bool,
+    // None when this account has no deposit in the vault
+    pub my_position: Option<VaultPosition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultPosition {
+    pub equity: f64,
+    // equity / vault equity
+    pub share: f64,
+    pub pnl: f64,
+    pub all_time_pnl: f64,
+    pub days_following: u64,
+    // withdrawals are refused before this (ms)
+    pub lockup_until: Option<u64>,
+}
+
+// volume tier from the perp fee schedule, tier 0 is the base rate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeTier {
+    pub tier: usize,
+    // 14 day volume needed to reach the tier
+    pub volume_cutoff: f64,
+    pub taker_rate: f64,
+    pub maker_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    // the user's effective rates, after referral and staking discounts
+    pub taker_rate: f64,
+    pub maker_rate: f64,
+    pub referral_discount: f64,
+    pub volume_14d: f64,
+    pub tiers: Vec<FeeTier>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeSummary {
+    pub schedule: FeeSchedule,
+    pub tier: FeeTier,
+    pub next_tier: Option<FeeTier>,
+    // volume still needed for next_tier
+    pub volume_to_next: Option<f64>,
+    pub window_start: u64,
+    pub window_fills: usize,
+    pub window_notional: f64,
+    pub window_fees: f64,
+}
+
+// a perp ranked by how much its funding pays the side that collects it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingCandidate {
+    pub symbol: String,
+    // hourly rate, positive when longs pay shorts
+    pub funding_rate: f64,
+    pub annualized: f64,
+    pub open_interest_usd: f64,
+    pub volume_24h: f64,
+    // perp side that receives funding, hedged with the opposite spot or perp leg
+    pub collect_side: String,
+    // clears the minimum annualized rate
+    pub candidate: bool,
+}
+
+// oldest recorded open interest / volume for a symbol inside the watch window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarketSample {
+    pub time: u64,
+    pub open_interest: f64,
+    pub volume_24h: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketAnomaly {
+    pub symbol: String,
+    // "open_interest" or "volume_24h"
+    pub metric: String,
+    pub from: f64,
+    pub to: f64,
+    // fraction, -0.3 = down 30%
+    pub change: f64,
+    pub since: u64,
+}
+
+// how close a position is to liquidation, worst last
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LiquidationLevel {
+    Safe,
+    Info,
+    Warn,
+    Critical,
+}
+
+impl std::fmt::Display for LiquidationLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            LiquidationLevel::Safe => "SAFE",
+            LiquidationLevel::Info => "INFO",
+            LiquidationLevel::Warn => "WARN",
+            LiquidationLevel::Critical => "CRITICAL",
+        };
+        f.write_str(label)
+    }
+}
+
+// size and liquidation price of a position, None when the account can't be liquidated on it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionLiquidation {
+    pub symbol: String,
+    pub size: f64,
+    pub liquidation_price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidationRisk {
+    pub symbol: String,
+    pub size: f64,
+    pub mark: f64,
+    pub liquidation_price: f64,
+    // fraction of the mark the price can still move against the position, 0.15 = 15%
+    pub distance: f64,
+    pub level: LiquidationLevel,
+}
+
+// mid, mark and oracle for one perp, with the 24h change of the mark
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PriceInfo {
+    pub symbol: String,
+    pub mid: Option<f64>,
+    pub mark: f64,
+    pub oracle: f64,
+    pub prev_day: f64,
+    // fraction, 0.01 = 1%
+    pub change_24h: f64,
+    pub premium: f64,
+    pub funding: f64,
+}
+
+impl PriceInfo {
+    pub fn set_mark(&mut self, mark: f64) {
+        self.mark = mark;
+        self.change_24h = if self.prev_day > 0.0 { mark / self.prev_day - 1.0 } else { 0.0 };
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BookLevel {
+    pub price: f64,
+    pub size: f64,
+}
+
+// both sides best price first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBook {
+    pub symbol: String,
+    pub time: u64,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+}
+
+impl OrderBook {
+    pub fn mid(&self) -> Option<f64> {
+        Some((self.bids.first()?.price + self.asks.first()?.price) / 2.0)
+    }
+}
+
+// what a market order of `qty` would pay walking the current book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactEstimate {
+    pub symbol: String,
+    pub is_buy: bool,
+    pub qty: f64,
+    pub mid: f64,
+    pub avg_price: f64,
+    // last level touched, the price an ioc limit must allow
+    pub worst_price: f64,
+    // avg_price vs mid as a fraction, always positive
+    pub slippage: f64,
+    // less than qty when the visible book is too thin
+    pub fillable_qty: f64,
+    pub levels_used: usize,
+}
+
+impl ImpactEstimate {
+    pub fn fully_fillable(&self) -> bool {
+        self.fillable_qty + 1e-9 >= self.qty
+    }
+
+    pub fn exceeds(&self, tolerance: f64) -> bool {
+        !self.fully_fillable() || self.slippage > tolerance
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Candle {
+    pub symbol: String,
+    pub interval: String,
+    pub open_time: u64,
+    pub close_time: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct FundingPayment {
+    pub symbol: String,
+    pub usdc: f64,
+    pub position_size: f64,
+    pub funding_rate: f64,
+    pub timestamp: u64,
+}
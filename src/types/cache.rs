@@ -0,0 +1,30 @@
+// [cache] section: how long symbol metadata is reused before it is fetched again
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CacheConfig {
+    #[serde(default = "default_ttl_secs")]
+    pub ttl_secs: u64,
+    // keep metadata between runs, under dir (default $XDG_CACHE_HOME/hl or ~/.cache/hl)
+    #[serde(default = "default_disk")]
+    pub disk: bool,
+    pub dir: Option<String>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: default_ttl_secs(),
+            disk: default_disk(),
+            dir: None,
+        }
+    }
+}
+
+fn default_ttl_secs() -> u64 {
+    600
+}
+
+fn default_disk() -> bool {
+    true
+}
@@ -1,14 +1,102 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Default)]
 pub struct InfoRequest {
     #[serde(rename = "type")]
     pub request_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
+    #[serde(rename = "startTime", skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<u64>,
+    #[serde(rename = "endTime", skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub req: Option<CandleRequest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coin: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oid: Option<OrderRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builder: Option<String>,
+    #[serde(rename = "vaultAddress", skip_serializing_if = "Option::is_none")]
+    pub vault_address: Option<String>,
+}
+
+// orderStatus accepts either the exchange oid or the 0x-hex client order id
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum OrderRef {
+    Oid(u64),
+    Cloid(String),
+}
+
+impl OrderRef {
+    pub fn parse(value: &str) -> Option<Self> {
+        if let Ok(oid) = value.parse() {
+            return Some(OrderRef::Oid(oid));
+        }
+        crate::types::parse_cloid(value).map(|cloid| OrderRef::Cloid(crate::types::cloid_hex(&cloid)))
+    }
 }
 
+impl std::fmt::Display for OrderRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderRef::Oid(oid) => write!(f, "{}", oid),
+            OrderRef::Cloid(cloid) => write!(f, "{}", cloid),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CandleRequest {
+    pub coin: String,
+    pub interval: String,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+// candleSnapshot rows and candle websocket updates share this shape
 #[derive(Deserialize, Debug, Clone)]
+pub struct RawCandle {
+    #[serde(rename = "t")]
+    pub open_time: u64,
+    #[serde(rename = "T")]
+    pub close_time: u64,
+    #[serde(rename = "s")]
+    pub coin: String,
+    #[serde(rename = "i")]
+    pub interval: String,
+    #[serde(rename = "o")]
+    pub open: String,
+    #[serde(rename = "h")]
+    pub high: String,
+    #[serde(rename = "l")]
+    pub low: String,
+    #[serde(rename = "c")]
+    pub close: String,
+    #[serde(rename = "v")]
+    pub volume: String,
+}
+
+impl RawCandle {
+    pub fn to_candle(&self) -> crate::types::Candle {
+        crate::types::Candle {
+            symbol: self.coin.clone(),
+            interval: self.interval.clone(),
+            open_time: self.open_time,
+            close_time: self.close_time,
+            open: self.open.parse().unwrap_or(0.0),
+            high: self.high.parse().unwrap_or(0.0),
+            low: self.low.parse().unwrap_or(0.0),
+            close: self.close.parse().unwrap_or(0.0),
+            volume: self.volume.parse().unwrap_or(0.0),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[allow(dead_code)]
 pub struct AssetInfo {
     pub name: String,
@@ -36,6 +124,9 @@ pub struct AssetContext {
     pub open_interest: Option<String>,
     #[serde(rename = "prevDayPx")]
     pub prev_day_px: Option<String>,
+    #[serde(rename = "oraclePx")]
+    pub oracle_px: Option<String>,
+    pub premium: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -46,6 +137,11 @@ pub struct ClearinghouseState {
     pub withdrawable: String,
     #[serde(rename = "crossMarginUsed", default)]
     pub cross_margin_used: Option<String>,
+    #[serde(rename = "crossMaintenanceMarginUsed", default)]
+    pub cross_maintenance_margin_used: Option<String>,
+    // marginSummary covers isolated positions too, this one only the cross account
+    #[serde(rename = "crossMarginSummary", default)]
+    pub cross_margin_summary: Option<MarginSummary>,
     #[serde(rename = "assetPositions")]
     pub asset_positions: Vec<AssetPosition>,
 }
@@ -58,6 +154,8 @@ pub struct MarginSummary {
     pub total_ntl_pos: String,
     #[serde(rename = "totalRawUsd")]
     pub total_raw_usd: String,
+    #[serde(rename = "totalMarginUsed", default)]
+    pub total_margin_used: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -79,6 +177,9 @@ pub struct Position {
     #[serde(rename = "positionValue")]
     pub position_value: String,
     pub szi: String,
+    // null when the rest of the account covers any move
+    #[serde(rename = "liquidationPx", default)]
+    pub liquidation_px: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -90,12 +191,208 @@ pub struct Leverage {
 }
 
 #[derive(Deserialize, Debug)]
+pub struct ActiveAssetData {
+    pub leverage: Leverage,
+}
+
+// l2Book: levels[0] are bids and levels[1] asks, best price first
+#[derive(Deserialize, Debug)]
+pub struct RawL2Book {
+    pub coin: String,
+    pub time: u64,
+    pub levels: Vec<Vec<RawBookLevel>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawBookLevel {
+    pub px: String,
+    pub sz: String,
+}
+
+// the rest snapshot and the websocket l2Book update share this shape
+impl RawL2Book {
+    pub fn into_order_book(self) -> crate::types::OrderBook {
+        let mut sides = self.levels.into_iter().map(|levels| {
+            levels
+                .into_iter()
+                .filter_map(|level| {
+                    Some(crate::types::BookLevel {
+                        price: level.px.parse().ok()?,
+                        size: level.sz.parse().ok()?,
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+
+        crate::types::OrderBook {
+            symbol: self.coin,
+            time: self.time,
+            bids: sides.next().unwrap_or_default(),
+            asks: sides.next().unwrap_or_default(),
+        }
+    }
+}
+
+// userFees: rates already include referral and staking discounts
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RawUserFees {
+    pub daily_user_vlm: Vec<RawDailyVolume>,
+    pub fee_schedule: RawFeeSchedule,
+    pub user_cross_rate: String,
+    pub user_add_rate: String,
+    #[serde(default)]
+    pub active_referral_discount: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RawDailyVolume {
+    pub date: String,
+    pub user_cross: String,
+    pub user_add: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawFeeSchedule {
+    pub cross: String,
+    pub add: String,
+    pub tiers: RawFeeTiers,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawFeeTiers {
+    pub vip: Vec<RawVipTier>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RawVipTier {
+    pub ntl_cutoff: String,
+    pub cross: String,
+    pub add: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RawReferral {
+    pub referred_by: Option<RawReferredBy>,
+    pub cum_vlm: String,
+    pub unclaimed_rewards: String,
+    pub claimed_rewards: String,
+    #[serde(default)]
+    pub builder_rewards: Option<String>,
+    pub referrer_state: RawReferrerState,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawReferredBy {
+    pub referrer: String,
+    pub code: String,
+}
+
+// stage is "ready" once the user has a code, else "needToCreateCode" or "needToTrade"
+#[derive(Deserialize, Debug)]
+pub struct RawReferrerState {
+    pub stage: String,
+    #[serde(default)]
+    pub data: Option<RawReferrerData>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RawReferrerData {
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub referral_states: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RawDelegatorSummary {
+    pub delegated: String,
+    pub undelegated: String,
+    pub total_pending_withdrawal: String,
+    pub n_pending_withdrawals: u64,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RawDelegation {
+    pub validator: String,
+    pub amount: String,
+    pub locked_until_timestamp: u64,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RawDelegatorReward {
+    pub time: u64,
+    pub source: String,
+    pub total_amount: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RawValidatorSummary {
+    pub validator: String,
+    pub name: String,
+    pub stake: u64,
+    pub commission: String,
+    pub is_jailed: bool,
+    pub is_active: bool,
+}
+
+// vaultDetails: summary numbers are json numbers, follower amounts are strings
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RawVaultDetails {
+    pub name: String,
+    pub vault_address: String,
+    pub leader: String,
+    #[serde(default)]
+    pub description: String,
+    pub apr: f64,
+    pub follower_state: Option<RawVaultFollower>,
+    pub leader_commission: f64,
+    pub max_withdrawable: f64,
+    pub is_closed: bool,
+    pub allow_deposits: bool,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RawVaultFollower {
+    pub vault_equity: String,
+    pub pnl: String,
+    pub all_time_pnl: String,
+    pub days_following: u64,
+    #[serde(default)]
+    pub lockup_until: Option<u64>,
+}
+
+// /exchange replies {"status":"ok"|"err","response":...}
+#[derive(Deserialize, Debug)]
+pub struct RawExchangeReply {
+    pub status: String,
+    #[serde(default)]
+    pub response: serde_json::Value,
+}
+
+// `meta` reply, the perp universe without the asset contexts
+#[derive(Deserialize, Debug)]
+pub struct RawMeta {
+    pub universe: Vec<AssetInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SpotMeta {
     pub tokens: Vec<SpotToken>,
     pub universe: Vec<SpotPair>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[allow(dead_code)]
 pub struct SpotToken {
     pub name: String,
@@ -106,7 +403,7 @@ pub struct SpotToken {
     pub token_id: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[allow(dead_code)]
 pub struct SpotPair {
     pub name: String,
@@ -114,6 +411,21 @@ pub struct SpotPair {
     pub index: u32,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct RawSpotClearinghouseState {
+    pub balances: Vec<RawSpotBalance>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RawSpotBalance {
+    pub coin: String,
+    pub token: u32,
+    pub hold: String,
+    pub total: String,
+    #[serde(rename = "entryNtl")]
+    pub entry_ntl: Option<String>,
+}
+
 #[derive(Deserialize, Debug)]
 #[allow(dead_code)]
 pub struct SpotAssetContext {
@@ -127,6 +439,110 @@ pub struct SpotAssetContext {
     pub prev_day_px: Option<String>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct RawOpenOrder {
+    pub coin: String,
+    #[serde(rename = "limitPx")]
+    pub limit_px: String,
+    pub oid: u64,
+    pub side: String,
+    pub sz: String,
+    #[serde(rename = "origSz", default)]
+    pub orig_sz: Option<String>,
+    #[serde(default)]
+    pub cloid: Option<String>,
+    pub timestamp: u64,
+}
+
+// frontendOpenOrders: openOrders plus the trigger details
+#[derive(Deserialize, Debug)]
+pub struct RawFrontendOrder {
+    pub coin: String,
+    pub oid: u64,
+    pub side: String,
+    pub sz: String,
+    // "Limit", "Stop Market", "Take Profit Limit", ...
+    #[serde(rename = "orderType", default)]
+    pub order_type: String,
+    #[serde(rename = "isTrigger", default)]
+    pub is_trigger: bool,
+    #[serde(rename = "triggerPx", default)]
+    pub trigger_px: String,
+    #[serde(rename = "reduceOnly", default)]
+    pub reduce_only: bool,
+}
+
+// orderStatus: {"status": "order", "order": {...}} or {"status": "unknownOid"}
+#[derive(Deserialize, Debug)]
+pub struct RawOrderStatusResponse {
+    pub status: String,
+    pub order: Option<RawOrderStatus>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawOrderStatus {
+    pub order: RawOpenOrder,
+    pub status: String,
+    #[serde(rename = "statusTimestamp")]
+    pub status_timestamp: u64,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+pub struct UserFill {
+    pub coin: String,
+    pub px: String,
+    pub sz: String,
+    pub side: String,
+    pub time: u64,
+    #[serde(rename = "startPosition")]
+    pub start_position: String,
+    pub dir: String,
+    #[serde(rename = "closedPnl")]
+    pub closed_pnl: String,
+    pub hash: String,
+    pub oid: u64,
+    pub crossed: bool,
+    pub fee: String,
+    #[serde(default)]
+    pub tid: u64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct UserFundingEntry {
+    pub time: u64,
+    pub delta: FundingDelta,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FundingDelta {
+    pub coin: String,
+    pub usdc: String,
+    pub szi: String,
+    #[serde(rename = "fundingRate")]
+    pub funding_rate: String,
+}
+
+// userNonFundingLedgerUpdates entry; deltas other than withdrawals carry other fields, which are ignored
+#[derive(Deserialize, Debug, Clone)]
+pub struct RawLedgerUpdate {
+    pub time: u64,
+    pub hash: String,
+    pub delta: RawLedgerDelta,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RawLedgerDelta {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub usdc: Option<String>,
+    #[serde(default)]
+    pub fee: Option<String>,
+    #[serde(default)]
+    pub nonce: Option<u64>,
+}
+
 #[derive(Serialize, Debug)]
 pub struct WsSubscription {
     pub method: String,
@@ -0,0 +1,63 @@
+// [http] section: outbound request behaviour towards the hyperliquid api and webhooks
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpConfig {
+    // tries per request including the first, 1 turns retries off
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    // first backoff, doubled on every retry
+    #[serde(default = "default_retry_base_ms")]
+    pub retry_base_ms: u64,
+    #[serde(default = "default_retry_max_ms")]
+    pub retry_max_ms: u64,
+    // whole request, per attempt
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    // tcp/tls setup, for rest and websocket connections alike
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    // http://, https://, socks5:// or socks5h:// with optional user:pass@, used for rest and websockets
+    pub proxy: Option<String>,
+    // hyperliquid's rest budget per ip, requests wait rather than exceed it
+    #[serde(default = "default_weight_per_minute")]
+    pub weight_per_minute: u32,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            retry_base_ms: default_retry_base_ms(),
+            retry_max_ms: default_retry_max_ms(),
+            timeout_secs: default_timeout_secs(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+            proxy: None,
+            weight_per_minute: default_weight_per_minute(),
+        }
+    }
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_retry_base_ms() -> u64 {
+    250
+}
+
+fn default_retry_max_ms() -> u64 {
+    5_000
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+fn default_weight_per_minute() -> u32 {
+    crate::services::rate_budget::DEFAULT_WEIGHT_PER_MINUTE
+}
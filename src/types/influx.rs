@@ -0,0 +1,35 @@
+// [influx] section: what the line-protocol sink writes, the endpoint itself is `influx_url`
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct InfluxConfig {
+    // sent as `Authorization: Token <token>` (influxdb 2), v1 credentials go in the url
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    // markets written each interval, empty means every perp
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    #[serde(default = "default_measurement_prefix")]
+    pub measurement_prefix: String,
+}
+
+impl Default for InfluxConfig {
+    fn default() -> Self {
+        Self {
+            token: None,
+            interval_secs: default_interval_secs(),
+            symbols: Vec::new(),
+            measurement_prefix: default_measurement_prefix(),
+        }
+    }
+}
+
+fn default_interval_secs() -> u64 {
+    10
+}
+
+fn default_measurement_prefix() -> String {
+    "hl".to_string()
+}
@@ -0,0 +1,72 @@
+// types for the trade journal
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JournalConfig {
+    // unset keeps daemon modes from recording; cli commands fall back to hl-journal.db
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default = "default_sync_interval_secs")]
+    pub sync_interval_secs: u64,
+    // server mode also snapshots account value this often, between full syncs
+    #[serde(default)]
+    pub equity_interval_secs: Option<u64>,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            sync_interval_secs: default_sync_interval_secs(),
+            equity_interval_secs: None,
+        }
+    }
+}
+
+fn default_sync_interval_secs() -> u64 {
+    300
+}
+
+// implementation shortfall of one execution, the fills of one or more orders
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionReport {
+    // when the first order went out and when the report was taken, ms
+    pub started: u64,
+    pub finished: u64,
+    pub symbol: String,
+    pub side: String,
+    pub requested_qty: f64,
+    pub filled_qty: f64,
+    pub fills: usize,
+    pub avg_price: f64,
+    pub arrival_mid: f64,
+    // volume weighted price of everyone's trades over the execution, from 1m candles
+    pub interval_vwap: Option<f64>,
+    pub fees: f64,
+    // positive is a cost: paid above the reference on a buy, below it on a sell
+    pub shortfall_bps: f64,
+    pub vs_vwap_bps: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncSummary {
+    pub new_fills: usize,
+    pub open_orders: usize,
+    pub new_funding_payments: usize,
+    pub account_value: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PortfolioStats {
+    pub days: usize,
+    pub total_return: f64,
+    pub sharpe: Option<f64>,
+    pub sortino: Option<f64>,
+    pub max_drawdown: f64,
+    pub closed_trades: usize,
+    pub win_rate: Option<f64>,
+    pub avg_win: Option<f64>,
+    pub avg_loss: Option<f64>,
+    pub profit_factor: Option<f64>,
+    pub realized_pnl: f64,
+}
@@ -0,0 +1,61 @@
+// [kafka] section: what the kafka sink produces and where, the brokers themselves are `kafka_brokers`
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KafkaConfig {
+    // trades and candles are produced for these, fills for every symbol
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    #[serde(default = "default_candle_interval")]
+    pub candle_interval: String,
+    #[serde(default = "default_trades_topic")]
+    pub trades_topic: String,
+    #[serde(default = "default_candles_topic")]
+    pub candles_topic: String,
+    #[serde(default = "default_user_events_topic")]
+    pub user_events_topic: String,
+    // messages held while the brokers are unreachable
+    #[serde(default = "default_buffer_messages")]
+    pub buffer_messages: usize,
+    // how long a message is retried before it's dropped
+    #[serde(default = "default_delivery_timeout_secs")]
+    pub delivery_timeout_secs: u64,
+}
+
+impl Default for KafkaConfig {
+    fn default() -> Self {
+        Self {
+            symbols: Vec::new(),
+            candle_interval: default_candle_interval(),
+            trades_topic: default_trades_topic(),
+            candles_topic: default_candles_topic(),
+            user_events_topic: default_user_events_topic(),
+            buffer_messages: default_buffer_messages(),
+            delivery_timeout_secs: default_delivery_timeout_secs(),
+        }
+    }
+}
+
+fn default_candle_interval() -> String {
+    "1m".to_string()
+}
+
+fn default_trades_topic() -> String {
+    "hl.trades".to_string()
+}
+
+fn default_candles_topic() -> String {
+    "hl.candles".to_string()
+}
+
+fn default_user_events_topic() -> String {
+    "hl.user_events".to_string()
+}
+
+fn default_buffer_messages() -> usize {
+    100_000
+}
+
+fn default_delivery_timeout_secs() -> u64 {
+    300
+}
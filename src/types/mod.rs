@@ -1,12 +1,38 @@
 // export modules
 pub mod api;
+pub mod cache;
 pub mod exchange;
+pub mod http;
+pub mod influx;
+pub mod kafka;
+pub mod mqtt;
+pub mod redis_bridge;
 pub mod streaming;
 pub mod trading;
 pub mod risk;
+pub mod notifications;
+pub mod server;
+pub mod snapshot;
+pub mod journal;
+pub mod tax;
+pub mod paper;
+pub mod strategy;
 
 pub use api::*;
+pub use cache::*;
 pub use exchange::*;
+pub use http::*;
+pub use influx::*;
+pub use kafka::*;
+pub use mqtt::*;
+pub use redis_bridge::*;
 // changed this due to ambigous warning.
-pub use trading::{OrderRequest, OrderResponse, OrderResult};
-pub use risk::*;
\ No newline at end of file
+pub use trading::{builder_fee_percent, cloid_hex, parse_cloid, BuilderFee, GroupedOrder, MarginMode, OpenOrder, OrderPreset, OrderTransport, OrderRequest, OrderResponse, OrderResult, TriggerOrder, MAX_BUILDER_FEE_BPS};
+pub use risk::*;
+pub use notifications::*;
+pub use server::*;
+pub use snapshot::*;
+pub use journal::*;
+pub use tax::*;
+pub use paper::*;
+pub use strategy::*;
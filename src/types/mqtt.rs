@@ -0,0 +1,36 @@
+// [mqtt] section: what the mqtt bridge publishes, the broker itself is `mqtt_broker`
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MqttConfig {
+    // trades and bbo are published for these, fills and positions for every symbol
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    #[serde(default = "default_topic_prefix")]
+    pub topic_prefix: String,
+    // 0 (at most once) or 1 (at least once)
+    #[serde(default)]
+    pub qos: u8,
+    // how often positions are polled for changes
+    #[serde(default = "default_position_interval_secs")]
+    pub position_interval_secs: u64,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            symbols: Vec::new(),
+            topic_prefix: default_topic_prefix(),
+            qos: 0,
+            position_interval_secs: default_position_interval_secs(),
+        }
+    }
+}
+
+fn default_topic_prefix() -> String {
+    "hl".to_string()
+}
+
+fn default_position_interval_secs() -> u64 {
+    5
+}
@@ -0,0 +1,125 @@
+// types for the notification layer
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::types::{BalanceResponse, LiquidationRisk, MarketAnomaly};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NotificationConfig {
+    #[serde(default)]
+    pub discord_webhook_url: Option<String>,
+    // event kind -> sinks, e.g. fills = ["discord"], risk = ["all"]
+    #[serde(default)]
+    pub routes: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyPnlSummary {
+    pub date: String,
+    pub account_value: f64,
+    pub unrealized_pnl: f64,
+    pub open_positions: usize,
+    pub best_position: Option<(String, f64)>,
+    pub worst_position: Option<(String, f64)>,
+}
+
+impl DailyPnlSummary {
+    pub fn from_balances(balances: &BalanceResponse) -> Self {
+        let by_pnl = |a: &&crate::types::PositionInfo, b: &&crate::types::PositionInfo| {
+            a.unrealized_pnl.total_cmp(&b.unrealized_pnl)
+        };
+
+        Self {
+            date: chrono::Utc::now().format("%Y-%m-%d").to_string(),
+            account_value: balances.account_value,
+            unrealized_pnl: balances.positions.iter().map(|p| p.unrealized_pnl).sum(),
+            open_positions: balances.positions.len(),
+            best_position: balances
+                .positions
+                .iter()
+                .max_by(by_pnl)
+                .map(|p| (p.symbol.clone(), p.unrealized_pnl)),
+            worst_position: balances
+                .positions
+                .iter()
+                .min_by(by_pnl)
+                .map(|p| (p.symbol.clone(), p.unrealized_pnl)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum NotificationEvent {
+    OrderConfirmation {
+        symbol: String,
+        side: String,
+        qty: f64,
+        order_id: u64,
+        price: Option<f64>,
+    },
+    Fill {
+        symbol: String,
+        side: String,
+        filled_qty: f64,
+        avg_price: f64,
+        order_id: u64,
+    },
+    RiskViolation {
+        symbol: String,
+        reason: String,
+    },
+    DailyPnl(DailyPnlSummary),
+    MarketAnomaly(MarketAnomaly),
+    Liquidation(LiquidationRisk),
+}
+
+impl NotificationEvent {
+    // routing key used in the `routes` config table
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NotificationEvent::OrderConfirmation { .. } => "orders",
+            NotificationEvent::Fill { .. } => "fills",
+            NotificationEvent::RiskViolation { .. } => "risk",
+            NotificationEvent::DailyPnl(_) => "pnl",
+            NotificationEvent::MarketAnomaly(_) => "market",
+            NotificationEvent::Liquidation(_) => "liquidation",
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            NotificationEvent::OrderConfirmation { symbol, side, .. } => {
+                format!("Order placed: {} {}", side, symbol)
+            }
+            NotificationEvent::Fill { symbol, side, .. } => format!("Filled: {} {}", side, symbol),
+            NotificationEvent::RiskViolation { symbol, .. } => format!("Risk check blocked {}", symbol),
+            NotificationEvent::DailyPnl(summary) => format!("Daily PnL summary {}", summary.date),
+            NotificationEvent::MarketAnomaly(anomaly) => format!("{} {} moved", anomaly.symbol, anomaly.metric),
+            NotificationEvent::Liquidation(risk) => format!("{} {} liquidation distance", risk.level, risk.symbol),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        match self {
+            NotificationEvent::OrderConfirmation { symbol, side, qty, order_id, price } => match price {
+                Some(px) => format!("{} {} {} @ ${:.4} (oid {})", side, qty, symbol, px, order_id),
+                None => format!("{} {} {} @ market (oid {})", side, qty, symbol, order_id),
+            },
+            NotificationEvent::Fill { symbol, side, filled_qty, avg_price, order_id } => {
+                format!("{} {} {} filled @ ${:.4} (oid {})", side, filled_qty, symbol, avg_price, order_id)
+            }
+            NotificationEvent::RiskViolation { reason, .. } => reason.clone(),
+            NotificationEvent::DailyPnl(summary) => format!(
+                "Account value ${:.2}, unrealized PnL ${:.2} across {} positions",
+                summary.account_value, summary.unrealized_pnl, summary.open_positions
+            ),
+            NotificationEvent::MarketAnomaly(anomaly) => format!(
+                "{} {} {:+.1}% ({:.0} -> {:.0})",
+                anomaly.symbol, anomaly.metric, anomaly.change * 100.0, anomaly.from, anomaly.to
+            ),
+            NotificationEvent::Liquidation(risk) => format!(
+                "{} {:+} is {:.2}% from liquidation (mark ${:.4}, liquidation ${:.4})",
+                risk.symbol, risk.size, risk.distance * 100.0, risk.mark, risk.liquidation_price
+            ),
+        }
+    }
+}
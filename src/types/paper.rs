@@ -0,0 +1,91 @@
+// types for the local paper-trading simulator
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PaperConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_paper_path")]
+    pub path: String,
+    #[serde(default = "default_starting_balance")]
+    pub starting_balance: f64,
+    // market orders fill at mid +/- this many basis points
+    #[serde(default = "default_slippage_bps")]
+    pub slippage_bps: f64,
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: default_paper_path(),
+            starting_balance: default_starting_balance(),
+            slippage_bps: default_slippage_bps(),
+        }
+    }
+}
+
+fn default_paper_path() -> String {
+    "hl-paper.json".to_string()
+}
+
+fn default_starting_balance() -> f64 {
+    10_000.0
+}
+
+fn default_slippage_bps() -> f64 {
+    5.0
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaperPosition {
+    // signed, negative is short
+    pub size: f64,
+    pub entry_price: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperOrder {
+    pub order_id: u64,
+    pub symbol: String,
+    pub is_buy: bool,
+    pub price: f64,
+    pub size: f64,
+    pub reduce_only: bool,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperFill {
+    pub order_id: u64,
+    pub symbol: String,
+    pub is_buy: bool,
+    pub price: f64,
+    pub size: f64,
+    pub fee: f64,
+    pub closed_pnl: f64,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperAccount {
+    // realized cash balance in usdc
+    pub balance: f64,
+    pub positions: HashMap<String, PaperPosition>,
+    pub open_orders: Vec<PaperOrder>,
+    pub fills: Vec<PaperFill>,
+    pub next_order_id: u64,
+}
+
+impl PaperAccount {
+    pub fn new(balance: f64) -> Self {
+        Self {
+            balance,
+            positions: HashMap::new(),
+            open_orders: Vec::new(),
+            fills: Vec::new(),
+            next_order_id: 1,
+        }
+    }
+}
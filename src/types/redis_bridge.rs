@@ -0,0 +1,36 @@
+// [redis] section: which streams the redis bridge forwards, the server itself is `redis_url`
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedisConfig {
+    // trades:BTC, bbo:ETH, l2Book:SOL, candle:BTC:1m, allMids, fills
+    #[serde(default)]
+    pub channels: Vec<String>,
+    #[serde(default = "default_channel_prefix")]
+    pub channel_prefix: String,
+    // also XADD every message to a stream of the same name, for consumers that can't miss any
+    #[serde(default)]
+    pub streams: bool,
+    // approximate cap on each stream's length
+    #[serde(default = "default_stream_maxlen")]
+    pub stream_maxlen: usize,
+}
+
+impl Default for RedisConfig {
+    fn default() -> Self {
+        Self {
+            channels: Vec::new(),
+            channel_prefix: default_channel_prefix(),
+            streams: false,
+            stream_maxlen: default_stream_maxlen(),
+        }
+    }
+}
+
+fn default_channel_prefix() -> String {
+    "hl".to_string()
+}
+
+fn default_stream_maxlen() -> usize {
+    10_000
+}
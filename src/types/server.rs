@@ -0,0 +1,122 @@
+// settings for `hl --server`
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiScope {
+    Read,
+    Trade,
+}
+
+impl ApiScope {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "read" => Some(ApiScope::Read),
+            "trade" => Some(ApiScope::Trade),
+            _ => None,
+        }
+    }
+
+    // trade-scoped keys can do everything read-scoped keys can
+    pub fn allows(&self, required: ApiScope) -> bool {
+        *self >= required
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiKeyConfig {
+    #[serde(default)]
+    pub name: String,
+    pub key: String,
+    pub scope: ApiScope,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitConfig {
+    #[serde(default = "default_orders_per_second")]
+    pub orders_per_second: u32,
+    #[serde(default = "default_info_per_minute")]
+    pub info_per_minute: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            orders_per_second: default_orders_per_second(),
+            info_per_minute: default_info_per_minute(),
+        }
+    }
+}
+
+fn default_orders_per_second() -> u32 {
+    10
+}
+
+fn default_info_per_minute() -> u32 {
+    60
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ServerConfig {
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+    #[serde(default)]
+    pub rate_limits: RateLimitConfig,
+    #[serde(default)]
+    pub tls_cert: Option<String>,
+    #[serde(default)]
+    pub tls_key: Option<String>,
+    // serve /graphql and /graphql/ws
+    #[serde(default)]
+    pub graphql: bool,
+    #[serde(default)]
+    pub cors: CorsConfig,
+    // listen address, loopback (127.0.0.1) when unset; "0.0.0.0" listens on every interface
+    pub bind: Option<String>,
+    // prefix for every route when behind a reverse proxy, e.g. "/hl"
+    pub base_path: Option<String>,
+}
+
+// [server.cors], browsers on other origins are refused unless listed here
+#[derive(Debug, Clone, Deserialize)]
+pub struct CorsConfig {
+    // exact origins like "https://dash.example.com", or "*" for any
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    #[serde(default = "default_cors_methods")]
+    pub allowed_methods: Vec<String>,
+    #[serde(default = "default_cors_headers")]
+    pub allowed_headers: Vec<String>,
+    #[serde(default)]
+    pub allow_credentials: bool,
+    #[serde(default = "default_cors_max_age_secs")]
+    pub max_age_secs: u64,
+    // any origin, method and header, the old behaviour; only for local development
+    #[serde(default)]
+    pub permissive: bool,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: default_cors_methods(),
+            allowed_headers: default_cors_headers(),
+            allow_credentials: false,
+            max_age_secs: default_cors_max_age_secs(),
+            permissive: false,
+        }
+    }
+}
+
+fn default_cors_methods() -> Vec<String> {
+    ["GET", "POST", "DELETE"].map(String::from).to_vec()
+}
+
+fn default_cors_headers() -> Vec<String> {
+    ["authorization", "content-type"].map(String::from).to_vec()
+}
+
+fn default_cors_max_age_secs() -> u64 {
+    600
+}
@@ -0,0 +1,73 @@
+// `hl snapshot` documents and what `hl diff` reports between two of them
+use serde::{Deserialize, Serialize};
+use crate::types::{BalanceResponse, OpenOrder, TradingLock};
+
+#[derive(Serialize, Deserialize)]
+pub struct AccountSnapshot {
+    pub taken_at: u64,
+    pub network: String,
+    pub address: String,
+    pub balances: BalanceResponse,
+    pub open_orders: Vec<OpenOrder>,
+    pub risk: RiskSnapshot,
+}
+
+// the configured limits and where the account stands against them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RiskSnapshot {
+    pub day: String,
+    pub realized_pnl_today: f64,
+    pub max_daily_loss: Option<f64>,
+    pub daily_loss_override: bool,
+    pub max_open_positions: Option<usize>,
+    pub max_total_notional: Option<f64>,
+    pub max_notional_per_order: f64,
+    pub lock: Option<TradingLock>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PositionChangeKind {
+    Opened,
+    Closed,
+    Increased,
+    Reduced,
+    Flipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionChange {
+    pub symbol: String,
+    pub kind: PositionChangeKind,
+    pub size_before: f64,
+    pub size_after: f64,
+    pub unrealized_pnl_before: f64,
+    pub unrealized_pnl_after: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderChange {
+    pub order_id: u64,
+    pub symbol: String,
+    pub side: String,
+    pub price: f64,
+    pub qty: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotDiff {
+    pub elapsed_ms: u64,
+    pub account_value: (f64, f64),
+    pub withdrawable: (f64, f64),
+    pub unrealized_pnl: (f64, f64),
+    pub margin_ratio: (f64, f64),
+    pub realized_pnl_today: (f64, f64),
+    // only when both snapshots are from the same utc day
+    pub same_day: bool,
+    pub positions: Vec<PositionChange>,
+    pub new_orders: Vec<OrderChange>,
+    // filled or cancelled since the first snapshot
+    pub gone_orders: Vec<OrderChange>,
+    pub lock_before: Option<TradingLock>,
+    pub lock_after: Option<TradingLock>,
+}
@@ -0,0 +1,47 @@
+// events fed to strategies and the actions they emit
+use serde::Serialize;
+use crate::types::OrderRequest;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Tick {
+    pub symbol: String,
+    pub bid: f64,
+    pub ask: f64,
+    pub mid: f64,
+    pub time: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketTrade {
+    pub symbol: String,
+    pub is_buy: bool,
+    pub price: f64,
+    pub size: f64,
+    pub time: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyFill {
+    pub symbol: String,
+    pub order_id: u64,
+    pub is_buy: bool,
+    pub price: f64,
+    pub size: f64,
+    pub closed_pnl: f64,
+    pub time: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum StrategyAction {
+    Place(OrderRequest),
+    Cancel { order_id: u64 },
+    CancelAll,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkingOrder {
+    pub order_id: u64,
+    pub is_buy: bool,
+    pub price: f64,
+    pub size: f64,
+}
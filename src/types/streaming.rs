@@ -1,3 +1,4 @@
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Debug)]
@@ -38,4 +39,85 @@ pub struct TradeData {
 #[derive(Deserialize, Debug)]
 pub struct TradesResponse {
     pub data: Vec<TradeData>,
-}
\ No newline at end of file
+}
+// bbo channel: bbo[0] is the best bid, bbo[1] the best ask, either may be missing
+#[derive(Deserialize, Debug)]
+pub struct BboData {
+    pub coin: String,
+    pub time: u64,
+    pub bbo: Vec<Option<BboLevel>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BboLevel {
+    pub px: String,
+    pub sz: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpreadQuote {
+    pub symbol: String,
+    pub bid: f64,
+    pub bid_size: f64,
+    pub ask: f64,
+    pub ask_size: f64,
+    pub time: u64,
+}
+
+impl SpreadQuote {
+    pub fn from_bbo(data: &BboData) -> Option<Self> {
+        let bid = data.bbo.first()?.as_ref()?;
+        let ask = data.bbo.get(1)?.as_ref()?;
+        Some(Self {
+            symbol: data.coin.clone(),
+            bid: bid.px.parse().ok()?,
+            bid_size: bid.sz.parse().ok()?,
+            ask: ask.px.parse().ok()?,
+            ask_size: ask.sz.parse().ok()?,
+            time: data.time,
+        })
+    }
+
+    pub fn spread_bps(&self) -> f64 {
+        let mid = (self.bid + self.ask) / 2.0;
+        (self.ask - self.bid) / mid * 10_000.0
+    }
+
+    // USD on the thinner side of the top of book
+    pub fn top_notional(&self) -> f64 {
+        (self.bid * self.bid_size).min(self.ask * self.ask_size)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum SpreadAlert {
+    Wide { symbol: String, spread_bps: f64 },
+    Thin { symbol: String, top_notional: f64, average: f64 },
+    Recovered { symbol: String, spread_bps: f64, top_notional: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+// which prints the trade tape shows; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct TradeFilter {
+    pub min_size: Option<f64>,
+    pub side: Option<TradeSide>,
+    pub min_notional: Option<f64>,
+}
+
+impl TradeFilter {
+    pub fn matches(&self, price: f64, size: f64, is_buy: bool) -> bool {
+        self.min_size.is_none_or(|min| size >= min)
+            && self.min_notional.is_none_or(|min| price * size >= min)
+            && self.side.is_none_or(|side| (side == TradeSide::Buy) == is_buy)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_size.is_none() && self.side.is_none() && self.min_notional.is_none()
+    }
+}
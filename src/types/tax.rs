@@ -0,0 +1,21 @@
+// types for cost-basis reporting
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CostMethod {
+    Fifo,
+    Average,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClosedTrade {
+    pub symbol: String,
+    pub direction: String,
+    pub quantity: f64,
+    pub acquired_at: u64,
+    pub closed_at: u64,
+    pub proceeds: f64,
+    pub cost_basis: f64,
+    pub gain: f64,
+}
@@ -1,6 +1,9 @@
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct OrderRequest {
     pub symbol: String,
     pub is_buy: bool,
@@ -9,6 +12,105 @@ pub struct OrderRequest {
     pub leverage: Option<u32>,
     pub reduce_only: bool,
     pub tif: String,
+    // falls back to the symbol's configured mode, unchanged if neither is set
+    pub margin_mode: Option<MarginMode>,
+    // client order id, lets the order be looked up or cancelled before its oid is known
+    pub cloid: Option<Uuid>,
+    // worst market fill vs mid as a fraction, None uses the configured default
+    pub slippage: Option<f64>,
+    // extra fee paid to a builder the user has approved
+    pub builder: Option<BuilderFee>,
+}
+
+impl Default for OrderRequest {
+    fn default() -> Self {
+        Self {
+            symbol: String::new(),
+            is_buy: true,
+            qty: 0.0,
+            limit_price: None,
+            leverage: None,
+            reduce_only: false,
+            tif: "Gtc".to_string(),
+            margin_mode: None,
+            cloid: None,
+            slippage: None,
+            builder: None,
+        }
+    }
+}
+
+// perp orders can pay a builder at most 0.1%
+pub const MAX_BUILDER_FEE_BPS: f64 = 10.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuilderFee {
+    pub address: String,
+    pub fee_bps: f64,
+}
+
+impl BuilderFee {
+    pub fn new(address: &str, fee_bps: f64) -> anyhow::Result<Self> {
+        let hex = address.strip_prefix("0x").unwrap_or(address);
+        if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            anyhow::bail!("Invalid builder address '{}', expected 0x + 40 hex digits", address);
+        }
+        // the exchange takes the fee in tenths of a basis point
+        let tenths = fee_bps * 10.0;
+        if !(fee_bps > 0.0 && fee_bps <= MAX_BUILDER_FEE_BPS) || (tenths - tenths.round()).abs() > 1e-9 {
+            anyhow::bail!("Builder fee must be a multiple of 0.1 bps between 0.1 and {} bps, got {}", MAX_BUILDER_FEE_BPS, fee_bps);
+        }
+        Ok(Self {
+            address: format!("0x{}", hex.to_lowercase()),
+            fee_bps,
+        })
+    }
+
+    pub fn tenths_bps(&self) -> u64 {
+        (self.fee_bps * 10.0).round() as u64
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.fee_bps / 10_000.0
+    }
+}
+
+// approveBuilderFee takes the max rate as a percentage string, 1 bps = "0.01%"
+pub fn builder_fee_percent(fee_bps: f64) -> String {
+    format!("{}%", fee_bps / 100.0)
+}
+
+// how signed orders reach the exchange; ws falls back to rest when the socket is unavailable
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderTransport {
+    #[default]
+    Rest,
+    Ws,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MarginMode {
+    Cross,
+    Isolated,
+}
+
+impl MarginMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "cross" => Some(MarginMode::Cross),
+            "isolated" => Some(MarginMode::Isolated),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarginMode::Cross => "cross",
+            MarginMode::Isolated => "isolated",
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -16,6 +118,8 @@ pub struct OrderResponse {
     pub status: String,
     pub result: OrderResult,
     pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cloid: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -32,9 +136,16 @@ pub enum OrderResult {
     Resting {
         order_id: u64,
     },
+    // validated but never sent to the exchange
+    DryRun {
+        price: f64,
+        size: f64,
+        notional: f64,
+        estimated_fee: f64,
+    },
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema, async_graphql::SimpleObject)]
 pub struct OpenOrder {
     pub order_id: u64,
     pub symbol: String,
@@ -47,6 +158,18 @@ pub struct OpenOrder {
     pub timestamp: u64,
 }
 
+// resting stop or take profit, waiting for its trigger price
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerOrder {
+    pub order_id: u64,
+    pub symbol: String,
+    pub side: String,
+    pub size: f64,
+    pub trigger_px: f64,
+    pub order_type: String,
+    pub reduce_only: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Position {
     pub symbol: String,
@@ -65,4 +188,57 @@ pub struct AccountSummary {
     pub withdrawable: f64,
     pub total_margin_used: f64,
     pub total_unrealized_pnl: f64,
-}
\ No newline at end of file
+}
+// one resting order placed as part of a named group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupedOrder {
+    pub symbol: String,
+    pub order_id: u64,
+    pub cloid: String,
+}
+
+// hyperliquid cloids are 16 bytes of hex ("0x" + 32 digits); plain uuids are accepted too
+pub fn parse_cloid(value: &str) -> Option<Uuid> {
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    Uuid::parse_str(hex).ok()
+}
+
+pub fn cloid_hex(cloid: &Uuid) -> String {
+    format!("0x{}", cloid.simple())
+}
+
+// [presets.<name>] in the config file, defaults for buy/sell that flags on the command line override
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OrderPreset {
+    pub tif: Option<String>,
+    pub slippage: Option<f64>,
+    pub leverage: Option<u32>,
+    pub margin: Option<MarginMode>,
+    pub reduce_only: Option<bool>,
+    pub tick_size: Option<f64>,
+}
+
+impl OrderPreset {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(tif) = &self.tif
+            && !["Gtc", "Ioc", "Alo"].contains(&tif.as_str())
+        {
+            anyhow::bail!("Invalid tif '{}', expected Gtc, Ioc or Alo", tif);
+        }
+        if let Some(slippage) = self.slippage
+            && !(slippage > 0.0 && slippage <= 0.1)
+        {
+            anyhow::bail!("Slippage must be between 0% and 10% (0.0 to 0.1), got {}", slippage);
+        }
+        if self.leverage == Some(0) {
+            anyhow::bail!("Leverage must be at least 1");
+        }
+        if let Some(tick_size) = self.tick_size
+            && tick_size <= 0.0
+        {
+            anyhow::bail!("Tick size must be greater than 0, got {}", tick_size);
+        }
+        Ok(())
+    }
+}